@@ -0,0 +1,195 @@
+//! Stake account inspection: the account decoded with the program's own parser, plus
+//! its activation status at the current epoch, for deployments `solana stake-account`
+//! doesn't know about.
+
+use std::fmt;
+
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_pinocchio_starter::{
+    sdk::{
+        self,
+        display::{DisplayMeta, DisplayStake, DisplayStakeState},
+        StakeActivation, StakeActivationState,
+    },
+    state::{StakeHistory, StakeStateV2},
+};
+use solana_sdk::{pubkey::Pubkey, sysvar};
+
+use crate::Result;
+
+pub struct Inspection {
+    pub address: Pubkey,
+    pub lamports: u64,
+    pub epoch: u64,
+    pub state: StakeStateV2,
+    /// `None` unless the account is delegated
+    pub activation: Option<StakeActivation>,
+}
+
+impl Inspection {
+    pub fn fetch(rpc: &RpcClient, address: &Pubkey) -> Result<Self> {
+        let account = rpc.get_account(address)?;
+        if account.owner != sdk::PROGRAM_ID {
+            return Err(format!(
+                "{address} is owned by {}, not {}",
+                account.owner,
+                sdk::PROGRAM_ID
+            )
+            .into());
+        }
+        let state = StakeStateV2::deserialize(&account.data)
+            .map_err(|err| format!("cannot decode stake account: {err:?}"))?;
+        let epoch = rpc.get_epoch_info()?.epoch;
+
+        let activation = match &state {
+            StakeStateV2::Stake(_, stake, _) => {
+                let history = rpc.get_account_data(&sysvar::stake_history::id())?;
+                let history = StakeHistory::from_sysvar_data(&history)
+                    .map_err(|err| format!("cannot decode stake history: {err:?}"))?;
+                Some(sdk::stake_activation(&stake.delegation, epoch, &history))
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            address: *address,
+            lamports: account.lamports,
+            epoch,
+            state,
+            activation,
+        })
+    }
+
+    /// Field names follow `solana stake-account --output json` where there is one
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "address": self.address.to_string(),
+            "accountBalance": self.lamports,
+            "epoch": self.epoch,
+            "stakeType": state_name(&self.state),
+        });
+        let object = value.as_object_mut().unwrap();
+
+        let display = DisplayStakeState::new(&self.state);
+        if let Some(meta) = display.meta {
+            object.insert("rentExemptReserve".into(), meta.rent_exempt_reserve.into());
+            object.insert("staker".into(), meta.staker.into());
+            object.insert("withdrawer".into(), meta.withdrawer.into());
+            object.insert("unixTimestamp".into(), meta.lockup.unix_timestamp.into());
+            if let Some(expires_at) = meta.lockup.expires_at {
+                object.insert("lockupExpiresAt".into(), expires_at.into());
+            }
+            object.insert("epochLockup".into(), meta.lockup.epoch.into());
+            object.insert("custodian".into(), meta.lockup.custodian.into());
+        }
+        if let Some(stake) = display.stake {
+            object.insert("delegatedVoteAccountAddress".into(), stake.voter.into());
+            object.insert("delegatedStake".into(), stake.stake.into());
+            // bootstrap stake reports epoch 0, as the solana CLI does
+            object.insert(
+                "activationEpoch".into(),
+                stake.activation_epoch.unwrap_or(0).into(),
+            );
+            if let Some(deactivation_epoch) = stake.deactivation_epoch {
+                object.insert("deactivationEpoch".into(), deactivation_epoch.into());
+            }
+            object.insert("creditsObserved".into(), stake.credits_observed.into());
+        }
+        if !display.flags.is_empty() {
+            object.insert("flags".into(), display.flags.into());
+        }
+        if let Some(activation) = &self.activation {
+            object.insert("activeStake".into(), activation.active.into());
+            object.insert("inactiveStake".into(), activation.inactive.into());
+            object.insert(
+                "activationState".into(),
+                activation_name(activation.state).into(),
+            );
+        }
+        value
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        writeln!(f, "Balance: {} lamports", self.lamports)?;
+        writeln!(f, "Stake type: {}", state_name(&self.state))?;
+        let display = DisplayStakeState::new(&self.state);
+        if let Some(meta) = &display.meta {
+            write_meta(f, meta)?;
+        }
+        if let Some(stake) = &display.stake {
+            write_stake(f, stake)?;
+        }
+        if !display.flags.is_empty() {
+            writeln!(f, "Flags: {}", display.flags.join(", "))?;
+        }
+        if let Some(activation) = &self.activation {
+            writeln!(
+                f,
+                "Activation state at epoch {}: {}",
+                self.epoch,
+                activation_name(activation.state)
+            )?;
+            writeln!(f, "Active stake: {} lamports", activation.active)?;
+            writeln!(f, "Inactive stake: {} lamports", activation.inactive)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_meta(f: &mut fmt::Formatter<'_>, meta: &DisplayMeta) -> fmt::Result {
+    writeln!(
+        f,
+        "Rent-exempt reserve: {} lamports",
+        meta.rent_exempt_reserve
+    )?;
+    writeln!(f, "Stake authority: {}", meta.staker)?;
+    writeln!(f, "Withdraw authority: {}", meta.withdrawer)?;
+    let lockup = &meta.lockup;
+    if lockup.unix_timestamp != 0 || lockup.epoch != 0 {
+        let until = match &lockup.expires_at {
+            Some(expires_at) => expires_at.clone(),
+            None => lockup.unix_timestamp.to_string(),
+        };
+        writeln!(
+            f,
+            "Lockup: until {until} and epoch {}, custodian {}",
+            lockup.epoch, lockup.custodian
+        )?;
+    }
+    Ok(())
+}
+
+fn write_stake(f: &mut fmt::Formatter<'_>, stake: &DisplayStake) -> fmt::Result {
+    writeln!(f, "Delegated vote account: {}", stake.voter)?;
+    writeln!(f, "Delegated stake: {} lamports", stake.stake)?;
+    match stake.activation_epoch {
+        Some(epoch) => writeln!(f, "Activation epoch: {epoch}")?,
+        None => writeln!(f, "Activation epoch: genesis (bootstrap stake)")?,
+    }
+    if let Some(epoch) = stake.deactivation_epoch {
+        writeln!(f, "Deactivation epoch: {epoch}")?;
+    }
+    writeln!(f, "Credits observed: {}", stake.credits_observed)
+}
+
+fn state_name(state: &StakeStateV2) -> &'static str {
+    match state {
+        StakeStateV2::Uninitialized => "Uninitialized",
+        StakeStateV2::Initialized(_) => "Initialized",
+        StakeStateV2::Stake(..) => "Stake",
+        StakeStateV2::RewardsPool => "RewardsPool",
+    }
+}
+
+fn activation_name(state: StakeActivationState) -> &'static str {
+    match state {
+        StakeActivationState::Activating => "activating",
+        StakeActivationState::Active => "active",
+        StakeActivationState::Deactivating => "deactivating",
+        StakeActivationState::Inactive => "inactive",
+    }
+}
@@ -0,0 +1,334 @@
+//! `pstake`: builds and sends stake transactions against this program's ID, for
+//! exercising the port on a cluster without writing scripts.
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+mod inspect;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use inspect::Inspection;
+use solana_client::rpc_client::RpcClient;
+use solana_pinocchio_starter::{
+    sdk,
+    state::{Authorized, Lockup, StakeAuthorize},
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{read_keypair_file, Keypair, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Stake operations against the pinocchio stake program"
+)]
+struct Cli {
+    /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+    #[arg(short, long, global = true, default_value = "devnet")]
+    url: String,
+
+    /// Fee payer and default authority keypair
+    #[arg(short, long, global = true)]
+    keypair: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create and initialize a stake account
+    Create {
+        /// Keypair for the new stake account
+        stake: PathBuf,
+        /// Lamports to stake on top of the rent-exempt reserve
+        lamports: u64,
+        /// Stake authority [default: the fee payer]
+        #[arg(long)]
+        staker: Option<Pubkey>,
+        /// Withdraw authority [default: the fee payer]
+        #[arg(long)]
+        withdrawer: Option<Pubkey>,
+    },
+    /// Delegate a stake account to a vote account
+    Delegate {
+        stake: Pubkey,
+        vote: Pubkey,
+        /// Stake authority keypair [default: the fee payer]
+        #[arg(long)]
+        authority: Option<PathBuf>,
+    },
+    /// Split lamports into a new stake account
+    Split {
+        stake: Pubkey,
+        /// Keypair for the new stake account
+        split_stake: PathBuf,
+        lamports: u64,
+        /// Stake authority keypair [default: the fee payer]
+        #[arg(long)]
+        authority: Option<PathBuf>,
+    },
+    /// Merge `source` into `destination`
+    Merge {
+        destination: Pubkey,
+        source: Pubkey,
+        /// Stake authority keypair [default: the fee payer]
+        #[arg(long)]
+        authority: Option<PathBuf>,
+    },
+    /// Deactivate a delegated stake account
+    Deactivate {
+        stake: Pubkey,
+        /// Stake authority keypair [default: the fee payer]
+        #[arg(long)]
+        authority: Option<PathBuf>,
+    },
+    /// Withdraw lamports from a stake account
+    Withdraw {
+        stake: Pubkey,
+        to: Pubkey,
+        lamports: u64,
+        /// Withdraw authority keypair [default: the fee payer]
+        #[arg(long)]
+        authority: Option<PathBuf>,
+        /// Lockup custodian keypair, to withdraw before the lockup expires
+        #[arg(long)]
+        custodian: Option<PathBuf>,
+    },
+    /// Assign a new stake or withdraw authority
+    Authorize {
+        stake: Pubkey,
+        new_authority: Pubkey,
+        #[arg(long, value_enum)]
+        role: Role,
+        /// Current authority keypair [default: the fee payer]
+        #[arg(long)]
+        authority: Option<PathBuf>,
+        /// Lockup custodian keypair, to change the withdrawer before the lockup expires
+        #[arg(long)]
+        custodian: Option<PathBuf>,
+    },
+    /// Print a stake account's decoded state and activation status
+    #[command(visible_alias = "show")]
+    Inspect {
+        stake: Pubkey,
+        #[arg(long, value_enum, default_value_t = Output::Display)]
+        output: Output,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    Display,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Role {
+    Staker,
+    Withdrawer,
+}
+
+impl From<Role> for StakeAuthorize {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Staker => StakeAuthorize::Staker,
+            Role::Withdrawer => StakeAuthorize::Withdrawer,
+        }
+    }
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let rpc = RpcClient::new_with_commitment(rpc_url(&cli.url), CommitmentConfig::confirmed());
+
+    if let Command::Inspect { stake, output } = cli.command {
+        let inspection = Inspection::fetch(&rpc, &stake)?;
+        match output {
+            Output::Display => print!("{inspection}"),
+            Output::Json => println!("{}", serde_json::to_string_pretty(&inspection.to_json())?),
+        }
+        return Ok(());
+    }
+
+    let payer = read_keypair(&cli.keypair.unwrap_or_else(default_keypair_path))?;
+    let authority = |path: Option<PathBuf>| -> Result<Option<Keypair>> {
+        path.as_deref().map(read_keypair).transpose()
+    };
+
+    match cli.command {
+        Command::Create {
+            stake,
+            lamports,
+            staker,
+            withdrawer,
+        } => {
+            let stake = read_keypair(&stake)?;
+            let authorized = Authorized {
+                staker: staker.unwrap_or(payer.pubkey()).to_bytes(),
+                withdrawer: withdrawer.unwrap_or(payer.pubkey()).to_bytes(),
+            };
+            let rent: Rent = bincode::deserialize(&rpc.get_account_data(&sysvar::rent::id())?)?;
+            let instructions = sdk::create_stake_account(
+                &payer.pubkey(),
+                &stake.pubkey(),
+                &authorized,
+                &Lockup::default(),
+                &rent,
+                lamports,
+            );
+            send(&rpc, &payer, &instructions, &[&stake])?;
+            println!("stake account: {}", stake.pubkey());
+        }
+        Command::Delegate {
+            stake,
+            vote,
+            authority: path,
+        } => {
+            let authority = authority(path)?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let instruction = sdk::delegate_stake(&stake, &authority.pubkey(), &vote);
+            send(&rpc, &payer, &[instruction], &[authority])?;
+        }
+        Command::Split {
+            stake,
+            split_stake,
+            lamports,
+            authority: path,
+        } => {
+            let split_stake = read_keypair(&split_stake)?;
+            let authority = authority(path)?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let instructions = sdk::split_into_new_account(
+                &stake,
+                &authority.pubkey(),
+                lamports,
+                &split_stake.pubkey(),
+            );
+            send(&rpc, &payer, &instructions, &[authority, &split_stake])?;
+            println!("split stake account: {}", split_stake.pubkey());
+        }
+        Command::Merge {
+            destination,
+            source,
+            authority: path,
+        } => {
+            let authority = authority(path)?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let instruction = sdk::merge(&destination, &source, &authority.pubkey());
+            send(&rpc, &payer, &[instruction], &[authority])?;
+        }
+        Command::Deactivate {
+            stake,
+            authority: path,
+        } => {
+            let authority = authority(path)?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let instruction = sdk::deactivate_stake(&stake, &authority.pubkey());
+            send(&rpc, &payer, &[instruction], &[authority])?;
+        }
+        Command::Withdraw {
+            stake,
+            to,
+            lamports,
+            authority: path,
+            custodian,
+        } => {
+            let authority = authority(path)?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let custodian = custodian.as_deref().map(read_keypair).transpose()?;
+            let custodian_pubkey = custodian.as_ref().map(Keypair::pubkey);
+            let instruction = sdk::withdraw(
+                &stake,
+                &authority.pubkey(),
+                &to,
+                lamports,
+                custodian_pubkey.as_ref(),
+            );
+            let mut signers = vec![authority];
+            signers.extend(custodian.as_ref());
+            send(&rpc, &payer, &[instruction], &signers)?;
+        }
+        Command::Authorize {
+            stake,
+            new_authority,
+            role,
+            authority: path,
+            custodian,
+        } => {
+            let authority = authority(path)?;
+            let authority = authority.as_ref().unwrap_or(&payer);
+            let custodian = custodian.as_deref().map(read_keypair).transpose()?;
+            let custodian_pubkey = custodian.as_ref().map(Keypair::pubkey);
+            let instruction = sdk::authorize(
+                &stake,
+                &authority.pubkey(),
+                &new_authority,
+                role.into(),
+                custodian_pubkey.as_ref(),
+            );
+            let mut signers = vec![authority];
+            signers.extend(custodian.as_ref());
+            send(&rpc, &payer, &[instruction], &signers)?;
+        }
+        Command::Inspect { .. } => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn send(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<()> {
+    let mut all_signers = vec![payer];
+    all_signers.extend(signers.iter().filter(|s| s.pubkey() != payer.pubkey()));
+
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        rpc.get_latest_blockhash()?,
+    );
+    let signature = rpc.send_and_confirm_transaction(&transaction)?;
+    println!("signature: {signature}");
+    Ok(())
+}
+
+fn rpc_url(url: &str) -> String {
+    match url {
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "localhost" | "l" => "http://localhost:8899",
+        url => url,
+    }
+    .to_string()
+}
+
+fn default_keypair_path() -> PathBuf {
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".config/solana/id.json")
+}
+
+fn read_keypair(path: &Path) -> Result<Keypair> {
+    read_keypair_file(path).map_err(|err| format!("{}: {err}", path.display()).into())
+}
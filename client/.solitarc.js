@@ -6,7 +6,7 @@ const binaryInstallDir = path.join(__dirname, ".crates");
 
 module.exports = {
   idlGenerator: "shank",
-  programName: "solana_pinocchio_starter", // Change: change the programName to the name of the program
+  programName: "solana_pinocchio_starter",
   idlDir,
   sdkDir,
   binaryInstallDir,
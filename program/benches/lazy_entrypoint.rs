@@ -0,0 +1,68 @@
+//! Runs the tracked cases against the default build and the `lazy-entrypoint` build and
+//! writes a CU comparison table to `benches/lazy_entrypoint.md`. The cases are the ones
+//! `benches/compute_units.rs` tracks.
+//!
+//! Build both programs first; the lazy one goes to its own directory so neither
+//! overwrites the other:
+//!
+//! ```sh
+//! cargo build-sbf
+//! cargo build-sbf --features lazy-entrypoint --sbf-out-dir target/deploy-lazy
+//! cargo bench --bench lazy_entrypoint
+//! ```
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::{fmt::Write, fs, path::Path, process::ExitCode};
+
+use mollusk_svm::Mollusk;
+
+const LAZY_ELF: &str = "target/deploy-lazy/solana_pinocchio_starter";
+const REPORT_PATH: &str = "benches/lazy_entrypoint.md";
+
+fn main() -> ExitCode {
+    if !Path::new(LAZY_ELF).with_extension("so").exists() {
+        eprintln!(
+            "{LAZY_ELF}.so is missing; run \
+             `cargo build-sbf --features lazy-entrypoint --sbf-out-dir target/deploy-lazy` first"
+        );
+        return ExitCode::FAILURE;
+    }
+    let default = common::mollusk();
+    let lazy = Mollusk::new(&common::PROGRAM, LAZY_ELF);
+
+    let mut report = String::from(
+        "| Instruction | default CUs | lazy CUs | Saved |\n\
+         | ----------- | ----------- | -------- | ----- |\n",
+    );
+    for case in common::compute_units::cases(&default) {
+        let default_run = default.process_instruction(&case.instruction, &case.accounts);
+        let lazy_run = lazy.process_instruction(&case.instruction, &case.accounts);
+        // both builds share the processors, so only the entrypoint should differ
+        assert!(
+            default_run.program_result.is_ok() && lazy_run.program_result.is_ok(),
+            "{}: default {:?}, lazy {:?}",
+            case.name,
+            default_run.program_result,
+            lazy_run.program_result
+        );
+
+        let (default_cus, lazy_cus) = (
+            default_run.compute_units_consumed,
+            lazy_run.compute_units_consumed,
+        );
+        let saved = default_cus as i64 - lazy_cus as i64;
+        let percent = saved as f64 * 100.0 / default_cus as f64;
+        writeln!(
+            report,
+            "| {} | {default_cus} | {lazy_cus} | {saved} ({percent:.1}%) |",
+            case.name
+        )
+        .unwrap();
+    }
+
+    print!("{report}");
+    fs::write(REPORT_PATH, &report).unwrap();
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,82 @@
+//! Runs the tracked cases against this program and the upstream BPF stake program and
+//! writes a CU comparison table to `benches/native_comparison.md`, for the README and
+//! release notes. The cases are the ones `benches/compute_units.rs` tracks; extension
+//! instructions the native program doesn't have are left out.
+//!
+//! The native ELF isn't checked in; dump it from mainnet first:
+//!
+//! ```sh
+//! scripts/fetch-native-stake.sh
+//! cargo build-sbf && cargo bench --bench native_comparison
+//! ```
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::{fmt::Write, fs, path::Path, process::ExitCode};
+
+use mollusk_svm::Mollusk;
+use solana_pinocchio_starter::instruction::StakeInstruction;
+use solana_sdk::instruction::Instruction;
+
+const NATIVE_ELF: &str = "tests/elfs/solana_stake_program";
+const REPORT_PATH: &str = "benches/native_comparison.md";
+
+/// `instruction` in the native wire format: the one-byte discriminator widened to the
+/// `u32` bincode writes, followed by the same arguments. `None` for extensions.
+fn native_instruction(instruction: &Instruction) -> Option<Instruction> {
+    let (discriminator, args) = instruction.data.split_first()?;
+    // native instructions end at `MoveLamports`
+    if *discriminator > StakeInstruction::MoveLamports as u8 {
+        return None;
+    }
+    let mut data = u32::from(*discriminator).to_le_bytes().to_vec();
+    data.extend_from_slice(args);
+    Some(Instruction {
+        data,
+        ..instruction.clone()
+    })
+}
+
+fn main() -> ExitCode {
+    if !Path::new(NATIVE_ELF).with_extension("so").exists() {
+        eprintln!("{NATIVE_ELF}.so is missing; run scripts/fetch-native-stake.sh first");
+        return ExitCode::FAILURE;
+    }
+    let pinocchio = common::mollusk();
+    let native = Mollusk::new(&common::PROGRAM, NATIVE_ELF);
+
+    let mut report = String::from(
+        "| Instruction | pinocchio CUs | native CUs | Saved |\n\
+         | ----------- | ------------- | ---------- | ----- |\n",
+    );
+    for case in common::compute_units::cases(&pinocchio) {
+        let Some(native_instruction) = native_instruction(&case.instruction) else {
+            continue;
+        };
+        let ours = pinocchio.process_instruction(&case.instruction, &case.accounts);
+        let theirs = native.process_instruction(&native_instruction, &case.accounts);
+        // a case only compares if both programs took the same path to success
+        assert!(
+            ours.program_result.is_ok() && theirs.program_result.is_ok(),
+            "{}: pinocchio {:?}, native {:?}",
+            case.name,
+            ours.program_result,
+            theirs.program_result
+        );
+
+        let (ours, theirs) = (ours.compute_units_consumed, theirs.compute_units_consumed);
+        let saved = theirs as i64 - ours as i64;
+        let percent = saved as f64 * 100.0 / theirs as f64;
+        writeln!(
+            report,
+            "| {} | {ours} | {theirs} | {saved} ({percent:.1}%) |",
+            case.name
+        )
+        .unwrap();
+    }
+
+    print!("{report}");
+    fs::write(REPORT_PATH, &report).unwrap();
+    ExitCode::SUCCESS
+}
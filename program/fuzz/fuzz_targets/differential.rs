@@ -0,0 +1,269 @@
+#![no_main]
+
+//! Runs the same instruction and accounts through this program and the upstream stake
+//! program and asserts identical results and account bytes, except for the state bytes
+//! this program zeroes when an account goes back to `Uninitialized`.
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use mollusk_svm::Mollusk;
+use solana_pinocchio_starter::{
+    instruction::LockupArgs,
+    sdk,
+    state::{Authorized, Delegation, Lockup, Meta, Stake, StakeFlags, StakeStateV2},
+};
+use solana_sdk::{account::Account, instruction::Instruction, pubkey::Pubkey};
+use solana_stake_interface::instruction as native;
+
+const STAKER: Pubkey = Pubkey::new_from_array([1; 32]);
+const OTHER: Pubkey = Pubkey::new_from_array([2; 32]);
+const VOTER: Pubkey = Pubkey::new_from_array([3; 32]);
+const SOURCE: Pubkey = Pubkey::new_from_array([4; 32]);
+const DESTINATION: Pubkey = Pubkey::new_from_array([5; 32]);
+
+#[derive(Arbitrary, Debug)]
+enum FuzzState {
+    Uninitialized,
+    Initialized,
+    Stake {
+        stake: u64,
+        activation_epoch: u8,
+        deactivation_epoch: Option<u8>,
+    },
+    RewardsPool,
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzStakeAccount {
+    lamports: u64,
+    state: FuzzState,
+    /// Authorities are `STAKER` when set, `OTHER` otherwise
+    staker_authorized: bool,
+    /// Lockup expiry epoch, if any
+    lockup_epoch: Option<u8>,
+    /// Shrinks the account so size checks are exercised
+    truncate: bool,
+}
+
+#[derive(Arbitrary, Debug)]
+enum FuzzInstruction {
+    Split {
+        lamports: u64,
+    },
+    Withdraw {
+        lamports: u64,
+    },
+    Deactivate,
+    SetLockup {
+        unix_timestamp: Option<i64>,
+        epoch: Option<u64>,
+        new_custodian: bool,
+    },
+    Merge,
+    MoveStake {
+        lamports: u64,
+    },
+    MoveLamports {
+        lamports: u64,
+    },
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzInput {
+    source: FuzzStakeAccount,
+    destination: FuzzStakeAccount,
+    instruction: FuzzInstruction,
+    epoch: u8,
+}
+
+/// Instructions whose processors the entrypoint routes; the rest still `todo!()`
+fn dispatched(instruction: &FuzzInstruction) -> bool {
+    matches!(
+        instruction,
+        FuzzInstruction::Split { .. } | FuzzInstruction::SetLockup { .. }
+    )
+}
+
+fn stake_account(account: &FuzzStakeAccount, rent_exempt_reserve: u64) -> Account {
+    let authority = if account.staker_authorized {
+        STAKER
+    } else {
+        OTHER
+    };
+    let lockup = Lockup {
+        epoch: u64::from(account.lockup_epoch.unwrap_or(0)).to_le_bytes(),
+        ..Lockup::default()
+    };
+    let meta = Meta::new(
+        rent_exempt_reserve,
+        Authorized::auto(&authority.to_bytes()),
+        lockup,
+    );
+    let state = match account.state {
+        FuzzState::Uninitialized => StakeStateV2::Uninitialized,
+        FuzzState::Initialized => StakeStateV2::Initialized(meta),
+        FuzzState::Stake {
+            stake,
+            activation_epoch,
+            deactivation_epoch,
+        } => {
+            let mut delegation = Delegation::new(&VOTER.to_bytes(), stake, activation_epoch.into());
+            if let Some(deactivation_epoch) = deactivation_epoch {
+                delegation.set_deactivation_epoch(deactivation_epoch.into());
+            }
+            StakeStateV2::Stake(meta, Stake::new(delegation, 0), StakeFlags::empty())
+        }
+        FuzzState::RewardsPool => StakeStateV2::RewardsPool,
+    };
+
+    let mut data = vec![0; StakeStateV2::size_of()];
+    state
+        .serialize(&mut data)
+        .expect("the buffer is the stake account size");
+    if account.truncate {
+        data.truncate(StakeStateV2::size_of() - 1);
+    }
+
+    Account {
+        lamports: account.lamports,
+        data,
+        owner: sdk::PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// The same instruction encoded for this program and for the upstream one
+fn instructions(instruction: &FuzzInstruction) -> (Instruction, Instruction) {
+    match *instruction {
+        FuzzInstruction::Split { lamports } => (
+            sdk::split(&SOURCE, &STAKER, lamports, &DESTINATION),
+            native::split(&SOURCE, &STAKER, lamports, &DESTINATION)
+                .pop()
+                .unwrap(),
+        ),
+        FuzzInstruction::Withdraw { lamports } => (
+            sdk::withdraw(&SOURCE, &STAKER, &DESTINATION, lamports, None),
+            native::withdraw(&SOURCE, &STAKER, &DESTINATION, lamports, None),
+        ),
+        FuzzInstruction::Deactivate => (
+            sdk::deactivate_stake(&SOURCE, &STAKER),
+            native::deactivate_stake(&SOURCE, &STAKER),
+        ),
+        FuzzInstruction::SetLockup {
+            unix_timestamp,
+            epoch,
+            new_custodian,
+        } => {
+            let custodian = new_custodian.then_some(OTHER);
+            (
+                sdk::set_lockup(
+                    &SOURCE,
+                    &LockupArgs {
+                        unix_timestamp: unix_timestamp.map(i64::to_le_bytes),
+                        epoch: epoch.map(u64::to_le_bytes),
+                        custodian: custodian.map(|c| c.to_bytes()),
+                    },
+                    &STAKER,
+                ),
+                native::set_lockup(
+                    &SOURCE,
+                    &native::LockupArgs {
+                        unix_timestamp,
+                        epoch,
+                        custodian,
+                    },
+                    &STAKER,
+                ),
+            )
+        }
+        FuzzInstruction::Merge => (
+            sdk::merge(&DESTINATION, &SOURCE, &STAKER),
+            native::merge(&DESTINATION, &SOURCE, &STAKER).pop().unwrap(),
+        ),
+        FuzzInstruction::MoveStake { lamports } => (
+            sdk::move_stake(&SOURCE, &DESTINATION, &STAKER, lamports),
+            native::move_stake(&SOURCE, &DESTINATION, &STAKER, lamports),
+        ),
+        FuzzInstruction::MoveLamports { lamports } => (
+            sdk::move_lamports(&SOURCE, &DESTINATION, &STAKER, lamports),
+            native::move_lamports(&SOURCE, &DESTINATION, &STAKER, lamports),
+        ),
+    }
+}
+
+/// Whether `ours` is `upstream` closed back to `Uninitialized` with the state bytes
+/// zeroed. This program clears everything after the tag on a full split or withdrawal,
+/// where upstream only rewrites the tag and leaves the old state behind, so those bytes
+/// are the one intended difference.
+fn zeroed_uninitialized(ours: &Account, upstream: &Account) -> bool {
+    ours.data.len() == upstream.data.len()
+        && ours.data.len() >= 4
+        && ours.data[..4] == [0; 4]
+        && upstream.data[..4] == [0; 4]
+        && ours.data[4..].iter().all(|byte| *byte == 0)
+}
+
+fn mollusk(program_name: &str, epoch: u8) -> Mollusk {
+    let mut mollusk = Mollusk::new(&sdk::PROGRAM_ID, program_name);
+    mollusk.sysvars.clock.epoch = epoch.into();
+    mollusk
+}
+
+fuzz_target!(|input: FuzzInput| {
+    if !dispatched(&input.instruction) {
+        return;
+    }
+
+    let ours = mollusk("solana_pinocchio_starter", input.epoch);
+    let upstream = mollusk("solana_stake_program", input.epoch);
+
+    let rent_exempt_reserve = ours.sysvars.rent.minimum_balance(StakeStateV2::size_of());
+    let (clock, clock_account) = ours.sysvars.keyed_account_for_clock_sysvar();
+    let (stake_history, stake_history_account) =
+        ours.sysvars.keyed_account_for_stake_history_sysvar();
+    let accounts = vec![
+        (SOURCE, stake_account(&input.source, rent_exempt_reserve)),
+        (
+            DESTINATION,
+            stake_account(&input.destination, rent_exempt_reserve),
+        ),
+        (STAKER, Account::default()),
+        (OTHER, Account::default()),
+        (clock, clock_account),
+        (stake_history, stake_history_account),
+    ];
+
+    let (our_instruction, upstream_instruction) = instructions(&input.instruction);
+    // the account lists must line up for the comparison to mean anything
+    assert_eq!(our_instruction.accounts, upstream_instruction.accounts);
+
+    let our_result = ours.process_instruction(&our_instruction, &accounts);
+    let upstream_result = upstream.process_instruction(&upstream_instruction, &accounts);
+
+    assert_eq!(
+        our_result.program_result, upstream_result.program_result,
+        "{input:?}"
+    );
+    assert_eq!(
+        our_result.resulting_accounts.len(),
+        upstream_result.resulting_accounts.len(),
+        "{input:?}"
+    );
+    for ((our_key, our_account), (upstream_key, upstream_account)) in our_result
+        .resulting_accounts
+        .iter()
+        .zip(&upstream_result.resulting_accounts)
+    {
+        assert_eq!(our_key, upstream_key, "{input:?}");
+        if zeroed_uninitialized(our_account, upstream_account) {
+            let our_account = Account {
+                data: upstream_account.data.clone(),
+                ..our_account.clone()
+            };
+            assert_eq!(our_account, *upstream_account, "{input:?}");
+        } else {
+            assert_eq!(our_account, upstream_account, "{input:?}");
+        }
+    }
+});
@@ -0,0 +1,45 @@
+#![no_main]
+
+//! Feeds arbitrary bytes to the instruction decoder: the discriminator lookup the
+//! entrypoint does, the argument parsers behind it and the full SDK decoder. None may
+//! panic, and anything that isn't a typed instruction must be `InvalidInstructionData`.
+
+use libfuzzer_sys::fuzz_target;
+use pinocchio::program_error::ProgramError;
+use solana_pinocchio_starter::{
+    instruction::{LockupArgs, LockupCheckedArgs, StakeInstruction},
+    sdk,
+};
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+fn assert_invalid_data<T>(result: Result<T, ProgramError>) {
+    if let Err(error) = result {
+        assert_eq!(error, ProgramError::InvalidInstructionData);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    // more accounts than any instruction can take, one per split for `SplitMany` and
+    // up to 255 for `DeactivateMany`, so only the data can fail decoding
+    let accounts = vec![AccountMeta::new(Pubkey::new_unique(), false); data.len() + 256];
+    let parsed = sdk::parse_instruction(data, &accounts).map_err(ProgramError::from);
+
+    let Some((discriminator, args)) = data.split_first() else {
+        assert_eq!(parsed, Err(ProgramError::InvalidInstructionData));
+        return;
+    };
+    let instruction = StakeInstruction::try_from(discriminator);
+    if let Err(error) = &instruction {
+        assert_eq!(*error, ProgramError::InvalidInstructionData);
+        assert!(parsed.is_err());
+    }
+    assert_invalid_data(parsed);
+
+    match instruction {
+        Ok(StakeInstruction::SetLockup) => assert_invalid_data(LockupArgs::from_data(args)),
+        Ok(StakeInstruction::SetLockupChecked) => {
+            assert_invalid_data(LockupCheckedArgs::from_data(args))
+        }
+        _ => {}
+    }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+
+//! Encodes generated `LockupArgs` the way the SDK does and checks that
+//! `LockupArgs::from_data` decodes them back unchanged.
+
+use libfuzzer_sys::fuzz_target;
+use solana_pinocchio_starter::instruction::LockupArgs;
+
+fn encode(args: &LockupArgs) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut push_option = |field: Option<&[u8]>| match field {
+        Some(bytes) => {
+            data.push(1);
+            data.extend_from_slice(bytes);
+        }
+        None => data.push(0),
+    };
+    push_option(args.unix_timestamp.as_ref().map(|t| t.as_slice()));
+    push_option(args.epoch.as_ref().map(|e| e.as_slice()));
+    push_option(args.custodian.as_ref().map(|c| c.as_slice()));
+    data
+}
+
+fuzz_target!(|args: LockupArgs| {
+    assert_eq!(LockupArgs::from_data(&encode(&args)), Ok(args));
+});
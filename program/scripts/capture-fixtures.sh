@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+# Snapshot stake accounts listed in tests/fixtures/accounts.txt into
+# tests/fixtures/stake_accounts/<label>.json for the replay tests.
+#
+# usage: scripts/capture-fixtures.sh [rpc-url]
+# needs the solana CLI and jq
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+RPC_URL="${1:-https://api.mainnet-beta.solana.com}"
+MANIFEST=tests/fixtures/accounts.txt
+OUT_DIR=tests/fixtures/stake_accounts
+
+mkdir -p "$OUT_DIR"
+# replays run at the epoch the account was captured in
+epoch="$(solana epoch --url "$RPC_URL")"
+
+grep -v '^\s*\(#\|$\)' "$MANIFEST" | while read -r label pubkey description; do
+    echo "capturing $label ($pubkey)"
+    solana account "$pubkey" --url "$RPC_URL" --output json-compact \
+        | jq --arg label "$label" --arg description "$description" --argjson epoch "$epoch" \
+            '{label: $label, description: $description, epoch: $epoch, account: .}' \
+        > "$OUT_DIR/$label.json"
+done
@@ -0,0 +1,21 @@
+#!/usr/bin/env bash
+# Build the on-chain program and fail if any allocator entry point survived linking.
+# The entrypoint installs `no_allocator!`, so an allocation would only show up as an
+# abort at runtime; this catches it at build time instead.
+#
+# usage: scripts/check-no-alloc.sh [extra cargo build-sbf args]
+# needs the Solana platform tools (cargo build-sbf and their llvm-objdump)
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+cargo build-sbf "$@"
+
+SO=target/deploy/solana_pinocchio_starter.so
+OBJDUMP="${OBJDUMP:-$(find ~/.cache/solana -name llvm-objdump -type f | sort | tail -n1)}"
+
+# `__rust_alloc` and friends are only kept when something calls them
+if "$OBJDUMP" --syms "$SO" | grep -E '__rust_(alloc|alloc_zeroed|realloc)\b'; then
+    echo "error: $SO links the allocator; a processor allocates" >&2
+    exit 1
+fi
+echo "ok: no allocator symbols in $SO"
@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+# Build the on-chain program and fail if any function's stack frame overflows. The SBF
+# backend only warns when a frame passes the 4KB limit and still emits the .so, whose
+# overflowing function then corrupts the frame below it at runtime; this turns the
+# warning into an error.
+#
+# usage: scripts/check-stack.sh [extra cargo build-sbf args]
+# needs the Solana platform tools (cargo build-sbf)
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+
+# a cached build prints nothing, so the object has to be rebuilt to see its warnings
+cargo clean -p solana-pinocchio-starter --target sbf-solana-solana 2>/dev/null || true
+LOG="$(mktemp)"
+trap 'rm -f "$LOG"' EXIT
+cargo build-sbf "$@" 2>&1 | tee "$LOG"
+
+# "Error: Function <symbol> Stack offset of N exceeded max offset of 4096 by M bytes"
+if grep -E 'Stack offset of [0-9]+ exceeded' "$LOG"; then
+    echo "error: a function in the program exceeds the 4KB stack frame" >&2
+    exit 1
+fi
+echo "ok: every stack frame fits in 4KB"
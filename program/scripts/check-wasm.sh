@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+# Build the state parsers and `sdk` instruction builders for the browser. Nothing on
+# this path may touch a syscall or the entrypoint, so a regression shows up as a
+# compile error here rather than in a wallet.
+#
+# usage: scripts/check-wasm.sh [extra cargo build args]
+# needs the wasm32-unknown-unknown target (rustup target add wasm32-unknown-unknown)
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+cargo build --target wasm32-unknown-unknown --no-default-features --features sdk "$@"
+echo "ok: state parsing and sdk builders build for wasm32-unknown-unknown"
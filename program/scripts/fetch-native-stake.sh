@@ -0,0 +1,13 @@
+#!/usr/bin/env bash
+# Dump the upstream BPF stake program into tests/elfs/ for the native comparison
+# bench (benches/native_comparison.rs).
+#
+# usage: scripts/fetch-native-stake.sh [rpc-url]
+# needs the solana CLI
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+RPC_URL="${1:-https://api.mainnet-beta.solana.com}"
+
+solana program dump Stake11111111111111111111111111111111111111 \
+    tests/elfs/solana_stake_program.so --url "$RPC_URL"
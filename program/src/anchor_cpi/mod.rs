@@ -0,0 +1,242 @@
+//! `CpiContext` wrappers for Anchor programs calling this one.
+//!
+//! The account structs list what each processor reads, in its expected order; the
+//! instruction itself comes from the `sdk` builders, so the two can't disagree on
+//! encoding. Sysvars are passed as accounts because the processors read them that way.
+
+use anchor_lang::{prelude::*, solana_program::program::invoke_signed};
+
+use crate::{
+    sdk,
+    state::{Authorized, Lockup, StakeAuthorize},
+};
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    /// CHECK: uninitialized stake account, validated by the stake program
+    pub stake: AccountInfo<'info>,
+    /// CHECK: rent sysvar
+    pub rent: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Authorize<'info> {
+    /// CHECK: stake account to update, validated by the stake program
+    pub stake: AccountInfo<'info>,
+    /// CHECK: clock sysvar
+    pub clock: AccountInfo<'info>,
+    /// CHECK: current stake or withdraw authority
+    pub authorized: AccountInfo<'info>,
+    /// CHECK: new stake or withdraw authority
+    pub new_authorized: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateStake<'info> {
+    /// CHECK: initialized stake account, validated by the stake program
+    pub stake: AccountInfo<'info>,
+    /// CHECK: vote account to delegate to
+    pub vote: AccountInfo<'info>,
+    /// CHECK: clock sysvar
+    pub clock: AccountInfo<'info>,
+    /// CHECK: stake history sysvar
+    pub stake_history: AccountInfo<'info>,
+    /// CHECK: unused stake config account
+    pub stake_config: AccountInfo<'info>,
+    /// CHECK: stake authority
+    pub staker: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Split<'info> {
+    /// CHECK: stake account to split, validated by the stake program
+    pub stake: AccountInfo<'info>,
+    /// CHECK: uninitialized, program-owned destination
+    pub split_stake: AccountInfo<'info>,
+    /// CHECK: stake authority
+    pub staker: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: stake account to withdraw from, validated by the stake program
+    pub stake: AccountInfo<'info>,
+    /// CHECK: any account can receive lamports
+    pub to: AccountInfo<'info>,
+    /// CHECK: clock sysvar
+    pub clock: AccountInfo<'info>,
+    /// CHECK: stake history sysvar
+    pub stake_history: AccountInfo<'info>,
+    /// CHECK: withdraw authority
+    pub withdrawer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivateStake<'info> {
+    /// CHECK: delegated stake account, validated by the stake program
+    pub stake: AccountInfo<'info>,
+    /// CHECK: clock sysvar
+    pub clock: AccountInfo<'info>,
+    /// CHECK: stake authority
+    pub staker: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Merge<'info> {
+    /// CHECK: destination stake account, validated by the stake program
+    pub destination_stake: AccountInfo<'info>,
+    /// CHECK: source stake account, drained and closed by the merge
+    pub source_stake: AccountInfo<'info>,
+    /// CHECK: clock sysvar
+    pub clock: AccountInfo<'info>,
+    /// CHECK: stake history sysvar
+    pub stake_history: AccountInfo<'info>,
+    /// CHECK: stake authority of both accounts
+    pub staker: AccountInfo<'info>,
+}
+
+pub fn initialize<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Initialize<'info>>,
+    authorized: &Authorized,
+    lockup: &Lockup,
+) -> Result<()> {
+    let ix = sdk::initialize(ctx.accounts.stake.key, authorized, lockup);
+    invoke_signed(
+        &ix,
+        &[ctx.accounts.stake, ctx.accounts.rent],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+pub fn authorize<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Authorize<'info>>,
+    stake_authorize: StakeAuthorize,
+    custodian: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let ix = sdk::authorize(
+        ctx.accounts.stake.key,
+        ctx.accounts.authorized.key,
+        ctx.accounts.new_authorized.key,
+        stake_authorize,
+        custodian.as_ref().map(|c| c.key),
+    );
+    let mut account_infos = std::vec![
+        ctx.accounts.stake,
+        ctx.accounts.clock,
+        ctx.accounts.authorized,
+    ];
+    if let Some(custodian) = custodian {
+        account_infos.push(custodian);
+    }
+    invoke_signed(&ix, &account_infos, ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn delegate_stake<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, DelegateStake<'info>>,
+) -> Result<()> {
+    let ix = sdk::delegate_stake(
+        ctx.accounts.stake.key,
+        ctx.accounts.staker.key,
+        ctx.accounts.vote.key,
+    );
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake,
+            ctx.accounts.vote,
+            ctx.accounts.clock,
+            ctx.accounts.stake_history,
+            ctx.accounts.stake_config,
+            ctx.accounts.staker,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+pub fn split<'info>(ctx: CpiContext<'_, '_, '_, 'info, Split<'info>>, lamports: u64) -> Result<()> {
+    let ix = sdk::split(
+        ctx.accounts.stake.key,
+        ctx.accounts.staker.key,
+        lamports,
+        ctx.accounts.split_stake.key,
+    );
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.stake,
+            ctx.accounts.split_stake,
+            ctx.accounts.staker,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+pub fn withdraw<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Withdraw<'info>>,
+    lamports: u64,
+    custodian: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let ix = sdk::withdraw(
+        ctx.accounts.stake.key,
+        ctx.accounts.withdrawer.key,
+        ctx.accounts.to.key,
+        lamports,
+        custodian.as_ref().map(|c| c.key),
+    );
+    let mut account_infos = std::vec![
+        ctx.accounts.stake,
+        ctx.accounts.to,
+        ctx.accounts.clock,
+        ctx.accounts.stake_history,
+        ctx.accounts.withdrawer,
+    ];
+    if let Some(custodian) = custodian {
+        account_infos.push(custodian);
+    }
+    invoke_signed(&ix, &account_infos, ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn deactivate_stake<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, DeactivateStake<'info>>,
+) -> Result<()> {
+    let ix = sdk::deactivate_stake(ctx.accounts.stake.key, ctx.accounts.staker.key);
+    invoke_signed(
+        &ix,
+        &[ctx.accounts.stake, ctx.accounts.clock, ctx.accounts.staker],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+pub fn merge<'info>(ctx: CpiContext<'_, '_, '_, 'info, Merge<'info>>) -> Result<()> {
+    let ix = sdk::merge(
+        ctx.accounts.destination_stake.key,
+        ctx.accounts.source_stake.key,
+        ctx.accounts.staker.key,
+    );
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.destination_stake,
+            ctx.accounts.source_stake,
+            ctx.accounts.clock,
+            ctx.accounts.stake_history,
+            ctx.accounts.staker,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Marker for `Program<'info, Stake>` account constraints
+#[derive(Clone)]
+pub struct Stake;
+
+impl anchor_lang::Id for Stake {
+    fn id() -> Pubkey {
+        sdk::PROGRAM_ID
+    }
+}
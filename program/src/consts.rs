@@ -2,6 +2,11 @@ use pinocchio::pubkey::Pubkey;
 use pinocchio_pubkey::pubkey;
 
 pub const MAX_SIGNERS: usize = 32;
+/// Capacity of the array `collect_signers_checked` returns: the authority, the
+/// custodian and one seed-derived key are the most any caller adds. Kept this small
+/// because the array is returned by value, and 32 keys would put 1KB of the 4KB stack
+/// frame in every caller.
+pub const MAX_CHECKED_SIGNERS: usize = 3;
 pub const FEATURE_STAKE_RAISE_MINIMUM_DELEGATION_TO_1_SOL: bool = false;
 pub const PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH: Option<[u8; 8]> = Some((0u64).to_le_bytes());
 pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
@@ -9,6 +14,8 @@ pub const SYSVAR: Pubkey = pubkey!("Sysvar1111111111111111111111111111111111111"
 pub const DEFAULT_WARMUP_COOLDOWN_RATE: f64 = 0.25;
 pub const NEW_WARMUP_COOLDOWN_RATE: f64 = 0.09;
 pub const CLOCK_ID: Pubkey = pubkey!("SysvarC1ock11111111111111111111111111111111");
+pub const RENT_ID: Pubkey = pubkey!("SysvarRent111111111111111111111111111111111");
+pub const STAKE_HISTORY_ID: Pubkey = pubkey!("SysvarStakeHistory1111111111111111111111111");
 pub const VOTE_PROGRAM_ID: Pubkey = pubkey!("Vote111111111111111111111111111111111111111");
 
 // Maximum number of votes to keep around, tightly coupled with epoch_schedule::MINIMUM_SLOTS_PER_EPOCH
@@ -18,6 +25,44 @@ pub const INITIAL_LOCKOUT: usize = 2;
 // Maximum number of credits history to keep around
 pub const MAX_EPOCH_CREDITS_HISTORY: usize = 64;
 
+/// Epochs a vote account has to go without voting before the stake delegated to it can
+/// be deactivated as delinquent, which is also how many consecutive epochs, up to the
+/// current one, the reference vote account must have voted in. Builds with
+/// `custom-delinquency-window` take it from the `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION`
+/// environment variable at compile time, for private clusters with short epochs.
+#[cfg(not(feature = "custom-delinquency-window"))]
+pub const MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION: usize = 5;
+#[cfg(feature = "custom-delinquency-window")]
+pub const MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION: usize =
+    parse_epochs(env!("MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION"));
+
+/// `digits` as a non-zero decimal count, failing the build otherwise
+#[cfg(feature = "custom-delinquency-window")]
+const fn parse_epochs(digits: &str) -> usize {
+    let digits = digits.as_bytes();
+    let mut epochs = 0usize;
+    let mut i = 0;
+    while i < digits.len() {
+        assert!(
+            digits[i].is_ascii_digit(),
+            "MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION must be a decimal number"
+        );
+        epochs = epochs * 10 + (digits[i] - b'0') as usize;
+        i += 1;
+    }
+    assert!(
+        epochs > 0,
+        "MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION must be at least 1"
+    );
+    epochs
+}
+
+// `VoteStateVersions` tags, the little-endian u32 that starts vote account data. Later
+// versions have a different layout and are rejected until this program reads them.
+pub const VOTE_STATE_VERSION_V0_23_5: u32 = 0;
+pub const VOTE_STATE_VERSION_V1_14_11: u32 = 1;
+pub const VOTE_STATE_VERSION_CURRENT: u32 = 2;
+
 // Offset of VoteState::prior_voters, for determining initialization status without deserialization
 const DEFAULT_PRIOR_VOTERS_OFFSET: usize = 114;
 
@@ -30,3 +75,7 @@ pub const VOTE_CREDITS_MAXIMUM_PER_SLOT: u8 = 16;
 pub const HASH_BYTES: usize = 32;
 /// Maximum string length of a base58 encoded hash.
 pub const MAX_BASE58_LEN: usize = 44;
+
+/// Most destinations one `SplitMany` may split into, which keeps the instruction within
+/// the lazy entrypoint's 16 accounts
+pub const MAX_SPLIT_MANY_DESTINATIONS: usize = 8;
@@ -0,0 +1,642 @@
+//! Cross-program invocation helpers for pinocchio programs composing with this one.
+//!
+//! Each helper takes the account infos in the order the processor expects them, encodes
+//! the instruction data on the stack and invokes the stake program. Authorities are
+//! passed through as signers, so they must have signed the outer transaction; the
+//! `_signed` variants take the seeds of PDA authorities owned by the calling program.
+
+use pinocchio::{
+    account_info::AccountInfo,
+    cpi::{get_return_data, invoke_signed},
+    instruction::{AccountMeta, Instruction, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::{
+    instruction::StakeInstruction,
+    state::{Authorized, Lockup, StakeAuthorize},
+};
+
+/// Discriminator, `Authorized` and `Lockup`
+const INITIALIZE_DATA_LEN: usize = 1 + 64 + 48;
+/// Discriminator, new authority and `StakeAuthorize` as a `u32`
+const AUTHORIZE_DATA_LEN: usize = 1 + 32 + 4;
+/// Discriminator and `StakeAuthorize` as a `u32`
+const AUTHORIZE_CHECKED_DATA_LEN: usize = 1 + 4;
+/// Discriminator and a `u64` lamport amount
+const LAMPORTS_DATA_LEN: usize = 1 + 8;
+
+#[inline(always)]
+fn invoke_stake<const ACCOUNTS: usize>(
+    account_metas: &[AccountMeta; ACCOUNTS],
+    account_infos: &[&AccountInfo; ACCOUNTS],
+    data: &[u8],
+    signers: &[Signer],
+) -> ProgramResult {
+    let instruction = Instruction {
+        program_id: &crate::ID,
+        accounts: account_metas,
+        data,
+    };
+    invoke_signed(&instruction, account_infos, signers)
+}
+
+pub(crate) fn initialize_data(
+    authorized: &Authorized,
+    lockup: &Lockup,
+) -> [u8; INITIALIZE_DATA_LEN] {
+    let mut data = [0u8; INITIALIZE_DATA_LEN];
+    data[0] = StakeInstruction::Initialize as u8;
+    data[1..33].copy_from_slice(&authorized.staker);
+    data[33..65].copy_from_slice(&authorized.withdrawer);
+    data[65..73].copy_from_slice(&lockup.unix_timestamp);
+    data[73..81].copy_from_slice(&lockup.epoch);
+    data[81..113].copy_from_slice(&lockup.custodian);
+    data
+}
+
+pub(crate) fn authorize_data(
+    new_authorized: &Pubkey,
+    stake_authorize: StakeAuthorize,
+) -> [u8; AUTHORIZE_DATA_LEN] {
+    let mut data = [0u8; AUTHORIZE_DATA_LEN];
+    data[0] = StakeInstruction::Authorize as u8;
+    data[1..33].copy_from_slice(new_authorized);
+    data[33..37].copy_from_slice(&(stake_authorize as u32).to_le_bytes());
+    data
+}
+
+pub(crate) fn authorize_checked_data(
+    stake_authorize: StakeAuthorize,
+) -> [u8; AUTHORIZE_CHECKED_DATA_LEN] {
+    let mut data = [0u8; AUTHORIZE_CHECKED_DATA_LEN];
+    data[0] = StakeInstruction::AuthorizeChecked as u8;
+    data[1..5].copy_from_slice(&(stake_authorize as u32).to_le_bytes());
+    data
+}
+
+pub(crate) fn lamports_data(
+    instruction: StakeInstruction,
+    lamports: u64,
+) -> [u8; LAMPORTS_DATA_LEN] {
+    let mut data = [0u8; LAMPORTS_DATA_LEN];
+    data[0] = instruction as u8;
+    data[1..].copy_from_slice(&lamports.to_le_bytes());
+    data
+}
+
+/// Initialize an uninitialized, rent-exempt stake account
+pub fn initialize(
+    stake: &AccountInfo,
+    rent_sysvar: &AccountInfo,
+    authorized: &Authorized,
+    lockup: &Lockup,
+) -> ProgramResult {
+    invoke_stake(
+        &[
+            AccountMeta::writable(stake.key()),
+            AccountMeta::readonly(rent_sysvar.key()),
+        ],
+        &[stake, rent_sysvar],
+        &initialize_data(authorized, lockup),
+        &[],
+    )
+}
+
+/// Initialize a stake account with a signing withdraw authority
+pub fn initialize_checked(
+    stake: &AccountInfo,
+    rent_sysvar: &AccountInfo,
+    staker: &AccountInfo,
+    withdrawer: &AccountInfo,
+) -> ProgramResult {
+    invoke_stake(
+        &[
+            AccountMeta::writable(stake.key()),
+            AccountMeta::readonly(rent_sysvar.key()),
+            AccountMeta::readonly(staker.key()),
+            AccountMeta::readonly_signer(withdrawer.key()),
+        ],
+        &[stake, rent_sysvar, staker, withdrawer],
+        &[StakeInstruction::InitializeChecked as u8],
+        &[],
+    )
+}
+
+/// Replace the staker or withdrawer of `stake` with `new_authorized`
+pub fn authorize(
+    stake: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    authority: &AccountInfo,
+    custodian: Option<&AccountInfo>,
+    new_authorized: &Pubkey,
+    stake_authorize: StakeAuthorize,
+) -> ProgramResult {
+    authorize_signed(
+        stake,
+        clock_sysvar,
+        authority,
+        custodian,
+        new_authorized,
+        stake_authorize,
+        &[],
+    )
+}
+
+/// [`authorize`] with `signers` seeds for PDA authorities
+pub fn authorize_signed(
+    stake: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    authority: &AccountInfo,
+    custodian: Option<&AccountInfo>,
+    new_authorized: &Pubkey,
+    stake_authorize: StakeAuthorize,
+    signers: &[Signer],
+) -> ProgramResult {
+    let data = authorize_data(new_authorized, stake_authorize);
+    match custodian {
+        Some(custodian) => invoke_stake(
+            &[
+                AccountMeta::writable(stake.key()),
+                AccountMeta::readonly(clock_sysvar.key()),
+                AccountMeta::readonly_signer(authority.key()),
+                AccountMeta::readonly_signer(custodian.key()),
+            ],
+            &[stake, clock_sysvar, authority, custodian],
+            &data,
+            signers,
+        ),
+        None => invoke_stake(
+            &[
+                AccountMeta::writable(stake.key()),
+                AccountMeta::readonly(clock_sysvar.key()),
+                AccountMeta::readonly_signer(authority.key()),
+            ],
+            &[stake, clock_sysvar, authority],
+            &data,
+            signers,
+        ),
+    }
+}
+
+/// Replace the staker or withdrawer of `stake` with the signing `new_authority`
+pub fn authorize_checked(
+    stake: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    authority: &AccountInfo,
+    new_authority: &AccountInfo,
+    custodian: Option<&AccountInfo>,
+    stake_authorize: StakeAuthorize,
+) -> ProgramResult {
+    authorize_checked_signed(
+        stake,
+        clock_sysvar,
+        authority,
+        new_authority,
+        custodian,
+        stake_authorize,
+        &[],
+    )
+}
+
+/// [`authorize_checked`] with `signers` seeds for PDA authorities
+pub fn authorize_checked_signed(
+    stake: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    authority: &AccountInfo,
+    new_authority: &AccountInfo,
+    custodian: Option<&AccountInfo>,
+    stake_authorize: StakeAuthorize,
+    signers: &[Signer],
+) -> ProgramResult {
+    let data = authorize_checked_data(stake_authorize);
+    match custodian {
+        Some(custodian) => invoke_stake(
+            &[
+                AccountMeta::writable(stake.key()),
+                AccountMeta::readonly(clock_sysvar.key()),
+                AccountMeta::readonly_signer(authority.key()),
+                AccountMeta::readonly_signer(new_authority.key()),
+                AccountMeta::readonly_signer(custodian.key()),
+            ],
+            &[stake, clock_sysvar, authority, new_authority, custodian],
+            &data,
+            signers,
+        ),
+        None => invoke_stake(
+            &[
+                AccountMeta::writable(stake.key()),
+                AccountMeta::readonly(clock_sysvar.key()),
+                AccountMeta::readonly_signer(authority.key()),
+                AccountMeta::readonly_signer(new_authority.key()),
+            ],
+            &[stake, clock_sysvar, authority, new_authority],
+            &data,
+            signers,
+        ),
+    }
+}
+
+/// Delegate `stake` to `vote`
+pub fn delegate(
+    stake: &AccountInfo,
+    vote: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    stake_history_sysvar: &AccountInfo,
+    stake_config: &AccountInfo,
+    stake_authority: &AccountInfo,
+) -> ProgramResult {
+    delegate_signed(
+        stake,
+        vote,
+        clock_sysvar,
+        stake_history_sysvar,
+        stake_config,
+        stake_authority,
+        &[],
+    )
+}
+
+/// [`delegate`] with `signers` seeds for PDA authorities
+pub fn delegate_signed(
+    stake: &AccountInfo,
+    vote: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    stake_history_sysvar: &AccountInfo,
+    stake_config: &AccountInfo,
+    stake_authority: &AccountInfo,
+    signers: &[Signer],
+) -> ProgramResult {
+    invoke_stake(
+        &[
+            AccountMeta::writable(stake.key()),
+            AccountMeta::readonly(vote.key()),
+            AccountMeta::readonly(clock_sysvar.key()),
+            AccountMeta::readonly(stake_history_sysvar.key()),
+            AccountMeta::readonly(stake_config.key()),
+            AccountMeta::readonly_signer(stake_authority.key()),
+        ],
+        &[
+            stake,
+            vote,
+            clock_sysvar,
+            stake_history_sysvar,
+            stake_config,
+            stake_authority,
+        ],
+        &[StakeInstruction::DelegateStake as u8],
+        signers,
+    )
+}
+
+/// Move `lamports` from `stake` into the uninitialized `split_stake`
+pub fn split(
+    stake: &AccountInfo,
+    split_stake: &AccountInfo,
+    stake_authority: &AccountInfo,
+    lamports: u64,
+) -> ProgramResult {
+    split_signed(stake, split_stake, stake_authority, lamports, &[])
+}
+
+/// [`split`] with `signers` seeds for PDA authorities
+pub fn split_signed(
+    stake: &AccountInfo,
+    split_stake: &AccountInfo,
+    stake_authority: &AccountInfo,
+    lamports: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    invoke_stake(
+        &[
+            AccountMeta::writable(stake.key()),
+            AccountMeta::writable(split_stake.key()),
+            AccountMeta::readonly_signer(stake_authority.key()),
+        ],
+        &[stake, split_stake, stake_authority],
+        &lamports_data(StakeInstruction::Split, lamports),
+        signers,
+    )
+}
+
+/// Withdraw `lamports` from `stake` to `recipient`
+pub fn withdraw(
+    stake: &AccountInfo,
+    recipient: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    stake_history_sysvar: &AccountInfo,
+    withdraw_authority: &AccountInfo,
+    custodian: Option<&AccountInfo>,
+    lamports: u64,
+) -> ProgramResult {
+    withdraw_signed(
+        stake,
+        recipient,
+        clock_sysvar,
+        stake_history_sysvar,
+        withdraw_authority,
+        custodian,
+        lamports,
+        &[],
+    )
+}
+
+/// [`withdraw`] with `signers` seeds for PDA authorities
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_signed(
+    stake: &AccountInfo,
+    recipient: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    stake_history_sysvar: &AccountInfo,
+    withdraw_authority: &AccountInfo,
+    custodian: Option<&AccountInfo>,
+    lamports: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    let data = lamports_data(StakeInstruction::Withdraw, lamports);
+    match custodian {
+        Some(custodian) => invoke_stake(
+            &[
+                AccountMeta::writable(stake.key()),
+                AccountMeta::writable(recipient.key()),
+                AccountMeta::readonly(clock_sysvar.key()),
+                AccountMeta::readonly(stake_history_sysvar.key()),
+                AccountMeta::readonly_signer(withdraw_authority.key()),
+                AccountMeta::readonly_signer(custodian.key()),
+            ],
+            &[
+                stake,
+                recipient,
+                clock_sysvar,
+                stake_history_sysvar,
+                withdraw_authority,
+                custodian,
+            ],
+            &data,
+            signers,
+        ),
+        None => invoke_stake(
+            &[
+                AccountMeta::writable(stake.key()),
+                AccountMeta::writable(recipient.key()),
+                AccountMeta::readonly(clock_sysvar.key()),
+                AccountMeta::readonly(stake_history_sysvar.key()),
+                AccountMeta::readonly_signer(withdraw_authority.key()),
+            ],
+            &[
+                stake,
+                recipient,
+                clock_sysvar,
+                stake_history_sysvar,
+                withdraw_authority,
+            ],
+            &data,
+            signers,
+        ),
+    }
+}
+
+/// Deactivate the delegated `stake`
+pub fn deactivate(
+    stake: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    stake_authority: &AccountInfo,
+) -> ProgramResult {
+    deactivate_signed(stake, clock_sysvar, stake_authority, &[])
+}
+
+/// [`deactivate`] with `signers` seeds for PDA authorities
+pub fn deactivate_signed(
+    stake: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    stake_authority: &AccountInfo,
+    signers: &[Signer],
+) -> ProgramResult {
+    invoke_stake(
+        &[
+            AccountMeta::writable(stake.key()),
+            AccountMeta::readonly(clock_sysvar.key()),
+            AccountMeta::readonly_signer(stake_authority.key()),
+        ],
+        &[stake, clock_sysvar, stake_authority],
+        &[StakeInstruction::Deactivate as u8],
+        signers,
+    )
+}
+
+/// Merge `source_stake` into `destination_stake`, draining and closing the source
+pub fn merge(
+    destination_stake: &AccountInfo,
+    source_stake: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    stake_history_sysvar: &AccountInfo,
+    stake_authority: &AccountInfo,
+) -> ProgramResult {
+    merge_signed(
+        destination_stake,
+        source_stake,
+        clock_sysvar,
+        stake_history_sysvar,
+        stake_authority,
+        &[],
+    )
+}
+
+/// [`merge`] with `signers` seeds for PDA authorities
+pub fn merge_signed(
+    destination_stake: &AccountInfo,
+    source_stake: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    stake_history_sysvar: &AccountInfo,
+    stake_authority: &AccountInfo,
+    signers: &[Signer],
+) -> ProgramResult {
+    invoke_stake(
+        &[
+            AccountMeta::writable(destination_stake.key()),
+            AccountMeta::writable(source_stake.key()),
+            AccountMeta::readonly(clock_sysvar.key()),
+            AccountMeta::readonly(stake_history_sysvar.key()),
+            AccountMeta::readonly_signer(stake_authority.key()),
+        ],
+        &[
+            destination_stake,
+            source_stake,
+            clock_sysvar,
+            stake_history_sysvar,
+            stake_authority,
+        ],
+        &[StakeInstruction::Merge as u8],
+        signers,
+    )
+}
+
+/// Move `lamports` of active stake between two stake accounts with the same authorities
+pub fn move_stake(
+    source_stake: &AccountInfo,
+    destination_stake: &AccountInfo,
+    stake_authority: &AccountInfo,
+    lamports: u64,
+) -> ProgramResult {
+    move_stake_signed(
+        source_stake,
+        destination_stake,
+        stake_authority,
+        lamports,
+        &[],
+    )
+}
+
+/// [`move_stake`] with `signers` seeds for PDA authorities
+pub fn move_stake_signed(
+    source_stake: &AccountInfo,
+    destination_stake: &AccountInfo,
+    stake_authority: &AccountInfo,
+    lamports: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    invoke_stake(
+        &[
+            AccountMeta::writable(source_stake.key()),
+            AccountMeta::writable(destination_stake.key()),
+            AccountMeta::readonly_signer(stake_authority.key()),
+        ],
+        &[source_stake, destination_stake, stake_authority],
+        &lamports_data(StakeInstruction::MoveStake, lamports),
+        signers,
+    )
+}
+
+/// Move `lamports` of free balance between two stake accounts with the same authorities
+pub fn move_lamports(
+    source_stake: &AccountInfo,
+    destination_stake: &AccountInfo,
+    stake_authority: &AccountInfo,
+    lamports: u64,
+) -> ProgramResult {
+    move_lamports_signed(
+        source_stake,
+        destination_stake,
+        stake_authority,
+        lamports,
+        &[],
+    )
+}
+
+/// [`move_lamports`] with `signers` seeds for PDA authorities
+pub fn move_lamports_signed(
+    source_stake: &AccountInfo,
+    destination_stake: &AccountInfo,
+    stake_authority: &AccountInfo,
+    lamports: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    invoke_stake(
+        &[
+            AccountMeta::writable(source_stake.key()),
+            AccountMeta::writable(destination_stake.key()),
+            AccountMeta::readonly_signer(stake_authority.key()),
+        ],
+        &[source_stake, destination_stake, stake_authority],
+        &lamports_data(StakeInstruction::MoveLamports, lamports),
+        signers,
+    )
+}
+
+/// Invoke GetMinimumDelegation and read the `u64` it sets as return data
+pub fn get_minimum_delegation() -> Result<u64, ProgramError> {
+    invoke_stake::<0>(
+        &[],
+        &[],
+        &[StakeInstruction::GetMinimumDelegation as u8],
+        &[],
+    )?;
+    let return_data = get_return_data().ok_or(ProgramError::InvalidAccountData)?;
+    parse_minimum_delegation(return_data.program_id(), return_data.as_slice())
+}
+
+/// Checks the return data was set by this program and holds exactly one `u64`
+pub(crate) fn parse_minimum_delegation(
+    program_id: &Pubkey,
+    data: &[u8],
+) -> Result<u64, ProgramError> {
+    if program_id != &crate::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let bytes: [u8; 8] = data
+        .try_into()
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_initialize_data() {
+        let authorized = Authorized {
+            staker: [1; 32],
+            withdrawer: [2; 32],
+        };
+        let lockup = Lockup {
+            unix_timestamp: 3i64.to_le_bytes(),
+            epoch: 4u64.to_le_bytes(),
+            custodian: [5; 32],
+        };
+        let data = initialize_data(&authorized, &lockup);
+
+        assert_eq!(data[0], 0);
+        assert_eq!(data[1..33], [1; 32]);
+        assert_eq!(data[33..65], [2; 32]);
+        assert_eq!(data[65..73], 3i64.to_le_bytes());
+        assert_eq!(data[73..81], 4u64.to_le_bytes());
+        assert_eq!(data[81..], [5; 32]);
+    }
+
+    #[test]
+    fn test_authorize_data() {
+        let data = authorize_data(&[7; 32], StakeAuthorize::Withdrawer);
+        assert_eq!(data[0], 1);
+        assert_eq!(data[1..33], [7; 32]);
+        assert_eq!(data[33..], 1u32.to_le_bytes());
+
+        assert_eq!(
+            authorize_checked_data(StakeAuthorize::Staker),
+            [10, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_lamports_data() {
+        let data = lamports_data(StakeInstruction::Split, 42);
+        assert_eq!(data[0], 3);
+        assert_eq!(data[1..], 42u64.to_le_bytes());
+
+        assert_eq!(lamports_data(StakeInstruction::MoveLamports, 0)[0], 17);
+    }
+
+    #[test]
+    fn test_parse_minimum_delegation() {
+        let data = 1_000_000_000u64.to_le_bytes();
+        assert_eq!(
+            parse_minimum_delegation(&crate::ID, &data),
+            Ok(1_000_000_000)
+        );
+
+        assert_eq!(
+            parse_minimum_delegation(&[1; 32], &data),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        assert_eq!(
+            parse_minimum_delegation(&crate::ID, &data[..7]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            parse_minimum_delegation(&crate::ID, &[0; 9]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! Decision-point logging for the `debug-logs` feature.
+//!
+//! Values go out through `sol_log_64` rather than `msg!` formatting, so a debug build
+//! still never touches the heap. Without the feature the macro expands to nothing and
+//! its arguments aren't evaluated.
+
+/// Logs `$label`, then up to five values as one `sol_log_64` line
+macro_rules! debug_log {
+    ($label:literal $(, $value:expr)* $(,)?) => {
+        #[cfg(feature = "debug-logs")]
+        $crate::debug_log::log($label, &[$(($value) as u64),*]);
+    };
+}
+
+#[cfg(feature = "debug-logs")]
+pub(crate) fn log(label: &str, values: &[u64]) {
+    pinocchio::log::sol_log(label);
+    if !values.is_empty() {
+        let mut padded = [0; 5];
+        for (slot, value) in padded.iter_mut().zip(values) {
+            *slot = *value;
+        }
+        let [a, b, c, d, e] = padded;
+        pinocchio::log::sol_log_64(a, b, c, d, e);
+    }
+}
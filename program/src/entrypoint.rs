@@ -2,25 +2,138 @@
 
 use crate::instruction::{self, StakeInstruction};
 use pinocchio::{
-    account_info::AccountInfo, default_panic_handler, default_allocator, program_entrypoint, program_error::ProgramError, pubkey::Pubkey, ProgramResult
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult
 };
 
-// This is the entrypoint for the program.
-program_entrypoint!(process_instruction);
-//Do not allocate memory.
-// no_allocator!();
-default_allocator!();
-// Use the no_std panic handler.
-default_panic_handler!();
+// The program entrypoint, allocator and panic handler are only emitted for the on-chain
+// build so the crate can be linked as a library without clashing symbols.
 
+// Disclosure information for scanners and auditors, read with `query-security-txt`. The
+// section holds a `#[no_mangle]` static, so like the entrypoint it's only emitted when
+// this crate is the program.
+#[cfg(all(feature = "bpf-entrypoint", not(feature = "no-entrypoint")))]
+solana_security_txt::security_txt! {
+    name: env!("CARGO_PKG_NAME"),
+    project_url: env!("CARGO_PKG_REPOSITORY"),
+    contacts: "link:https://github.com/Princeadxisrael/pinocchio-stake/security/advisories/new",
+    policy: "https://github.com/Princeadxisrael/pinocchio-stake/blob/main/SECURITY.md",
+    source_code: env!("CARGO_PKG_REPOSITORY")
+}
+
+// Browser builds link the state parsers and `sdk` builders only; there is no runtime
+// to call an entrypoint and no syscalls behind it.
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "bpf-entrypoint",
+    not(feature = "no-entrypoint")
+))]
+compile_error!("the entrypoint is for the on-chain build; build wasm32 with `--no-default-features`");
+
+#[cfg(all(feature = "minimal", feature = "logging"))]
+compile_error!("`minimal` strips the log messages that `logging` adds; enable only one");
+#[cfg(all(feature = "minimal", feature = "debug-logs"))]
+compile_error!("`minimal` strips the log messages that `debug-logs` adds; enable only one");
+
+/// Panic hook for the `minimal` build. The runtime still reports the failure, but the
+/// message is never formatted, so the `core::fmt` machinery the default hook pulls in
+/// is left out of the binary.
+#[cfg(feature = "minimal")]
+macro_rules! minimal_panic_handler {
+    () => {
+        #[cfg(target_os = "solana")]
+        #[no_mangle]
+        fn custom_panic(_info: &core::panic::PanicInfo<'_>) {}
+    };
+}
+#[cfg(all(
+    feature = "bpf-entrypoint",
+    not(feature = "lazy-entrypoint"),
+    not(feature = "no-entrypoint")
+))]
+mod program {
+    use pinocchio::{no_allocator, program_entrypoint};
+
+    // the macro takes a bare identifier, not a path
+    use super::process_instruction;
+
+    // This is the entrypoint for the program.
+    program_entrypoint!(process_instruction);
+    // The processors never touch the heap, which each dispatched one's tests check with
+    // `heap_allocations`; an allocation aborts instead of silently costing compute.
+    no_allocator!();
+    // Use the no_std panic handler.
+    #[cfg(not(feature = "minimal"))]
+    pinocchio::default_panic_handler!();
+    #[cfg(feature = "minimal")]
+    minimal_panic_handler!();
+}
+
+// Same, but accounts are parsed one at a time into a small fixed array instead of the
+// full 64-slot one, which is cheaper for instructions that only touch a few accounts.
+#[cfg(all(
+    feature = "bpf-entrypoint",
+    feature = "lazy-entrypoint",
+    not(feature = "no-entrypoint")
+))]
+mod lazy_program {
+    use core::mem::MaybeUninit;
+
+    use pinocchio::{
+        account_info::AccountInfo,
+        entrypoint::{InstructionContext, MaybeAccount},
+        lazy_program_entrypoint, no_allocator,
+        program_error::ProgramError,
+        ProgramResult,
+    };
+
+    /// Accounts handed to the processors. No stake instruction needs more than six;
+    /// the rest leave room for extra signers. An instruction with more accounts fails
+    /// rather than having the ones past this dropped, so no signer goes unseen.
+    const MAX_ACCOUNTS: usize = 16;
+
+    lazy_program_entrypoint!(process_lazy);
+    no_allocator!();
+    #[cfg(not(feature = "minimal"))]
+    pinocchio::default_panic_handler!();
+    #[cfg(feature = "minimal")]
+    minimal_panic_handler!();
+
+    fn process_lazy(mut context: InstructionContext) -> ProgramResult {
+        if context.available() > MAX_ACCOUNTS as u64 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut accounts = [const { MaybeUninit::<AccountInfo>::uninit() }; MAX_ACCOUNTS];
+        let mut count = 0;
+
+        while context.remaining() > 0 {
+            let account = match context.next_account()? {
+                MaybeAccount::Account(account) => account,
+                // duplicates point at an earlier account, and every earlier one is stored
+                MaybeAccount::Duplicated(index) => unsafe {
+                    accounts[index as usize].assume_init_ref().clone()
+                },
+            };
+            accounts[count].write(account);
+            count += 1;
+        }
+
+        let accounts =
+            unsafe { core::slice::from_raw_parts(accounts.as_ptr() as *const AccountInfo, count) };
+        super::process_instruction(context.program_id()?, accounts, context.instruction_data()?)
+    }
+}
+
+/// Dispatches `instruction_data` to the matching `process_*` handler. Exposed so
+/// simulators and other programs can drive the processors without the entrypoint.
 #[inline(always)]
-fn process_instruction(
+pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
     // convenience so we can safely use id() everywhere
-    if *program_id != crate::ID {
+    if !crate::check_id(program_id) {
         return Err(ProgramError::IncorrectProgramId);
     }
 
@@ -42,7 +155,10 @@ fn process_instruction(
     //     return Err(StakeError::EpochRewardsActive.into());
     // }
 
-    match instruction {
+    #[cfg(feature = "paranoid")]
+    let snapshot = crate::helpers::invariants::Snapshot::take(accounts);
+
+    let result = match instruction {
         StakeInstruction::Initialize => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: Initialize");
@@ -65,7 +181,12 @@ fn process_instruction(
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: Split");
 
-            todo!()
+            let lamports = instruction_data
+                .get(..8)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(ProgramError::InvalidInstructionData)?;
+            instruction::process_split(accounts, lamports)
         }
         StakeInstruction::Withdraw => {
             #[cfg(feature = "logging")]
@@ -119,7 +240,7 @@ fn process_instruction(
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: SetLockupChecked");
 
-            todo!()
+            instruction::process_set_lockup_checked(accounts, instruction_data)
         }
         StakeInstruction::GetMinimumDelegation => {
             #[cfg(feature = "logging")]
@@ -150,5 +271,61 @@ fn process_instruction(
             // instruction::process_move_lamports(accounts, lamports)
             todo!()
         }
+        StakeInstruction::WithdrawMax => {
+            #[cfg(feature = "logging")]
+            pinocchio::msg!("Instruction: WithdrawMax");
+
+            instruction::process_withdraw_max(accounts)
+        }
+        StakeInstruction::SplitMany => {
+            #[cfg(feature = "logging")]
+            pinocchio::msg!("Instruction: SplitMany");
+
+            instruction::process_split_many(accounts, instruction_data)
+        }
+        StakeInstruction::AuthorizeBoth => {
+            #[cfg(feature = "logging")]
+            pinocchio::msg!("Instruction: AuthorizeBoth");
+
+            instruction::process_authorize_both(accounts, instruction_data)
+        }
+        StakeInstruction::DeactivateMany => {
+            #[cfg(feature = "logging")]
+            pinocchio::msg!("Instruction: DeactivateMany");
+
+            instruction::process_deactivate_many(accounts, instruction_data)
+        }
+        StakeInstruction::InitializeAndDelegate => {
+            #[cfg(feature = "logging")]
+            pinocchio::msg!("Instruction: InitializeAndDelegate");
+
+            instruction::process_initialize_and_delegate(accounts, instruction_data)
+        }
+        StakeInstruction::PartialDeactivate => {
+            #[cfg(feature = "logging")]
+            pinocchio::msg!("Instruction: PartialDeactivate");
+
+            instruction::process_partial_deactivate(accounts, instruction_data)
+        }
+        StakeInstruction::WithdrawWithSeed => {
+            #[cfg(feature = "logging")]
+            pinocchio::msg!("Instruction: WithdrawWithSeed");
+
+            instruction::process_withdraw_with_seed(accounts, instruction_data)
+        }
+        StakeInstruction::WithdrawExcess => {
+            #[cfg(feature = "logging")]
+            pinocchio::msg!("Instruction: WithdrawExcess");
+
+            instruction::process_withdraw_excess(accounts)
+        }
+    };
+
+    // a failed instruction is rolled back by the runtime, so only successes are checked
+    #[cfg(feature = "paranoid")]
+    if result.is_ok() {
+        snapshot.check(accounts);
     }
+
+    result
 }
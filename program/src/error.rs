@@ -17,63 +17,64 @@ pub trait ToPrimitive {
 }
 
 /// Reasons the Stake might have had an error.
+///
+/// Discriminants are the `ProgramError::Custom` codes the native stake program
+/// returns, so clients and explorers decode failures from either program the same way.
+/// New variants go at the end; existing codes never change.
+#[repr(u32)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum StakeError {
-    // 0
     /// Not enough credits to redeem.
-    NoCreditsToRedeem,
+    NoCreditsToRedeem = 0,
 
     /// Lockup has not yet expired.
-    LockupInForce,
+    LockupInForce = 1,
 
     /// Stake already deactivated.
-    AlreadyDeactivated,
+    AlreadyDeactivated = 2,
 
     /// One re-delegation permitted per epoch.
-    TooSoonToRedelegate,
+    TooSoonToRedelegate = 3,
 
     /// Split amount is more than is staked.
-    InsufficientStake,
+    InsufficientStake = 4,
 
-    // 5
     /// Stake account with transient stake cannot be merged.
-    MergeTransientStake,
+    MergeTransientStake = 5,
 
     /// Stake account merge failed due to different authority, lockups or state.
-    MergeMismatch,
+    MergeMismatch = 6,
 
     /// Custodian address not present.
-    CustodianMissing,
+    CustodianMissing = 7,
 
     /// Custodian signature not present.
-    CustodianSignatureMissing,
+    CustodianSignatureMissing = 8,
 
     /// Insufficient voting activity in the reference vote account.
-    InsufficientReferenceVotes,
+    InsufficientReferenceVotes = 9,
 
-    // 10
     /// Stake account is not delegated to the provided vote account.
-    VoteAddressMismatch,
+    VoteAddressMismatch = 10,
 
     /// Stake account has not been delinquent for the minimum epochs required
     /// for deactivation.
-    MinimumDelinquentEpochsForDeactivationNotMet,
+    MinimumDelinquentEpochsForDeactivationNotMet = 11,
 
     /// Delegation amount is less than the minimum.
-    InsufficientDelegation,
+    InsufficientDelegation = 12,
 
     /// Stake account with transient or inactive stake cannot be redelegated.
-    RedelegateTransientOrInactiveStake,
+    RedelegateTransientOrInactiveStake = 13,
 
     /// Stake redelegation to the same vote account is not permitted.
-    RedelegateToSameVoteAccount,
+    RedelegateToSameVoteAccount = 14,
 
-    // 15
     /// Redelegated stake must be fully activated before deactivation.
-    RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted,
+    RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted = 15,
 
     /// Stake action is not permitted while the epoch rewards period is active.
-    EpochRewardsActive,
+    EpochRewardsActive = 16,
 }
 
 impl From<StakeError> for ProgramError {
@@ -82,6 +83,70 @@ impl From<StakeError> for ProgramError {
     }
 }
 
+impl TryFrom<u32> for StakeError {
+    type Error = ProgramError;
+
+    /// Decodes a `ProgramError::Custom` code returned by the program
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        Self::from_u64(code as u64).ok_or(ProgramError::InvalidArgument)
+    }
+}
+
+impl TryFrom<ProgramError> for StakeError {
+    type Error = ProgramError;
+
+    /// Recovers the variant from a simulation or transaction error; anything that isn't
+    /// one of this program's custom codes is handed back unchanged
+    fn try_from(error: ProgramError) -> Result<Self, Self::Error> {
+        match error {
+            ProgramError::Custom(code) => Self::try_from(code).map_err(|_| error),
+            _ => Err(error),
+        }
+    }
+}
+
+impl core::fmt::Display for StakeError {
+    /// Same messages as the native program's errors
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::NoCreditsToRedeem => "not enough credits to redeem",
+            Self::LockupInForce => "lockup has not yet expired",
+            Self::AlreadyDeactivated => "stake already deactivated",
+            Self::TooSoonToRedelegate => "one re-delegation permitted per epoch",
+            Self::InsufficientStake => "split amount is more than is staked",
+            Self::MergeTransientStake => "stake account with transient stake cannot be merged",
+            Self::MergeMismatch => {
+                "stake account merge failed due to different authority, lockups or state"
+            }
+            Self::CustodianMissing => "custodian address not present",
+            Self::CustodianSignatureMissing => "custodian signature not present",
+            Self::InsufficientReferenceVotes => {
+                "insufficient voting activity in the reference vote account"
+            }
+            Self::VoteAddressMismatch => {
+                "stake account is not delegated to the provided vote account"
+            }
+            Self::MinimumDelinquentEpochsForDeactivationNotMet => {
+                "stake account has not been delinquent for the minimum epochs required for \
+                 deactivation"
+            }
+            Self::InsufficientDelegation => "delegation amount is less than the minimum",
+            Self::RedelegateTransientOrInactiveStake => {
+                "stake account with transient or inactive stake cannot be redelegated"
+            }
+            Self::RedelegateToSameVoteAccount => {
+                "stake redelegation to the same vote account is not permitted"
+            }
+            Self::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted => {
+                "redelegated stake must be fully activated before deactivation"
+            }
+            Self::EpochRewardsActive => {
+                "stake action is not permitted while the epoch rewards period is active"
+            }
+        })
+    }
+}
+
 impl FromPrimitive for StakeError {
     #[inline]
     fn from_i64(n: i64) -> Option<Self> {
@@ -391,3 +456,172 @@ impl TryFrom<InstructionError> for ProgramError {
 pub(crate) fn to_program_error(e: InstructionError) -> ProgramError {
     ProgramError::try_from(e).unwrap_or(ProgramError::InvalidAccountData)
 }
+
+/// Validation an account failed, logged with its index by [`AccountError`]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AccountCheck {
+    /// Not owned by this program
+    Owner = 0,
+    /// Data isn't the stake account size
+    Size = 1,
+    /// Data doesn't hold a stake state the instruction accepts
+    State = 2,
+    /// Passed read-only where the instruction writes it
+    Writable = 3,
+    /// Not the sysvar the instruction expects at this position
+    Address = 4,
+}
+
+/// A `ProgramError` caused by the account at `index` in the instruction's account list.
+///
+/// Converting it into the `ProgramError` logs the index and the failed check, so with
+/// several stake accounts in a transaction the failing one can be told apart. The
+/// error code itself is unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AccountError {
+    pub index: usize,
+    pub check: AccountCheck,
+    pub error: ProgramError,
+}
+
+impl AccountError {
+    pub(crate) fn new(index: usize, check: AccountCheck, error: ProgramError) -> Self {
+        Self {
+            index,
+            check,
+            error,
+        }
+    }
+}
+
+impl From<AccountError> for ProgramError {
+    fn from(e: AccountError) -> Self {
+        #[cfg(not(feature = "minimal"))]
+        {
+            pinocchio::log::sol_log(
+                "Account check failed: index, check (0 owner, 1 size, 2 state, 3 writable, 4 address)",
+            );
+            pinocchio::log::sol_log_64(e.index as u64, e.check as u64, 0, 0, 0);
+        }
+        e.error
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_sdk::{
+        program_error::ProgramError as NativeProgramError,
+        stake::instruction::StakeError as NativeStakeError,
+    };
+
+    #[test]
+    fn test_codes_match_native() {
+        let pairs = [
+            (
+                StakeError::NoCreditsToRedeem,
+                NativeStakeError::NoCreditsToRedeem,
+            ),
+            (StakeError::LockupInForce, NativeStakeError::LockupInForce),
+            (
+                StakeError::AlreadyDeactivated,
+                NativeStakeError::AlreadyDeactivated,
+            ),
+            (
+                StakeError::TooSoonToRedelegate,
+                NativeStakeError::TooSoonToRedelegate,
+            ),
+            (
+                StakeError::InsufficientStake,
+                NativeStakeError::InsufficientStake,
+            ),
+            (
+                StakeError::MergeTransientStake,
+                NativeStakeError::MergeTransientStake,
+            ),
+            (StakeError::MergeMismatch, NativeStakeError::MergeMismatch),
+            (
+                StakeError::CustodianMissing,
+                NativeStakeError::CustodianMissing,
+            ),
+            (
+                StakeError::CustodianSignatureMissing,
+                NativeStakeError::CustodianSignatureMissing,
+            ),
+            (
+                StakeError::InsufficientReferenceVotes,
+                NativeStakeError::InsufficientReferenceVotes,
+            ),
+            (
+                StakeError::VoteAddressMismatch,
+                NativeStakeError::VoteAddressMismatch,
+            ),
+            (
+                StakeError::MinimumDelinquentEpochsForDeactivationNotMet,
+                NativeStakeError::MinimumDelinquentEpochsForDeactivationNotMet,
+            ),
+            (
+                StakeError::InsufficientDelegation,
+                NativeStakeError::InsufficientDelegation,
+            ),
+            (
+                StakeError::RedelegateTransientOrInactiveStake,
+                NativeStakeError::RedelegateTransientOrInactiveStake,
+            ),
+            (
+                StakeError::RedelegateToSameVoteAccount,
+                NativeStakeError::RedelegateToSameVoteAccount,
+            ),
+            (
+                StakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted,
+                NativeStakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted,
+            ),
+            (
+                StakeError::EpochRewardsActive,
+                NativeStakeError::EpochRewardsActive,
+            ),
+        ];
+
+        for (error, native) in pairs {
+            let NativeProgramError::Custom(native_code) = NativeProgramError::from(native.clone())
+            else {
+                panic!("{error:?} is not a custom error natively");
+            };
+            assert_eq!(
+                ProgramError::from(error.clone()),
+                ProgramError::Custom(native_code)
+            );
+            assert_eq!(StakeError::try_from(native_code), Ok(error.clone()));
+            assert_eq!(error.to_string(), native.to_string());
+        }
+    }
+
+    #[test]
+    fn test_account_error_keeps_code() {
+        let error = AccountError::new(1, AccountCheck::Owner, ProgramError::InvalidAccountOwner);
+        assert_eq!(ProgramError::from(error), ProgramError::InvalidAccountOwner);
+    }
+
+    #[test]
+    fn test_program_error_round_trip() {
+        let error = ProgramError::from(StakeError::MergeMismatch);
+        assert_eq!(StakeError::try_from(error), Ok(StakeError::MergeMismatch));
+        assert_eq!(
+            StakeError::try_from(ProgramError::InvalidAccountData),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            StakeError::try_from(ProgramError::Custom(100)),
+            Err(ProgramError::Custom(100))
+        );
+    }
+
+    #[test]
+    fn test_unknown_code_does_not_decode() {
+        assert_eq!(
+            StakeError::try_from(StakeError::EpochRewardsActive as u32 + 1),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
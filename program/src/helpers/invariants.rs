@@ -0,0 +1,109 @@
+//! Post-condition checks for the `paranoid` feature.
+//!
+//! `Snapshot::take` records the lamports and stake state tags of the accounts before a
+//! processor runs; `Snapshot::check` then asserts that the instruction moved lamports
+//! without creating or destroying any, that every delegation still fits in its
+//! account's balance above the rent-exempt reserve, and that each stake account only
+//! moved along a state transition the stake program can make. A violation is a bug in
+//! a processor, so it panics and the transaction aborts rather than returning an error
+//! a caller could handle.
+
+use pinocchio::account_info::AccountInfo;
+
+use crate::state::{StakeStateTag, StakeStateV2};
+
+/// Accounts whose state tags are tracked; later ones only count towards the lamport
+/// total
+const MAX_TRACKED: usize = 16;
+
+pub(crate) struct Snapshot {
+    lamports: u128,
+    tags: [Option<StakeStateTag>; MAX_TRACKED],
+}
+
+impl Snapshot {
+    pub(crate) fn take(accounts: &[AccountInfo]) -> Self {
+        let mut tags = [None; MAX_TRACKED];
+        for (tag, account) in tags.iter_mut().zip(accounts) {
+            *tag = stake_tag(account);
+        }
+        Self {
+            lamports: total_lamports(accounts),
+            tags,
+        }
+    }
+
+    pub(crate) fn check(&self, accounts: &[AccountInfo]) {
+        assert_eq!(
+            total_lamports(accounts),
+            self.lamports,
+            "paranoid: lamports not conserved"
+        );
+
+        for (index, account) in accounts.iter().enumerate() {
+            let after = stake_tag(account);
+            if let Some(Some(before)) = self.tags.get(index) {
+                let after = after.expect("paranoid: stake account became unreadable");
+                assert!(
+                    is_allowed_transition(*before, after),
+                    "paranoid: account {index} moved from {before:?} to {after:?}"
+                );
+            }
+            if after == Some(StakeStateTag::Stake) {
+                check_stake_fits(index, account);
+            }
+        }
+    }
+}
+
+/// Sum over distinct accounts; a duplicated account is counted once
+fn total_lamports(accounts: &[AccountInfo]) -> u128 {
+    accounts
+        .iter()
+        .enumerate()
+        .filter(|(index, account)| {
+            !accounts[..*index]
+                .iter()
+                .any(|prior| prior.key() == account.key())
+        })
+        .map(|(_, account)| account.lamports() as u128)
+        .sum()
+}
+
+/// Tag of a program-owned, stake-sized account; `None` for anything else
+fn stake_tag(account: &AccountInfo) -> Option<StakeStateTag> {
+    if !account.is_owned_by(&crate::ID) || account.data_len() != StakeStateV2::size_of() {
+        return None;
+    }
+    StakeStateV2::peek_tag(&account.try_borrow_data().ok()?).ok()
+}
+
+fn is_allowed_transition(before: StakeStateTag, after: StakeStateTag) -> bool {
+    use StakeStateTag::*;
+
+    matches!(
+        (before, after),
+        // Initialize, or the destination of a split or move
+        (Uninitialized, Uninitialized | Initialized | Stake)
+            // Delegate, or drained by a withdraw, split or merge
+            | (Initialized, Initialized | Stake | Uninitialized)
+            // a delegated account never goes back to Initialized
+            | (Stake, Stake | Uninitialized)
+            | (RewardsPool, RewardsPool)
+    )
+}
+
+fn check_stake_fits(index: usize, account: &AccountInfo) {
+    let data = account
+        .try_borrow_data()
+        .expect("paranoid: stake account borrowed");
+    let Ok(StakeStateV2::Stake(meta, stake, _)) = StakeStateV2::deserialize(&data) else {
+        panic!("paranoid: account {index} has an invalid Stake state");
+    };
+    let reserve = meta.rent_exempt_reserve();
+    let delegated = stake.delegation.delegated_stake();
+    assert!(
+        delegated <= account.lamports().saturating_sub(reserve),
+        "paranoid: account {index} delegates more than its balance above the reserve"
+    );
+}
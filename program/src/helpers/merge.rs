@@ -1,12 +1,13 @@
 use {
-    super::checked_add,
+    super::{checked_add, get_stake_status},
     crate::{
-        consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
         error::StakeError,
-        state::{Delegation, Meta, Stake, StakeFlags, StakeHistoryGetEntry, StakeStateV2},
+        state::{
+            Delegation, Meta, Stake, StakeActivationStatus, StakeFlags, StakeHistoryGetEntry,
+            StakeStateV2,
+        },
     },
     pinocchio::{
-        msg,
         program_error::ProgramError,
         sysvars::clock::{Clock, Epoch},
         ProgramResult,
@@ -43,34 +44,53 @@ impl MergeKind {
         clock: &Clock,
         stake_history: &T,
     ) -> Result<Self, ProgramError> {
-        match stake_state {
+        let kind = match stake_state {
             StakeStateV2::Stake(meta, stake, stake_flags) => {
                 // stake must not be in a transient state. Transient here meaning
                 // activating or deactivating with non-zero effective stake.
-                let status = stake.delegation.stake_activating_and_deactivating(
-                    clock.epoch.to_le_bytes(),
-                    stake_history,
-                    PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
-                );
+                let status = get_stake_status(stake, clock.epoch, stake_history);
 
-                let effective = u64::from_le_bytes(status.effective);
-                let activating = u64::from_le_bytes(status.activating);
-                let deactivating = u64::from_le_bytes(status.deactivating);
+                let StakeActivationStatus {
+                    effective,
+                    activating,
+                    deactivating,
+                } = status;
+                debug_log!(
+                    "merge: effective, activating, deactivating",
+                    effective,
+                    activating,
+                    deactivating
+                );
 
                 match (effective, activating, deactivating) {
-                    (0, 0, 0) => Ok(Self::Inactive(*meta, stake_lamports, *stake_flags)),
-                    (0, _, _) => Ok(Self::ActivationEpoch(*meta, *stake, *stake_flags)),
-                    (_, 0, 0) => Ok(Self::FullyActive(*meta, *stake)),
+                    (0, 0, 0) => Self::Inactive(*meta, stake_lamports, *stake_flags),
+                    (0, _, _) => Self::ActivationEpoch(*meta, *stake, *stake_flags),
+                    (_, 0, 0) => Self::FullyActive(*meta, *stake),
                     _ => {
                         let err = StakeError::MergeTransientStake;
-                        Err(err.into())
+                        return Err(err.into());
                     }
                 }
             }
             StakeStateV2::Initialized(meta) => {
-                Ok(Self::Inactive(*meta, stake_lamports, StakeFlags::empty()))
+                Self::Inactive(*meta, stake_lamports, StakeFlags::empty())
             }
-            _ => Err(ProgramError::InvalidAccountData),
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        debug_log!(
+            "merge: kind (0 inactive, 1 activation epoch, 2 fully active)",
+            kind.debug_code()
+        );
+        Ok(kind)
+    }
+
+    /// Stable number for the variant, for `debug_log!`
+    #[cfg(feature = "debug-logs")]
+    fn debug_code(&self) -> u64 {
+        match self {
+            Self::Inactive(..) => 0,
+            Self::ActivationEpoch(..) => 1,
+            Self::FullyActive(..) => 2,
         }
     }
 
@@ -86,7 +106,8 @@ impl MergeKind {
         if stake.authorized == source.authorized && can_merge_lockups {
             Ok(())
         } else {
-            msg!("Unable to merge due to metadata mismatch");
+            #[cfg(not(feature = "minimal"))]
+            pinocchio::msg!("Unable to merge due to metadata mismatch");
             Err(StakeError::MergeMismatch.into())
         }
     }
@@ -96,14 +117,16 @@ impl MergeKind {
         source: &Delegation,
     ) -> ProgramResult {
         if stake.voter_pubkey != source.voter_pubkey {
-            msg!("Unable to merge due to voter mismatch");
+            #[cfg(not(feature = "minimal"))]
+            pinocchio::msg!("Unable to merge due to voter mismatch");
             Err(StakeError::MergeMismatch.into())
-        } else if u64::from_le_bytes(stake.deactivation_epoch) == Epoch::MAX
-            && u64::from_le_bytes(source.deactivation_epoch) == Epoch::MAX
+        } else if stake.deactivation_epoch() == Epoch::MAX
+            && source.deactivation_epoch() == Epoch::MAX
         {
             Ok(())
         } else {
-            msg!("Unable to merge due to stake deactivation");
+            #[cfg(not(feature = "minimal"))]
+            pinocchio::msg!("Unable to merge due to stake deactivation");
             Err(StakeError::MergeMismatch.into())
         }
     }
@@ -128,8 +151,7 @@ impl MergeKind {
                 Self::Inactive(_, source_lamports, source_stake_flags),
             ) => {
                 stake.delegation.stake =
-                    checked_add(u64::from_le_bytes(stake.delegation.stake), source_lamports)?
-                        .to_le_bytes();
+                    checked_add(stake.delegation.delegated_stake(), source_lamports)?.to_le_bytes();
                 Some(StakeStateV2::Stake(
                     meta,
                     stake,
@@ -141,8 +163,8 @@ impl MergeKind {
                 Self::ActivationEpoch(source_meta, source_stake, source_stake_flags),
             ) => {
                 let source_lamports = checked_add(
-                    u64::from_le_bytes(source_meta.rent_exempt_reserve),
-                    u64::from_le_bytes(source_stake.delegation.stake),
+                    source_meta.rent_exempt_reserve(),
+                    source_stake.delegation.delegated_stake(),
                 )?;
                 merge_delegation_stake_and_credits_observed(
                     &mut stake,
@@ -162,7 +184,7 @@ impl MergeKind {
                 // withdrawable `lamports`
                 merge_delegation_stake_and_credits_observed(
                     &mut stake,
-                    u64::from_le_bytes(source_stake.delegation.stake),
+                    source_stake.delegation.delegated_stake(),
                     source_stake.credits_observed(),
                 )?;
                 Some(StakeStateV2::Stake(meta, stake, StakeFlags::empty()))
@@ -184,11 +206,8 @@ pub(crate) fn merge_delegation_stake_and_credits_observed(
 
     stake.set_credits_observed(credits_observed);
 
-    stake.delegation.stake = checked_add(
-        u64::from_le_bytes(stake.delegation.stake),
-        absorbed_lamports,
-    )?
-    .to_le_bytes();
+    stake.delegation.stake =
+        checked_add(stake.delegation.delegated_stake(), absorbed_lamports)?.to_le_bytes();
     Ok(())
 }
 
@@ -200,16 +219,11 @@ pub(crate) fn stake_weighted_credits_observed(
     if stake.credits_observed() == absorbed_credits_observed {
         Some(stake.credits_observed())
     } else {
-        let total_stake = u128::from(
-            checked_add(
-                u64::from_le_bytes(stake.delegation.stake),
-                absorbed_lamports,
-            )
-            .ok()?,
-        );
+        let total_stake =
+            u128::from(checked_add(stake.delegation.delegated_stake(), absorbed_lamports).ok()?);
 
         let stake_weighted_credits = u128::from(stake.credits_observed())
-            .checked_mul(u128::from(u64::from_le_bytes(stake.delegation.stake)))?;
+            .checked_mul(u128::from(stake.delegation.delegated_stake()))?;
         let absorbed_weighted_credits =
             u128::from(absorbed_credits_observed).checked_mul(u128::from(absorbed_lamports))?;
         // Discard fractional credits as a merge side-effect friction by taking
@@ -221,3 +235,348 @@ pub(crate) fn stake_weighted_credits_observed(
         u64::try_from(total_weighted_credits.checked_div(total_stake)?).ok()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{stake_weighted_credits_observed, MergeKind};
+    use crate::{
+        error::StakeError,
+        state::{
+            Authorized, Delegation, Lockup, Meta, Stake, StakeFlags, StakeHistory, StakeStateV2,
+        },
+    };
+    use pinocchio::{program_error::ProgramError, sysvars::clock::Clock};
+    use proptest::prelude::*;
+
+    fn stake(stake: u64, credits_observed: u64) -> Stake {
+        Stake::new(Delegation::new(&[7; 32], stake, u64::MAX), credits_observed)
+    }
+
+    const EPOCH: u64 = 10;
+    const RESERVE: u64 = 100;
+    const FLAGS: StakeFlags = StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED;
+
+    /// A stake of 500 observing 5 credits, delegated to `voter` from `activation_epoch`
+    /// and deactivated at `deactivation_epoch`
+    fn delegated(voter: u8, activation_epoch: u64, deactivation_epoch: u64) -> Stake {
+        let mut delegation = Delegation::new(&[voter; 32], 500, activation_epoch);
+        delegation.set_deactivation_epoch(deactivation_epoch);
+        Stake::new(delegation, 5)
+    }
+
+    fn meta() -> Meta {
+        Meta::new(RESERVE, Authorized::auto(&[1; 32]), Lockup::default())
+    }
+
+    /// One account of each merge kind at `EPOCH`, with its lamports
+    fn accounts() -> [(&'static str, StakeStateV2, u64); 5] {
+        [
+            ("initialized", StakeStateV2::Initialized(meta()), 1_100),
+            (
+                "deactivated",
+                StakeStateV2::Stake(meta(), delegated(7, 0, 1), StakeFlags::empty()),
+                1_100,
+            ),
+            (
+                "activating this epoch",
+                StakeStateV2::Stake(meta(), delegated(7, EPOCH, u64::MAX), FLAGS),
+                600,
+            ),
+            (
+                "active",
+                StakeStateV2::Stake(
+                    meta(),
+                    delegated(7, u64::MAX, u64::MAX),
+                    StakeFlags::empty(),
+                ),
+                600,
+            ),
+            (
+                "deactivating",
+                StakeStateV2::Stake(meta(), delegated(7, u64::MAX, EPOCH), StakeFlags::empty()),
+                600,
+            ),
+        ]
+    }
+
+    /// The merge `process_merge` does, destination first
+    fn merge(
+        destination: &StakeStateV2,
+        destination_lamports: u64,
+        source: &StakeStateV2,
+        source_lamports: u64,
+    ) -> Result<Option<StakeStateV2>, ProgramError> {
+        let clock = Clock {
+            epoch: EPOCH,
+            ..Clock::default()
+        };
+        let history = StakeHistory::default();
+        let destination =
+            MergeKind::get_if_mergeable(destination, destination_lamports, &clock, &history)?;
+        let source = MergeKind::get_if_mergeable(source, source_lamports, &clock, &history)?;
+        destination.merge(source, &clock)
+    }
+
+    fn mismatch() -> Result<Option<StakeStateV2>, ProgramError> {
+        Err(StakeError::MergeMismatch.into())
+    }
+
+    fn transient() -> Result<Option<StakeStateV2>, ProgramError> {
+        Err(StakeError::MergeTransientStake.into())
+    }
+
+    /// `state` with its delegated stake and credits replaced
+    fn restaked(
+        state: &StakeStateV2,
+        stake: u64,
+        credits_observed: u64,
+        flags: StakeFlags,
+    ) -> StakeStateV2 {
+        let StakeStateV2::Stake(meta, mut delegated, _) = *state else {
+            panic!("expected a Stake state, got {state:?}");
+        };
+        delegated.delegation.set_stake(stake);
+        delegated.set_credits_observed(credits_observed);
+        StakeStateV2::Stake(meta, delegated, flags)
+    }
+
+    #[test]
+    fn test_merge_kind_matrix() {
+        let accounts = accounts();
+        let [_, _, (_, activating, _), (_, active, _), _] = accounts;
+
+        // rows are destinations, columns sources, in the order of `accounts`
+        let expected: [[Result<Option<StakeStateV2>, ProgramError>; 5]; 5] = [
+            [Ok(None), Ok(None), Ok(None), mismatch(), transient()],
+            [Ok(None), Ok(None), Ok(None), mismatch(), transient()],
+            [
+                // an inactive source stakes all its lamports, reserve included
+                Ok(Some(restaked(&activating, 1_600, 5, FLAGS))),
+                Ok(Some(restaked(&activating, 1_600, 5, FLAGS))),
+                // as does one activating, whatever it had delegated
+                Ok(Some(restaked(&activating, 1_100, 5, FLAGS))),
+                mismatch(),
+                transient(),
+            ],
+            [
+                mismatch(),
+                mismatch(),
+                mismatch(),
+                // the source's reserve stays undelegated, and the flags are cleared
+                Ok(Some(restaked(&active, 1_000, 5, StakeFlags::empty()))),
+                transient(),
+            ],
+            [
+                transient(),
+                transient(),
+                transient(),
+                transient(),
+                transient(),
+            ],
+        ];
+
+        for ((destination_name, destination, destination_lamports), expected) in
+            accounts.iter().zip(expected)
+        {
+            for ((source_name, source, source_lamports), expected) in accounts.iter().zip(expected)
+            {
+                assert_eq!(
+                    merge(destination, *destination_lamports, source, *source_lamports),
+                    expected,
+                    "{source_name} into {destination_name}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_mismatched_voters() {
+        let active = |voter| {
+            StakeStateV2::Stake(
+                meta(),
+                delegated(voter, u64::MAX, u64::MAX),
+                StakeFlags::empty(),
+            )
+        };
+        let activating = |voter| {
+            StakeStateV2::Stake(
+                meta(),
+                delegated(voter, EPOCH, u64::MAX),
+                StakeFlags::empty(),
+            )
+        };
+        let deactivated =
+            |voter| StakeStateV2::Stake(meta(), delegated(voter, 0, 1), StakeFlags::empty());
+
+        assert_eq!(merge(&active(7), 600, &active(8), 600), mismatch());
+        assert_eq!(merge(&activating(7), 600, &activating(8), 600), mismatch());
+
+        // an inactive account's voter doesn't carry over, so it needn't match
+        assert_eq!(
+            merge(&activating(7), 600, &deactivated(8), 600),
+            Ok(Some(restaked(
+                &activating(7),
+                1_100,
+                5,
+                StakeFlags::empty()
+            )))
+        );
+        assert_eq!(merge(&deactivated(7), 600, &activating(8), 600), Ok(None));
+    }
+
+    #[test]
+    fn test_merge_mismatched_credits() {
+        let with_credits = |activation_epoch, credits_observed| {
+            let mut stake = delegated(7, activation_epoch, u64::MAX);
+            stake.set_credits_observed(credits_observed);
+            StakeStateV2::Stake(meta(), stake, StakeFlags::empty())
+        };
+
+        // weighted by the stake each side brings, rounded up: (10 * 500 + 20 * 500) / 1_000
+        let destination = with_credits(u64::MAX, 10);
+        assert_eq!(
+            merge(&destination, 600, &with_credits(u64::MAX, 20), 600),
+            Ok(Some(restaked(&destination, 1_000, 15, StakeFlags::empty())))
+        );
+        // (10 * 500 + 21 * 500) / 1_000 = 15.5
+        assert_eq!(
+            merge(&destination, 600, &with_credits(u64::MAX, 21), 600),
+            Ok(Some(restaked(&destination, 1_000, 16, StakeFlags::empty())))
+        );
+
+        // an activating source brings its reserve too: (10 * 500 + 20 * 600) / 1_100
+        let destination = with_credits(EPOCH, 10);
+        assert_eq!(
+            merge(&destination, 600, &with_credits(EPOCH, 20), 600),
+            Ok(Some(restaked(&destination, 1_100, 16, StakeFlags::empty())))
+        );
+    }
+
+    #[test]
+    fn test_merge_metadata_mismatch() {
+        let stake = delegated(7, u64::MAX, u64::MAX);
+        let state = |meta| StakeStateV2::Stake(meta, stake, StakeFlags::empty());
+        let merged = |meta| -> Result<Option<StakeStateV2>, ProgramError> {
+            Ok(Some(restaked(&state(meta), 1_000, 5, StakeFlags::empty())))
+        };
+
+        let mut other_staker = meta();
+        other_staker.authorized.staker = [2; 32];
+        assert_eq!(
+            merge(&state(meta()), 600, &state(other_staker), 600),
+            mismatch()
+        );
+
+        let mut other_withdrawer = meta();
+        other_withdrawer.authorized.withdrawer = [2; 32];
+        assert_eq!(
+            merge(&state(meta()), 600, &state(other_withdrawer), 600),
+            mismatch()
+        );
+
+        // lockups must match while either is in force, and needn't once both expire
+        let locked = |epoch| {
+            let mut meta = meta();
+            meta.lockup.set_epoch(epoch);
+            meta
+        };
+        assert_eq!(
+            merge(&state(locked(EPOCH + 1)), 600, &state(meta()), 600),
+            mismatch()
+        );
+        assert_eq!(
+            merge(&state(meta()), 600, &state(locked(EPOCH + 1)), 600),
+            mismatch()
+        );
+        assert_eq!(
+            merge(
+                &state(locked(EPOCH + 1)),
+                600,
+                &state(locked(EPOCH + 1)),
+                600
+            ),
+            merged(locked(EPOCH + 1))
+        );
+        assert_eq!(
+            merge(&state(locked(EPOCH)), 600, &state(locked(EPOCH - 1)), 600),
+            merged(locked(EPOCH))
+        );
+
+        // the reserve has no bearing on mergeability
+        let mut other_reserve = meta();
+        other_reserve.set_rent_exempt_reserve(RESERVE + 1);
+        assert_eq!(
+            merge(&state(meta()), 600, &state(other_reserve), 601),
+            merged(meta())
+        );
+    }
+
+    #[test]
+    fn test_bootstrap_stake_is_fully_active() {
+        let meta = Meta::auto(&[1; 32]);
+        let bootstrap = stake(1_000, 0);
+        let state = StakeStateV2::Stake(meta, bootstrap, StakeFlags::empty());
+
+        // even at epoch 0 with no history, never in its activation epoch
+        assert_eq!(
+            MergeKind::get_if_mergeable(&state, 1_000, &Clock::default(), &StakeHistory::default()),
+            Ok(MergeKind::FullyActive(meta, bootstrap))
+        );
+
+        let mut deactivating = bootstrap;
+        deactivating.delegation.set_deactivation_epoch(0);
+        let state = StakeStateV2::Stake(meta, deactivating, StakeFlags::empty());
+        assert_eq!(
+            MergeKind::get_if_mergeable(&state, 1_000, &Clock::default(), &StakeHistory::default()),
+            Err(ProgramError::from(StakeError::MergeTransientStake))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn split_then_merge_restores_delegation(
+            delegated in 1..u64::MAX / 2,
+            split_fraction in 0.0..1.0f64,
+            credits_observed: u64,
+        ) {
+            let original = stake(delegated, credits_observed);
+            let split_amount = ((delegated as f64) * split_fraction) as u64;
+
+            let mut remaining = original;
+            let split = remaining.split(split_amount, split_amount).unwrap();
+            prop_assert_eq!(
+                remaining.delegation.delegated_stake()
+                    + split.delegation.delegated_stake(),
+                delegated
+            );
+
+            let meta = Meta::auto(&[1; 32]);
+            let merged = MergeKind::FullyActive(meta, remaining)
+                .merge(MergeKind::FullyActive(meta, split), &Clock::default())
+                .unwrap()
+                .unwrap();
+            let crate::state::StakeStateV2::Stake(_, merged_stake, _) = merged else {
+                panic!("merge of two active stakes must produce a Stake");
+            };
+            prop_assert_eq!(merged_stake.delegation, original.delegation);
+            prop_assert_eq!(merged_stake.credits_observed(), credits_observed);
+        }
+
+        #[test]
+        fn weighted_credits_stay_between_inputs(
+            stake_amount in 1..u64::MAX / 2,
+            absorbed_lamports in 1..u64::MAX / 2,
+            credits_observed: u64,
+            absorbed_credits_observed: u64,
+        ) {
+            let credits = stake_weighted_credits_observed(
+                &stake(stake_amount, credits_observed),
+                absorbed_lamports,
+                absorbed_credits_observed,
+            )
+            .unwrap();
+            prop_assert!(credits >= credits_observed.min(absorbed_credits_observed));
+            prop_assert!(credits <= credits_observed.max(absorbed_credits_observed));
+        }
+    }
+}
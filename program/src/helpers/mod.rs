@@ -1,7 +1,67 @@
+#[cfg(feature = "paranoid")]
+pub(crate) mod invariants;
 pub(crate) mod merge;
 pub(crate) use merge::*;
 use pinocchio::program_error::ProgramError;
 
+use crate::state::{FeatureSet, Stake, StakeActivationStatus, StakeHistoryGetEntry};
+
+/// Effective, activating and deactivating stake of `stake` at `epoch`, at the
+/// warmup/cooldown rate mainnet applies. Everything that classifies a stake
+/// account by its activation goes through this or `get_stake_status_with_features`.
+#[inline(always)]
+pub(crate) fn get_stake_status<T: StakeHistoryGetEntry>(
+    stake: &Stake,
+    epoch: u64,
+    stake_history: &T,
+) -> StakeActivationStatus {
+    get_stake_status_with_features(stake, epoch, stake_history, &FeatureSet::MAINNET)
+}
+
+/// `get_stake_status` at the warmup/cooldown rate `features` selects
+#[inline(always)]
+pub(crate) fn get_stake_status_with_features<T: StakeHistoryGetEntry>(
+    stake: &Stake,
+    epoch: u64,
+    stake_history: &T,
+    features: &FeatureSet,
+) -> StakeActivationStatus {
+    stake.delegation.stake_activating_and_deactivating(
+        epoch.to_le_bytes(),
+        stake_history,
+        features.warmup_cooldown_rate_epoch(),
+    )
+}
+
 pub(crate) fn checked_add(a: u64, b: u64) -> Result<u64, ProgramError> {
     a.checked_add(b).ok_or(ProgramError::InsufficientFunds)
 }
+
+// Lamport and stake arithmetic goes through these so that `strict-math` builds surface every
+// over/underflow as `ArithmeticOverflow`. Default builds keep the native program's saturating
+// behavior. Subtractions that clamp to zero on purpose (e.g. a balance deficit that may be
+// negative) should keep calling `saturating_sub` directly.
+
+#[inline(always)]
+pub(crate) fn lamports_add(a: u64, b: u64) -> Result<u64, ProgramError> {
+    #[cfg(feature = "strict-math")]
+    {
+        a.checked_add(b).ok_or(ProgramError::ArithmeticOverflow)
+    }
+    #[cfg(not(feature = "strict-math"))]
+    {
+        Ok(a.saturating_add(b))
+    }
+}
+
+#[inline(always)]
+pub(crate) fn lamports_sub(a: u64, b: u64) -> Result<u64, ProgramError> {
+    #[cfg(feature = "strict-math")]
+    {
+        a.checked_sub(b).ok_or(ProgramError::ArithmeticOverflow)
+    }
+    #[cfg(not(feature = "strict-math"))]
+    {
+        Ok(a.saturating_sub(b))
+    }
+}
@@ -0,0 +1,472 @@
+//! Accounts of each instruction, in their documented order.
+//!
+//! `try_from` runs the checks that don't need the account state up front: stake
+//! accounts must be owned by this program, have the stake account size and, where the
+//! instruction writes them, be writable. Failures are reported with the account index
+//! (see `AccountError`). Signers are still collected from the whole account list; see
+//! the note in `split.rs`.
+//!
+//! Sysvar accounts that native clients pass at fixed positions are optional, so
+//! transactions built by older clients keep working without newer ones having to pass
+//! them. A clock account that is passed is checked to be the Clock sysvar, as the
+//! native program did; the stake history account never was, so it isn't here either.
+//! Their data is never read: the processors get every sysvar through `SysvarProvider`.
+
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    consts::{CLOCK_ID, MAX_SPLIT_MANY_DESTINATIONS},
+    error::{AccountCheck, AccountError},
+    state::StakeStateV2,
+};
+
+/// The account at `index`, checked to be a writable stake account
+fn writable_stake_account(
+    accounts: &[AccountInfo],
+    index: usize,
+) -> Result<&AccountInfo, ProgramError> {
+    let account = writable_account(accounts, index)?;
+    if !account.is_owned_by(&crate::ID) {
+        return Err(AccountError::new(
+            index,
+            AccountCheck::Owner,
+            ProgramError::InvalidAccountOwner,
+        )
+        .into());
+    }
+    if account.data_len() != StakeStateV2::size_of() {
+        return Err(
+            AccountError::new(index, AccountCheck::Size, ProgramError::InvalidAccountData).into(),
+        );
+    }
+    Ok(account)
+}
+
+/// The account at `index`, checked to be writable
+fn writable_account(accounts: &[AccountInfo], index: usize) -> Result<&AccountInfo, ProgramError> {
+    let account = accounts
+        .get(index)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if !account.is_writable() {
+        return Err(AccountError::new(
+            index,
+            AccountCheck::Writable,
+            ProgramError::InvalidAccountData,
+        )
+        .into());
+    }
+    Ok(account)
+}
+
+/// The account at `index`, checked to be the sysvar `id`
+fn sysvar_account<'a>(
+    accounts: &'a [AccountInfo],
+    index: usize,
+    id: &Pubkey,
+) -> Result<&'a AccountInfo, ProgramError> {
+    let account = account(accounts, index)?;
+    if account.key() != id {
+        return Err(
+            AccountError::new(index, AccountCheck::Address, ProgramError::InvalidArgument).into(),
+        );
+    }
+    Ok(account)
+}
+
+/// The account at `index` if there is one, checked to be the sysvar `id`
+fn optional_sysvar_account<'a>(
+    accounts: &'a [AccountInfo],
+    index: usize,
+    id: &Pubkey,
+) -> Result<Option<&'a AccountInfo>, ProgramError> {
+    if index < accounts.len() {
+        sysvar_account(accounts, index, id).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// The account at `index`, unchecked
+fn account(accounts: &[AccountInfo], index: usize) -> Result<&AccountInfo, ProgramError> {
+    accounts
+        .get(index)
+        .ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+/// `Split`
+pub struct SplitAccounts<'a> {
+    /// 0. stake account to split from
+    pub source: &'a AccountInfo,
+    /// 1. uninitialized stake account receiving the split
+    pub destination: &'a AccountInfo,
+    // 2. stake authority, found among the signers
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SplitAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        // both must be present before either is checked, like the slice pattern was
+        if accounts.len() < 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(Self {
+            source: writable_stake_account(accounts, 0)?,
+            destination: writable_stake_account(accounts, 1)?,
+        })
+    }
+}
+
+/// `SetLockup`
+pub struct SetLockupAccounts<'a> {
+    /// 0. initialized stake account
+    pub stake: &'a AccountInfo,
+    // 1. lockup or withdraw authority, found among the signers
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetLockupAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            stake: writable_stake_account(accounts, 0)?,
+        })
+    }
+}
+
+/// `SetLockupChecked`
+pub struct SetLockupCheckedAccounts<'a> {
+    /// 0. initialized stake account
+    pub stake: &'a AccountInfo,
+    // 1. lockup or withdraw authority, found among the signers
+    /// 2. optional new lockup custodian, which must sign
+    pub custodian: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetLockupCheckedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            stake: writable_stake_account(accounts, 0)?,
+            custodian: accounts.get(2),
+        })
+    }
+}
+
+/// `DelegateStake`
+pub struct DelegateAccounts<'a> {
+    /// 0. initialized or delegated stake account
+    pub stake: &'a AccountInfo,
+    /// 1. vote account to delegate to; validated when its state is read
+    pub vote: &'a AccountInfo,
+    /// 2. optional clock sysvar
+    pub clock: Option<&'a AccountInfo>,
+    /// 3. optional stake history sysvar
+    pub stake_history: Option<&'a AccountInfo>,
+    /// 4. optional, unused stake config account
+    pub stake_config: Option<&'a AccountInfo>,
+    // 5. stake authority, found among the signers
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for DelegateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        if accounts.len() < 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(Self {
+            stake: writable_stake_account(accounts, 0)?,
+            vote: account(accounts, 1)?,
+            clock: optional_sysvar_account(accounts, 2, &CLOCK_ID)?,
+            stake_history: accounts.get(3),
+            stake_config: accounts.get(4),
+        })
+    }
+}
+
+/// `InitializeAndDelegate`
+pub struct InitializeAndDelegateAccounts<'a> {
+    /// 0. uninitialized stake account
+    pub stake: &'a AccountInfo,
+    /// 1. vote account to delegate to; validated when its state is read
+    pub vote: &'a AccountInfo,
+    // 2. the new stake authority, found among the signers
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeAndDelegateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        if accounts.len() < 3 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(Self {
+            stake: writable_stake_account(accounts, 0)?,
+            vote: account(accounts, 1)?,
+        })
+    }
+}
+
+/// `Merge`
+pub struct MergeAccounts<'a> {
+    /// 0. stake account merged into
+    pub destination: &'a AccountInfo,
+    /// 1. stake account drained by the merge
+    pub source: &'a AccountInfo,
+    /// 2. optional clock sysvar
+    pub clock: Option<&'a AccountInfo>,
+    /// 3. optional stake history sysvar
+    pub stake_history: Option<&'a AccountInfo>,
+    // 4. stake authority of both accounts, found among the signers
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MergeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        if accounts.len() < 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(Self {
+            destination: writable_stake_account(accounts, 0)?,
+            source: writable_stake_account(accounts, 1)?,
+            clock: optional_sysvar_account(accounts, 2, &CLOCK_ID)?,
+            stake_history: accounts.get(3),
+        })
+    }
+}
+
+/// `Withdraw`
+pub struct WithdrawAccounts<'a> {
+    /// 0. stake account to withdraw from
+    pub stake: &'a AccountInfo,
+    /// 1. any account receiving the lamports
+    pub recipient: &'a AccountInfo,
+    /// 2. optional clock sysvar
+    pub clock: Option<&'a AccountInfo>,
+    /// 3. optional stake history sysvar
+    pub stake_history: Option<&'a AccountInfo>,
+    // 4. withdraw authority, found among the signers
+    /// 5. optional lockup custodian
+    pub custodian: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        if accounts.len() < 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(Self {
+            stake: writable_stake_account(accounts, 0)?,
+            recipient: writable_account(accounts, 1)?,
+            clock: optional_sysvar_account(accounts, 2, &CLOCK_ID)?,
+            stake_history: accounts.get(3),
+            custodian: accounts.get(5),
+        })
+    }
+}
+
+/// `AuthorizeBoth`
+pub struct AuthorizeBothAccounts<'a> {
+    /// 0. initialized or delegated stake account
+    pub stake: &'a AccountInfo,
+    /// 1. clock sysvar
+    pub clock: &'a AccountInfo,
+    /// 2. current stake authority, which must sign
+    pub stake_authority: &'a AccountInfo,
+    /// 3. current withdraw authority, which must sign
+    pub withdraw_authority: &'a AccountInfo,
+    /// 4. optional lockup custodian
+    pub custodian: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AuthorizeBothAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        if accounts.len() < 4 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(Self {
+            stake: writable_stake_account(accounts, 0)?,
+            clock: sysvar_account(accounts, 1, &CLOCK_ID)?,
+            stake_authority: account(accounts, 2)?,
+            withdraw_authority: account(accounts, 3)?,
+            custodian: accounts.get(4),
+        })
+    }
+}
+
+/// `SplitMany`
+pub struct SplitManyAccounts<'a> {
+    /// 0. stake account to split from
+    pub source: &'a AccountInfo,
+    /// 1. stake authority of the source
+    pub stake_authority: &'a AccountInfo,
+    /// 2.. uninitialized stake accounts receiving the splits, one per amount
+    pub destinations: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SplitManyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        if accounts.len() < 3 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        if accounts.len() - 2 > MAX_SPLIT_MANY_DESTINATIONS {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let source = writable_stake_account(accounts, 0)?;
+        let stake_authority = account(accounts, 1)?;
+        for index in 2..accounts.len() {
+            writable_stake_account(accounts, index)?;
+        }
+        Ok(Self {
+            source,
+            stake_authority,
+            destinations: &accounts[2..],
+        })
+    }
+}
+
+/// `DeactivateMany`
+pub struct DeactivateManyAccounts<'a> {
+    /// 0. stake authority shared by every stake account
+    pub stake_authority: &'a AccountInfo,
+    /// 1.. delegated stake accounts to deactivate
+    pub stakes: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for DeactivateManyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        if accounts.len() < 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let stake_authority = account(accounts, 0)?;
+        for index in 1..accounts.len() {
+            writable_stake_account(accounts, index)?;
+        }
+        Ok(Self {
+            stake_authority,
+            stakes: &accounts[1..],
+        })
+    }
+}
+
+/// `MoveStake` and `MoveLamports`
+pub struct MoveAccounts<'a> {
+    /// 0. stake account moved from
+    pub source: &'a AccountInfo,
+    /// 1. stake account moved to
+    pub destination: &'a AccountInfo,
+    /// 2. stake authority of the source, which must sign
+    pub stake_authority: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MoveAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        if accounts.len() < 3 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(Self {
+            source: writable_stake_account(accounts, 0)?,
+            destination: writable_stake_account(accounts, 1)?,
+            stake_authority: account(accounts, 2)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::STAKE_HISTORY_ID,
+        test_utils::{SerializedInput, TestAccount},
+    };
+
+    #[test]
+    fn test_split_accounts_checks() {
+        let stake = || TestAccount::stake([1; 32], 0, StakeStateV2::Uninitialized);
+
+        let mut input = SerializedInput::new(&[stake()]);
+        let accounts: [AccountInfo; 1] = input.accounts();
+        assert_eq!(
+            SplitAccounts::try_from(&accounts[..]).err(),
+            Some(ProgramError::NotEnoughAccountKeys)
+        );
+
+        let mut input = SerializedInput::new(&[
+            stake(),
+            TestAccount {
+                is_writable: false,
+                ..stake()
+            },
+        ]);
+        let accounts: [AccountInfo; 2] = input.accounts();
+        assert_eq!(
+            SplitAccounts::try_from(&accounts[..]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+
+        let mut input = SerializedInput::new(&[
+            stake(),
+            TestAccount {
+                owner: [7; 32],
+                ..stake()
+            },
+        ]);
+        let accounts: [AccountInfo; 2] = input.accounts();
+        assert_eq!(
+            SplitAccounts::try_from(&accounts[..]).err(),
+            Some(ProgramError::InvalidAccountOwner)
+        );
+
+        let mut input = SerializedInput::new(&[stake(), stake()]);
+        let accounts: [AccountInfo; 2] = input.accounts();
+        assert!(SplitAccounts::try_from(&accounts[..]).is_ok());
+    }
+
+    #[test]
+    fn test_sysvar_accounts() {
+        let accounts = |clock, stake_history| {
+            SerializedInput::new(&[
+                TestAccount::stake([1; 32], 0, StakeStateV2::Uninitialized),
+                TestAccount::writable([2; 32]),
+                TestAccount::readonly(clock),
+                TestAccount::readonly(stake_history),
+            ])
+        };
+
+        let mut input = accounts(CLOCK_ID, STAKE_HISTORY_ID);
+        let valid: [AccountInfo; 4] = input.accounts();
+        assert!(WithdrawAccounts::try_from(&valid[..]).is_ok());
+
+        // both sysvars can be left out
+        let parsed = WithdrawAccounts::try_from(&valid[..2]).unwrap();
+        assert!(parsed.clock.is_none() && parsed.stake_history.is_none());
+
+        // native never checked the stake history account
+        let mut input = accounts(CLOCK_ID, [3; 32]);
+        let unchecked: [AccountInfo; 4] = input.accounts();
+        assert!(WithdrawAccounts::try_from(&unchecked[..]).is_ok());
+
+        // but a passed clock has to be the Clock sysvar
+        for clock in [STAKE_HISTORY_ID, [3; 32]] {
+            let mut input = accounts(clock, STAKE_HISTORY_ID);
+            let accounts: [AccountInfo; 4] = input.accounts();
+            assert_eq!(
+                WithdrawAccounts::try_from(&accounts[..]).err(),
+                Some(ProgramError::InvalidArgument)
+            );
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::state::{clock_from_account_info, do_authorize, Signers, StakeAuthorize};
+
+pub fn process_authorize(
+    accounts: &[AccountInfo],
+    new_authority: &Pubkey,
+    authority_type: StakeAuthorize,
+) -> ProgramResult {
+    let signers = Signers::new(accounts);
+
+    let [stake_account_info, clock_info, _stake_or_withdraw_authority_info, remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let clock = clock_from_account_info(clock_info)?;
+
+    // other accounts: the lockup custodian, which `do_authorize` requires to have signed
+    // when the lockup is in force
+    let custodian = remaining.first().map(AccountInfo::key);
+
+    do_authorize(
+        stake_account_info,
+        &signers,
+        new_authority,
+        authority_type,
+        custodian,
+        &clock,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::CLOCK_ID,
+        error::StakeError,
+        state::{Authorized, Lockup, Meta, StakeStateV2},
+        test_utils::{SerializedInput, TestAccount},
+    };
+    use pinocchio::sysvars::clock::Clock;
+
+    const STAKER: Pubkey = [3; 32];
+    const WITHDRAWER: Pubkey = [4; 32];
+    const CUSTODIAN: Pubkey = [5; 32];
+    const NEW: Pubkey = [6; 32];
+
+    /// `custodian_signs` is `None` to leave the custodian account out
+    fn authorize(
+        authority_type: StakeAuthorize,
+        custodian_signs: Option<bool>,
+    ) -> (ProgramResult, Authorized) {
+        // in force at the default clock
+        let lockup = Lockup {
+            epoch: 1u64.to_le_bytes(),
+            custodian: CUSTODIAN,
+            ..Lockup::default()
+        };
+        let authorized = Authorized {
+            staker: STAKER,
+            withdrawer: WITHDRAWER,
+        };
+        let meta = Meta::new(0, authorized, lockup);
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake([1; 32], 0, StakeStateV2::Initialized(meta)),
+            TestAccount {
+                data: vec![0; core::mem::size_of::<Clock>()],
+                ..TestAccount::readonly(CLOCK_ID)
+            },
+            TestAccount::signer(WITHDRAWER),
+            TestAccount {
+                is_signer: custodian_signs == Some(true),
+                ..TestAccount::readonly(CUSTODIAN)
+            },
+        ]);
+        let accounts: [AccountInfo; 4] = input.accounts();
+        let accounts = match custodian_signs {
+            Some(_) => &accounts[..],
+            None => &accounts[..3],
+        };
+
+        let result = process_authorize(accounts, &NEW, authority_type);
+        let state = StakeStateV2::deserialize(&accounts[0].try_borrow_data().unwrap()).unwrap();
+        (result, state.meta().unwrap().authorized)
+    }
+
+    #[test]
+    fn test_authorize_staker_ignores_lockup() {
+        let (result, authorized) = authorize(StakeAuthorize::Staker, None);
+        assert_eq!(result, Ok(()));
+        assert_eq!(authorized.staker, NEW);
+    }
+
+    #[test]
+    fn test_authorize_withdrawer_needs_custodian_in_lockup() {
+        let (result, authorized) = authorize(StakeAuthorize::Withdrawer, None);
+        assert_eq!(result, Err(StakeError::CustodianMissing.into()));
+        assert_eq!(authorized.withdrawer, WITHDRAWER);
+
+        let (result, authorized) = authorize(StakeAuthorize::Withdrawer, Some(false));
+        assert_eq!(result, Err(StakeError::CustodianSignatureMissing.into()));
+        assert_eq!(authorized.withdrawer, WITHDRAWER);
+
+        let (result, authorized) = authorize(StakeAuthorize::Withdrawer, Some(true));
+        assert_eq!(result, Ok(()));
+        assert_eq!(authorized.withdrawer, NEW);
+    }
+}
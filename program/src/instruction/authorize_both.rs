@@ -0,0 +1,184 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    instruction::AuthorizeBothAccounts,
+    state::{
+        collect_signers_checked_with_custodian, to_program_error, try_get_stake_state_mut_at,
+        StakeAuthorize, SyscallSysvars, SysvarProvider,
+    },
+};
+
+// Extension instruction: replaces the staker and the withdrawer in one step, so a
+// custody migration can't be left half-rotated. Both current authorities must sign,
+// even though `Authorize` lets the withdrawer replace the staker alone, and the
+// withdrawer change is subject to the lockup like `Authorize`. Data is the new staker
+// followed by the new withdrawer.
+
+pub fn process_authorize_both(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_authorize_both_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_authorize_both` reading Clock from `sysvars`
+pub fn process_authorize_both_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    sysvars: &S,
+) -> ProgramResult {
+    let AuthorizeBothAccounts {
+        stake: stake_account_info,
+        custodian: custodian_info,
+        ..
+    } = AuthorizeBothAccounts::try_from(accounts)?;
+
+    #[cfg(not(feature = "strict-interface"))]
+    let signer_accounts = accounts;
+    // 0. stake, 1. clock, 2. stake authority, 3. withdraw authority, 4. custodian, which
+    // `authorize` looks for among the signers
+    #[cfg(feature = "strict-interface")]
+    let signer_accounts = &accounts[2..accounts.len().min(5)];
+
+    let (signers, custodian) =
+        collect_signers_checked_with_custodian(signer_accounts, custodian_info, false)?;
+
+    if data.len() != 64 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let new_staker: Pubkey = data[..32].try_into().unwrap();
+    let new_withdrawer: Pubkey = data[32..].try_into().unwrap();
+
+    let clock = sysvars.clock()?;
+
+    let mut stake_state = try_get_stake_state_mut_at(stake_account_info, 0)?;
+    let meta = stake_state
+        .meta_mut()
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    // updated on a copy so a failed check leaves the account untouched
+    let mut authorized = meta.authorized;
+    authorized
+        .check(&signers, StakeAuthorize::Staker)
+        .map_err(to_program_error)?;
+    let lockup_custodian_args = Some((&meta.lockup, &clock, custodian));
+    authorized
+        .authorize(
+            &signers,
+            &new_staker,
+            StakeAuthorize::Staker,
+            lockup_custodian_args,
+        )
+        .map_err(to_program_error)?;
+    authorized
+        .authorize(
+            &signers,
+            &new_withdrawer,
+            StakeAuthorize::Withdrawer,
+            lockup_custodian_args,
+        )
+        .map_err(to_program_error)?;
+    meta.authorized = authorized;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::CLOCK_ID,
+        state::{Authorized, Lockup, Meta, MockSysvars, StakeStateV2},
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+
+    const STAKER: [u8; 32] = [3; 32];
+    const WITHDRAWER: [u8; 32] = [4; 32];
+    const NEW_STAKER: [u8; 32] = [5; 32];
+    const NEW_WITHDRAWER: [u8; 32] = [6; 32];
+
+    fn authorize_both(staker_signs: bool) -> (ProgramResult, StakeStateV2) {
+        let meta = Meta::new(
+            0,
+            Authorized {
+                staker: STAKER,
+                withdrawer: WITHDRAWER,
+            },
+            Lockup::default(),
+        );
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake([1; 32], 0, StakeStateV2::Initialized(meta)),
+            TestAccount::readonly(CLOCK_ID),
+            TestAccount {
+                is_signer: staker_signs,
+                ..TestAccount::readonly(STAKER)
+            },
+            TestAccount::signer(WITHDRAWER),
+        ]);
+        let accounts: [AccountInfo; 4] = input.accounts();
+        let data = [NEW_STAKER, NEW_WITHDRAWER].concat();
+
+        let sysvars = MockSysvars::default();
+        let (result, allocations) =
+            heap_allocations(|| process_authorize_both_with_sysvars(&accounts, &data, &sysvars));
+        assert_eq!(allocations, 0);
+        let state = StakeStateV2::deserialize(&accounts[0].try_borrow_data().unwrap()).unwrap();
+        (result, state)
+    }
+
+    #[test]
+    fn test_authorize_both_rotates_both_keys() {
+        let (result, state) = authorize_both(true);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            state.authorized(),
+            Some(Authorized {
+                staker: NEW_STAKER,
+                withdrawer: NEW_WITHDRAWER,
+            })
+        );
+    }
+
+    #[test]
+    fn test_authorize_both_needs_the_staker() {
+        let (result, state) = authorize_both(false);
+
+        assert_eq!(result, Err(ProgramError::MissingRequiredSignature));
+        assert_eq!(
+            state.authorized(),
+            Some(Authorized {
+                staker: STAKER,
+                withdrawer: WITHDRAWER,
+            })
+        );
+    }
+
+    #[cfg(feature = "strict-interface")]
+    #[test]
+    fn test_strict_authorize_both_ignores_misplaced_signer() {
+        let meta = Meta::new(
+            0,
+            Authorized {
+                staker: STAKER,
+                withdrawer: WITHDRAWER,
+            },
+            Lockup::default(),
+        );
+        // the staker signs, but after the custodian rather than at index 2
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake([1; 32], 0, StakeStateV2::Initialized(meta)),
+            TestAccount::readonly(CLOCK_ID),
+            TestAccount::readonly([9; 32]),
+            TestAccount::signer(WITHDRAWER),
+            TestAccount::readonly([8; 32]),
+            TestAccount::signer(STAKER),
+        ]);
+        let accounts: [AccountInfo; 6] = input.accounts();
+        let data = [NEW_STAKER, NEW_WITHDRAWER].concat();
+
+        assert_eq!(
+            process_authorize_both_with_sysvars(&accounts, &data, &MockSysvars::default()),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+}
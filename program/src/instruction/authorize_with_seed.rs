@@ -1,15 +1,14 @@
 use pinocchio::{
-    account_info::AccountInfo,
-    program_error::ProgramError,
-    pubkey::{self, Pubkey},
-    ProgramResult,
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
 };
 
 use crate::state::{
-    add_signer, clock_from_account_info, collect_signers_checked, do_authorize, StakeAuthorize,
+    add_signer, clock_from_account_info, collect_signers_checked, create_with_seed, do_authorize,
+    StakeAuthorize,
 };
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct AuthorizeWithSeedArgs<'a> {
     pub new_authorized_pubkey: Pubkey,
     pub stake_authorize: StakeAuthorize,
@@ -28,6 +27,20 @@ pub struct AuthorizeCheckedWithSeedArgs<'a> {
     // 7 bytes
 }
 
+// `authority_seed_len` always matches the seed, as it would after deserializing
+#[cfg(feature = "fuzz")]
+impl<'a> arbitrary::Arbitrary<'a> for AuthorizeCheckedWithSeedArgs<'a> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let authority_seed: &'a str = u.arbitrary()?;
+        Ok(Self {
+            authority_owner: u.arbitrary()?,
+            authority_seed_len: authority_seed.len() as u32,
+            authority_seed,
+            stake_authorize: u.arbitrary()?,
+        })
+    }
+}
+
 // Borsh
 // 10 (4bytes)
 // abcdefghij (10 bytes)
@@ -52,12 +65,11 @@ pub fn process_authorize_with_seed(
     let (mut signers, custodian, mut signers_count) =
         collect_signers_checked(None, option_lockup_authority_info)?;
 
-    let seeds = &[
-        stake_or_withdraw_authority_base_info.key().as_ref(),
+    let derived_key = create_with_seed(
+        stake_or_withdraw_authority_base_info.key(),
         authorize_args.authority_seed.as_bytes(),
-        authorize_args.authority_owner.as_ref(),
-    ];
-    let derived_key = pubkey::checked_create_program_address(seeds, &crate::id())?;
+        &authorize_args.authority_owner,
+    )?;
 
     if stake_or_withdraw_authority_base_info.is_signer() {
         add_signer(&mut signers, &mut signers_count, &derived_key)?;
@@ -74,3 +86,47 @@ pub fn process_authorize_with_seed(
 
     Ok(())
 }
+
+pub fn process_authorize_checked_with_seed(
+    accounts: &[AccountInfo],
+    authorize_args: AuthorizeCheckedWithSeedArgs,
+) -> ProgramResult {
+    let [stake_account_info, stake_or_withdraw_authority_base_info, clock_info, new_stake_or_withdraw_authority_info, remaining @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let clock = clock_from_account_info(clock_info)?;
+
+    if !new_stake_or_withdraw_authority_info.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // other accounts
+    let option_lockup_authority_info = remaining.first();
+
+    let (mut signers, custodian, mut signers_count) =
+        collect_signers_checked(None, option_lockup_authority_info)?;
+
+    let derived_key = create_with_seed(
+        stake_or_withdraw_authority_base_info.key(),
+        authorize_args.authority_seed.as_bytes(),
+        &authorize_args.authority_owner,
+    )?;
+
+    if stake_or_withdraw_authority_base_info.is_signer() {
+        add_signer(&mut signers, &mut signers_count, &derived_key)?;
+    }
+
+    do_authorize(
+        stake_account_info,
+        &signers,
+        new_stake_or_withdraw_authority_info.key(),
+        authorize_args.stake_authorize,
+        custodian,
+        &clock,
+    )?;
+
+    Ok(())
+}
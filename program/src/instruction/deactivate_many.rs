@@ -0,0 +1,181 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::{AccountCheck, AccountError, StakeError},
+    helpers::get_stake_status_with_features,
+    instruction::DeactivateManyAccounts,
+    state::{
+        to_program_error, try_get_stake_state_mut_at, FeatureSet, Signers, StakeAuthorize,
+        StakeFlags, StakeHistoryGetEntry, StakeStateV2, SyscallSysvars, SysvarProvider,
+    },
+};
+
+// Extension instruction: `Deactivate` for every stake account after the shared stake
+// authority, in one instruction. Data is the number of stake accounts as a single byte,
+// so an account list that doesn't match the count fails the batch instead of leaving
+// stake accounts active. Each account is checked exactly like a standalone `Deactivate`, including
+// the must-fully-activate flag, and any failure fails the whole batch.
+
+pub fn process_deactivate_many(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_deactivate_many_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_deactivate_many` reading Clock and StakeHistory from `sysvars`
+pub fn process_deactivate_many_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    sysvars: &S,
+) -> ProgramResult {
+    #[cfg(not(feature = "strict-interface"))]
+    let signers = Signers::new(accounts);
+    // 0. stake authority, 1.. stake accounts
+    #[cfg(feature = "strict-interface")]
+    let signers = Signers::at(accounts, 0)?;
+
+    let DeactivateManyAccounts { stakes, .. } = DeactivateManyAccounts::try_from(accounts)?;
+
+    match data {
+        [count] if *count as usize == stakes.len() => {}
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+
+    let clock = sysvars.clock()?;
+    let stake_history = &sysvars.stake_history(clock.epoch);
+    let features = sysvars.features();
+
+    for (index, stake_account_info) in (1..).zip(stakes) {
+        deactivate_stake_account(
+            stake_account_info,
+            index,
+            &signers,
+            clock.epoch,
+            stake_history,
+            &features,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Native `Deactivate` of the stake account at `index`, authorized by `signers`
+pub(crate) fn deactivate_stake_account<T: StakeHistoryGetEntry>(
+    stake_account_info: &AccountInfo,
+    index: usize,
+    signers: &Signers,
+    epoch: u64,
+    stake_history: &T,
+    features: &FeatureSet,
+) -> ProgramResult {
+    let mut stake_state = try_get_stake_state_mut_at(stake_account_info, index)?;
+    let StakeStateV2::Stake(meta, stake, stake_flags) = &mut *stake_state else {
+        return Err(AccountError::new(
+            index,
+            AccountCheck::State,
+            ProgramError::InvalidAccountData,
+        )
+        .into());
+    };
+
+    meta.authorized
+        .check(signers, StakeAuthorize::Staker)
+        .map_err(to_program_error)?;
+
+    // stake moved in by a redelegation can't start cooling down until it has warmed up
+    let flag = StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED;
+    if stake_flags.contains(flag) {
+        if get_stake_status_with_features(stake, epoch, stake_history, features).activating != 0 {
+            return Err(
+                StakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted.into(),
+            );
+        }
+        stake_flags.remove(flag);
+    }
+    stake.deactivate(epoch.to_le_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        state::{Authorized, Delegation, Lockup, Meta, MockSysvars, Stake},
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+
+    const STAKER: [u8; 32] = [3; 32];
+
+    fn delegated(activation_epoch: u64, flags: StakeFlags) -> StakeStateV2 {
+        StakeStateV2::Stake(
+            Meta::new(0, Authorized::auto(&STAKER), Lockup::default()),
+            Stake::new(Delegation::new(&[9; 32], 1_000_000, activation_epoch), 0),
+            flags,
+        )
+    }
+
+    fn state(account: &AccountInfo) -> StakeStateV2 {
+        StakeStateV2::deserialize(&account.try_borrow_data().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_deactivate_many_deactivates_each_account() {
+        let flag = StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED;
+        let mut input = SerializedInput::new(&[
+            TestAccount::signer(STAKER),
+            TestAccount::stake([1; 32], 1_000_000, delegated(0, StakeFlags::empty())),
+            TestAccount::stake([2; 32], 1_000_000, delegated(0, flag)),
+        ]);
+        let accounts: [AccountInfo; 3] = input.accounts();
+        let mut sysvars = MockSysvars::default();
+        sysvars.clock.epoch = 10;
+
+        let (result, allocations) =
+            heap_allocations(|| process_deactivate_many_with_sysvars(&accounts, &[2], &sysvars));
+        assert_eq!(result, Ok(()));
+        assert_eq!(allocations, 0);
+
+        for account in &accounts[1..] {
+            let StakeStateV2::Stake(_, stake, flags) = state(account) else {
+                panic!("not delegated");
+            };
+            assert_eq!(stake.delegation.deactivation_epoch(), 10);
+            assert_eq!(flags, StakeFlags::empty());
+        }
+    }
+
+    #[test]
+    fn test_deactivate_many_respects_must_fully_activate() {
+        let flag = StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED;
+        let mut input = SerializedInput::new(&[
+            TestAccount::signer(STAKER),
+            TestAccount::stake([1; 32], 1_000_000, delegated(0, StakeFlags::empty())),
+            TestAccount::stake([2; 32], 1_000_000, delegated(10, flag)),
+        ]);
+        let accounts: [AccountInfo; 3] = input.accounts();
+        let mut sysvars = MockSysvars::default();
+        sysvars.clock.epoch = 10;
+
+        assert_eq!(
+            process_deactivate_many_with_sysvars(&accounts, &[2], &sysvars),
+            Err(StakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted.into())
+        );
+        assert_eq!(state(&accounts[2]), delegated(10, flag));
+    }
+
+    #[test]
+    fn test_deactivate_many_checks_the_count() {
+        let mut input = SerializedInput::new(&[
+            TestAccount::signer(STAKER),
+            TestAccount::stake([1; 32], 1_000_000, delegated(0, StakeFlags::empty())),
+        ]);
+        let accounts: [AccountInfo; 2] = input.accounts();
+        let sysvars = MockSysvars::default();
+
+        for data in [&[][..], &[2], &[1, 0]] {
+            assert_eq!(
+                process_deactivate_many_with_sysvars(&accounts, data, &sysvars),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+}
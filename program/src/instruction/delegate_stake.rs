@@ -1,46 +1,52 @@
 use pinocchio::{
     account_info::AccountInfo,
     program_error::ProgramError,
-    pubkey::Pubkey,
     ProgramResult,
 };
+use crate::instruction::DelegateAccounts;
 use crate::state::{
-    bytes_to_u64,
-    clock_from_account_info,
-    collect_signers,
+    get_minimum_delegation,
     get_stake_state,
-    get_vote_state,
+    get_vote_credits,
     new_stake,
-    next_account_info,
     redelegate_stake,
     set_stake_state,
+    Signers,
     to_program_error,
     validate_delegated_amount,
     StakeFlags,
-    StakeHistorySysvar,
     StakeStateV2,
+    SyscallSysvars,
+    SysvarProvider,
     ValidatedDelegatedInfo,
 };
 
-pub fn process_delegate(accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
-    let mut signers = [Pubkey::default(); 32];
-    let _signers_len = collect_signers(accounts, &mut signers)?;
+pub fn process_delegate(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_delegate_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_delegate` reading Clock and StakeHistory from `sysvars`
+pub fn process_delegate_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    _data: &[u8],
+    sysvars: &S
+) -> ProgramResult {
+    let signers = Signers::new(accounts);
 
     // native accounts -- asserted
-    let accounts_info_iter = &mut accounts.iter();
-    let stake_account_info = next_account_info(accounts_info_iter)?;
-    let vote_account_info = next_account_info(accounts_info_iter)?;
-    let clock_info = next_account_info(accounts_info_iter)?;
-    let _stake_history_info = next_account_info(accounts_info_iter)?;
-    let _stake_config_info = next_account_info(accounts_info_iter)?;
+    let DelegateAccounts {
+        stake: stake_account_info,
+        vote: vote_account_info,
+        ..
+    } = DelegateAccounts::try_from(accounts)?;
 
     // for future refactors, after the bpf switchover we may assert them as well.
     // other account info
     // let _stake_authority_info = next_account_info(accounts_info_iter)?;
 
-    let clock = clock_from_account_info(clock_info)?;
-    let stake_history = &StakeHistorySysvar(bytes_to_u64(clock.epoch.to_le_bytes()));
-    let vote_state = get_vote_state(vote_account_info)?;
+    let clock = sysvars.clock()?;
+    let stake_history = &sysvars.stake_history(clock.epoch);
+    let vote_credits = get_vote_credits(vote_account_info)?;
 
     match *get_stake_state(stake_account_info)? {
         crate::state::StakeStateV2::Initialized(meta) => {
@@ -49,13 +55,14 @@ pub fn process_delegate(accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult
                 .map_err(to_program_error)?;
             let ValidatedDelegatedInfo { stake_amount } = validate_delegated_amount(
                 stake_account_info,
-                &meta
+                &meta,
+                get_minimum_delegation()
             )?;
             let stake = new_stake(
                 stake_amount,
                 vote_account_info.key(),
-                &vote_state,
-                clock.epoch.to_le_bytes()
+                vote_credits,
+                clock.epoch
             );
             set_stake_state(
                 stake_account_info,
@@ -68,14 +75,15 @@ pub fn process_delegate(accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult
                 .map_err(to_program_error)?;
             let ValidatedDelegatedInfo { stake_amount } = validate_delegated_amount(
                 stake_account_info,
-                &meta
+                &meta,
+                get_minimum_delegation()
             )?;
 
             redelegate_stake(
                 &mut stake,
                 stake_amount,
                 vote_account_info.key(),
-                &vote_state,
+                vote_credits,
                 clock.epoch.to_le_bytes(),
                 stake_history
             )?;
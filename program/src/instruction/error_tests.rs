@@ -0,0 +1,830 @@
+//! Every error a dispatched processor returns from its own checks, one case per exit,
+//! each the smallest change from an input the processor accepts. The account checks
+//! shared through `accounts.rs` are covered there.
+//!
+//! `test_every_stake_error_is_pinned` ties the cases to `StakeError`: each variant
+//! either has a case here or is listed as unreachable with the reason, so a new variant,
+//! or a processor newly dispatched that returns an old one, has to be accounted for.
+
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvars::clock::Clock,
+    ProgramResult,
+};
+
+use super::{
+    process_authorize_both_with_sysvars, process_deactivate_many_with_sysvars,
+    process_initialize_and_delegate_with_sysvars, process_partial_deactivate_with_sysvars,
+    process_set_lockup_with_sysvars, process_split_many_with_sysvars, process_split_with_sysvars,
+    process_withdraw_excess_with_sysvars, process_withdraw_max_with_sysvars,
+    process_withdraw_with_seed_with_sysvars,
+};
+use crate::{
+    consts::{CLOCK_ID, STAKE_HISTORY_ID},
+    error::StakeError,
+    state::{
+        create_with_seed, Authorized, Delegation, Lockup, Meta, MockSysvars, Stake, StakeFlags,
+        StakeStateV2,
+    },
+    test_utils::{SerializedInput, TestAccount},
+};
+
+const STAKE: Pubkey = [1; 32];
+const DESTINATION: Pubkey = [2; 32];
+const STAKER: Pubkey = [3; 32];
+const WITHDRAWER: Pubkey = [4; 32];
+const CUSTODIAN: Pubkey = [5; 32];
+const RECIPIENT: Pubkey = [6; 32];
+const VOTE: Pubkey = [7; 32];
+const NEW: Pubkey = [8; 32];
+const BASE: Pubkey = [9; 32];
+const SEED: &[u8] = b"withdrawer";
+
+const EPOCH: u64 = 10;
+
+/// `(what the case does, its result, the error it must fail with)`
+type Case = (&'static str, ProgramResult, ProgramError);
+
+fn sysvars() -> MockSysvars {
+    MockSysvars {
+        clock: clock(),
+        ..MockSysvars::default()
+    }
+}
+
+fn clock() -> Clock {
+    Clock {
+        epoch: EPOCH,
+        unix_timestamp: 1_000,
+        ..Clock::default()
+    }
+}
+
+fn reserve() -> u64 {
+    sysvars().rent.minimum_balance(StakeStateV2::size_of())
+}
+
+fn meta() -> Meta {
+    Meta::new(
+        reserve(),
+        Authorized {
+            staker: STAKER,
+            withdrawer: WITHDRAWER,
+        },
+        Lockup::default(),
+    )
+}
+
+/// `meta` locked up until the next epoch, with `CUSTODIAN` as custodian
+fn locked_meta() -> Meta {
+    let mut meta = meta();
+    meta.lockup.set_epoch(EPOCH + 1);
+    meta.lockup.set_custodian(&CUSTODIAN);
+    meta
+}
+
+/// Fully effective from genesis
+fn active(stake: u64) -> Stake {
+    Stake::new(Delegation::new(&VOTE, stake, u64::MAX), 0)
+}
+
+/// Activating since the current epoch
+fn activating(stake: u64) -> Stake {
+    Stake::new(Delegation::new(&VOTE, stake, EPOCH), 0)
+}
+
+fn delegated(stake: Stake) -> StakeStateV2 {
+    StakeStateV2::Stake(meta(), stake, StakeFlags::empty())
+}
+
+fn unsigned(key: Pubkey) -> TestAccount {
+    TestAccount::readonly(key)
+}
+
+fn clock_account() -> TestAccount {
+    let clock = clock();
+    let data = unsafe {
+        core::slice::from_raw_parts(
+            &clock as *const Clock as *const u8,
+            core::mem::size_of::<Clock>(),
+        )
+    };
+    TestAccount {
+        data: data.to_vec(),
+        ..TestAccount::readonly(CLOCK_ID)
+    }
+}
+
+/// Runs `process` over `accounts`
+fn run(
+    mut accounts: Vec<TestAccount>,
+    process: impl FnOnce(&[AccountInfo]) -> ProgramResult,
+) -> ProgramResult {
+    // padded to a fixed count; the processor only sees the real accounts
+    let count = accounts.len();
+    accounts.resize_with(8, || TestAccount::readonly([0xff; 32]));
+    let mut input = SerializedInput::new(&accounts);
+    let accounts: [AccountInfo; 8] = input.accounts();
+    process(&accounts[..count])
+}
+
+fn check(cases: Vec<Case>) {
+    for (case, result, expected) in cases {
+        assert_eq!(result, Err(expected), "{case}");
+    }
+}
+
+fn split(source: TestAccount, destination_lamports: u64, lamports: u64) -> ProgramResult {
+    split_with(
+        source,
+        destination_lamports,
+        TestAccount::signer(STAKER),
+        lamports,
+    )
+}
+
+fn split_with(
+    source: TestAccount,
+    destination_lamports: u64,
+    authority: TestAccount,
+    lamports: u64,
+) -> ProgramResult {
+    let destination = TestAccount::stake(
+        DESTINATION,
+        destination_lamports,
+        StakeStateV2::Uninitialized,
+    );
+    run(vec![source, destination, authority], |accounts| {
+        process_split_with_sysvars(accounts, lamports, &sysvars())
+    })
+}
+
+fn split_cases() -> Vec<Case> {
+    let reserve = reserve();
+    let initialized = || TestAccount::stake(STAKE, reserve * 2, StakeStateV2::Initialized(meta()));
+    vec![
+        (
+            "more than the source holds",
+            split(initialized(), 0, reserve * 2 + 1),
+            ProgramError::InsufficientFunds,
+        ),
+        (
+            "nothing",
+            split(initialized(), 0, 0),
+            ProgramError::InsufficientFunds,
+        ),
+        (
+            "initialized destination",
+            run(
+                vec![
+                    initialized(),
+                    TestAccount::stake(DESTINATION, 0, StakeStateV2::Initialized(meta())),
+                    TestAccount::signer(STAKER),
+                ],
+                |accounts| process_split_with_sysvars(accounts, reserve, &sysvars()),
+            ),
+            ProgramError::InvalidAccountData,
+        ),
+        (
+            "source as its own destination",
+            run(
+                vec![
+                    initialized(),
+                    TestAccount::stake(STAKE, 0, StakeStateV2::Uninitialized),
+                    TestAccount::signer(STAKER),
+                ],
+                |accounts| process_split_with_sysvars(accounts, reserve, &sysvars()),
+            ),
+            ProgramError::InvalidArgument,
+        ),
+        (
+            "rewards pool source",
+            split(
+                TestAccount::stake(STAKE, reserve * 2, StakeStateV2::RewardsPool),
+                0,
+                reserve,
+            ),
+            ProgramError::InvalidAccountData,
+        ),
+        (
+            "staker not signing",
+            split_with(initialized(), 0, unsigned(STAKER), reserve),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "uninitialized source not signing",
+            split(
+                TestAccount::stake(STAKE, 100, StakeStateV2::Uninitialized),
+                0,
+                40,
+            ),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "active stake into an unfunded destination",
+            split(
+                TestAccount::stake(STAKE, reserve + 2_000, delegated(active(2_000))),
+                0,
+                1_000,
+            ),
+            ProgramError::InsufficientFunds,
+        ),
+        (
+            // enough lamports stay behind, but none of them staked
+            "leaving the source no stake",
+            split(
+                TestAccount::stake(STAKE, reserve + 2_000, delegated(activating(1_000))),
+                reserve,
+                1_000,
+            ),
+            StakeError::InsufficientDelegation.into(),
+        ),
+        (
+            // the destination only needs the one lamport of minimum delegation on top
+            "splitting off no stake",
+            split(
+                TestAccount::stake(STAKE, reserve, delegated(activating(0))),
+                1,
+                reserve,
+            ),
+            StakeError::InsufficientDelegation.into(),
+        ),
+        (
+            // the undelegated lamports would become stake
+            "all of a source holding more than its stake",
+            split(
+                TestAccount::stake(STAKE, reserve + 1_500, delegated(activating(1_000))),
+                0,
+                reserve + 1_500,
+            ),
+            StakeError::InsufficientStake.into(),
+        ),
+    ]
+}
+
+fn split_many_cases() -> Vec<Case> {
+    let reserve = reserve();
+    let split_many = |source: StakeStateV2, source_lamports: u64, amounts: &[u64]| {
+        let data: Vec<u8> = amounts
+            .iter()
+            .flat_map(|amount| amount.to_le_bytes())
+            .collect();
+        run(
+            vec![
+                TestAccount::stake(STAKE, source_lamports, source),
+                TestAccount::signer(STAKER),
+                TestAccount::stake(DESTINATION, reserve, StakeStateV2::Uninitialized),
+            ],
+            |accounts| process_split_many_with_sysvars(accounts, &data, &sysvars()),
+        )
+    };
+    vec![
+        (
+            "more amounts than destinations",
+            split_many(StakeStateV2::Initialized(meta()), reserve * 2, &[1, 1]),
+            ProgramError::InvalidInstructionData,
+        ),
+        (
+            "a failing split",
+            split_many(delegated(activating(1_000)), reserve + 2_000, &[1_000]),
+            StakeError::InsufficientDelegation.into(),
+        ),
+    ]
+}
+
+fn partial_deactivate_cases() -> Vec<Case> {
+    let reserve = reserve();
+    let partial_deactivate = |source: StakeStateV2, data: &[u8]| {
+        run(
+            vec![
+                TestAccount::stake(STAKE, reserve + 10_000, source),
+                TestAccount::stake(DESTINATION, reserve, StakeStateV2::Uninitialized),
+                TestAccount::signer(STAKER),
+            ],
+            |accounts| process_partial_deactivate_with_sysvars(accounts, data, &sysvars()),
+        )
+    };
+    let mut deactivating = active(10_000);
+    deactivating.delegation.set_deactivation_epoch(EPOCH);
+    vec![
+        (
+            "short data",
+            partial_deactivate(delegated(active(10_000)), &[0; 4]),
+            ProgramError::InvalidInstructionData,
+        ),
+        (
+            "already deactivating",
+            partial_deactivate(delegated(deactivating), &4_000u64.to_le_bytes()),
+            StakeError::AlreadyDeactivated.into(),
+        ),
+        (
+            "redelegated stake still activating",
+            partial_deactivate(
+                StakeStateV2::Stake(
+                    meta(),
+                    activating(10_000),
+                    StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+                ),
+                &4_000u64.to_le_bytes(),
+            ),
+            StakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted.into(),
+        ),
+    ]
+}
+
+fn deactivate_many_cases() -> Vec<Case> {
+    let reserve = reserve();
+    let deactivate_many = |authority: TestAccount, stake: StakeStateV2, count: u8| {
+        run(
+            vec![authority, TestAccount::stake(STAKE, reserve + 1_000, stake)],
+            |accounts| process_deactivate_many_with_sysvars(accounts, &[count], &sysvars()),
+        )
+    };
+    let mut deactivated = active(1_000);
+    deactivated.delegation.set_deactivation_epoch(EPOCH - 1);
+    vec![
+        (
+            "count mismatch",
+            deactivate_many(TestAccount::signer(STAKER), delegated(active(1_000)), 2),
+            ProgramError::InvalidInstructionData,
+        ),
+        (
+            "initialized stake",
+            deactivate_many(
+                TestAccount::signer(STAKER),
+                StakeStateV2::Initialized(meta()),
+                1,
+            ),
+            ProgramError::InvalidAccountData,
+        ),
+        (
+            "staker not signing",
+            deactivate_many(unsigned(STAKER), delegated(active(1_000)), 1),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "already deactivated",
+            deactivate_many(TestAccount::signer(STAKER), delegated(deactivated), 1),
+            StakeError::AlreadyDeactivated.into(),
+        ),
+        (
+            "redelegated stake still activating",
+            deactivate_many(
+                TestAccount::signer(STAKER),
+                StakeStateV2::Stake(
+                    meta(),
+                    activating(1_000),
+                    StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+                ),
+                1,
+            ),
+            StakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted.into(),
+        ),
+    ]
+}
+
+/// `Withdraw`'s accounts around `stake`, with the withdraw authority's account
+fn withdraw_accounts(
+    stake: StakeStateV2,
+    lamports: u64,
+    authority: TestAccount,
+) -> Vec<TestAccount> {
+    vec![
+        TestAccount::stake(STAKE, lamports, stake),
+        TestAccount::writable(RECIPIENT),
+        TestAccount::readonly(CLOCK_ID),
+        TestAccount::readonly(STAKE_HISTORY_ID),
+        authority,
+    ]
+}
+
+fn withdraw_max_cases() -> Vec<Case> {
+    let reserve = reserve();
+    let withdraw_max = |stake: StakeStateV2, authority: TestAccount| {
+        run(
+            withdraw_accounts(stake, reserve + 1_000, authority),
+            |accounts| process_withdraw_max_with_sysvars(accounts, &sysvars()),
+        )
+    };
+    vec![
+        (
+            "withdrawer not signing",
+            withdraw_max(StakeStateV2::Initialized(meta()), unsigned(WITHDRAWER)),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "rewards pool",
+            withdraw_max(StakeStateV2::RewardsPool, TestAccount::signer(WITHDRAWER)),
+            ProgramError::InvalidAccountData,
+        ),
+        (
+            "lockup in force",
+            withdraw_max(
+                StakeStateV2::Initialized(locked_meta()),
+                TestAccount::signer(WITHDRAWER),
+            ),
+            StakeError::LockupInForce.into(),
+        ),
+    ]
+}
+
+fn withdraw_excess_cases() -> Vec<Case> {
+    let reserve = reserve();
+    let withdraw_excess = |stake: StakeStateV2, authority: TestAccount| {
+        run(
+            withdraw_accounts(stake, reserve + 1_000, authority),
+            |accounts| process_withdraw_excess_with_sysvars(accounts, &sysvars()),
+        )
+    };
+    vec![
+        (
+            "uninitialized",
+            withdraw_excess(StakeStateV2::Uninitialized, TestAccount::signer(STAKE)),
+            ProgramError::InvalidAccountData,
+        ),
+        (
+            "withdrawer not signing",
+            withdraw_excess(StakeStateV2::Initialized(meta()), unsigned(WITHDRAWER)),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "lockup in force",
+            withdraw_excess(
+                StakeStateV2::Initialized(locked_meta()),
+                TestAccount::signer(WITHDRAWER),
+            ),
+            StakeError::LockupInForce.into(),
+        ),
+    ]
+}
+
+fn withdraw_with_seed_cases() -> Vec<Case> {
+    let reserve = reserve();
+    let derived = create_with_seed(&BASE, SEED, &NEW).unwrap();
+    let with_derived_withdrawer = |mut meta: Meta| {
+        meta.authorized.withdrawer = derived;
+        meta
+    };
+    let data = |lamports: u64| {
+        let mut data = lamports.to_le_bytes().to_vec();
+        data.extend_from_slice(&(SEED.len() as u64).to_le_bytes());
+        data.extend_from_slice(SEED);
+        data.extend_from_slice(&NEW);
+        data
+    };
+    let withdraw_with_seed = |stake: StakeStateV2, base: TestAccount, data: &[u8]| {
+        run(
+            withdraw_accounts(stake, reserve + 1_000, base),
+            |accounts| process_withdraw_with_seed_with_sysvars(accounts, data, &sysvars()),
+        )
+    };
+    let initialized = StakeStateV2::Initialized(with_derived_withdrawer(meta()));
+    vec![
+        (
+            "short data",
+            withdraw_with_seed(initialized, TestAccount::signer(BASE), &data(1)[..20]),
+            ProgramError::InvalidInstructionData,
+        ),
+        (
+            "base not signing",
+            withdraw_with_seed(initialized, unsigned(BASE), &data(1)),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "into the reserve",
+            withdraw_with_seed(initialized, TestAccount::signer(BASE), &data(1_001)),
+            ProgramError::InsufficientFunds,
+        ),
+        (
+            "closing live stake",
+            withdraw_with_seed(
+                StakeStateV2::Stake(
+                    with_derived_withdrawer(meta()),
+                    active(1_000),
+                    StakeFlags::empty(),
+                ),
+                TestAccount::signer(BASE),
+                &data(reserve + 1_000),
+            ),
+            ProgramError::InsufficientFunds,
+        ),
+        (
+            "lockup in force",
+            withdraw_with_seed(
+                StakeStateV2::Initialized(with_derived_withdrawer(locked_meta())),
+                TestAccount::signer(BASE),
+                &data(1),
+            ),
+            StakeError::LockupInForce.into(),
+        ),
+    ]
+}
+
+fn authorize_both_cases() -> Vec<Case> {
+    let authorize_both =
+        |stake: StakeStateV2, staker: TestAccount, custodian: Option<TestAccount>, data: &[u8]| {
+            let mut accounts = vec![
+                TestAccount::stake(STAKE, 0, stake),
+                clock_account(),
+                staker,
+                TestAccount::signer(WITHDRAWER),
+            ];
+            accounts.extend(custodian);
+            run(accounts, |accounts| {
+                process_authorize_both_with_sysvars(accounts, data, &sysvars())
+            })
+        };
+    let data = [NEW, NEW].concat();
+    let locked = StakeStateV2::Initialized(locked_meta());
+    vec![
+        (
+            "short data",
+            authorize_both(
+                StakeStateV2::Initialized(meta()),
+                TestAccount::signer(STAKER),
+                None,
+                &data[..32],
+            ),
+            ProgramError::InvalidInstructionData,
+        ),
+        (
+            "uninitialized",
+            authorize_both(
+                StakeStateV2::Uninitialized,
+                TestAccount::signer(STAKER),
+                None,
+                &data,
+            ),
+            ProgramError::InvalidAccountData,
+        ),
+        (
+            "staker not signing",
+            authorize_both(
+                StakeStateV2::Initialized(meta()),
+                unsigned(STAKER),
+                None,
+                &data,
+            ),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "lockup in force without a custodian",
+            authorize_both(locked, TestAccount::signer(STAKER), None, &data),
+            StakeError::CustodianMissing.into(),
+        ),
+        (
+            // only a signing custodian is taken, so an unsigned one is a missing one
+            "lockup in force with an unsigned custodian",
+            authorize_both(
+                locked,
+                TestAccount::signer(STAKER),
+                Some(unsigned(CUSTODIAN)),
+                &data,
+            ),
+            StakeError::CustodianMissing.into(),
+        ),
+        (
+            "lockup in force with another custodian",
+            authorize_both(
+                locked,
+                TestAccount::signer(STAKER),
+                Some(TestAccount::signer(NEW)),
+                &data,
+            ),
+            StakeError::LockupInForce.into(),
+        ),
+    ]
+}
+
+fn initialize_and_delegate_cases() -> Vec<Case> {
+    let reserve = reserve();
+    let mut data = [STAKER, WITHDRAWER].concat();
+    data.extend_from_slice(&[0; 48]);
+    let initialize_and_delegate =
+        |stake: StakeStateV2, lamports: u64, staker: TestAccount, data: &[u8]| {
+            run(
+                vec![
+                    TestAccount::stake(STAKE, lamports, stake),
+                    unsigned(VOTE),
+                    staker,
+                ],
+                |accounts| process_initialize_and_delegate_with_sysvars(accounts, data, &sysvars()),
+            )
+        };
+    let uninitialized = StakeStateV2::Uninitialized;
+    vec![
+        (
+            "short data",
+            initialize_and_delegate(
+                uninitialized,
+                reserve * 2,
+                TestAccount::signer(STAKER),
+                &data[..64],
+            ),
+            ProgramError::InvalidInstructionData,
+        ),
+        (
+            "already initialized",
+            initialize_and_delegate(
+                StakeStateV2::Initialized(meta()),
+                reserve * 2,
+                TestAccount::signer(STAKER),
+                &data,
+            ),
+            ProgramError::InvalidAccountData,
+        ),
+        (
+            "below the reserve",
+            initialize_and_delegate(
+                uninitialized,
+                reserve - 1,
+                TestAccount::signer(STAKER),
+                &data,
+            ),
+            ProgramError::InsufficientFunds,
+        ),
+        (
+            "staker not signing",
+            initialize_and_delegate(uninitialized, reserve * 2, unsigned(STAKER), &data),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "nothing to delegate",
+            initialize_and_delegate(uninitialized, reserve, TestAccount::signer(STAKER), &data),
+            StakeError::InsufficientDelegation.into(),
+        ),
+        (
+            "vote account not owned by the vote program",
+            initialize_and_delegate(
+                uninitialized,
+                reserve * 2,
+                TestAccount::signer(STAKER),
+                &data,
+            ),
+            ProgramError::IncorrectProgramId,
+        ),
+    ]
+}
+
+fn set_lockup_cases() -> Vec<Case> {
+    // LockupArgs { unix_timestamp: None, epoch: Some(20), custodian: None }
+    let mut data = vec![0, 1];
+    data.extend(20u64.to_le_bytes());
+    data.push(0);
+    let set_lockup = |stake: StakeStateV2, signer: TestAccount, data: &[u8]| {
+        run(
+            vec![TestAccount::stake(STAKE, 0, stake), signer],
+            |accounts| process_set_lockup_with_sysvars(accounts, data, &sysvars()),
+        )
+    };
+    vec![
+        (
+            "malformed data",
+            set_lockup(
+                StakeStateV2::Initialized(meta()),
+                TestAccount::signer(WITHDRAWER),
+                &data[..10],
+            ),
+            ProgramError::InvalidInstructionData,
+        ),
+        (
+            "uninitialized",
+            set_lockup(
+                StakeStateV2::Uninitialized,
+                TestAccount::signer(WITHDRAWER),
+                &data,
+            ),
+            ProgramError::InvalidAccountData,
+        ),
+        (
+            "withdrawer not signing",
+            set_lockup(
+                StakeStateV2::Initialized(meta()),
+                unsigned(WITHDRAWER),
+                &data,
+            ),
+            ProgramError::MissingRequiredSignature,
+        ),
+        (
+            "lockup in force without the custodian",
+            set_lockup(
+                StakeStateV2::Initialized(locked_meta()),
+                TestAccount::signer(WITHDRAWER),
+                &data,
+            ),
+            ProgramError::MissingRequiredSignature,
+        ),
+    ]
+}
+
+fn all_cases() -> Vec<Case> {
+    [
+        split_cases(),
+        split_many_cases(),
+        partial_deactivate_cases(),
+        deactivate_many_cases(),
+        withdraw_max_cases(),
+        withdraw_excess_cases(),
+        withdraw_with_seed_cases(),
+        authorize_both_cases(),
+        initialize_and_delegate_cases(),
+        set_lockup_cases(),
+    ]
+    .concat()
+}
+
+/// Why no dispatched processor returns `error`, or `None` if one does
+fn unreachable_reason(error: &StakeError) -> Option<&'static str> {
+    match error {
+        StakeError::LockupInForce
+        | StakeError::AlreadyDeactivated
+        | StakeError::InsufficientStake
+        | StakeError::CustodianMissing
+        | StakeError::InsufficientDelegation
+        | StakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted => None,
+        StakeError::NoCreditsToRedeem => Some("not returned by the native program either"),
+        StakeError::TooSoonToRedelegate => Some("`DelegateStake` isn't dispatched"),
+        StakeError::MergeTransientStake | StakeError::MergeMismatch => {
+            Some("`Merge` isn't dispatched")
+        }
+        StakeError::CustodianSignatureMissing => {
+            Some("`Authorize` isn't dispatched, and `AuthorizeBoth` only takes a signing custodian")
+        }
+        StakeError::InsufficientReferenceVotes
+        | StakeError::VoteAddressMismatch
+        | StakeError::MinimumDelinquentEpochsForDeactivationNotMet => {
+            Some("`DeactivateDelinquent` isn't dispatched")
+        }
+        StakeError::RedelegateTransientOrInactiveStake
+        | StakeError::RedelegateToSameVoteAccount => Some("`Redelegate` is rejected outright"),
+        StakeError::EpochRewardsActive => {
+            Some("the entrypoint doesn't check the epoch rewards period yet")
+        }
+    }
+}
+
+#[test]
+fn test_split_errors() {
+    check(split_cases());
+}
+
+#[test]
+fn test_split_many_errors() {
+    check(split_many_cases());
+}
+
+#[test]
+fn test_partial_deactivate_errors() {
+    check(partial_deactivate_cases());
+}
+
+#[test]
+fn test_deactivate_many_errors() {
+    check(deactivate_many_cases());
+}
+
+#[test]
+fn test_withdraw_max_errors() {
+    check(withdraw_max_cases());
+}
+
+#[test]
+fn test_withdraw_excess_errors() {
+    check(withdraw_excess_cases());
+}
+
+#[test]
+fn test_withdraw_with_seed_errors() {
+    check(withdraw_with_seed_cases());
+}
+
+#[test]
+fn test_authorize_both_errors() {
+    check(authorize_both_cases());
+}
+
+#[test]
+fn test_initialize_and_delegate_errors() {
+    check(initialize_and_delegate_cases());
+}
+
+#[test]
+fn test_set_lockup_errors() {
+    check(set_lockup_cases());
+}
+
+#[test]
+fn test_every_stake_error_is_pinned() {
+    let cases = all_cases();
+    let errors = (0u32..).map_while(|code| StakeError::try_from(code).ok());
+    for error in errors {
+        let code = ProgramError::from(error.clone());
+        let case = cases.iter().find(|(_, _, expected)| *expected == code);
+        match (unreachable_reason(&error), case) {
+            (None, None) => panic!("{error:?} is returned but has no case"),
+            (Some(reason), Some((case, ..))) => {
+                panic!("{error:?} is listed as unreachable ({reason}) but \"{case}\" returns it")
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::{AccountCheck, AccountError},
+    instruction::InitializeAndDelegateAccounts,
+    state::{
+        get_vote_credits, new_stake, set_stake_state, stake_rent_exempt_reserve, to_program_error,
+        validate_delegated_amount, Authorized, Lockup, Meta, Signers, StakeAuthorize, StakeFlags,
+        StakeStateTag, StakeStateV2, SyscallSysvars, SysvarProvider, ValidatedDelegatedInfo,
+    },
+};
+
+// Extension instruction: `Initialize` followed by `DelegateStake`, so a freshly created
+// account is staking after one instruction. Data is `Initialize`'s: the authorities,
+// then the lockup. The new stake authority must sign, as it would for the delegation,
+// and everything above the rent-exempt reserve is delegated.
+
+/// Length of the instruction data, the same as `Initialize`'s
+const DATA_LEN: usize = 112;
+
+pub fn process_initialize_and_delegate(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_initialize_and_delegate_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_initialize_and_delegate` reading Clock and Rent from `sysvars`
+pub fn process_initialize_and_delegate_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    sysvars: &S,
+) -> ProgramResult {
+    #[cfg(not(feature = "strict-interface"))]
+    let signers = Signers::new(accounts);
+    // 0. stake, 1. vote, 2. stake authority
+    #[cfg(feature = "strict-interface")]
+    let signers = Signers::at(accounts, 2)?;
+
+    let InitializeAndDelegateAccounts {
+        stake: stake_account_info,
+        vote: vote_account_info,
+    } = InitializeAndDelegateAccounts::try_from(accounts)?;
+
+    if data.len() != DATA_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let authorized = Authorized {
+        staker: data[0..32].try_into().unwrap(),
+        withdrawer: data[32..64].try_into().unwrap(),
+    };
+    let lockup = Lockup {
+        unix_timestamp: data[64..72].try_into().unwrap(),
+        epoch: data[72..80].try_into().unwrap(),
+        custodian: data[80..112].try_into().unwrap(),
+    };
+
+    if StakeStateV2::peek_tag(&stake_account_info.try_borrow_data()?)?
+        != StakeStateTag::Uninitialized
+    {
+        return Err(
+            AccountError::new(0, AccountCheck::State, ProgramError::InvalidAccountData).into(),
+        );
+    }
+
+    // the `Initialize` half
+    let rent_exempt_reserve = stake_rent_exempt_reserve(&sysvars.rent()?);
+    if stake_account_info.lamports() < rent_exempt_reserve {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    let meta = Meta::new(rent_exempt_reserve, authorized, lockup);
+
+    // the `DelegateStake` half
+    meta.authorized
+        .check(&signers, StakeAuthorize::Staker)
+        .map_err(to_program_error)?;
+    let ValidatedDelegatedInfo { stake_amount } = validate_delegated_amount(
+        stake_account_info,
+        &meta,
+        sysvars.features().minimum_delegation(),
+    )?;
+    let clock = sysvars.clock()?;
+    let stake = new_stake(
+        stake_amount,
+        vote_account_info.key(),
+        get_vote_credits(vote_account_info)?,
+        clock.epoch,
+    );
+
+    set_stake_state(
+        stake_account_info,
+        &StakeStateV2::Stake(meta, stake, StakeFlags::empty()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        error::StakeError,
+        state::{Delegation, MockSysvars, Stake},
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+
+    const STAKE: [u8; 32] = [1; 32];
+    const STAKER: [u8; 32] = [3; 32];
+    const VOTE: [u8; 32] = [9; 32];
+    const EPOCH_CREDITS: [(u64, u64, u64); 2] = [(0, 40, 0), (1, 70, 40)];
+
+    fn initialize_and_delegate(
+        state: StakeStateV2,
+        lamports: u64,
+        staker_signs: bool,
+    ) -> (ProgramResult, StakeStateV2) {
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake(STAKE, lamports, state),
+            TestAccount::vote(VOTE, &EPOCH_CREDITS),
+            TestAccount {
+                is_signer: staker_signs,
+                ..TestAccount::readonly(STAKER)
+            },
+        ]);
+        let accounts: [AccountInfo; 3] = input.accounts();
+        let mut data = [STAKER, STAKER].concat();
+        data.extend_from_slice(&[0; 48]);
+
+        let sysvars = MockSysvars::default();
+        let (result, allocations) = heap_allocations(|| {
+            process_initialize_and_delegate_with_sysvars(&accounts, &data, &sysvars)
+        });
+        assert_eq!(allocations, 0);
+        let state = StakeStateV2::deserialize(&accounts[0].try_borrow_data().unwrap()).unwrap();
+        (result, state)
+    }
+
+    fn reserve() -> u64 {
+        MockSysvars::default()
+            .rent
+            .minimum_balance(StakeStateV2::size_of())
+    }
+
+    #[test]
+    fn test_initialize_and_delegate_needs_uninitialized() {
+        let meta = Meta::auto(&STAKER);
+        let (result, state) =
+            initialize_and_delegate(StakeStateV2::Initialized(meta), 10_000_000_000, true);
+
+        assert_eq!(result, Err(ProgramError::InvalidAccountData));
+        assert_eq!(state, StakeStateV2::Initialized(meta));
+    }
+
+    #[test]
+    fn test_initialize_and_delegate_needs_the_staker() {
+        let (result, state) =
+            initialize_and_delegate(StakeStateV2::Uninitialized, 10_000_000_000, false);
+
+        assert_eq!(result, Err(ProgramError::MissingRequiredSignature));
+        assert_eq!(state, StakeStateV2::Uninitialized);
+    }
+
+    #[test]
+    fn test_initialize_and_delegate_needs_funds() {
+        let (result, _) = initialize_and_delegate(StakeStateV2::Uninitialized, reserve() - 1, true);
+        assert_eq!(result, Err(ProgramError::InsufficientFunds));
+
+        let (result, state) = initialize_and_delegate(StakeStateV2::Uninitialized, reserve(), true);
+        assert_eq!(result, Err(StakeError::InsufficientDelegation.into()));
+        assert_eq!(state, StakeStateV2::Uninitialized);
+    }
+
+    #[test]
+    fn test_initialize_and_delegate() {
+        let lamports = reserve() + 5_000_000;
+        let (result, state) = initialize_and_delegate(StakeStateV2::Uninitialized, lamports, true);
+
+        assert_eq!(result, Ok(()));
+        let meta = Meta::new(
+            reserve(),
+            Authorized {
+                staker: STAKER,
+                withdrawer: STAKER,
+            },
+            Lockup::default(),
+        );
+        let stake = Stake::new(Delegation::new(&VOTE, lamports - reserve(), 0), 70);
+        assert_eq!(state, StakeStateV2::Stake(meta, stake, StakeFlags::empty()));
+    }
+}
@@ -0,0 +1,242 @@
+//! Lockups by timestamp, by epoch and by both, in force and expired, against each
+//! way the custodian can turn up, across the three instructions a lockup gates:
+//! withdrawing, moving the lockup and handing over the withdraw authority.
+//!
+//! `Withdraw` isn't dispatched yet, so its column runs `WithdrawMax`, which takes the
+//! same accounts and applies the same lockup check before computing the amount.
+
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvars::clock::Clock,
+    ProgramResult,
+};
+
+use super::{
+    process_authorize, process_set_lockup_with_sysvars, process_withdraw_max_with_sysvars,
+};
+use crate::{
+    consts::{CLOCK_ID, STAKE_HISTORY_ID},
+    error::StakeError,
+    state::{Authorized, Lockup, Meta, MockSysvars, StakeAuthorize, StakeStateV2},
+    test_utils::{SerializedInput, TestAccount},
+};
+
+const STAKE: Pubkey = [1; 32];
+const WITHDRAWER: Pubkey = [2; 32];
+const CUSTODIAN: Pubkey = [3; 32];
+const IMPOSTOR: Pubkey = [4; 32];
+const NEW: Pubkey = [5; 32];
+
+const EPOCH: u64 = 10;
+const UNIX_TIMESTAMP: i64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Custodian {
+    /// left out of the instruction
+    Absent,
+    /// passed but not signing
+    Unsigned,
+    Signed,
+    /// some other key signing in the custodian's place
+    Impostor,
+}
+
+const CUSTODIANS: [Custodian; 4] = [
+    Custodian::Absent,
+    Custodian::Unsigned,
+    Custodian::Signed,
+    Custodian::Impostor,
+];
+
+/// `(name, unix_timestamp, epoch, in force)` at `EPOCH` and `UNIX_TIMESTAMP`; a lockup
+/// ending at the current time or epoch has expired
+const LOCKUPS: [(&str, i64, u64, bool); 9] = [
+    ("none", 0, 0, false),
+    ("timestamp", UNIX_TIMESTAMP + 1, 0, true),
+    ("expired timestamp", UNIX_TIMESTAMP, 0, false),
+    ("epoch", 0, EPOCH + 1, true),
+    ("expired epoch", 0, EPOCH, false),
+    ("both", UNIX_TIMESTAMP + 1, EPOCH + 1, true),
+    ("timestamp, expired epoch", UNIX_TIMESTAMP + 1, EPOCH, true),
+    ("epoch, expired timestamp", UNIX_TIMESTAMP, EPOCH + 1, true),
+    ("both expired", UNIX_TIMESTAMP, EPOCH, false),
+];
+
+fn stake(unix_timestamp: i64, epoch: u64) -> TestAccount {
+    let mut lockup = Lockup::default();
+    lockup.set_unix_timestamp(unix_timestamp);
+    lockup.set_epoch(epoch);
+    lockup.set_custodian(&CUSTODIAN);
+    let meta = Meta::new(0, Authorized::auto(&WITHDRAWER), lockup);
+    TestAccount::stake(STAKE, 1_000, StakeStateV2::Initialized(meta))
+}
+
+fn custodian(custodian: Custodian) -> Option<TestAccount> {
+    match custodian {
+        Custodian::Absent => None,
+        Custodian::Unsigned => Some(TestAccount::readonly(CUSTODIAN)),
+        Custodian::Signed => Some(TestAccount::signer(CUSTODIAN)),
+        Custodian::Impostor => Some(TestAccount::signer(IMPOSTOR)),
+    }
+}
+
+fn clock() -> Clock {
+    Clock {
+        epoch: EPOCH,
+        unix_timestamp: UNIX_TIMESTAMP,
+        ..Clock::default()
+    }
+}
+
+fn sysvars() -> MockSysvars {
+    MockSysvars {
+        clock: clock(),
+        ..MockSysvars::default()
+    }
+}
+
+/// Runs `process` over `accounts`, then returns its result and the stake state
+fn run(
+    mut accounts: Vec<TestAccount>,
+    process: impl FnOnce(&[AccountInfo]) -> ProgramResult,
+) -> (ProgramResult, StakeStateV2) {
+    // padded to a fixed count; the processor only sees the real accounts
+    let count = accounts.len();
+    accounts.resize_with(6, || TestAccount::readonly([9; 32]));
+    let mut input = SerializedInput::new(&accounts);
+    let accounts: [AccountInfo; 6] = input.accounts();
+
+    let result = process(&accounts[..count]);
+    let state = StakeStateV2::deserialize(&accounts[0].try_borrow_data().unwrap()).unwrap();
+    (result, state)
+}
+
+fn withdraw(lockup: (i64, u64), custodian: Custodian) -> ProgramResult {
+    let mut accounts = vec![
+        stake(lockup.0, lockup.1),
+        TestAccount::writable(NEW),
+        TestAccount::readonly(CLOCK_ID),
+        TestAccount::readonly(STAKE_HISTORY_ID),
+        TestAccount::signer(WITHDRAWER),
+    ];
+    accounts.extend(self::custodian(custodian));
+    run(accounts, |accounts| {
+        process_withdraw_max_with_sysvars(accounts, &sysvars())
+    })
+    .0
+}
+
+/// Moves the lockup epoch to 20, signed by the withdrawer if `withdrawer_signs`
+fn set_lockup(
+    lockup: (i64, u64),
+    custodian: Custodian,
+    withdrawer_signs: bool,
+) -> (ProgramResult, u64) {
+    let mut accounts = vec![stake(lockup.0, lockup.1)];
+    if withdrawer_signs {
+        accounts.push(TestAccount::signer(WITHDRAWER));
+    }
+    accounts.extend(self::custodian(custodian));
+    // LockupArgs { unix_timestamp: None, epoch: Some(20), custodian: None }
+    let mut data = vec![0, 1];
+    data.extend(20u64.to_le_bytes());
+    data.push(0);
+
+    let (result, state) = run(accounts, |accounts| {
+        process_set_lockup_with_sysvars(accounts, &data, &sysvars())
+    });
+    (result, state.meta().unwrap().lockup.epoch())
+}
+
+fn authorize_withdrawer(lockup: (i64, u64), custodian: Custodian) -> (ProgramResult, Pubkey) {
+    let clock = clock();
+    let clock_data = unsafe {
+        core::slice::from_raw_parts(
+            &clock as *const Clock as *const u8,
+            core::mem::size_of::<Clock>(),
+        )
+    };
+    let mut accounts = vec![
+        stake(lockup.0, lockup.1),
+        TestAccount {
+            data: clock_data.to_vec(),
+            ..TestAccount::readonly(CLOCK_ID)
+        },
+        TestAccount::signer(WITHDRAWER),
+    ];
+    accounts.extend(self::custodian(custodian));
+
+    let (result, state) = run(accounts, |accounts| {
+        process_authorize(accounts, &NEW, StakeAuthorize::Withdrawer)
+    });
+    (result, state.meta().unwrap().authorized.withdrawer)
+}
+
+#[test]
+fn test_withdraw_lockup_matrix() {
+    for (name, unix_timestamp, epoch, in_force) in LOCKUPS {
+        for custodian in CUSTODIANS {
+            // a custodian only counts if it's the lockup's own and it signed
+            let expected: ProgramResult = if in_force && custodian != Custodian::Signed {
+                Err(StakeError::LockupInForce.into())
+            } else {
+                Ok(())
+            };
+            assert_eq!(
+                withdraw((unix_timestamp, epoch), custodian),
+                expected,
+                "{name} lockup, custodian {custodian:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_set_lockup_matrix() {
+    for (name, unix_timestamp, epoch, in_force) in LOCKUPS {
+        for custodian in CUSTODIANS {
+            for withdrawer_signs in [false, true] {
+                // the custodian alone while in force, the withdrawer alone after
+                let allowed = if in_force {
+                    custodian == Custodian::Signed
+                } else {
+                    withdrawer_signs
+                };
+                let (result, new_epoch) =
+                    set_lockup((unix_timestamp, epoch), custodian, withdrawer_signs);
+                let case = format!(
+                    "{name} lockup, custodian {custodian:?}, withdrawer signs: {withdrawer_signs}"
+                );
+                if allowed {
+                    assert_eq!(result, Ok(()), "{case}");
+                    assert_eq!(new_epoch, 20, "{case}");
+                } else {
+                    assert_eq!(
+                        result,
+                        Err(ProgramError::MissingRequiredSignature),
+                        "{case}"
+                    );
+                    assert_eq!(new_epoch, epoch, "{case}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_authorize_withdrawer_lockup_matrix() {
+    for (name, unix_timestamp, epoch, in_force) in LOCKUPS {
+        for custodian in CUSTODIANS {
+            let expected: ProgramResult = match (in_force, custodian) {
+                (false, _) | (true, Custodian::Signed) => Ok(()),
+                (true, Custodian::Absent) => Err(StakeError::CustodianMissing.into()),
+                (true, Custodian::Unsigned) => Err(StakeError::CustodianSignatureMissing.into()),
+                (true, Custodian::Impostor) => Err(StakeError::LockupInForce.into()),
+            };
+            let (result, withdrawer) = authorize_withdrawer((unix_timestamp, epoch), custodian);
+            let case = format!("{name} lockup, custodian {custodian:?}");
+            assert_eq!(result, expected, "{case}");
+            let expected_withdrawer = if expected.is_ok() { NEW } else { WITHDRAWER };
+            assert_eq!(withdrawer, expected_withdrawer, "{case}");
+        }
+    }
+}
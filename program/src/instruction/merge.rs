@@ -1,17 +1,21 @@
+use crate::instruction::MergeAccounts;
 use crate::state::{
-    clock_from_account_info, get_stake_state, relocate_lamports, set_stake_state, MergeKind,
-    StakeAuthorize, StakeHistorySysvar, StakeStateV2,
-};
-use pinocchio::{
-    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+    get_stake_state, relocate_all_lamports, set_stake_state, MergeKind, Signers, StakeAuthorize,
+    StakeStateV2, SyscallSysvars, SysvarProvider,
 };
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
 use pinocchio_log::log;
 
-// const MAX_SIGNERS: usize = 32;
-use crate::consts::MAX_SIGNERS;
-
 pub fn process_merge(accounts: &[AccountInfo]) -> ProgramResult {
-    let signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    process_merge_with_sysvars(accounts, &SyscallSysvars)
+}
+
+/// `process_merge` reading Clock and StakeHistory from `sysvars`
+pub fn process_merge_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    sysvars: &S,
+) -> ProgramResult {
+    let signers = Signers::new(accounts);
 
     // native asserts: 4 accounts (2 sysvars)
     // let destination_stake_account_info = next_account_info(account_info_iter)?;
@@ -19,17 +23,17 @@ pub fn process_merge(accounts: &[AccountInfo]) -> ProgramResult {
     // let clock_info = next_account_info(account_info_iter)?;
     // let _stake_history_info = next_account_info(account_info_iter)?;
 
-    let [destination_stake_account_info, source_stake_account_info, clock_info, _stake_history_info] =
-        accounts
-    else {
-        return Err(ProgramError::NotEnoughAccountKeys);
-    };
+    let MergeAccounts {
+        destination: destination_stake_account_info,
+        source: source_stake_account_info,
+        ..
+    } = MergeAccounts::try_from(accounts)?;
 
     // other accounts
     // let _stake_authority_info = next_account_info(account_info_iter)?;
 
-    let clock = clock_from_account_info(clock_info)?;
-    let stake_history = &StakeHistorySysvar(clock.epoch);
+    let clock = sysvars.clock()?;
+    let stake_history = &sysvars.stake_history(clock.epoch);
 
     // check source stake account and destination stake account are not having same key
     if source_stake_account_info.key() == destination_stake_account_info.key() {
@@ -49,7 +53,7 @@ pub fn process_merge(accounts: &[AccountInfo]) -> ProgramResult {
     destination_merge_kind
         .meta() // implementation of state.rs
         .authorized
-        .check(&signers_arr, StakeAuthorize::Staker) // implementation of state.rs
+        .check(&signers, StakeAuthorize::Staker) // implementation of state.rs
         .map_err(|_| ProgramError::MissingRequiredSignature)?;
 
     log!("Checking if source stake is mergeable");
@@ -69,11 +73,7 @@ pub fn process_merge(accounts: &[AccountInfo]) -> ProgramResult {
     set_stake_state(source_stake_account_info, &StakeStateV2::Uninitialized)?;
 
     // Drain the source stake account and transfer the lamports to the destination stake account
-    relocate_lamports(
-        source_stake_account_info,
-        destination_stake_account_info,
-        source_stake_account_info.lamports(),
-    )?;
+    relocate_all_lamports(source_stake_account_info, destination_stake_account_info)?;
 
     Ok(())
 }
@@ -1,22 +1,48 @@
 use pinocchio::program_error::ProgramError;
 
+pub mod accounts;
+pub mod authorize;
+pub mod authorize_both;
+pub mod deactivate_many;
+pub mod initialize_and_delegate;
+pub mod partial_deactivate;
 pub mod authorize_with_seed;
 pub mod authorized_checked;
 pub mod move_lamports;
 pub mod redelegate;
 pub mod set_lockup;
 pub mod split;
+pub mod split_many;
+#[cfg(feature = "stake-interface")]
+pub mod stake_interface;
 pub mod delegate_stake;
 pub mod merge;
+pub mod withdraw_excess;
+pub mod withdraw_max;
+pub mod withdraw_with_seed;
+#[cfg(test)]
+mod error_tests;
+#[cfg(test)]
+mod lockup_tests;
 
+pub use accounts::*;
+pub use authorize::*;
+pub use authorize_both::*;
+pub use deactivate_many::*;
+pub use initialize_and_delegate::*;
+pub use partial_deactivate::*;
 pub use authorize_with_seed::*;
 pub use authorized_checked::*;
 pub use move_lamports::*;
 pub use redelegate::*;
 pub use set_lockup::*;
 pub use split::*;
+pub use split_many::*;
 pub use delegate_stake::*;
 pub use merge::*;
+pub use withdraw_excess::*;
+pub use withdraw_max::*;
+pub use withdraw_with_seed::*;
 
 #[repr(u8)]
 pub enum StakeInstruction {
@@ -39,6 +65,24 @@ pub enum StakeInstruction {
     Redelegate,
     MoveStake,
     MoveLamports,
+    // Extensions specific to this program, numbered from 128 so they can't collide
+    // with instructions the native program adds later
+    /// `Withdraw` of everything currently withdrawable, computed on-chain
+    WithdrawMax = 128,
+    /// `Split` into several destinations at once
+    SplitMany,
+    /// `Authorize` of the staker and the withdrawer together
+    AuthorizeBoth,
+    /// `Deactivate` of several stake accounts sharing one stake authority
+    DeactivateMany,
+    /// `Initialize` and `DelegateStake` of a new stake account in one instruction
+    InitializeAndDelegate,
+    /// `Split` into a new account and `Deactivate` of only the split-off stake
+    PartialDeactivate,
+    /// `Withdraw` by a withdraw authority derived from a base key and seed
+    WithdrawWithSeed,
+    /// `Withdraw` of only the lamports above the delegated stake and reserve
+    WithdrawExcess,
 }
 
 impl TryFrom<&u8> for StakeInstruction {
@@ -65,22 +109,224 @@ impl TryFrom<&u8> for StakeInstruction {
             15 => Ok(StakeInstruction::Redelegate),
             16 => Ok(StakeInstruction::MoveStake),
             17 => Ok(StakeInstruction::MoveLamports),
+            128 => Ok(StakeInstruction::WithdrawMax),
+            129 => Ok(StakeInstruction::SplitMany),
+            130 => Ok(StakeInstruction::AuthorizeBoth),
+            131 => Ok(StakeInstruction::DeactivateMany),
+            132 => Ok(StakeInstruction::InitializeAndDelegate),
+            133 => Ok(StakeInstruction::PartialDeactivate),
+            134 => Ok(StakeInstruction::WithdrawWithSeed),
+            135 => Ok(StakeInstruction::WithdrawExcess),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
 }
 
+// Mirrors `StakeInstruction` for shank, which reads the account ordering of each
+// processor and the argument layout from these attributes to emit the IDL the
+// TypeScript client is generated from. Argument types use native integers and `Pubkey`
+// instead of the byte arrays the account layout stores. Extensions keep their
+// discriminants from 128; the ones taking any number of stake accounts list the first
+// and say the rest follow, since shank only knows fixed account lists.
+#[allow(dead_code)]
 mod idl_gen {
+    use alloc::string::String;
+    use pinocchio::pubkey::Pubkey;
+
+    #[derive(shank::ShankType)]
+    pub struct Authorized {
+        pub staker: Pubkey,
+        pub withdrawer: Pubkey,
+    }
+
+    #[derive(shank::ShankType)]
+    pub struct Lockup {
+        pub unix_timestamp: i64,
+        pub epoch: u64,
+        pub custodian: Pubkey,
+    }
+
+    #[derive(shank::ShankType)]
+    pub struct LockupArgs {
+        pub unix_timestamp: Option<i64>,
+        pub epoch: Option<u64>,
+        pub custodian: Option<Pubkey>,
+    }
+
+    #[derive(shank::ShankType)]
+    pub enum StakeAuthorize {
+        Staker,
+        Withdrawer,
+    }
+
     #[derive(shank::ShankInstruction)]
-    enum _MyProgramInstruction {
-        #[account(0, writable, signer, name = "payer_acc", desc = "Fee payer account")]
-        #[account(1, writable, name = "state_acc", desc = "New State account")]
-        #[account(2, name = "sysvar_rent_acc", desc = "Sysvar rent account")]
-        #[account(3, name = "system_program_acc", desc = "System program account")]
-        InitializeState,
-        #[account(0, writable, signer, name = "payer_acc", desc = "Fee payer account")]
-        #[account(1, writable, name = "state_acc", desc = "State account")]
-        UpdateState,
+    #[repr(u8)]
+    enum _StakeInstruction {
+        #[account(0, writable, name = "stake", desc = "Uninitialized stake account")]
+        #[account(1, name = "rent_sysvar", desc = "Rent sysvar")]
+        Initialize { authorized: Authorized, lockup: Lockup },
+
+        #[account(0, writable, name = "stake", desc = "Stake account to be updated")]
+        #[account(1, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(2, signer, name = "authority", desc = "Stake or withdraw authority")]
+        #[account(3, optional, signer, name = "lockup_authority", desc = "Lockup authority, if updating StakeAuthorize::Withdrawer before lockup expiration")]
+        Authorize { new_authorized: Pubkey, stake_authorize: StakeAuthorize },
+
+        #[account(0, writable, name = "stake", desc = "Initialized stake account to be delegated")]
+        #[account(1, name = "vote", desc = "Vote account to which this stake will be delegated")]
+        #[account(2, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(3, name = "stake_history_sysvar", desc = "Stake history sysvar")]
+        #[account(4, name = "stake_config", desc = "Unused account, formerly the stake config")]
+        #[account(5, signer, name = "stake_authority", desc = "Stake authority")]
+        DelegateStake,
+
+        #[account(0, writable, name = "stake", desc = "Stake account to be split")]
+        #[account(1, writable, name = "split_stake", desc = "Uninitialized stake account that will take the split-off amount")]
+        #[account(2, signer, name = "stake_authority", desc = "Stake authority")]
+        Split { lamports: u64 },
+
+        #[account(0, writable, name = "stake", desc = "Stake account from which to withdraw")]
+        #[account(1, writable, name = "recipient", desc = "Recipient account")]
+        #[account(2, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(3, name = "stake_history_sysvar", desc = "Stake history sysvar")]
+        #[account(4, signer, name = "withdraw_authority", desc = "Withdraw authority")]
+        #[account(5, optional, signer, name = "lockup_authority", desc = "Lockup authority, if before lockup expiration")]
+        Withdraw { lamports: u64 },
+
+        #[account(0, writable, name = "stake", desc = "Delegated stake account")]
+        #[account(1, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(2, signer, name = "stake_authority", desc = "Stake authority")]
+        Deactivate,
+
+        #[account(0, writable, name = "stake", desc = "Initialized stake account")]
+        #[account(1, signer, name = "authority", desc = "Lockup authority or withdraw authority")]
+        SetLockup(LockupArgs),
+
+        #[account(0, writable, name = "destination_stake", desc = "Destination stake account for the merge")]
+        #[account(1, writable, name = "source_stake", desc = "Source stake account to merge into the destination, drained and closed")]
+        #[account(2, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(3, name = "stake_history_sysvar", desc = "Stake history sysvar")]
+        #[account(4, signer, name = "stake_authority", desc = "Stake authority")]
+        Merge,
+
+        #[account(0, writable, name = "stake", desc = "Stake account to be updated")]
+        #[account(1, signer, name = "authority_base", desc = "Base key of stake or withdraw authority")]
+        #[account(2, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(3, optional, signer, name = "lockup_authority", desc = "Lockup authority, if updating StakeAuthorize::Withdrawer before lockup expiration")]
+        AuthorizeWithSeed {
+            new_authorized: Pubkey,
+            stake_authorize: StakeAuthorize,
+            authority_seed: String,
+            authority_owner: Pubkey,
+        },
+
+        #[account(0, writable, name = "stake", desc = "Uninitialized stake account")]
+        #[account(1, name = "rent_sysvar", desc = "Rent sysvar")]
+        #[account(2, name = "stake_authority", desc = "The stake authority")]
+        #[account(3, signer, name = "withdraw_authority", desc = "The withdraw authority")]
+        InitializeChecked,
+
+        #[account(0, writable, name = "stake", desc = "Stake account to be updated")]
+        #[account(1, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(2, signer, name = "authority", desc = "The stake or withdraw authority")]
+        #[account(3, signer, name = "new_authority", desc = "The new stake or withdraw authority")]
+        #[account(4, optional, signer, name = "lockup_authority", desc = "Lockup authority, if updating StakeAuthorize::Withdrawer before lockup expiration")]
+        AuthorizeChecked { stake_authorize: StakeAuthorize },
+
+        #[account(0, writable, name = "stake", desc = "Stake account to be updated")]
+        #[account(1, signer, name = "authority_base", desc = "Base key of stake or withdraw authority")]
+        #[account(2, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(3, signer, name = "new_authority", desc = "The new stake or withdraw authority")]
+        #[account(4, optional, signer, name = "lockup_authority", desc = "Lockup authority, if updating StakeAuthorize::Withdrawer before lockup expiration")]
+        AuthorizeCheckedWithSeed {
+            stake_authorize: StakeAuthorize,
+            authority_seed: String,
+            authority_owner: Pubkey,
+        },
+
+        #[account(0, writable, name = "stake", desc = "Initialized stake account")]
+        #[account(1, signer, name = "authority", desc = "Lockup authority or withdraw authority")]
+        #[account(2, optional, signer, name = "new_lockup_authority", desc = "New lockup authority")]
+        SetLockupChecked { unix_timestamp: Option<i64>, epoch: Option<u64> },
+
+        GetMinimumDelegation,
+
+        #[account(0, writable, name = "stake", desc = "Delegated stake account")]
+        #[account(1, name = "delinquent_vote", desc = "Delinquent vote account for the delegated stake account")]
+        #[account(2, name = "reference_vote", desc = "Reference vote account that has voted at least once in the last N epochs")]
+        DeactivateDelinquent,
+
+        #[account(0, writable, name = "stake", desc = "Delegated stake account to be redelegated")]
+        #[account(1, writable, name = "uninitialized_stake", desc = "Uninitialized stake account that will hold the redelegated stake")]
+        #[account(2, name = "vote", desc = "Vote account to which this stake will be re-delegated")]
+        #[account(3, name = "stake_config", desc = "Unused account, formerly the stake config")]
+        #[account(4, signer, name = "stake_authority", desc = "Stake authority")]
+        Redelegate,
+
+        #[account(0, writable, name = "source_stake", desc = "Active source stake account")]
+        #[account(1, writable, name = "destination_stake", desc = "Active or inactive destination stake account")]
+        #[account(2, signer, name = "stake_authority", desc = "Stake authority")]
+        MoveStake { lamports: u64 },
+
+        #[account(0, writable, name = "source_stake", desc = "Active or inactive source stake account")]
+        #[account(1, writable, name = "destination_stake", desc = "Mergeable destination stake account")]
+        #[account(2, signer, name = "stake_authority", desc = "Stake authority")]
+        MoveLamports { lamports: u64 },
+
+        #[account(0, writable, name = "stake", desc = "Stake account from which to withdraw")]
+        #[account(1, writable, name = "recipient", desc = "Recipient account")]
+        #[account(2, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(3, name = "stake_history_sysvar", desc = "Stake history sysvar")]
+        #[account(4, signer, name = "withdraw_authority", desc = "Withdraw authority")]
+        #[account(5, optional, signer, name = "lockup_authority", desc = "Lockup authority, if before lockup expiration")]
+        WithdrawMax = 128,
+
+        // the amounts are the instruction data, one little-endian `u64` per destination
+        // with no length prefix, which shank can't describe; build it with `sdk`
+        #[account(0, writable, name = "stake", desc = "Stake account to be split")]
+        #[account(1, signer, name = "stake_authority", desc = "Stake authority")]
+        #[account(2, writable, name = "split_stake", desc = "First uninitialized stake account to split into; up to 7 more follow it")]
+        SplitMany,
+
+        #[account(0, writable, name = "stake", desc = "Stake account to be updated")]
+        #[account(1, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(2, signer, name = "stake_authority", desc = "Current stake authority")]
+        #[account(3, signer, name = "withdraw_authority", desc = "Current withdraw authority")]
+        #[account(4, optional, signer, name = "lockup_authority", desc = "Lockup authority, if before lockup expiration")]
+        AuthorizeBoth { new_staker: Pubkey, new_withdrawer: Pubkey },
+
+        #[account(0, signer, name = "stake_authority", desc = "Stake authority of every stake account")]
+        #[account(1, writable, name = "stake", desc = "First delegated stake account; the rest follow it")]
+        DeactivateMany { count: u8 },
+
+        #[account(0, writable, name = "stake", desc = "Uninitialized stake account")]
+        #[account(1, name = "vote", desc = "Vote account to which this stake will be delegated")]
+        #[account(2, signer, name = "stake_authority", desc = "The new stake authority")]
+        InitializeAndDelegate { authorized: Authorized, lockup: Lockup },
+
+        #[account(0, writable, name = "stake", desc = "Delegated stake account to be split")]
+        #[account(1, writable, name = "split_stake", desc = "Uninitialized stake account that will take the deactivated amount")]
+        #[account(2, signer, name = "stake_authority", desc = "Stake authority")]
+        PartialDeactivate { lamports: u64 },
+
+        #[account(0, writable, name = "stake", desc = "Stake account from which to withdraw")]
+        #[account(1, writable, name = "recipient", desc = "Recipient account")]
+        #[account(2, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(3, name = "stake_history_sysvar", desc = "Stake history sysvar")]
+        #[account(4, signer, name = "authority_base", desc = "Base key of the withdraw authority")]
+        #[account(5, optional, signer, name = "lockup_authority", desc = "Lockup authority, if before lockup expiration")]
+        WithdrawWithSeed {
+            lamports: u64,
+            authority_seed: String,
+            authority_owner: Pubkey,
+        },
+
+        #[account(0, writable, name = "stake", desc = "Stake account from which to withdraw")]
+        #[account(1, writable, name = "recipient", desc = "Recipient account")]
+        #[account(2, name = "clock_sysvar", desc = "Clock sysvar")]
+        #[account(3, name = "stake_history_sysvar", desc = "Stake history sysvar")]
+        #[account(4, signer, name = "withdraw_authority", desc = "Withdraw authority")]
+        #[account(5, optional, signer, name = "lockup_authority", desc = "Lockup authority, if before lockup expiration")]
+        WithdrawExcess,
     }
 }
-
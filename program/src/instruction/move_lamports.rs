@@ -1,7 +1,13 @@
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
 
 use crate::{
-    helpers::MergeKind,
+    helpers::{lamports_sub, MergeKind},
+    instruction::MoveAccounts,
     state::{move_stake_or_lamports_shared_checks, relocate_lamports},
 };
 
@@ -9,25 +15,31 @@ pub fn process_move_lamports(accounts: &[AccountInfo], lamports: u64) -> Program
     if lamports <= 0 {
         return Err(ProgramError::InvalidArgument);
     }
-    let [source_stake_account_info, destination_stake_account_info, stake_authority_info, _remaining @ ..] =
-        accounts
-    else {
-        return Err(ProgramError::NotEnoughAccountKeys);
-    };
+    let MoveAccounts {
+        source: source_stake_account_info,
+        destination: destination_stake_account_info,
+        stake_authority: stake_authority_info,
+    } = MoveAccounts::try_from(accounts)?;
+
+    let clock = Clock::get()?;
 
     let (source_merge_kind, _) = move_stake_or_lamports_shared_checks(
         source_stake_account_info,
         destination_stake_account_info,
         stake_authority_info,
+        &clock,
     )?;
 
     let source_free_lamports = match source_merge_kind {
-        MergeKind::FullyActive(source_meta, source_stake) => source_stake_account_info
-            .lamports()
-            .saturating_sub(u64::from_le_bytes(source_stake.delegation.stake))
-            .saturating_sub(u64::from_le_bytes(source_meta.rent_exempt_reserve)),
+        MergeKind::FullyActive(source_meta, source_stake) => lamports_sub(
+            lamports_sub(
+                source_stake_account_info.lamports(),
+                source_stake.delegation.delegated_stake(),
+            )?,
+            source_meta.rent_exempt_reserve(),
+        )?,
         MergeKind::Inactive(source_meta, source_lamports, _) => {
-            source_lamports.saturating_sub(u64::from_le_bytes(source_meta.rent_exempt_reserve))
+            lamports_sub(source_lamports, source_meta.rent_exempt_reserve())?
         }
         _ => return Err(ProgramError::InvalidAccountData),
     };
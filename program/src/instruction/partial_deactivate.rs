@@ -0,0 +1,122 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    instruction::{deactivate_stake_account, process_split_with_sysvars, SplitAccounts},
+    state::{Signers, SyscallSysvars, SysvarProvider},
+};
+
+// Extension instruction: `Split` of `lamports` into an uninitialized destination, then
+// `Deactivate` of the destination only, so part of a delegation can start cooling down
+// while the rest stays staked. Takes `Split`'s accounts and data; the destination ends
+// up deactivating at the current epoch and the source is left active.
+
+pub fn process_partial_deactivate(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_partial_deactivate_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_partial_deactivate` reading Clock, Rent and StakeHistory from `sysvars`
+pub fn process_partial_deactivate_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    sysvars: &S,
+) -> ProgramResult {
+    let lamports = data
+        .try_into()
+        .map(u64::from_le_bytes)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    process_split_with_sysvars(accounts, lamports, sysvars)?;
+
+    #[cfg(not(feature = "strict-interface"))]
+    let signers = Signers::new(accounts);
+    // 0. source, 1. destination, 2. stake authority
+    #[cfg(feature = "strict-interface")]
+    let signers = Signers::at(accounts, 2)?;
+
+    let SplitAccounts { destination, .. } = SplitAccounts::try_from(accounts)?;
+    let clock = sysvars.clock()?;
+    let stake_history = &sysvars.stake_history(clock.epoch);
+
+    // the split copied the source's authorities and flags, so this is the check a
+    // `Deactivate` of the destination would make
+    deactivate_stake_account(
+        destination,
+        1,
+        &signers,
+        clock.epoch,
+        stake_history,
+        &sysvars.features(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        state::{
+            Authorized, Delegation, Lockup, Meta, MockSysvars, Stake, StakeFlags, StakeStateV2,
+        },
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+
+    const STAKER: [u8; 32] = [3; 32];
+
+    fn state(account: &AccountInfo) -> StakeStateV2 {
+        StakeStateV2::deserialize(&account.try_borrow_data().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_partial_deactivate_deactivates_only_the_split() {
+        let mut sysvars = MockSysvars::default();
+        sysvars.clock.epoch = 10;
+        let reserve = sysvars.rent.minimum_balance(StakeStateV2::size_of());
+        let stake = 3_000_000_000;
+        let source = StakeStateV2::Stake(
+            Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default()),
+            Stake::new(Delegation::new(&[9; 32], stake, 0), 0),
+            StakeFlags::empty(),
+        );
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake([1; 32], reserve + stake, source),
+            TestAccount::stake([2; 32], reserve, StakeStateV2::Uninitialized),
+            TestAccount::signer(STAKER),
+        ]);
+        let accounts: [AccountInfo; 3] = input.accounts();
+
+        let data = 1_000_000_000u64.to_le_bytes();
+        let (result, allocations) = heap_allocations(|| {
+            process_partial_deactivate_with_sysvars(&accounts, &data, &sysvars)
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(allocations, 0);
+
+        let StakeStateV2::Stake(_, source, _) = state(&accounts[0]) else {
+            panic!("source not delegated");
+        };
+        let StakeStateV2::Stake(_, destination, _) = state(&accounts[1]) else {
+            panic!("destination not delegated");
+        };
+        assert_eq!(source.delegation.delegated_stake(), 2_000_000_000);
+        assert_eq!(source.delegation.deactivation_epoch(), u64::MAX);
+        assert_eq!(destination.delegation.delegated_stake(), 1_000_000_000);
+        assert_eq!(destination.delegation.deactivation_epoch(), 10);
+    }
+
+    #[test]
+    fn test_partial_deactivate_needs_a_delegation() {
+        let sysvars = MockSysvars::default();
+        let meta = Meta::new(0, Authorized::auto(&STAKER), Lockup::default());
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake([1; 32], 2_000_000_000, StakeStateV2::Initialized(meta)),
+            TestAccount::stake([2; 32], 0, StakeStateV2::Uninitialized),
+            TestAccount::signer(STAKER),
+        ]);
+        let accounts: [AccountInfo; 3] = input.accounts();
+
+        let data = 1_000_000_000u64.to_le_bytes();
+        assert_eq!(
+            process_partial_deactivate_with_sysvars(&accounts, &data, &sysvars),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -1,5 +1,9 @@
 use pinocchio::{
-    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
 };
 
 use pinocchio::instruction::{Seed, Signer};
@@ -55,7 +59,7 @@ pub fn process_start_redelegation(accounts: &[AccountInfo], data: &[u8]) -> Prog
         return Err(ProgramError::InvalidArgument);
     }
 
-    redelegate_state.start_redelegation(&ix_data)
+    redelegate_state.start_redelegation(ix_data, &Clock::get()?)
 }
 
 pub fn process_complete_redelegation(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
@@ -1,22 +1,22 @@
 use pinocchio::{
-    account_info::AccountInfo,
-    program_error::ProgramError,
-    pubkey::Pubkey,
-    sysvars::{clock::Clock, Sysvar},
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvars::clock::Clock,
     ProgramResult,
 };
 
 use crate::{
-    error::to_program_error,
+    error::{to_program_error, AccountCheck, AccountError},
+    instruction::{SetLockupAccounts, SetLockupCheckedAccounts},
     state::{
-        get_stake_state, try_get_stake_state_mut, Epoch, SetLockupSignerArgs, StakeStateV2,
-        UnixTimestamp,
+        collect_signers_checked_with_custodian, get_stake_state_at, try_get_stake_state_mut_at,
+        Epoch, Lockup, SetLockupSignerArgs, SignerSet, Signers, StakeStateV2, SyscallSysvars,
+        SysvarProvider, UnixTimestamp,
     },
 };
 
 #[cfg(not(test))]
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct LockupArgs {
     pub unix_timestamp: Option<UnixTimestamp>,
     pub epoch: Option<Epoch>,
@@ -26,6 +26,7 @@ pub struct LockupArgs {
 #[cfg(test)]
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct LockupArgs {
     pub unix_timestamp: Option<UnixTimestamp>,
     pub epoch: Option<Epoch>,
@@ -126,24 +127,140 @@ impl LockupArgs {
             _ => return Err(ProgramError::InvalidInstructionData),
         }
     }
+
+    /// `lockup` with only the fields given here replaced. Every lockup change, checked
+    /// or not, goes through this.
+    pub fn merge(&self, lockup: &Lockup) -> Lockup {
+        Lockup {
+            unix_timestamp: self.unix_timestamp.unwrap_or(lockup.unix_timestamp),
+            epoch: self.epoch.unwrap_or(lockup.epoch),
+            custodian: self.custodian.unwrap_or(lockup.custodian),
+        }
+    }
+}
+
+/// `SetLockupChecked` data: `LockupArgs` without the custodian, which is passed as a
+/// signing account instead
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct LockupCheckedArgs {
+    pub unix_timestamp: Option<UnixTimestamp>,
+    pub epoch: Option<Epoch>,
+}
+
+impl LockupCheckedArgs {
+    pub fn from_data(data: &[u8]) -> Result<Self, ProgramError> {
+        let (unix_timestamp, data) = parse_option(data)?;
+        let (epoch, data) = parse_option(data)?;
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            unix_timestamp,
+            epoch,
+        })
+    }
+
+    /// The `LockupArgs` these amount to, with the new `custodian` if its account signed
+    pub fn with_custodian(self, custodian: Option<&Pubkey>) -> LockupArgs {
+        LockupArgs {
+            unix_timestamp: self.unix_timestamp,
+            epoch: self.epoch,
+            custodian: custodian.copied(),
+        }
+    }
+
+    /// `lockup` with only the fields given here, and `custodian` if its account signed,
+    /// replaced
+    pub fn merge(&self, lockup: &Lockup, custodian: Option<&Pubkey>) -> Lockup {
+        self.with_custodian(custodian).merge(lockup)
+    }
+}
+
+/// A bincode `Option<[u8; 8]>` off the front of `data`, and the rest
+fn parse_option(data: &[u8]) -> Result<(Option<[u8; 8]>, &[u8]), ProgramError> {
+    match data.split_first() {
+        Some((0, rest)) => Ok((None, rest)),
+        Some((1, rest)) => rest
+            .split_first_chunk::<8>()
+            .map(|(value, rest)| (Some(*value), rest))
+            .ok_or(ProgramError::InvalidInstructionData),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
 }
 
 pub fn process_set_lockup(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_set_lockup_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_set_lockup` reading Clock from `sysvars`
+pub fn process_set_lockup_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    sysvars: &S,
+) -> ProgramResult {
     let lockup_args = LockupArgs::from_data(data)?;
 
-    let [stake_account_info, _remaining @ ..] = accounts else {
-        return Err(ProgramError::NotEnoughAccountKeys);
-    };
+    let SetLockupAccounts {
+        stake: stake_account_info,
+    } = SetLockupAccounts::try_from(accounts)?;
+
+    // 0. stake account, 1. lockup or withdraw authority
+    #[cfg(feature = "strict-interface")]
+    let accounts = accounts
+        .get(1..2)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
 
-    let signer_args = get_set_lockup_signer_args(stake_account_info, accounts)?;
+    let signer_args = get_set_lockup_signer_args(stake_account_info, &Signers::new(accounts))?;
 
-    let clock = Clock::get()?;
+    let clock = sysvars.clock()?;
 
     do_set_lookup(stake_account_info, &lockup_args, signer_args, &clock)?;
 
     Ok(())
 }
 
+pub fn process_set_lockup_checked(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_set_lockup_checked_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_set_lockup_checked` reading Clock from `sysvars`
+pub fn process_set_lockup_checked_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    sysvars: &S,
+) -> ProgramResult {
+    let lockup_checked_args = LockupCheckedArgs::from_data(data)?;
+
+    let SetLockupCheckedAccounts {
+        stake: stake_account_info,
+        custodian: custodian_info,
+    } = SetLockupCheckedAccounts::try_from(accounts)?;
+
+    // 0. stake account, 1. lockup or withdraw authority
+    #[cfg(feature = "strict-interface")]
+    let accounts = accounts
+        .get(1..2)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let (signers, custodian) =
+        collect_signers_checked_with_custodian(accounts, custodian_info, true)?;
+    let signer_args = get_set_lockup_signer_args(stake_account_info, &signers)?;
+
+    let clock = sysvars.clock()?;
+
+    do_set_lookup(
+        stake_account_info,
+        &lockup_checked_args.with_custodian(custodian),
+        signer_args,
+        &clock,
+    )?;
+
+    Ok(())
+}
+
 fn do_set_lookup(
     stake_account_info: &AccountInfo,
     lockup: &LockupArgs,
@@ -151,7 +268,7 @@ fn do_set_lookup(
     clock: &Clock,
 ) -> ProgramResult {
     let mut stake_account: pinocchio::account_info::RefMut<'_, StakeStateV2> =
-        try_get_stake_state_mut(stake_account_info)?;
+        try_get_stake_state_mut_at(stake_account_info, 0)?;
     match *stake_account {
         StakeStateV2::Initialized(ref mut meta) => meta
             .set_lockup(lockup, signer_args, clock)
@@ -159,47 +276,198 @@ fn do_set_lookup(
         StakeStateV2::Stake(ref mut meta, _stake, _stake_flags) => meta
             .set_lockup(lockup, signer_args, clock)
             .map_err(to_program_error),
-        _ => Err(ProgramError::InvalidAccountData),
+        _ => {
+            Err(AccountError::new(0, AccountCheck::State, ProgramError::InvalidAccountData).into())
+        }
     }
 }
 
-fn get_set_lockup_signer_args(
+fn get_set_lockup_signer_args<S: SignerSet + ?Sized>(
     stake_account_info: &AccountInfo,
-    accounts: &[AccountInfo],
+    signers: &S,
 ) -> Result<SetLockupSignerArgs, ProgramError> {
     let stake_account: pinocchio::account_info::Ref<'_, StakeStateV2> =
-        get_stake_state(stake_account_info)?;
+        get_stake_state_at(stake_account_info, 0)?;
 
-    let mut has_custodian_signer = false;
-    let mut has_withdrawer_signer = false;
     match *stake_account {
         StakeStateV2::Initialized(ref meta) | StakeStateV2::Stake(ref meta, _, _) => {
-            for account in accounts {
-                if account.is_signer() {
-                    if meta.lockup.custodian == *account.key() {
-                        has_custodian_signer = true;
-                    }
-                    if meta.authorized.withdrawer == *account.key() {
-                        has_withdrawer_signer = true;
-                    }
-                }
-            }
+            Ok(SetLockupSignerArgs {
+                has_custodian_signer: signers.contains_signer(&meta.lockup.custodian),
+                has_withdrawer_signer: signers.contains_signer(&meta.authorized.withdrawer),
+            })
         }
         _ => {
-            return Err(ProgramError::InvalidAccountData);
+            Err(AccountError::new(0, AccountCheck::State, ProgramError::InvalidAccountData).into())
         }
     }
-    Ok(SetLockupSignerArgs {
-        has_custodian_signer,
-        has_withdrawer_signer,
-    })
 }
 
 #[cfg(test)]
 mod test {
-    use super::LockupArgs;
+    use super::*;
+    use crate::{
+        state::{Authorized, CountingSysvars, Lockup, Meta, MockSysvars},
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
     use bincode::serialize;
 
+    #[test]
+    fn test_set_lockup_does_not_allocate() {
+        let withdrawer = [3; 32];
+        let meta = Meta::new(0, Authorized::auto(&withdrawer), Lockup::default());
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake([1; 32], 0, StakeStateV2::Initialized(meta)),
+            TestAccount::signer(withdrawer),
+        ]);
+        let accounts: [AccountInfo; 2] = input.accounts();
+        let args = LockupArgs {
+            unix_timestamp: None,
+            epoch: Some(10u64.to_le_bytes()),
+            custodian: None,
+        };
+
+        let (result, allocations) = heap_allocations(|| {
+            let signer_args = get_set_lockup_signer_args(&accounts[0], &Signers::new(&accounts))?;
+            do_set_lookup(&accounts[0], &args, signer_args, &Clock::default())
+        });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(allocations, 0);
+    }
+
+    #[test]
+    fn test_set_lockup_reads_clock_once() {
+        let withdrawer = [3; 32];
+        let custodian = [5; 32];
+        let lockup = Lockup {
+            unix_timestamp: 0i64.to_le_bytes(),
+            epoch: 100u64.to_le_bytes(),
+            custodian,
+        };
+        let meta = Meta::new(0, Authorized::auto(&withdrawer), lockup);
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake([1; 32], 0, StakeStateV2::Initialized(meta)),
+            TestAccount::signer(custodian),
+        ]);
+        let accounts: [AccountInfo; 2] = input.accounts();
+        let data = serialize(&LockupArgs {
+            unix_timestamp: None,
+            epoch: Some(200u64.to_le_bytes()),
+            custodian: None,
+        })
+        .unwrap();
+        let sysvars = CountingSysvars::new(MockSysvars::default());
+
+        // in force at the mock epoch 0, so only the custodian may change it
+        process_set_lockup_with_sysvars(&accounts, &data, &sysvars).unwrap();
+
+        assert_eq!(sysvars.clock_reads(), 1);
+    }
+
+    #[test]
+    fn test_set_lockup_checked_takes_the_signing_custodian() {
+        let withdrawer = [3; 32];
+        let new_custodian = [5; 32];
+        let meta = Meta::new(0, Authorized::auto(&withdrawer), Lockup::default());
+        let data = serialize(&LockupCheckedArgs {
+            unix_timestamp: None,
+            epoch: Some(200u64.to_le_bytes()),
+        })
+        .unwrap();
+
+        for custodian_signs in [true, false] {
+            let mut input = SerializedInput::new(&[
+                TestAccount::stake([1; 32], 0, StakeStateV2::Initialized(meta)),
+                TestAccount::signer(withdrawer),
+                TestAccount {
+                    is_signer: custodian_signs,
+                    ..TestAccount::readonly(new_custodian)
+                },
+            ]);
+            let accounts: [AccountInfo; 3] = input.accounts();
+
+            let sysvars = MockSysvars::default();
+            let (result, allocations) = heap_allocations(|| {
+                process_set_lockup_checked_with_sysvars(&accounts, &data, &sysvars)
+            });
+            assert_eq!(allocations, 0);
+            let state = StakeStateV2::deserialize(&accounts[0].try_borrow_data().unwrap()).unwrap();
+            if custodian_signs {
+                assert_eq!(result, Ok(()));
+                assert_eq!(
+                    state.lockup(),
+                    Some(Lockup {
+                        unix_timestamp: 0i64.to_le_bytes(),
+                        epoch: 200u64.to_le_bytes(),
+                        custodian: new_custodian,
+                    })
+                );
+            } else {
+                assert_eq!(result, Err(ProgramError::MissingRequiredSignature));
+                assert_eq!(state.lockup(), Some(Lockup::default()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_changes_only_given_fields() {
+        let lockup = Lockup {
+            unix_timestamp: 1i64.to_le_bytes(),
+            epoch: 2u64.to_le_bytes(),
+            custodian: [3; 32],
+        };
+
+        let args = LockupArgs {
+            unix_timestamp: None,
+            epoch: Some(20u64.to_le_bytes()),
+            custodian: None,
+        };
+        let merged = args.merge(&lockup);
+        assert_eq!(merged.unix_timestamp(), 1);
+        assert_eq!(merged.epoch(), 20);
+        assert_eq!(merged.custodian(), &[3; 32]);
+
+        let checked = LockupCheckedArgs {
+            unix_timestamp: Some(10i64.to_le_bytes()),
+            epoch: None,
+        };
+        assert_eq!(
+            checked.merge(&lockup, None),
+            Lockup {
+                unix_timestamp: 10i64.to_le_bytes(),
+                ..lockup
+            }
+        );
+        assert_eq!(
+            checked.merge(&lockup, Some(&[9; 32])),
+            Lockup {
+                unix_timestamp: 10i64.to_le_bytes(),
+                custodian: [9; 32],
+                ..lockup
+            }
+        );
+    }
+
+    #[test]
+    fn test_checked_instruction_data() {
+        for unix_timestamp in [None, Some((-7i64).to_le_bytes())] {
+            for epoch in [None, Some(9464321479845648u64.to_le_bytes())] {
+                let args = LockupCheckedArgs {
+                    unix_timestamp,
+                    epoch,
+                };
+                let data = serialize(&args).unwrap();
+                assert_eq!(LockupCheckedArgs::from_data(&data), Ok(args));
+
+                // truncated or padded data is rejected
+                assert!(LockupCheckedArgs::from_data(&data[..data.len() - 1]).is_err());
+                let padded = [data.as_slice(), &[0]].concat();
+                assert!(LockupCheckedArgs::from_data(&padded).is_err());
+            }
+        }
+        assert!(LockupCheckedArgs::from_data(&[2, 0]).is_err());
+    }
+
     #[test]
     fn test_instruction_data() {
         let args_arr = [
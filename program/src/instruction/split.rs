@@ -1,44 +1,66 @@
 use crate::{
-    consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
-    error::StakeError,
+    error::{AccountCheck, AccountError, StakeError},
+    helpers::{get_stake_status_with_features, lamports_sub},
+    instruction::SplitAccounts,
     state::{
-        bytes_to_u64, get_minimum_delegation, relocate_lamports, to_program_error,
-        try_get_stake_state_mut, validate_split_amount, StakeAuthorize, StakeHistorySysvar,
-        StakeStateV2,
+        pubkey_eq, relocate_lamports, stake_rent_exempt_reserve, to_program_error,
+        try_get_stake_state_mut_at, validate_split_amount, Signers, StakeAuthorize, StakeStateTag,
+        StakeStateV2, SyscallSysvars, SysvarProvider, ValidatedSplitInfo,
     },
 };
-use pinocchio::{
-    account_info::AccountInfo,
-    program_error::ProgramError,
-    pubkey::Pubkey,
-    sysvars::{clock::Clock, Sysvar},
-    ProgramResult,
-};
-
-use crate::state::utils::collect_signers;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
 
 // almost all native stake program processors accumulate every account signer
 // they then defer all signer validation to functions on Meta or Authorized
 // this results in an instruction interface that is much looser than the one documented
 // to avoid breaking backwards compatibility, we do the same here
-// in the future, we may decide to tighten the interface and break badly formed transactions
+// the `strict-interface` feature tightens it: only the documented authority position counts
 
 pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramResult {
-    let mut signers_arr = [Pubkey::default(); 32];
-    let _signers = collect_signers(accounts, &mut signers_arr)?;
+    process_split_with_sysvars(accounts, split_lamports, &SyscallSysvars)
+}
 
-    let [source_stake_account_info, destination_stake_account_info, _rest @ ..] = accounts else {
-        return Err(ProgramError::NotEnoughAccountKeys);
-    };
+/// `process_split` reading Clock, Rent and StakeHistory from `sysvars`
+pub fn process_split_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    split_lamports: u64,
+    sysvars: &S,
+) -> ProgramResult {
+    let reserve = stake_rent_exempt_reserve(&sysvars.rent()?);
+    split_with_reserve(accounts, split_lamports, sysvars, reserve)
+}
+
+/// `process_split_with_sysvars` given the stake account rent-exempt `reserve`, so
+/// `SplitMany` computes it once for all of its splits
+pub(crate) fn split_with_reserve<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    split_lamports: u64,
+    sysvars: &S,
+    reserve: u64,
+) -> ProgramResult {
+    #[cfg(not(feature = "strict-interface"))]
+    let signers = Signers::new(accounts);
+    // 0. source, 1. destination, 2. stake authority
+    #[cfg(feature = "strict-interface")]
+    let signers = Signers::at(accounts, 2)?;
 
-    let clock = Clock::get()?;
-    let stake_history = &StakeHistorySysvar(clock.epoch);
+    let SplitAccounts {
+        source: source_stake_account_info,
+        destination: destination_stake_account_info,
+    } = SplitAccounts::try_from(accounts)?;
 
-    let destination_data_len = destination_stake_account_info.data_len();
-    if destination_data_len != StakeStateV2::size_of() {
-        return Err(ProgramError::InvalidAccountData);
+    // the same account twice would otherwise surface as a failed second borrow of its state
+    if pubkey_eq(
+        source_stake_account_info.key(),
+        destination_stake_account_info.key(),
+    ) {
+        return Err(ProgramError::InvalidArgument);
     }
 
+    let clock = sysvars.clock()?;
+    let stake_history = &sysvars.stake_history(clock.epoch);
+    let features = sysvars.features();
+
     let source_lamport_balance = source_stake_account_info.lamports();
     let destination_lamport_balance = destination_stake_account_info.lamports();
 
@@ -46,136 +68,128 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
         return Err(ProgramError::InsufficientFunds);
     }
 
-    let mut source_stake_account: pinocchio::account_info::RefMut<'_, StakeStateV2> =
-        try_get_stake_state_mut(source_stake_account_info)?;
-    let mut dest_stake_account: pinocchio::account_info::RefMut<'_, StakeStateV2> =
-        try_get_stake_state_mut(destination_stake_account_info)?;
-
-    if let StakeStateV2::Uninitialized = *dest_stake_account {
-        // we can split into this
-    } else {
-        return Err(ProgramError::InvalidAccountData);
+    if StakeStateV2::peek_tag(&destination_stake_account_info.try_borrow_data()?)?
+        != StakeStateTag::Uninitialized
+    {
+        return Err(
+            AccountError::new(1, AccountCheck::State, ProgramError::InvalidAccountData).into(),
+        );
     }
 
-    match *source_stake_account {
-        StakeStateV2::Stake(source_meta, mut source_stake, stake_flags) => {
-            source_meta
-                .authorized
-                .check(&signers_arr, StakeAuthorize::Staker)
-                .map_err(to_program_error)?;
+    // both state borrows are released before the lamports move, so `relocate_lamports`
+    // and anything it calls can borrow the accounts again
+    {
+        let mut source_stake_account: pinocchio::account_info::RefMut<'_, StakeStateV2> =
+            try_get_stake_state_mut_at(source_stake_account_info, 0)?;
+        let mut dest_stake_account: pinocchio::account_info::RefMut<'_, StakeStateV2> =
+            try_get_stake_state_mut_at(destination_stake_account_info, 1)?;
 
-            let minimum_delegation = get_minimum_delegation();
+        match &mut *source_stake_account {
+            StakeStateV2::Stake(source_meta, source_stake, _stake_flags) => {
+                source_meta
+                    .authorized
+                    .check(&signers, StakeAuthorize::Staker)
+                    .map_err(to_program_error)?;
 
-            let status = source_stake.delegation.stake_activating_and_deactivating(
-                clock.epoch.to_be_bytes(),
-                stake_history,
-                PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
-            );
+                let minimum_delegation = features.minimum_delegation();
 
-            let is_active = bytes_to_u64(status.effective) > 0;
+                let status = get_stake_status_with_features(
+                    source_stake,
+                    clock.epoch,
+                    stake_history,
+                    &features,
+                );
 
-            // NOTE this function also internally summons Rent via syscall
-            let validated_split_info = validate_split_amount(
-                source_lamport_balance,
-                destination_lamport_balance,
-                split_lamports,
-                &source_meta,
-                destination_data_len,
-                minimum_delegation,
-                is_active,
-            )?;
-
-            // split the stake, subtract rent_exempt_balance unless
-            // the destination account already has those lamports
-            // in place.
-            // this means that the new stake account will have a stake equivalent to
-            // lamports minus rent_exempt_reserve if it starts out with a zero balance
-            let (remaining_stake_delta, split_stake_amount) =
-                if validated_split_info.source_remaining_balance == 0 {
-                    // If split amount equals the full source stake (as implied by 0
-                    // source_remaining_balance), the new split stake must equal the same
-                    // amount, regardless of any current lamport balance in the split account.
-                    // Since split accounts retain the state of their source account, this
-                    // prevents any magic activation of stake by prefunding the split account.
-                    //
-                    // The new split stake also needs to ignore any positive delta between the
-                    // original rent_exempt_reserve and the split_rent_exempt_reserve, in order
-                    // to prevent magic activation of stake by splitting between accounts of
-                    // different sizes.
-                    let remaining_stake_delta = split_lamports
-                        .saturating_sub(u64::from_le_bytes(source_meta.rent_exempt_reserve));
-                    (remaining_stake_delta, remaining_stake_delta)
-                } else {
-                    // Otherwise, the new split stake should reflect the entire split
-                    // requested, less any lamports needed to cover the
-                    // split_rent_exempt_reserve.
-                    if u64::from_le_bytes(source_stake.delegation.stake)
-                        .saturating_sub(split_lamports)
-                        < minimum_delegation
-                    {
-                        return Err(StakeError::InsufficientDelegation.into());
-                    }
-
-                    (
-                        split_lamports,
-                        split_lamports.saturating_sub(
-                            validated_split_info
-                                .destination_rent_exempt_reserve
-                                .saturating_sub(destination_lamport_balance),
-                        ),
-                    )
-                };
-
-            if split_stake_amount < minimum_delegation {
-                return Err(StakeError::InsufficientDelegation.into());
-            }
+                let is_active = status.effective > 0;
 
-            let destination_stake =
-                source_stake.split(remaining_stake_delta, split_stake_amount)?;
+                let validated_split_info = validate_split_amount(
+                    source_lamport_balance,
+                    destination_lamport_balance,
+                    split_lamports,
+                    source_meta,
+                    // the destination is checked to be the stake account size by `SplitAccounts`
+                    reserve,
+                    minimum_delegation,
+                    is_active,
+                )?;
 
-            let mut destination_meta = source_meta;
-            destination_meta.rent_exempt_reserve = validated_split_info
-                .destination_rent_exempt_reserve
-                .to_be_bytes();
+                let (remaining_stake_delta, split_stake_amount) = split_stake_amounts(
+                    source_stake.delegation.delegated_stake(),
+                    source_meta.rent_exempt_reserve(),
+                    split_lamports,
+                    destination_lamport_balance,
+                    &validated_split_info,
+                    minimum_delegation,
+                )?;
+                debug_log!(
+                    "split: lamports, stake removed, stake split, source remaining, dest reserve",
+                    split_lamports,
+                    remaining_stake_delta,
+                    split_stake_amount,
+                    validated_split_info.source_remaining_balance,
+                    validated_split_info.destination_rent_exempt_reserve
+                );
 
-            *source_stake_account = StakeStateV2::Stake(source_meta, source_stake, stake_flags);
+                // only the delegated amount changes on the source
+                source_stake.remove_stake(remaining_stake_delta)?;
 
-            *dest_stake_account =
-                StakeStateV2::Stake(destination_meta, destination_stake, stake_flags);
-        }
-        StakeStateV2::Initialized(source_meta) => {
-            source_meta
-                .authorized
-                .check(&signers_arr, StakeAuthorize::Staker)
-                .map_err(to_program_error)?;
-
-            // NOTE this function also internally summons Rent via syscall
-            let validated_split_info = validate_split_amount(
-                source_lamport_balance,
-                destination_lamport_balance,
-                split_lamports,
-                &source_meta,
-                destination_data_len,
-                0,     // additional_required_lamports
-                false, // is_active
-            )?;
-
-            let mut destination_meta = source_meta;
-            destination_meta.rent_exempt_reserve = validated_split_info
-                .destination_rent_exempt_reserve
-                .to_le_bytes();
-
-            *dest_stake_account = StakeStateV2::Initialized(destination_meta);
-        }
-        StakeStateV2::Uninitialized => {
-            if !source_stake_account_info.is_signer() {
-                return Err(ProgramError::MissingRequiredSignature);
+                // the destination inherits everything else, flags included
+                *dest_stake_account = *source_stake_account;
+                if let Some(destination_meta) = dest_stake_account.meta_mut() {
+                    destination_meta.set_rent_exempt_reserve(
+                        validated_split_info.destination_rent_exempt_reserve,
+                    );
+                }
+                if let Some(destination_stake) = dest_stake_account.stake_mut() {
+                    destination_stake.delegation.set_stake(split_stake_amount);
+                }
+            }
+            StakeStateV2::Initialized(source_meta) => {
+                source_meta
+                    .authorized
+                    .check(&signers, StakeAuthorize::Staker)
+                    .map_err(to_program_error)?;
+
+                let validated_split_info = validate_split_amount(
+                    source_lamport_balance,
+                    destination_lamport_balance,
+                    split_lamports,
+                    source_meta,
+                    reserve,
+                    0,     // additional_required_lamports
+                    false, // is_active
+                )?;
+                debug_log!(
+                    "split: lamports, source remaining, dest reserve",
+                    split_lamports,
+                    validated_split_info.source_remaining_balance,
+                    validated_split_info.destination_rent_exempt_reserve
+                );
+
+                *dest_stake_account = *source_stake_account;
+                if let Some(destination_meta) = dest_stake_account.meta_mut() {
+                    destination_meta.set_rent_exempt_reserve(
+                        validated_split_info.destination_rent_exempt_reserve,
+                    );
+                }
+            }
+            StakeStateV2::Uninitialized => {
+                if !source_stake_account_info.is_signer() {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+            }
+            _ => {
+                return Err(AccountError::new(
+                    0,
+                    AccountCheck::State,
+                    ProgramError::InvalidAccountData,
+                )
+                .into())
             }
         }
-        _ => return Err(ProgramError::InvalidAccountData),
-    }
-    if split_lamports == source_lamport_balance {
-        *source_stake_account = StakeStateV2::Uninitialized;
+        if split_lamports == source_lamport_balance {
+            source_stake_account.reset();
+        }
     }
     relocate_lamports(
         source_stake_account_info,
@@ -185,3 +199,384 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
 
     Ok(())
 }
+
+/// Stake taken off the source delegation and stake given to the destination when
+/// splitting `split_lamports` out of a delegated account, once `validate_split_amount`
+/// has accepted the amount. Fails if either side would end up below
+/// `minimum_delegation`.
+pub(crate) fn split_stake_amounts(
+    source_stake: u64,
+    source_rent_exempt_reserve: u64,
+    split_lamports: u64,
+    destination_lamports: u64,
+    validated_split_info: &ValidatedSplitInfo,
+    minimum_delegation: u64,
+) -> Result<(u64, u64), ProgramError> {
+    // split the stake, subtract rent_exempt_balance unless
+    // the destination account already has those lamports
+    // in place.
+    // this means that the new stake account will have a stake equivalent to
+    // lamports minus rent_exempt_reserve if it starts out with a zero balance
+    let (remaining_stake_delta, split_stake_amount) =
+        if validated_split_info.source_remaining_balance == 0 {
+            // If split amount equals the full source stake (as implied by 0
+            // source_remaining_balance), the new split stake must equal the same
+            // amount, regardless of any current lamport balance in the split account.
+            // Since split accounts retain the state of their source account, this
+            // prevents any magic activation of stake by prefunding the split account.
+            //
+            // The new split stake also needs to ignore any positive delta between the
+            // original rent_exempt_reserve and the split_rent_exempt_reserve, in order
+            // to prevent magic activation of stake by splitting between accounts of
+            // different sizes.
+            let remaining_stake_delta = lamports_sub(split_lamports, source_rent_exempt_reserve)?;
+            (remaining_stake_delta, remaining_stake_delta)
+        } else {
+            // Otherwise, the new split stake should reflect the entire split
+            // requested, less any lamports needed to cover the
+            // split_rent_exempt_reserve.
+            // saturating on purpose: over-splitting the delegation is an
+            // InsufficientDelegation error, not an arithmetic one
+            if source_stake.saturating_sub(split_lamports) < minimum_delegation {
+                return Err(StakeError::InsufficientDelegation.into());
+            }
+
+            (
+                split_lamports,
+                lamports_sub(
+                    split_lamports,
+                    // a prefunded destination may already exceed its reserve
+                    validated_split_info
+                        .destination_rent_exempt_reserve
+                        .saturating_sub(destination_lamports),
+                )?,
+            )
+        };
+
+    if split_stake_amount < minimum_delegation {
+        return Err(StakeError::InsufficientDelegation.into());
+    }
+
+    Ok((remaining_stake_delta, split_stake_amount))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        state::{
+            Authorized, CountingSysvars, Delegation, FeatureSet, Lockup, Meta, MockSysvars, Stake,
+            StakeFlags, WithFeatures,
+        },
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+    const SOURCE: [u8; 32] = [1; 32];
+    const DESTINATION: [u8; 32] = [2; 32];
+    const STAKER: [u8; 32] = [3; 32];
+
+    #[test]
+    fn test_split_reads_each_sysvar_at_most_once() {
+        let rent = MockSysvars::default().rent;
+        let reserve = rent.minimum_balance(StakeStateV2::size_of());
+        let meta = Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default());
+        let stake = Stake::new(Delegation::new(&[4; 32], 1_000_000_000, u64::MAX), 0);
+
+        for source_state in [
+            StakeStateV2::Initialized(meta),
+            StakeStateV2::Stake(meta, stake, StakeFlags::empty()),
+        ] {
+            let mut input = SerializedInput::new(&[
+                TestAccount::stake(SOURCE, reserve + 1_000_000_000, source_state),
+                TestAccount::stake(DESTINATION, reserve, StakeStateV2::Uninitialized),
+                TestAccount::signer(STAKER),
+            ]);
+            let accounts: [AccountInfo; 3] = input.accounts();
+            let sysvars = CountingSysvars::new(MockSysvars::default());
+
+            process_split_with_sysvars(&accounts, 500_000_000, &sysvars).unwrap();
+
+            assert!(sysvars.clock_reads() <= 1, "{source_state:?}");
+            // fetched once in the processor and passed down to validate_split_amount
+            assert_eq!(sysvars.rent_reads(), 1, "{source_state:?}");
+            assert!(sysvars.stake_history_reads() <= 1, "{source_state:?}");
+            assert_eq!(accounts[1].lamports(), reserve + 500_000_000);
+        }
+    }
+
+    #[test]
+    fn test_split_into_itself() {
+        let sysvars = MockSysvars::default();
+        let reserve = sysvars.rent.minimum_balance(StakeStateV2::size_of());
+        let meta = Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default());
+
+        for source_state in [StakeStateV2::Uninitialized, StakeStateV2::Initialized(meta)] {
+            let mut input = SerializedInput::new(&[
+                TestAccount {
+                    is_signer: true,
+                    ..TestAccount::stake(SOURCE, reserve + 1_000_000_000, source_state)
+                },
+                TestAccount::readonly([9; 32]),
+                TestAccount::signer(STAKER),
+            ]);
+            let [source, _, staker]: [AccountInfo; 3] = input.accounts();
+            // the runtime hands both positions the same account
+            let accounts = [source.clone(), source, staker];
+
+            assert_eq!(
+                process_split_with_sysvars(&accounts, 500_000_000, &sysvars),
+                Err(ProgramError::InvalidArgument),
+                "{source_state:?}"
+            );
+            assert_eq!(accounts[0].lamports(), reserve + 1_000_000_000);
+            assert_eq!(
+                *try_get_stake_state_mut_at(&accounts[0], 0).unwrap(),
+                source_state
+            );
+        }
+    }
+
+    #[cfg(feature = "strict-interface")]
+    #[test]
+    fn test_strict_split_ignores_misplaced_signer() {
+        let sysvars = MockSysvars::default();
+        let reserve = sysvars.rent.minimum_balance(StakeStateV2::size_of());
+        let meta = Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default());
+        let source = StakeStateV2::Initialized(meta);
+
+        // the staker signs, but as a trailing account rather than at index 2
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake(SOURCE, reserve + 1_000_000_000, source),
+            TestAccount::stake(DESTINATION, 0, StakeStateV2::Uninitialized),
+            TestAccount {
+                is_signer: false,
+                ..TestAccount::signer([9; 32])
+            },
+            TestAccount::signer(STAKER),
+        ]);
+        let accounts: [AccountInfo; 4] = input.accounts();
+
+        assert_eq!(
+            process_split_with_sysvars(&accounts, 500_000_000, &sysvars),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_full_split_zeroes_source() {
+        let sysvars = MockSysvars::default();
+        let reserve = sysvars.rent.minimum_balance(StakeStateV2::size_of());
+        let meta = Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default());
+        let stake = Stake::new(Delegation::new(&[4; 32], 1_000_000_000, u64::MAX), 0);
+
+        for source_state in [
+            StakeStateV2::Initialized(meta),
+            StakeStateV2::Stake(meta, stake, StakeFlags::empty()),
+        ] {
+            let mut input = SerializedInput::new(&[
+                TestAccount::stake(SOURCE, reserve + 1_000_000_000, source_state),
+                TestAccount::stake(DESTINATION, 0, StakeStateV2::Uninitialized),
+                TestAccount::signer(STAKER),
+            ]);
+            let accounts: [AccountInfo; 3] = input.accounts();
+
+            process_split_with_sysvars(&accounts, reserve + 1_000_000_000, &sysvars).unwrap();
+
+            assert_eq!(accounts[0].lamports(), 0, "{source_state:?}");
+            // the source keeps none of the state it handed to the destination
+            assert!(
+                accounts[0]
+                    .try_borrow_data()
+                    .unwrap()
+                    .iter()
+                    .all(|byte| *byte == 0),
+                "{source_state:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_split_applies_feature_set_minimum_delegation() {
+        let reserve = MockSysvars::default()
+            .rent
+            .minimum_balance(StakeStateV2::size_of());
+        let meta = Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default());
+        let stake = Stake::new(Delegation::new(&[4; 32], 2_000_000_000, u64::MAX), 0);
+        let source_state = StakeStateV2::Stake(meta, stake, StakeFlags::empty());
+
+        // leaves 0.5 SOL delegated on the source, enough only without the raised minimum
+        for (features, expected) in [
+            (FeatureSet::MAINNET, Ok(())),
+            (
+                FeatureSet {
+                    raise_minimum_delegation_to_1_sol: true,
+                    ..FeatureSet::MAINNET
+                },
+                Err(ProgramError::from(StakeError::InsufficientDelegation)),
+            ),
+        ] {
+            let mut input = SerializedInput::new(&[
+                TestAccount::stake(SOURCE, reserve + 3_000_000_000, source_state),
+                TestAccount::stake(DESTINATION, reserve, StakeStateV2::Uninitialized),
+                TestAccount::signer(STAKER),
+            ]);
+            let accounts: [AccountInfo; 3] = input.accounts();
+            let sysvars = WithFeatures::new(MockSysvars::default(), features);
+
+            assert_eq!(
+                process_split_with_sysvars(&accounts, 1_500_000_000, &sysvars),
+                expected,
+                "{features:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_split_does_not_allocate() {
+        let sysvars = MockSysvars::default();
+        let reserve = sysvars.rent.minimum_balance(StakeStateV2::size_of());
+        let meta = Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default());
+        let stake = Stake::new(Delegation::new(&[4; 32], 1_000_000_000, u64::MAX), 0);
+
+        let delegated = StakeStateV2::Stake(meta, stake, StakeFlags::empty());
+
+        for (source_state, split_lamports) in [
+            (StakeStateV2::Initialized(meta), 500_000_000),
+            (delegated, 500_000_000),
+            // whole balance, which takes the early-exit path
+            (delegated, reserve + 1_000_000_000),
+        ] {
+            let mut input = SerializedInput::new(&[
+                TestAccount::stake(SOURCE, reserve + 1_000_000_000, source_state),
+                // prefunded, as splitting active stake requires
+                TestAccount::stake(DESTINATION, reserve, StakeStateV2::Uninitialized),
+                TestAccount::signer(STAKER),
+            ]);
+            let accounts: [AccountInfo; 3] = input.accounts();
+
+            let (result, allocations) = heap_allocations(|| {
+                process_split_with_sysvars(&accounts, split_lamports, &sysvars)
+            });
+
+            assert_eq!(result, Ok(()), "{source_state:?}");
+            assert_eq!(allocations, 0, "{source_state:?}");
+        }
+    }
+}
+
+/// Kani proofs for the split arithmetic, run with `cargo kani`. Every lamport amount
+/// is symbolic; rent uses mainnet parameters since the reserve only enters as a
+/// concrete `minimum_balance`.
+#[cfg(kani)]
+mod verification {
+    use super::*;
+    use crate::state::{get_minimum_delegation, Delegation, Meta, Stake};
+    use pinocchio::sysvars::rent::Rent;
+
+    fn rent() -> Rent {
+        Rent {
+            lamports_per_byte_year: 3_480,
+            exemption_threshold: 2.0,
+            burn_percent: 50,
+        }
+    }
+
+    fn meta(rent_exempt_reserve: u64) -> Meta {
+        Meta {
+            rent_exempt_reserve: rent_exempt_reserve.to_le_bytes(),
+            ..Meta::default()
+        }
+    }
+
+    #[kani::proof]
+    fn validate_split_amount_enforces_minimum_balances() {
+        let source_lamports: u64 = kani::any();
+        let destination_lamports: u64 = kani::any();
+        let split_lamports: u64 = kani::any();
+        let rent_exempt_reserve: u64 = kani::any();
+        let additional_required_lamports: u64 = kani::any();
+
+        let Ok(info) = validate_split_amount(
+            source_lamports,
+            destination_lamports,
+            split_lamports,
+            &meta(rent_exempt_reserve),
+            stake_rent_exempt_reserve(&rent()),
+            additional_required_lamports,
+            kani::any(),
+        ) else {
+            return;
+        };
+
+        assert!(split_lamports > 0 && split_lamports <= source_lamports);
+        assert!(info.source_remaining_balance == source_lamports - split_lamports);
+        // either closed or still above its own minimum
+        assert!(
+            info.source_remaining_balance == 0
+                || rent_exempt_reserve
+                    .checked_add(additional_required_lamports)
+                    .is_some_and(|minimum| info.source_remaining_balance >= minimum)
+        );
+        // the destination ends up with at least its minimum balance
+        assert!(info
+            .destination_rent_exempt_reserve
+            .checked_add(additional_required_lamports)
+            .is_some_and(|minimum| {
+                destination_lamports.saturating_add(split_lamports) >= minimum
+            }));
+    }
+
+    #[kani::proof]
+    fn split_keeps_delegations_funded_and_above_minimum() {
+        let reserve = stake_rent_exempt_reserve(&rent());
+        let minimum_delegation = get_minimum_delegation();
+
+        let source_lamports: u64 = kani::any();
+        let source_stake: u64 = kani::any();
+        let destination_lamports: u64 = kani::any();
+        let split_lamports: u64 = kani::any();
+        // a well-formed source: delegation and reserve covered by its balance
+        kani::assume(source_stake >= minimum_delegation);
+        kani::assume(
+            source_stake
+                .checked_add(reserve)
+                .is_some_and(|funded| funded <= source_lamports),
+        );
+        kani::assume(destination_lamports.checked_add(source_lamports).is_some());
+
+        let Ok(info) = validate_split_amount(
+            source_lamports,
+            destination_lamports,
+            split_lamports,
+            &meta(reserve),
+            reserve,
+            minimum_delegation,
+            kani::any(),
+        ) else {
+            return;
+        };
+        let Ok((remaining_stake_delta, split_stake_amount)) = split_stake_amounts(
+            source_stake,
+            reserve,
+            split_lamports,
+            destination_lamports,
+            &info,
+            minimum_delegation,
+        ) else {
+            return;
+        };
+        let mut stake = Stake::new(Delegation::new(&[0; 32], source_stake, 0), 0);
+        let Ok(destination_stake) = stake.split(remaining_stake_delta, split_stake_amount) else {
+            return;
+        };
+
+        let destination_delegated = destination_stake.delegation.delegated_stake();
+        assert!(destination_delegated >= minimum_delegation);
+        assert!(destination_delegated + reserve <= destination_lamports + split_lamports);
+
+        if info.source_remaining_balance != 0 {
+            let source_delegated = stake.delegation.delegated_stake();
+            assert!(source_delegated >= minimum_delegation);
+            assert!(source_delegated + reserve <= info.source_remaining_balance);
+        }
+    }
+}
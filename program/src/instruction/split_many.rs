@@ -0,0 +1,166 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    instruction::{split_with_reserve, SplitManyAccounts},
+    state::{stake_rent_exempt_reserve, SyscallSysvars, SysvarProvider},
+};
+
+// Extension instruction: one `Split` per destination, in order, all in one instruction.
+// Data is the lamports for each destination as consecutive little-endian `u64`s, one
+// per destination account. Each split is validated exactly like a standalone `Split`
+// from the source's balance at that point, and any failure fails the whole batch.
+//
+// The stake authority sits at index 1, before the variable-length destination list,
+// and is the only signer each split sees. Account indices in error logs are those of
+// the failing split: 0 for the source, 1 for its destination.
+
+pub fn process_split_many(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_split_many_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_split_many` reading Clock, Rent and StakeHistory from `sysvars`
+pub fn process_split_many_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    sysvars: &S,
+) -> ProgramResult {
+    let SplitManyAccounts {
+        source,
+        stake_authority,
+        destinations,
+    } = SplitManyAccounts::try_from(accounts)?;
+
+    let amounts = data.chunks_exact(8);
+    if !amounts.remainder().is_empty() || amounts.len() != destinations.len() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // every destination is a stake account, so they all share one reserve
+    let reserve = stake_rent_exempt_reserve(&sysvars.rent()?);
+    for (destination, amount) in destinations.iter().zip(amounts) {
+        // laid out as a `Split`, so `strict-interface` finds the authority at index 2
+        let split_accounts = [source.clone(), destination.clone(), stake_authority.clone()];
+        let lamports = u64::from_le_bytes(amount.try_into().unwrap());
+        split_with_reserve(&split_accounts, lamports, sysvars, reserve)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::MAX_SPLIT_MANY_DESTINATIONS,
+        state::{Authorized, CountingSysvars, Lockup, Meta, MockSysvars, StakeStateV2},
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+
+    const SOURCE: [u8; 32] = [1; 32];
+    const STAKER: [u8; 32] = [3; 32];
+
+    fn amounts(amounts: &[u64]) -> Vec<u8> {
+        amounts
+            .iter()
+            .flat_map(|amount| amount.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_many_splits_into_each_destination() {
+        let sysvars = MockSysvars::default();
+        let reserve = sysvars.rent.minimum_balance(StakeStateV2::size_of());
+        let meta = Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default());
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake(
+                SOURCE,
+                reserve + 3_000_000_000,
+                StakeStateV2::Initialized(meta),
+            ),
+            TestAccount::signer(STAKER),
+            TestAccount::stake([4; 32], 0, StakeStateV2::Uninitialized),
+            TestAccount::stake([5; 32], 0, StakeStateV2::Uninitialized),
+        ]);
+        let accounts: [AccountInfo; 4] = input.accounts();
+
+        let data = amounts(&[1_000_000_000, 500_000_000]);
+        let (result, allocations) =
+            heap_allocations(|| process_split_many_with_sysvars(&accounts, &data, &sysvars));
+        assert_eq!(result, Ok(()));
+        assert_eq!(allocations, 0);
+
+        assert_eq!(accounts[0].lamports(), reserve + 1_500_000_000);
+        assert_eq!(accounts[2].lamports(), 1_000_000_000);
+        assert_eq!(accounts[3].lamports(), 500_000_000);
+        for destination in &accounts[2..] {
+            let state = StakeStateV2::deserialize(&destination.try_borrow_data().unwrap());
+            assert!(matches!(state, Ok(StakeStateV2::Initialized(_))));
+        }
+    }
+
+    #[test]
+    fn test_split_many_reads_rent_once() {
+        let sysvars = CountingSysvars::new(MockSysvars::default());
+        let reserve = sysvars.inner.rent.minimum_balance(StakeStateV2::size_of());
+        let meta = Meta::new(reserve, Authorized::auto(&STAKER), Lockup::default());
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake(SOURCE, reserve * 4, StakeStateV2::Initialized(meta)),
+            TestAccount::signer(STAKER),
+            TestAccount::stake([4; 32], 0, StakeStateV2::Uninitialized),
+            TestAccount::stake([5; 32], 0, StakeStateV2::Uninitialized),
+            TestAccount::stake([6; 32], 0, StakeStateV2::Uninitialized),
+        ]);
+        let accounts: [AccountInfo; 5] = input.accounts();
+
+        let data = amounts(&[reserve, reserve, reserve]);
+        let (result, allocations) =
+            heap_allocations(|| process_split_many_with_sysvars(&accounts, &data, &sysvars));
+        assert_eq!(result, Ok(()));
+        assert_eq!(allocations, 0);
+
+        assert_eq!(sysvars.rent_reads(), 1);
+        assert_eq!(accounts[0].lamports(), reserve);
+    }
+
+    #[test]
+    fn test_split_many_rejects_mismatched_amounts() {
+        let sysvars = MockSysvars::default();
+        let meta = Meta::new(0, Authorized::auto(&STAKER), Lockup::default());
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake(SOURCE, 1_000_000_000, StakeStateV2::Initialized(meta)),
+            TestAccount::signer(STAKER),
+            TestAccount::stake([4; 32], 0, StakeStateV2::Uninitialized),
+        ]);
+        let accounts: [AccountInfo; 3] = input.accounts();
+
+        for data in [amounts(&[]), amounts(&[1, 2]), vec![0; 7]] {
+            assert_eq!(
+                process_split_many_with_sysvars(&accounts, &data, &sysvars),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+
+    #[test]
+    fn test_split_many_limits_destinations() {
+        let meta = Meta::new(0, Authorized::auto(&STAKER), Lockup::default());
+        let mut accounts = vec![
+            TestAccount::stake(SOURCE, 1_000_000_000, StakeStateV2::Initialized(meta)),
+            TestAccount::signer(STAKER),
+        ];
+        for i in 0..=MAX_SPLIT_MANY_DESTINATIONS as u8 {
+            accounts.push(TestAccount::stake(
+                [10 + i; 32],
+                0,
+                StakeStateV2::Uninitialized,
+            ));
+        }
+        let mut input = SerializedInput::new(&accounts);
+        let accounts: [AccountInfo; MAX_SPLIT_MANY_DESTINATIONS + 3] = input.accounts();
+
+        assert_eq!(
+            SplitManyAccounts::try_from(&accounts[..]).err(),
+            Some(ProgramError::InvalidArgument)
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Conversions from `solana-stake-interface` instructions, so test harnesses can run
+//! instructions built with existing Solana tooling against this program.
+//!
+//! Native instruction data is bincode: a `u32` discriminator followed by the arguments.
+//! This program takes the same arguments behind a one-byte discriminator, so
+//! converting only narrows the discriminator.
+
+use std::vec::Vec;
+
+use pinocchio::program_error::ProgramError;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_stake_interface::instruction as interface;
+
+use super::StakeInstruction;
+
+impl TryFrom<&interface::StakeInstruction> for StakeInstruction {
+    type Error = ProgramError;
+
+    /// Fails only for `Redelegate`, which this program rejects like the native one
+    #[allow(deprecated)]
+    fn try_from(instruction: &interface::StakeInstruction) -> Result<Self, Self::Error> {
+        Ok(match instruction {
+            interface::StakeInstruction::Initialize(..) => StakeInstruction::Initialize,
+            interface::StakeInstruction::Authorize(..) => StakeInstruction::Authorize,
+            interface::StakeInstruction::DelegateStake => StakeInstruction::DelegateStake,
+            interface::StakeInstruction::Split(_) => StakeInstruction::Split,
+            interface::StakeInstruction::Withdraw(_) => StakeInstruction::Withdraw,
+            interface::StakeInstruction::Deactivate => StakeInstruction::Deactivate,
+            interface::StakeInstruction::SetLockup(_) => StakeInstruction::SetLockup,
+            interface::StakeInstruction::Merge => StakeInstruction::Merge,
+            interface::StakeInstruction::AuthorizeWithSeed(_) => {
+                StakeInstruction::AuthorizeWithSeed
+            }
+            interface::StakeInstruction::InitializeChecked => StakeInstruction::InitializeChecked,
+            interface::StakeInstruction::AuthorizeChecked(_) => StakeInstruction::AuthorizeChecked,
+            interface::StakeInstruction::AuthorizeCheckedWithSeed(_) => {
+                StakeInstruction::AuthorizeCheckedWithSeed
+            }
+            interface::StakeInstruction::SetLockupChecked(_) => StakeInstruction::SetLockupChecked,
+            interface::StakeInstruction::GetMinimumDelegation => {
+                StakeInstruction::GetMinimumDelegation
+            }
+            interface::StakeInstruction::DeactivateDelinquent => {
+                StakeInstruction::DeactivateDelinquent
+            }
+            interface::StakeInstruction::Redelegate => {
+                return Err(ProgramError::InvalidInstructionData)
+            }
+            interface::StakeInstruction::MoveStake(_) => StakeInstruction::MoveStake,
+            interface::StakeInstruction::MoveLamports(_) => StakeInstruction::MoveLamports,
+        })
+    }
+}
+
+impl TryFrom<interface::StakeInstruction> for StakeInstruction {
+    type Error = ProgramError;
+
+    fn try_from(instruction: interface::StakeInstruction) -> Result<Self, Self::Error> {
+        Self::try_from(&instruction)
+    }
+}
+
+/// `instruction` encoded as this program's instruction data
+pub fn instruction_data(
+    instruction: &interface::StakeInstruction,
+) -> Result<Vec<u8>, ProgramError> {
+    let discriminator = StakeInstruction::try_from(instruction)? as u8;
+    let native =
+        bincode::serialize(instruction).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let mut data = Vec::with_capacity(native.len() - 3);
+    data.push(discriminator);
+    data.extend_from_slice(&native[4..]);
+    Ok(data)
+}
+
+/// An instruction built for the native stake program, such as one from
+/// `solana_stake_interface::instruction`, re-addressed to this program with its data
+/// re-encoded. The accounts are passed through unchanged, since both programs take the
+/// same ones.
+pub fn from_native_instruction(instruction: &Instruction) -> Result<Instruction, ProgramError> {
+    let native: interface::StakeInstruction = bincode::deserialize(&instruction.data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    Ok(Instruction {
+        program_id: Pubkey::new_from_array(crate::ID),
+        accounts: instruction.accounts.clone(),
+        data: instruction_data(&native)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_stake_interface::state::{Authorized, Lockup, StakeAuthorize};
+
+    #[test]
+    fn test_native_instruction_data() {
+        let stake = Pubkey::new_from_array([1; 32]);
+        let authority = Pubkey::new_from_array([2; 32]);
+        let other = Pubkey::new_from_array([3; 32]);
+
+        let native = [
+            interface::split(&stake, &authority, 42, &other)
+                .pop()
+                .unwrap(),
+            interface::initialize(
+                &stake,
+                &Authorized::auto(&authority),
+                &Lockup {
+                    unix_timestamp: -1,
+                    epoch: 2,
+                    custodian: other,
+                },
+            ),
+            interface::authorize(&stake, &authority, &other, StakeAuthorize::Withdrawer, None),
+            interface::deactivate_stake(&stake, &authority),
+            interface::move_lamports(&stake, &other, &authority, 7),
+        ];
+        for native in native {
+            let converted = from_native_instruction(&native).unwrap();
+
+            assert_eq!(converted.program_id, Pubkey::new_from_array(crate::ID));
+            assert_eq!(converted.accounts, native.accounts);
+            // the discriminator is narrowed from four bytes to one, the rest is kept
+            let discriminator = u32::from_le_bytes(native.data[..4].try_into().unwrap());
+            assert_eq!(u32::from(converted.data[0]), discriminator);
+            assert_eq!(converted.data[1..], native.data[4..]);
+            assert!(StakeInstruction::try_from(&converted.data[0]).is_ok());
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_redelegate_is_rejected() {
+        assert_eq!(
+            StakeInstruction::try_from(interface::StakeInstruction::Redelegate).err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            instruction_data(&interface::StakeInstruction::Redelegate),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
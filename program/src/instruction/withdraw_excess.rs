@@ -0,0 +1,149 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::{
+    error::StakeError,
+    instruction::WithdrawAccounts,
+    state::{
+        collect_signers_checked_with_custodian, get_stake_state_at, relocate_lamports,
+        to_program_error, StakeAuthorize, StakeStateV2, SyscallSysvars, SysvarProvider,
+    },
+};
+
+// Extension instruction: withdraws only the lamports above the delegated stake and the
+// rent-exempt reserve, such as rewards paid to the account from outside or airdropped
+// dust, to sweep them without touching the delegation. The full delegated amount is
+// kept whatever its activation, so this can never reach staked funds, and the account is
+// never closed. Takes `Withdraw`'s accounts and no data; the amount withdrawn, possibly
+// zero, is set as the return data, as a little-endian `u64`.
+
+pub fn process_withdraw_excess(accounts: &[AccountInfo]) -> ProgramResult {
+    process_withdraw_excess_with_sysvars(accounts, &SyscallSysvars)
+}
+
+/// `process_withdraw_excess` reading Clock from `sysvars`
+pub fn process_withdraw_excess_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    sysvars: &S,
+) -> ProgramResult {
+    let WithdrawAccounts {
+        stake: stake_account_info,
+        recipient: recipient_account_info,
+        custodian: custodian_info,
+        ..
+    } = WithdrawAccounts::try_from(accounts)?;
+
+    #[cfg(not(feature = "strict-interface"))]
+    let signer_accounts = accounts;
+    // 0. stake, 1. recipient, 2. clock, 3. stake history, 4. withdraw authority
+    #[cfg(feature = "strict-interface")]
+    let signer_accounts = accounts
+        .get(4..=4)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let (signers, custodian) =
+        collect_signers_checked_with_custodian(signer_accounts, custodian_info, false)?;
+
+    let (meta, staked) = match *get_stake_state_at(stake_account_info, 0)? {
+        StakeStateV2::Stake(meta, stake, _) => (meta, stake.delegation.delegated_stake()),
+        StakeStateV2::Initialized(meta) => (meta, 0),
+        // nothing is held back from these, so there is no excess to tell apart
+        StakeStateV2::Uninitialized | StakeStateV2::RewardsPool => {
+            return Err(ProgramError::InvalidAccountData)
+        }
+    };
+    meta.authorized
+        .check(&signers, StakeAuthorize::Withdrawer)
+        .map_err(to_program_error)?;
+
+    let clock = sysvars.clock()?;
+    if meta.lockup.is_in_force(&clock, custodian) {
+        return Err(StakeError::LockupInForce.into());
+    }
+
+    let held = staked
+        .checked_add(meta.rent_exempt_reserve())
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let stake_lamports = stake_account_info.lamports();
+    let lamports = stake_lamports.saturating_sub(held);
+    debug_log!(
+        "withdraw excess: balance, held, withdrawn",
+        stake_lamports,
+        held,
+        lamports
+    );
+
+    relocate_lamports(stake_account_info, recipient_account_info, lamports)?;
+
+    set_return_data(&lamports.to_le_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::{CLOCK_ID, STAKE_HISTORY_ID},
+        state::{Authorized, Delegation, Lockup, Meta, MockSysvars, Stake, StakeFlags},
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+
+    const WITHDRAWER: [u8; 32] = [3; 32];
+    const RESERVE: u64 = 2_282_880;
+
+    fn withdraw_excess(state: StakeStateV2, lamports: u64) -> (ProgramResult, u64, u64) {
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake([1; 32], lamports, state),
+            TestAccount::writable([2; 32]),
+            TestAccount::readonly(CLOCK_ID),
+            TestAccount::readonly(STAKE_HISTORY_ID),
+            TestAccount::signer(WITHDRAWER),
+        ]);
+        let accounts: [AccountInfo; 5] = input.accounts();
+        let mut sysvars = MockSysvars::default();
+        sysvars.clock.epoch = 10;
+
+        let (result, allocations) =
+            heap_allocations(|| process_withdraw_excess_with_sysvars(&accounts, &sysvars));
+        assert_eq!(allocations, 0);
+        (result, accounts[0].lamports(), accounts[1].lamports())
+    }
+
+    fn meta() -> Meta {
+        Meta::new(RESERVE, Authorized::auto(&WITHDRAWER), Lockup::default())
+    }
+
+    #[test]
+    fn test_withdraw_excess_keeps_the_delegation() {
+        // fully deactivated, but the delegated amount is still left alone
+        let mut delegation = Delegation::new(&[9; 32], 1_000_000, 0);
+        delegation.set_deactivation_epoch(5);
+        let state = StakeStateV2::Stake(meta(), Stake::new(delegation, 0), StakeFlags::empty());
+
+        let (result, stake, recipient) = withdraw_excess(state, RESERVE + 1_000_000 + 42);
+        assert_eq!(result, Ok(()));
+        assert_eq!((stake, recipient), (RESERVE + 1_000_000, 42));
+    }
+
+    #[test]
+    fn test_withdraw_excess_keeps_the_reserve() {
+        let state = StakeStateV2::Initialized(meta());
+
+        let (result, stake, recipient) = withdraw_excess(state, RESERVE + 7);
+        assert_eq!(result, Ok(()));
+        assert_eq!((stake, recipient), (RESERVE, 7));
+
+        // nothing to sweep is not an error
+        let (result, stake, recipient) = withdraw_excess(state, RESERVE);
+        assert_eq!(result, Ok(()));
+        assert_eq!((stake, recipient), (RESERVE, 0));
+    }
+
+    #[test]
+    fn test_withdraw_excess_needs_initialized() {
+        let (result, stake, _) = withdraw_excess(StakeStateV2::Uninitialized, 1_000);
+        assert_eq!(result, Err(ProgramError::InvalidAccountData));
+        assert_eq!(stake, 1_000);
+    }
+}
@@ -0,0 +1,198 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::{
+    error::StakeError,
+    instruction::WithdrawAccounts,
+    state::{
+        collect_signers_checked_with_custodian, get_stake_state_at, max_withdrawable_with_features,
+        relocate_lamports, set_stake_state, to_program_error, Lockup, StakeAuthorize, StakeStateV2,
+        SyscallSysvars, SysvarProvider,
+    },
+};
+
+// Extension instruction: `Withdraw` with the amount computed here instead of by the
+// client, so it can't go stale between building the transaction and executing it.
+// Takes the same accounts as `Withdraw` and no data. The amount withdrawn is set as
+// the return data, as a little-endian `u64`.
+
+pub fn process_withdraw_max(accounts: &[AccountInfo]) -> ProgramResult {
+    process_withdraw_max_with_sysvars(accounts, &SyscallSysvars)
+}
+
+/// `process_withdraw_max` reading Clock and StakeHistory from `sysvars`
+pub fn process_withdraw_max_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    sysvars: &S,
+) -> ProgramResult {
+    let WithdrawAccounts {
+        stake: stake_account_info,
+        recipient: recipient_account_info,
+        custodian: custodian_info,
+        ..
+    } = WithdrawAccounts::try_from(accounts)?;
+
+    #[cfg(not(feature = "strict-interface"))]
+    let signer_accounts = accounts;
+    // 0. stake, 1. recipient, 2. clock, 3. stake history, 4. withdraw authority
+    #[cfg(feature = "strict-interface")]
+    let signer_accounts = accounts
+        .get(4..=4)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let (signers, custodian) =
+        collect_signers_checked_with_custodian(signer_accounts, custodian_info, false)?;
+
+    let clock = sysvars.clock()?;
+    let stake_history = &sysvars.stake_history(clock.epoch);
+
+    let stake_state = *get_stake_state_at(stake_account_info, 0)?;
+    let lockup = match &stake_state {
+        StakeStateV2::Stake(meta, _, _) | StakeStateV2::Initialized(meta) => {
+            meta.authorized
+                .check(&signers, StakeAuthorize::Withdrawer)
+                .map_err(to_program_error)?;
+            meta.lockup
+        }
+        StakeStateV2::Uninitialized => {
+            // an uninitialized account can only be emptied by its own key
+            if !stake_account_info.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            Lockup::default()
+        }
+        StakeStateV2::RewardsPool => return Err(ProgramError::InvalidAccountData),
+    };
+
+    if lockup.is_in_force(&clock, custodian) {
+        return Err(StakeError::LockupInForce.into());
+    }
+
+    let stake_lamports = stake_account_info.lamports();
+    let lamports = max_withdrawable_with_features(
+        &stake_state,
+        stake_lamports,
+        &clock,
+        stake_history,
+        &sysvars.features(),
+    )?;
+    debug_log!("withdraw max: balance, withdrawn", stake_lamports, lamports);
+
+    // draining the account closes it, like a full `Withdraw`
+    if lamports == stake_lamports {
+        set_stake_state(stake_account_info, &StakeStateV2::Uninitialized)?;
+    }
+    relocate_lamports(stake_account_info, recipient_account_info, lamports)?;
+
+    set_return_data(&lamports.to_le_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::{CLOCK_ID, STAKE_HISTORY_ID},
+        state::{Authorized, Delegation, Meta, MockSysvars, Stake, StakeFlags},
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+
+    const STAKE: [u8; 32] = [1; 32];
+    const RECIPIENT: [u8; 32] = [2; 32];
+    const WITHDRAWER: [u8; 32] = [3; 32];
+    const RESERVE: u64 = 2_282_880;
+
+    fn withdraw_max(state: StakeStateV2, lamports: u64, epoch: u64) -> (ProgramResult, u64, u64) {
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake(STAKE, lamports, state),
+            TestAccount::writable(RECIPIENT),
+            TestAccount::readonly(CLOCK_ID),
+            TestAccount::readonly(STAKE_HISTORY_ID),
+            TestAccount::signer(WITHDRAWER),
+        ]);
+        let accounts: [AccountInfo; 5] = input.accounts();
+        let mut sysvars = MockSysvars::default();
+        sysvars.clock.epoch = epoch;
+
+        let (result, allocations) =
+            heap_allocations(|| process_withdraw_max_with_sysvars(&accounts, &sysvars));
+        assert_eq!(allocations, 0);
+        (result, accounts[0].lamports(), accounts[1].lamports())
+    }
+
+    fn delegated(deactivation_epoch: u64) -> StakeStateV2 {
+        let mut delegation = Delegation::new(&[9; 32], 1_000_000, 0);
+        delegation.set_deactivation_epoch(deactivation_epoch);
+        StakeStateV2::Stake(
+            Meta::new(RESERVE, Authorized::auto(&WITHDRAWER), Lockup::default()),
+            Stake::new(delegation, 0),
+            StakeFlags::empty(),
+        )
+    }
+
+    #[test]
+    fn test_withdraw_max_keeps_active_stake_and_reserve() {
+        let lamports = RESERVE + 1_000_000 + 500;
+        let (result, stake, recipient) = withdraw_max(delegated(u64::MAX), lamports, 10);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!((stake, recipient), (RESERVE + 1_000_000, 500));
+    }
+
+    #[test]
+    fn test_withdraw_max_drains_inactive_account() {
+        let meta = Meta::new(RESERVE, Authorized::auto(&WITHDRAWER), Lockup::default());
+        let lamports = RESERVE + 700;
+        let (result, stake, recipient) =
+            withdraw_max(StakeStateV2::Initialized(meta), lamports, 10);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!((stake, recipient), (0, lamports));
+
+        // fully cooled down by the epoch after deactivation without history entries
+        let (result, stake, recipient) = withdraw_max(delegated(5), lamports + 1_000_000, 6);
+        assert_eq!(result, Ok(()));
+        assert_eq!((stake, recipient), (0, lamports + 1_000_000));
+    }
+
+    #[test]
+    fn test_withdraw_max_zeroes_drained_account() {
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake(STAKE, RESERVE + 1_000_000, delegated(5)),
+            TestAccount::writable(RECIPIENT),
+            TestAccount::readonly(CLOCK_ID),
+            TestAccount::readonly(STAKE_HISTORY_ID),
+            TestAccount::signer(WITHDRAWER),
+        ]);
+        let accounts: [AccountInfo; 5] = input.accounts();
+        let mut sysvars = MockSysvars::default();
+        sysvars.clock.epoch = 6;
+
+        assert_eq!(
+            process_withdraw_max_with_sysvars(&accounts, &sysvars),
+            Ok(())
+        );
+        assert_eq!(accounts[0].lamports(), 0);
+        // the closed account keeps none of its authorities or delegation
+        assert!(accounts[0]
+            .try_borrow_data()
+            .unwrap()
+            .iter()
+            .all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_withdraw_max_respects_lockup() {
+        let lockup = Lockup {
+            unix_timestamp: 0i64.to_le_bytes(),
+            epoch: 100u64.to_le_bytes(),
+            custodian: [6; 32],
+        };
+        let meta = Meta::new(RESERVE, Authorized::auto(&WITHDRAWER), lockup);
+        let (result, stake, recipient) = withdraw_max(StakeStateV2::Initialized(meta), RESERVE, 10);
+
+        assert_eq!(result, Err(StakeError::LockupInForce.into()));
+        assert_eq!((stake, recipient), (RESERVE, 0));
+    }
+}
@@ -0,0 +1,247 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvars::clock::Clock,
+    ProgramResult,
+};
+
+use crate::{
+    error::StakeError,
+    helpers::get_stake_status_with_features,
+    instruction::WithdrawAccounts,
+    state::{
+        collect_signers_checked_with_custodian, create_with_seed, get_stake_state_at,
+        relocate_lamports, set_stake_state, to_program_error, FeatureSet, Lockup, SignerSet,
+        StakeAuthorize, StakeHistoryGetEntry, StakeStateV2, SyscallSysvars, SysvarProvider,
+    },
+};
+
+// Extension instruction: `Withdraw` by a withdraw authority derived with `create_with_seed`,
+// signed for by its base key, as `AuthorizeWithSeed` allows for authorizing. Programs that
+// set such an authority can withdraw without first rotating to a key that signs directly.
+//
+// Accounts are `Withdraw`'s with the base key in place of the withdraw authority: 0. stake,
+// 1. recipient, 2. clock, 3. stake history, 4. authority base, 5. optional custodian.
+// Data is the lamports, then the seed and its owner as in `AuthorizeWithSeed`: a
+// little-endian `u64` length, the seed bytes and the 32-byte owner.
+
+pub fn process_withdraw_with_seed(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    process_withdraw_with_seed_with_sysvars(accounts, data, &SyscallSysvars)
+}
+
+/// `process_withdraw_with_seed` reading Clock and StakeHistory from `sysvars`
+pub fn process_withdraw_with_seed_with_sysvars<S: SysvarProvider>(
+    accounts: &[AccountInfo],
+    data: &[u8],
+    sysvars: &S,
+) -> ProgramResult {
+    let WithdrawAccounts {
+        stake: stake_account_info,
+        recipient: recipient_account_info,
+        custodian: custodian_info,
+        ..
+    } = WithdrawAccounts::try_from(accounts)?;
+    let authority_base_info = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let (lamports, authority_seed, authority_owner) =
+        parse_data(data).ok_or(ProgramError::InvalidInstructionData)?;
+
+    // the derived key is the only authority; the base signing stands in for it
+    let derived_key =
+        create_with_seed(authority_base_info.key(), authority_seed, &authority_owner)?;
+    let derived_signer = [derived_key];
+    let signers: &[Pubkey] = if authority_base_info.is_signer() {
+        &derived_signer
+    } else {
+        &[]
+    };
+
+    // only the custodian is taken from here; the derived key above is the authority
+    let (_, custodian) = collect_signers_checked_with_custodian(accounts, custodian_info, false)?;
+
+    let clock = sysvars.clock()?;
+    let stake_history = &sysvars.stake_history(clock.epoch);
+
+    withdraw_stake(
+        stake_account_info,
+        recipient_account_info,
+        lamports,
+        signers,
+        custodian,
+        &clock,
+        stake_history,
+        &sysvars.features(),
+    )
+}
+
+/// Native `Withdraw` of `lamports` from `stake_account_info`, with `signers` as the
+/// withdraw authorities that signed
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn withdraw_stake<S: SignerSet + ?Sized, T: StakeHistoryGetEntry>(
+    stake_account_info: &AccountInfo,
+    recipient_account_info: &AccountInfo,
+    lamports: u64,
+    signers: &S,
+    custodian: Option<&Pubkey>,
+    clock: &Clock,
+    stake_history: &T,
+    features: &FeatureSet,
+) -> ProgramResult {
+    let (lockup, reserve, is_staked) = match *get_stake_state_at(stake_account_info, 0)? {
+        StakeStateV2::Stake(meta, stake, _) => {
+            meta.authorized
+                .check(signers, StakeAuthorize::Withdrawer)
+                .map_err(to_program_error)?;
+            // stake that hasn't started deactivating is counted in full, since what
+            // is effective now may still grow with warmup
+            let staked = if clock.epoch >= stake.delegation.deactivation_epoch() {
+                get_stake_status_with_features(&stake, clock.epoch, stake_history, features)
+                    .effective
+            } else {
+                stake.delegation.delegated_stake()
+            };
+            let staked_and_reserve = staked
+                .checked_add(meta.rent_exempt_reserve())
+                .ok_or(ProgramError::InsufficientFunds)?;
+            (meta.lockup, staked_and_reserve, staked != 0)
+        }
+        StakeStateV2::Initialized(meta) => {
+            meta.authorized
+                .check(signers, StakeAuthorize::Withdrawer)
+                .map_err(to_program_error)?;
+            (meta.lockup, meta.rent_exempt_reserve(), false)
+        }
+        StakeStateV2::Uninitialized => {
+            // an uninitialized account can only be emptied by its own key
+            if !stake_account_info.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            (Lockup::default(), 0, false)
+        }
+        StakeStateV2::RewardsPool => return Err(ProgramError::InvalidAccountData),
+    };
+
+    if lockup.is_in_force(clock, custodian) {
+        return Err(StakeError::LockupInForce.into());
+    }
+
+    let stake_account_lamports = stake_account_info.lamports();
+    if lamports == stake_account_lamports {
+        // a full withdrawal closes the account, which live stake forbids
+        if is_staked {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        set_stake_state(stake_account_info, &StakeStateV2::Uninitialized)?;
+    } else {
+        // a partial withdrawal has to leave the reserve and any stake behind
+        let withdraw_lamports_and_reserve = lamports
+            .checked_add(reserve)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        if withdraw_lamports_and_reserve > stake_account_lamports {
+            return Err(ProgramError::InsufficientFunds);
+        }
+    }
+
+    relocate_lamports(stake_account_info, recipient_account_info, lamports)
+}
+
+/// Lamports, authority seed and authority owner
+fn parse_data(data: &[u8]) -> Option<(u64, &[u8], Pubkey)> {
+    let (lamports, data) = data.split_first_chunk::<8>()?;
+    let (seed_len, data) = data.split_first_chunk::<8>()?;
+    let seed_len = usize::try_from(u64::from_le_bytes(*seed_len)).ok()?;
+    let seed = data.get(..seed_len)?;
+    let owner = data.get(seed_len..)?.try_into().ok()?;
+    Some((u64::from_le_bytes(*lamports), seed, owner))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::{CLOCK_ID, STAKE_HISTORY_ID},
+        state::{Authorized, Meta, MockSysvars},
+        test_utils::{heap_allocations, SerializedInput, TestAccount},
+    };
+
+    const STAKE: [u8; 32] = [1; 32];
+    const RECIPIENT: [u8; 32] = [2; 32];
+    const BASE: [u8; 32] = [3; 32];
+    const OWNER: [u8; 32] = [4; 32];
+    const SEED: &[u8] = b"withdrawer";
+    const RESERVE: u64 = 2_282_880;
+
+    fn data(lamports: u64) -> Vec<u8> {
+        let mut data = lamports.to_le_bytes().to_vec();
+        data.extend_from_slice(&(SEED.len() as u64).to_le_bytes());
+        data.extend_from_slice(SEED);
+        data.extend_from_slice(&OWNER);
+        data
+    }
+
+    fn withdraw_with_seed(
+        withdrawer: Pubkey,
+        lamports: u64,
+        base_signs: bool,
+    ) -> (ProgramResult, u64) {
+        let meta = Meta::new(RESERVE, Authorized::auto(&withdrawer), Lockup::default());
+        let mut input = SerializedInput::new(&[
+            TestAccount::stake(STAKE, RESERVE + 1_000, StakeStateV2::Initialized(meta)),
+            TestAccount::writable(RECIPIENT),
+            TestAccount::readonly(CLOCK_ID),
+            TestAccount::readonly(STAKE_HISTORY_ID),
+            TestAccount {
+                is_signer: base_signs,
+                ..TestAccount::readonly(BASE)
+            },
+        ]);
+        let accounts: [AccountInfo; 5] = input.accounts();
+
+        let data = data(lamports);
+        let sysvars = MockSysvars::default();
+        let (result, allocations) = heap_allocations(|| {
+            process_withdraw_with_seed_with_sysvars(&accounts, &data, &sysvars)
+        });
+        assert_eq!(allocations, 0);
+        (result, accounts[1].lamports())
+    }
+
+    #[test]
+    fn test_withdraw_with_seed_signs_as_derived_key() {
+        let derived_key = create_with_seed(&BASE, SEED, &OWNER).unwrap();
+
+        assert_eq!(
+            withdraw_with_seed(derived_key, 1_000, true),
+            (Ok(()), 1_000)
+        );
+        assert_eq!(
+            withdraw_with_seed(derived_key, 1_000, false),
+            (Err(ProgramError::MissingRequiredSignature), 0)
+        );
+        // the base key itself is not the authority
+        assert_eq!(
+            withdraw_with_seed(BASE, 1_000, true),
+            (Err(ProgramError::MissingRequiredSignature), 0)
+        );
+    }
+
+    #[test]
+    fn test_withdraw_with_seed_keeps_the_reserve() {
+        let derived_key = create_with_seed(&BASE, SEED, &OWNER).unwrap();
+
+        assert_eq!(
+            withdraw_with_seed(derived_key, 1_001, true),
+            (Err(ProgramError::InsufficientFunds), 0)
+        );
+        assert_eq!(
+            withdraw_with_seed(derived_key, RESERVE + 1_000, true),
+            (Ok(()), RESERVE + 1_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_data() {
+        assert_eq!(parse_data(&data(7)), Some((7, SEED, OWNER)));
+        let data = data(7);
+        assert_eq!(parse_data(&data[..data.len() - 1]), None);
+        assert_eq!(parse_data(&[0; 15]), None);
+    }
+}
@@ -1,16 +1,62 @@
 #![cfg_attr(not(test), no_std)]
 
-#[cfg(not(feature = "no-entrypoint"))]
-mod entrypoint;
-
 #[cfg(feature = "std")]
 extern crate std;
 extern crate alloc;
 
+// first, so the macro is visible to every module below
+#[macro_use]
+mod debug_log;
+
+#[cfg(feature = "anchor-cpi")]
+pub mod anchor_cpi;
 pub mod consts;
+pub mod cpi;
+pub mod entrypoint;
 pub mod error;
 pub mod helpers;
 pub mod instruction;
+pub mod prelude;
+#[cfg(feature = "sdk")]
+pub mod sdk;
 pub mod state;
+#[cfg(test)]
+mod test_utils;
+
+/// Address of the native stake program, which this program answers to by default
+pub const CANONICAL_ID: pinocchio::pubkey::Pubkey =
+    pinocchio_pubkey::pubkey!("Stake11111111111111111111111111111111111111");
 
+// `ID`, `id()` and `check_id()`. Builds with `custom-program-id` take the address from
+// `STAKE_PROGRAM_ID` at compile time so the program can be deployed next to the native
+// one; everything else, including the `sdk` builders and the Anchor CPI wrappers, reads
+// `ID` rather than hardcoding a key.
+#[cfg(not(feature = "custom-program-id"))]
 pinocchio_pubkey::declare_id!("Stake11111111111111111111111111111111111111");
+#[cfg(feature = "custom-program-id")]
+mod custom_id {
+    use pinocchio::pubkey::Pubkey;
+
+    /// The const program ID, from `STAKE_PROGRAM_ID`
+    pub const ID: Pubkey = match option_env!("STAKE_PROGRAM_ID") {
+        Some(address) => pinocchio_pubkey::from_str(address),
+        None => panic!(
+            "the `custom-program-id` feature takes the program ID from STAKE_PROGRAM_ID; \
+             set it to the base58 address to deploy at"
+        ),
+    };
+
+    /// Returns `true` if given pubkey is the program ID
+    #[inline]
+    pub fn check_id(id: &Pubkey) -> bool {
+        id == &ID
+    }
+
+    /// Returns the program ID
+    #[inline]
+    pub const fn id() -> Pubkey {
+        ID
+    }
+}
+#[cfg(feature = "custom-program-id")]
+pub use custom_id::*;
@@ -0,0 +1,45 @@
+//! The commonly used parts of the crate in one import, for programs that CPI into or
+//! embed the stake program and for their tests:
+//!
+//! ```ignore
+//! use solana_pinocchio_starter::prelude::*;
+//! ```
+//!
+//! With `sdk`, the instruction builders come in as the `stake_instruction` module rather
+//! than one by one, since names like `split` and `merge` would crowd the caller's scope.
+//! This matches the usual `solana_stake_interface::instruction as stake_instruction`.
+
+pub use crate::{
+    check_id,
+    entrypoint::process_instruction,
+    error::{InstructionError, StakeError},
+    id,
+    instruction::{
+        process_authorize, process_authorize_both, process_authorize_both_with_sysvars,
+        process_authorize_checked, process_authorize_checked_with_seed,
+        process_authorize_with_seed, process_deactivate_many, process_deactivate_many_with_sysvars,
+        process_delegate, process_delegate_with_sysvars, process_initialize_and_delegate,
+        process_initialize_and_delegate_with_sysvars, process_merge, process_merge_with_sysvars,
+        process_move_lamports,
+        process_partial_deactivate, process_partial_deactivate_with_sysvars, process_set_lockup,
+        process_set_lockup_checked, process_set_lockup_checked_with_sysvars,
+        process_set_lockup_with_sysvars, process_split, process_split_many,
+        process_split_many_with_sysvars, process_split_with_sysvars, process_withdraw_excess,
+        process_withdraw_excess_with_sysvars, process_withdraw_max,
+        process_withdraw_max_with_sysvars, process_withdraw_with_seed,
+        process_withdraw_with_seed_with_sysvars, LockupArgs, LockupCheckedArgs, StakeInstruction,
+    },
+    state::{
+        get_minimum_delegation, Authorized, Delegation, FeatureSet, Lockup, Meta, Signers, Stake,
+        StakeActivationStatus, StakeAuthorize, StakeFlags, StakeHistory, StakeHistoryEntry,
+        StakeHistoryGetEntry, StakeHistorySysvar, StakeStateTag, StakeStateV2, SyscallSysvars,
+        SysvarProvider, WithFeatures,
+    },
+    CANONICAL_ID, ID,
+};
+
+#[cfg(any(test, feature = "std"))]
+pub use crate::state::MockSysvars;
+
+#[cfg(feature = "sdk")]
+pub use crate::sdk::{instruction as stake_instruction, CANONICAL_PROGRAM_ID, PROGRAM_ID};
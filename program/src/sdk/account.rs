@@ -0,0 +1,100 @@
+//! Conversions between the state types and `solana-account`'s `Account` and
+//! `AccountSharedData`, so mollusk and LiteSVM fixtures can be built from a typed
+//! state in one call and read back the same way.
+
+use std::vec::Vec;
+
+use pinocchio::program_error::ProgramError;
+use solana_account::{Account, AccountSharedData, ReadableAccount};
+
+use super::PROGRAM_ID;
+use crate::state::StakeStateV2;
+
+/// `state` serialized as stake account data. Bytes the state doesn't use are zero, as
+/// in accounts the program writes.
+pub fn stake_state_data(state: &StakeStateV2) -> Vec<u8> {
+    let mut data = std::vec![0; StakeStateV2::size_of()];
+    state
+        .serialize(&mut data)
+        .expect("the buffer is the stake account size");
+    data
+}
+
+/// A stake account owned by this program holding `lamports` and `state`
+pub fn stake_account(lamports: u64, state: &StakeStateV2) -> Account {
+    Account {
+        lamports,
+        data: stake_state_data(state),
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// `stake_account` as `AccountSharedData`, which LiteSVM and the runtime APIs take
+pub fn stake_account_shared_data(lamports: u64, state: &StakeStateV2) -> AccountSharedData {
+    stake_account(lamports, state).into()
+}
+
+/// The state held by `account`, which must be a stake account owned by this program
+pub fn stake_account_state<A: ReadableAccount>(account: &A) -> Result<StakeStateV2, ProgramError> {
+    if account.owner() != &PROGRAM_ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    StakeStateV2::deserialize(account.data())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::{Authorized, Delegation, Lockup, Meta, Stake, StakeFlags};
+
+    #[test]
+    fn test_stake_account_round_trip() {
+        let meta = Meta::new(
+            2_282_880,
+            Authorized::auto(&[1; 32]),
+            Lockup::new(-2, 3, [4; 32]),
+        );
+        let stake = Stake::new(Delegation::new(&[5; 32], 6, 7), 8);
+
+        for state in [
+            StakeStateV2::Uninitialized,
+            StakeStateV2::Initialized(meta),
+            StakeStateV2::Stake(meta, stake, StakeFlags::empty()),
+            StakeStateV2::Stake(
+                meta,
+                stake,
+                StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+            ),
+            StakeStateV2::RewardsPool,
+        ] {
+            let account = stake_account(42, &state);
+            assert_eq!(account.lamports, 42);
+            assert_eq!(account.owner, PROGRAM_ID);
+            assert_eq!(stake_account_state(&account), Ok(state));
+
+            let shared = stake_account_shared_data(42, &state);
+            assert_eq!(shared, AccountSharedData::from(account));
+            assert_eq!(stake_account_state(&shared), Ok(state));
+        }
+
+        // only the tag is set for states without a payload
+        let data = stake_state_data(&StakeStateV2::Uninitialized);
+        assert!(data.iter().all(|byte| *byte == 0));
+        assert_eq!(
+            stake_state_data(&StakeStateV2::RewardsPool)[..4],
+            3u32.to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_stake_account_state_checks_owner() {
+        let mut account = stake_account(42, &StakeStateV2::Uninitialized);
+        account.owner = solana_pubkey::Pubkey::new_from_array([9; 32]);
+        assert_eq!(
+            stake_account_state(&account),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Activation status of a delegation at a given epoch, computed locally with the same
+//! warmup/cooldown math the program runs. Stands in for the removed
+//! `getStakeActivation` RPC method.
+
+use crate::{
+    consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+    state::{Delegation, StakeActivationStatus, StakeHistoryGetEntry},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StakeActivationState {
+    Activating,
+    Active,
+    Deactivating,
+    Inactive,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct StakeActivation {
+    pub state: StakeActivationState,
+    /// stake currently earning rewards
+    pub active: u64,
+    /// delegated stake that is not (or no longer) effective
+    pub inactive: u64,
+}
+
+/// `stake_history` is typically a `StakeHistory` decoded from the stake history sysvar
+/// account fetched over RPC.
+pub fn stake_activation<T: StakeHistoryGetEntry>(
+    delegation: &Delegation,
+    target_epoch: u64,
+    stake_history: &T,
+) -> StakeActivation {
+    let status = delegation.stake_activating_and_deactivating(
+        target_epoch.to_le_bytes(),
+        stake_history,
+        PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+    );
+    let StakeActivationStatus {
+        effective,
+        activating,
+        deactivating,
+    } = status;
+
+    let state = if deactivating > 0 {
+        StakeActivationState::Deactivating
+    } else if activating > 0 {
+        StakeActivationState::Activating
+    } else if effective > 0 {
+        StakeActivationState::Active
+    } else {
+        StakeActivationState::Inactive
+    };
+
+    StakeActivation {
+        state,
+        active: effective,
+        inactive: delegation.delegated_stake().saturating_sub(effective),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::{StakeHistory, StakeHistoryEntry};
+
+    fn delegation(stake: u64, activation_epoch: u64) -> Delegation {
+        Delegation::new(&[1; 32], stake, activation_epoch)
+    }
+
+    #[test]
+    fn test_activating() {
+        let mut history = StakeHistory::default();
+        history.add(
+            0,
+            StakeHistoryEntry {
+                effective: 10_000u64.to_le_bytes(),
+                activating: 1_000u64.to_le_bytes(),
+                deactivating: [0; 8],
+            },
+        );
+        let delegation = delegation(1_000, 0);
+
+        assert_eq!(
+            stake_activation(&delegation, 0, &history),
+            StakeActivation {
+                state: StakeActivationState::Activating,
+                active: 0,
+                inactive: 1_000,
+            }
+        );
+
+        // 9% of the 10k effective cluster stake warms up, all of it ours
+        assert_eq!(
+            stake_activation(&delegation, 1, &history),
+            StakeActivation {
+                state: StakeActivationState::Activating,
+                active: 900,
+                inactive: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_active_and_deactivating() {
+        let history = StakeHistory::default();
+        let mut delegation = delegation(1_000, 0);
+
+        // no history for the activation epoch: presumed fully active
+        assert_eq!(
+            stake_activation(&delegation, 10, &history).state,
+            StakeActivationState::Active
+        );
+
+        delegation.set_deactivation_epoch(10);
+        assert_eq!(
+            stake_activation(&delegation, 10, &history),
+            StakeActivation {
+                state: StakeActivationState::Deactivating,
+                active: 1_000,
+                inactive: 0,
+            }
+        );
+        assert_eq!(
+            stake_activation(&delegation, 11, &history).state,
+            StakeActivationState::Inactive
+        );
+    }
+}
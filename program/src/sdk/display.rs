@@ -0,0 +1,239 @@
+//! Stake state rendered for explorers and inspectors: authorities as base58 strings,
+//! lockup expiry as an ISO 8601 time, sentinel epochs as `None` and flags by name.
+//! Needs no RPC or clock, so it builds for wasm32 along with the parsers.
+
+use std::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use solana_pubkey::Pubkey;
+
+use crate::state::{Lockup, Meta, Stake, StakeFlags, StakeStateV2};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct DisplayStakeState {
+    /// `uninitialized`, `initialized`, `delegated` or `rewardsPool`, the account types
+    /// explorers already show for native stake accounts
+    pub kind: &'static str,
+    pub meta: Option<DisplayMeta>,
+    pub stake: Option<DisplayStake>,
+    pub flags: Vec<&'static str>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct DisplayMeta {
+    pub rent_exempt_reserve: u64,
+    pub staker: String,
+    pub withdrawer: String,
+    pub lockup: DisplayLockup,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct DisplayLockup {
+    pub unix_timestamp: i64,
+    /// `unix_timestamp` as an ISO 8601 UTC time; `None` without a time lockup
+    pub expires_at: Option<String>,
+    pub epoch: u64,
+    pub custodian: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct DisplayStake {
+    pub voter: String,
+    pub stake: u64,
+    /// `None` for bootstrap stake, active since genesis
+    pub activation_epoch: Option<u64>,
+    /// `None` until the stake is deactivated
+    pub deactivation_epoch: Option<u64>,
+    pub credits_observed: u64,
+}
+
+impl DisplayStakeState {
+    pub fn new(state: &StakeStateV2) -> Self {
+        let (kind, meta, stake, flags) = match state {
+            StakeStateV2::Uninitialized => ("uninitialized", None, None, Vec::new()),
+            StakeStateV2::Initialized(meta) => ("initialized", Some(meta), None, Vec::new()),
+            StakeStateV2::Stake(meta, stake, flags) => {
+                ("delegated", Some(meta), Some(stake), flag_names(flags))
+            }
+            StakeStateV2::RewardsPool => ("rewardsPool", None, None, Vec::new()),
+        };
+
+        Self {
+            kind,
+            meta: meta.map(DisplayMeta::new),
+            stake: stake.map(DisplayStake::new),
+            flags,
+        }
+    }
+}
+
+impl DisplayMeta {
+    pub fn new(meta: &Meta) -> Self {
+        Self {
+            rent_exempt_reserve: meta.rent_exempt_reserve(),
+            staker: base58(meta.authorized.staker),
+            withdrawer: base58(meta.authorized.withdrawer),
+            lockup: DisplayLockup::new(&meta.lockup),
+        }
+    }
+}
+
+impl DisplayLockup {
+    pub fn new(lockup: &Lockup) -> Self {
+        let unix_timestamp = lockup.unix_timestamp();
+        Self {
+            unix_timestamp,
+            expires_at: (unix_timestamp != 0).then(|| iso8601(unix_timestamp)),
+            epoch: lockup.epoch(),
+            custodian: base58(lockup.custodian),
+        }
+    }
+}
+
+impl DisplayStake {
+    pub fn new(stake: &Stake) -> Self {
+        let delegation = &stake.delegation;
+        Self {
+            voter: base58(delegation.voter_pubkey),
+            stake: delegation.delegated_stake(),
+            activation_epoch: (!delegation.is_bootstrap()).then(|| delegation.activation_epoch()),
+            deactivation_epoch: Some(delegation.deactivation_epoch())
+                .filter(|epoch| *epoch != u64::MAX),
+            credits_observed: stake.credits_observed(),
+        }
+    }
+}
+
+/// Names of the flags set in `flags`, in camelCase like the other field names
+pub fn flag_names(flags: &StakeFlags) -> Vec<&'static str> {
+    [(
+        StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+        "mustFullyActivateBeforeDeactivationIsPermitted",
+    )]
+    .into_iter()
+    .filter(|(flag, _)| flags.contains(*flag))
+    .map(|(_, name)| name)
+    .collect()
+}
+
+/// `unix_timestamp` as `YYYY-MM-DDTHH:MM:SSZ`, using the proleptic Gregorian calendar
+pub fn iso8601(unix_timestamp: i64) -> String {
+    let days = unix_timestamp.div_euclid(86_400);
+    let seconds = unix_timestamp.rem_euclid(86_400);
+
+    // civil_from_days: counts from 0000-03-01 in 400-year eras, so leap days fall at
+    // the end of each year
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    std::format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+fn base58(bytes: [u8; 32]) -> String {
+    Pubkey::new_from_array(bytes).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::{Authorized, Delegation};
+
+    #[test]
+    fn test_iso8601() {
+        assert_eq!(iso8601(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso8601(-1), "1969-12-31T23:59:59Z");
+        assert_eq!(iso8601(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(iso8601(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_display_delegated() {
+        let lockup = Lockup {
+            unix_timestamp: 1_700_000_000i64.to_le_bytes(),
+            ..Lockup::default()
+        };
+        let meta = Meta::new(10, Authorized::auto(&[0; 32]), lockup);
+        let mut delegation = Delegation::new(&[0; 32], 1_000, u64::MAX);
+        delegation.set_deactivation_epoch(7);
+        let state = StakeStateV2::Stake(
+            meta,
+            Stake::new(delegation, 3),
+            StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+        );
+
+        let display = DisplayStakeState::new(&state);
+        assert_eq!(display.kind, "delegated");
+        assert_eq!(
+            display.flags,
+            ["mustFullyActivateBeforeDeactivationIsPermitted"]
+        );
+
+        let meta = display.meta.unwrap();
+        assert_eq!(meta.staker, "11111111111111111111111111111111");
+        assert_eq!(
+            meta.lockup.expires_at.as_deref(),
+            Some("2023-11-14T22:13:20Z")
+        );
+
+        assert_eq!(
+            display.stake,
+            Some(DisplayStake {
+                voter: "11111111111111111111111111111111".into(),
+                stake: 1_000,
+                activation_epoch: None,
+                deactivation_epoch: Some(7),
+                credits_observed: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_display_initialized() {
+        let meta = Meta::new(10, Authorized::auto(&[0; 32]), Lockup::default());
+        let display = DisplayStakeState::new(&StakeStateV2::Initialized(meta));
+
+        assert_eq!(display.kind, "initialized");
+        assert_eq!(display.meta.unwrap().lockup.expires_at, None);
+        assert_eq!(display.stake, None);
+        assert!(display.flags.is_empty());
+    }
+}
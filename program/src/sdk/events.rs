@@ -0,0 +1,276 @@
+//! Decoder for the structured stake events meant to be emitted with `sol_log_data`.
+//! The program doesn't emit them yet; the layouts are pinned here first so the
+//! emitter and indexers share one definition.
+//!
+//! An event is two `sol_log_data` fields: a header of `EVENT_PREFIX`, the event kind
+//! and the layout version, then the payload. Layouts only ever grow by appending
+//! fields, with the version bumped, so the decoder reads the fields it knows from a
+//! newer payload and ignores the rest. Unknown kinds come back as `Unknown` rather
+//! than an error, so an indexer built against this version skips them.
+
+use core::fmt;
+use std::vec::Vec;
+
+use solana_pubkey::Pubkey;
+
+use crate::state::StakeAuthorize;
+
+/// Marks a `sol_log_data` header as a stake event
+pub const EVENT_PREFIX: [u8; 4] = *b"pstk";
+
+/// Layout version this decoder was written against
+pub const EVENT_VERSION: u8 = 1;
+
+#[repr(u8)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EventKind {
+    Delegated,
+    Deactivated,
+    Split,
+    Merged,
+    Withdrawn,
+    AuthorityChanged,
+}
+
+impl TryFrom<u8> for EventKind {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Delegated),
+            1 => Ok(Self::Deactivated),
+            2 => Ok(Self::Split),
+            3 => Ok(Self::Merged),
+            4 => Ok(Self::Withdrawn),
+            5 => Ok(Self::AuthorityChanged),
+            _ => Err(value),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StakeEvent {
+    /// v1: stake, vote, stake amount (u64), activation epoch (u64)
+    Delegated {
+        stake: Pubkey,
+        vote: Pubkey,
+        amount: u64,
+        activation_epoch: u64,
+    },
+    /// v1: stake, deactivation epoch (u64)
+    Deactivated { stake: Pubkey, epoch: u64 },
+    /// v1: source, destination, lamports (u64)
+    Split {
+        source: Pubkey,
+        destination: Pubkey,
+        lamports: u64,
+    },
+    /// v1: destination, source, lamports moved (u64)
+    Merged {
+        destination: Pubkey,
+        source: Pubkey,
+        lamports: u64,
+    },
+    /// v1: stake, recipient, lamports (u64)
+    Withdrawn {
+        stake: Pubkey,
+        recipient: Pubkey,
+        lamports: u64,
+    },
+    /// v1: stake, new authority, `StakeAuthorize` (u32)
+    AuthorityChanged {
+        stake: Pubkey,
+        new_authority: Pubkey,
+        stake_authorize: StakeAuthorize,
+    },
+    /// A kind added after this decoder, passed through undecoded
+    Unknown {
+        kind: u8,
+        version: u8,
+        payload: Vec<u8>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EventError {
+    /// not two fields, or a header without `EVENT_PREFIX`: not a stake event
+    NotAnEvent,
+    /// version 0, which no layout has
+    InvalidVersion,
+    /// a payload shorter than its kind's first layout, or with an invalid field
+    InvalidPayload(EventKind),
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnEvent => f.write_str("not a stake event"),
+            Self::InvalidVersion => f.write_str("invalid stake event version"),
+            Self::InvalidPayload(kind) => write!(f, "invalid {kind:?} stake event payload"),
+        }
+    }
+}
+
+impl std::error::Error for EventError {}
+
+/// Decodes the fields of one `sol_log_data` call, base64-decoded from its
+/// `Program data:` log line
+pub fn decode_event(fields: &[&[u8]]) -> Result<StakeEvent, EventError> {
+    let [header, payload] = fields else {
+        return Err(EventError::NotAnEvent);
+    };
+    let [p0, p1, p2, p3, kind, version] = **header else {
+        return Err(EventError::NotAnEvent);
+    };
+    if [p0, p1, p2, p3] != EVENT_PREFIX {
+        return Err(EventError::NotAnEvent);
+    }
+    if version == 0 {
+        return Err(EventError::InvalidVersion);
+    }
+    let kind = match EventKind::try_from(kind) {
+        Ok(kind) => kind,
+        Err(kind) => {
+            return Ok(StakeEvent::Unknown {
+                kind,
+                version,
+                payload: payload.to_vec(),
+            })
+        }
+    };
+
+    // every version starts with the v1 fields; anything after them is newer
+    let mut payload = Payload(payload);
+    let invalid = || EventError::InvalidPayload(kind);
+    let event = match kind {
+        EventKind::Delegated => StakeEvent::Delegated {
+            stake: payload.pubkey().ok_or_else(invalid)?,
+            vote: payload.pubkey().ok_or_else(invalid)?,
+            amount: payload.u64().ok_or_else(invalid)?,
+            activation_epoch: payload.u64().ok_or_else(invalid)?,
+        },
+        EventKind::Deactivated => StakeEvent::Deactivated {
+            stake: payload.pubkey().ok_or_else(invalid)?,
+            epoch: payload.u64().ok_or_else(invalid)?,
+        },
+        EventKind::Split => StakeEvent::Split {
+            source: payload.pubkey().ok_or_else(invalid)?,
+            destination: payload.pubkey().ok_or_else(invalid)?,
+            lamports: payload.u64().ok_or_else(invalid)?,
+        },
+        EventKind::Merged => StakeEvent::Merged {
+            destination: payload.pubkey().ok_or_else(invalid)?,
+            source: payload.pubkey().ok_or_else(invalid)?,
+            lamports: payload.u64().ok_or_else(invalid)?,
+        },
+        EventKind::Withdrawn => StakeEvent::Withdrawn {
+            stake: payload.pubkey().ok_or_else(invalid)?,
+            recipient: payload.pubkey().ok_or_else(invalid)?,
+            lamports: payload.u64().ok_or_else(invalid)?,
+        },
+        EventKind::AuthorityChanged => StakeEvent::AuthorityChanged {
+            stake: payload.pubkey().ok_or_else(invalid)?,
+            new_authority: payload.pubkey().ok_or_else(invalid)?,
+            stake_authorize: match payload.u32().ok_or_else(invalid)? {
+                0 => StakeAuthorize::Staker,
+                1 => StakeAuthorize::Withdrawer,
+                _ => return Err(invalid()),
+            },
+        },
+    };
+    Ok(event)
+}
+
+struct Payload<'a>(&'a [u8]);
+
+impl Payload<'_> {
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (value, rest) = self.0.split_first_chunk::<N>()?;
+        self.0 = rest;
+        Some(*value)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.array().map(Pubkey::new_from_array)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn header(kind: u8, version: u8) -> Vec<u8> {
+        let mut header = EVENT_PREFIX.to_vec();
+        header.extend([kind, version]);
+        header
+    }
+
+    fn split_payload() -> Vec<u8> {
+        let mut payload = [[1; 32], [2; 32]].concat();
+        payload.extend(5u64.to_le_bytes());
+        payload
+    }
+
+    #[test]
+    fn test_decode_v1() {
+        let header = header(EventKind::Split as u8, EVENT_VERSION);
+        assert_eq!(
+            decode_event(&[&header, &split_payload()]),
+            Ok(StakeEvent::Split {
+                source: Pubkey::new_from_array([1; 32]),
+                destination: Pubkey::new_from_array([2; 32]),
+                lamports: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_newer_versions() {
+        // a v2 payload with a field appended decodes as its v1 fields
+        let header = header(EventKind::Split as u8, 2);
+        let mut payload = split_payload();
+        payload.extend(9u64.to_le_bytes());
+        assert!(matches!(
+            decode_event(&[&header, &payload]),
+            Ok(StakeEvent::Split { lamports: 5, .. })
+        ));
+
+        // so does a kind this decoder doesn't know, undecoded
+        let header = self::header(200, 1);
+        assert_eq!(
+            decode_event(&[&header, &[7]]),
+            Ok(StakeEvent::Unknown {
+                kind: 200,
+                version: 1,
+                payload: std::vec![7],
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let payload = split_payload();
+
+        assert_eq!(decode_event(&[b"pstk"]), Err(EventError::NotAnEvent));
+        assert_eq!(
+            decode_event(&[b"nope\x02\x01", &payload]),
+            Err(EventError::NotAnEvent)
+        );
+        assert_eq!(
+            decode_event(&[&header(EventKind::Split as u8, 0), &payload]),
+            Err(EventError::InvalidVersion)
+        );
+        assert_eq!(
+            decode_event(&[&header(EventKind::Split as u8, 1), &payload[..40]]),
+            Err(EventError::InvalidPayload(EventKind::Split))
+        );
+    }
+}
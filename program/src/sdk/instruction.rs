@@ -0,0 +1,765 @@
+//! Instruction builders mirroring `solana_stake_interface::instruction`, with the
+//! account ordering and payload encoding the processors in this crate expect.
+//!
+//! Instruction data is the one-byte `StakeInstruction` discriminator followed by the
+//! bincode encoding of the native instruction's arguments.
+
+use std::vec::Vec;
+
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::{Pubkey, PubkeyError};
+use solana_rent::Rent;
+use solana_system_interface::instruction as system_instruction;
+
+use super::{sysvar, PROGRAM_ID, STAKE_CONFIG_ID};
+use crate::{
+    instruction::{LockupArgs, StakeInstruction},
+    state::{Authorized, Lockup, StakeAuthorize, StakeStateV2},
+};
+
+/// Bincode-compatible little-endian encoder for instruction payloads
+pub(crate) struct InstructionData(Vec<u8>);
+
+impl InstructionData {
+    pub(crate) fn new(instruction: StakeInstruction) -> Self {
+        Self(std::vec![instruction as u8])
+    }
+
+    pub(crate) fn u32(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub(crate) fn u64(mut self, value: u64) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub(crate) fn bytes(mut self, value: &[u8]) -> Self {
+        self.0.extend_from_slice(value);
+        self
+    }
+
+    pub(crate) fn pubkey(self, value: &Pubkey) -> Self {
+        self.bytes(value.as_ref())
+    }
+
+    pub(crate) fn str(self, value: &str) -> Self {
+        self.u64(value.len() as u64).bytes(value.as_bytes())
+    }
+
+    pub(crate) fn option(mut self, value: Option<&[u8]>) -> Self {
+        match value {
+            Some(value) => {
+                self.0.push(1);
+                self.0.extend_from_slice(value);
+            }
+            None => self.0.push(0),
+        }
+        self
+    }
+
+    pub(crate) fn stake_authorize(self, stake_authorize: StakeAuthorize) -> Self {
+        self.u32(stake_authorize as u32)
+    }
+
+    pub(crate) fn lockup_args(self, lockup: &LockupArgs) -> Self {
+        self.option(lockup.unix_timestamp.as_ref().map(|v| v.as_slice()))
+            .option(lockup.epoch.as_ref().map(|v| v.as_slice()))
+            .option(lockup.custodian.as_ref().map(|v| v.as_slice()))
+    }
+
+    pub(crate) fn into_instruction(self, accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: self.0,
+        }
+    }
+}
+
+fn push_custodian(accounts: &mut Vec<AccountMeta>, custodian: Option<&Pubkey>) {
+    if let Some(custodian) = custodian {
+        accounts.push(AccountMeta::new_readonly(*custodian, true));
+    }
+}
+
+pub fn initialize(stake_pubkey: &Pubkey, authorized: &Authorized, lockup: &Lockup) -> Instruction {
+    InstructionData::new(StakeInstruction::Initialize)
+        .bytes(&authorized.staker)
+        .bytes(&authorized.withdrawer)
+        .bytes(&lockup.unix_timestamp)
+        .bytes(&lockup.epoch)
+        .bytes(&lockup.custodian)
+        .into_instruction(std::vec![
+            AccountMeta::new(*stake_pubkey, false),
+            AccountMeta::new_readonly(sysvar::RENT_ID, false),
+        ])
+}
+
+/// Creates `stake_pubkey` with exactly `StakeStateV2::size_of()` bytes, funds it with the
+/// rent-exempt reserve plus `stake_lamports`, and initializes it.
+///
+/// Both `from_pubkey` and `stake_pubkey` must sign the transaction.
+pub fn create_stake_account(
+    from_pubkey: &Pubkey,
+    stake_pubkey: &Pubkey,
+    authorized: &Authorized,
+    lockup: &Lockup,
+    rent: &Rent,
+    stake_lamports: u64,
+) -> Vec<Instruction> {
+    let lamports = rent
+        .minimum_balance(StakeStateV2::size_of())
+        .saturating_add(stake_lamports);
+
+    std::vec![
+        system_instruction::create_account(
+            from_pubkey,
+            stake_pubkey,
+            lamports,
+            StakeStateV2::size_of() as u64,
+            &PROGRAM_ID,
+        ),
+        initialize(stake_pubkey, authorized, lockup),
+    ]
+}
+
+/// Same as `create_stake_account`, but initializes with `InitializeChecked`, so the
+/// withdrawer must also sign.
+pub fn create_stake_account_checked(
+    from_pubkey: &Pubkey,
+    stake_pubkey: &Pubkey,
+    authorized: &Authorized,
+    rent: &Rent,
+    stake_lamports: u64,
+) -> Vec<Instruction> {
+    let lamports = rent
+        .minimum_balance(StakeStateV2::size_of())
+        .saturating_add(stake_lamports);
+
+    std::vec![
+        system_instruction::create_account(
+            from_pubkey,
+            stake_pubkey,
+            lamports,
+            StakeStateV2::size_of() as u64,
+            &PROGRAM_ID,
+        ),
+        initialize_checked(stake_pubkey, authorized),
+    ]
+}
+
+/// Extension: initializes `stake_pubkey` and delegates everything above its rent-exempt
+/// reserve to `vote_pubkey`. The new staker, `authorized.staker`, must sign.
+pub fn initialize_and_delegate(
+    stake_pubkey: &Pubkey,
+    authorized: &Authorized,
+    lockup: &Lockup,
+    vote_pubkey: &Pubkey,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::InitializeAndDelegate)
+        .bytes(&authorized.staker)
+        .bytes(&authorized.withdrawer)
+        .bytes(&lockup.unix_timestamp)
+        .bytes(&lockup.epoch)
+        .bytes(&lockup.custodian)
+        .into_instruction(std::vec![
+            AccountMeta::new(*stake_pubkey, false),
+            AccountMeta::new_readonly(*vote_pubkey, false),
+            AccountMeta::new_readonly(Pubkey::new_from_array(authorized.staker), true),
+        ])
+}
+
+/// `create_stake_account` with `initialize_and_delegate` in place of `initialize`, so
+/// the account is delegated to `vote_pubkey` as soon as it exists. `from_pubkey`,
+/// `stake_pubkey` and the staker must sign.
+pub fn create_and_delegate_stake_account(
+    from_pubkey: &Pubkey,
+    stake_pubkey: &Pubkey,
+    authorized: &Authorized,
+    lockup: &Lockup,
+    vote_pubkey: &Pubkey,
+    rent: &Rent,
+    stake_lamports: u64,
+) -> Vec<Instruction> {
+    let lamports = rent
+        .minimum_balance(StakeStateV2::size_of())
+        .saturating_add(stake_lamports);
+
+    std::vec![
+        system_instruction::create_account(
+            from_pubkey,
+            stake_pubkey,
+            lamports,
+            StakeStateV2::size_of() as u64,
+            &PROGRAM_ID,
+        ),
+        initialize_and_delegate(stake_pubkey, authorized, lockup, vote_pubkey),
+    ]
+}
+
+/// Address of the stake account `base` creates with `seed`, as derived by
+/// `SystemProgram::CreateAccountWithSeed` with this program as owner.
+pub fn stake_address_with_seed(base: &Pubkey, seed: &str) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_with_seed(base, seed, &PROGRAM_ID)
+}
+
+/// Seed-derived counterpart of `create_stake_account`. `stake_pubkey` must equal
+/// `stake_address_with_seed(base, seed)`; `from_pubkey` and `base` must sign.
+#[allow(clippy::too_many_arguments)]
+pub fn create_stake_account_with_seed(
+    from_pubkey: &Pubkey,
+    stake_pubkey: &Pubkey,
+    base: &Pubkey,
+    seed: &str,
+    authorized: &Authorized,
+    lockup: &Lockup,
+    rent: &Rent,
+    stake_lamports: u64,
+) -> Vec<Instruction> {
+    let lamports = rent
+        .minimum_balance(StakeStateV2::size_of())
+        .saturating_add(stake_lamports);
+
+    std::vec![
+        system_instruction::create_account_with_seed(
+            from_pubkey,
+            stake_pubkey,
+            base,
+            seed,
+            lamports,
+            StakeStateV2::size_of() as u64,
+            &PROGRAM_ID,
+        ),
+        initialize(stake_pubkey, authorized, lockup),
+    ]
+}
+
+pub fn initialize_checked(stake_pubkey: &Pubkey, authorized: &Authorized) -> Instruction {
+    InstructionData::new(StakeInstruction::InitializeChecked).into_instruction(std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(sysvar::RENT_ID, false),
+        AccountMeta::new_readonly(Pubkey::new_from_array(authorized.staker), false),
+        AccountMeta::new_readonly(Pubkey::new_from_array(authorized.withdrawer), true),
+    ])
+}
+
+pub fn authorize(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    new_authorized_pubkey: &Pubkey,
+    stake_authorize: StakeAuthorize,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::Authorize)
+        .pubkey(new_authorized_pubkey)
+        .stake_authorize(stake_authorize)
+        .into_instruction(accounts)
+}
+
+/// Extension: replaces both authorities at once. The current staker and withdrawer must
+/// both sign, plus `custodian_pubkey` while the lockup is in force.
+pub fn authorize_both(
+    stake_pubkey: &Pubkey,
+    staker_pubkey: &Pubkey,
+    withdrawer_pubkey: &Pubkey,
+    new_authorized: &Authorized,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(*staker_pubkey, true),
+        AccountMeta::new_readonly(*withdrawer_pubkey, true),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::AuthorizeBoth)
+        .bytes(&new_authorized.staker)
+        .bytes(&new_authorized.withdrawer)
+        .into_instruction(accounts)
+}
+
+pub fn authorize_checked(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    new_authorized_pubkey: &Pubkey,
+    stake_authorize: StakeAuthorize,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+        AccountMeta::new_readonly(*new_authorized_pubkey, true),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::AuthorizeChecked)
+        .stake_authorize(stake_authorize)
+        .into_instruction(accounts)
+}
+
+pub fn authorize_with_seed(
+    stake_pubkey: &Pubkey,
+    authority_base: &Pubkey,
+    authority_seed: &str,
+    authority_owner: &Pubkey,
+    new_authorized_pubkey: &Pubkey,
+    stake_authorize: StakeAuthorize,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(*authority_base, true),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::AuthorizeWithSeed)
+        .pubkey(new_authorized_pubkey)
+        .stake_authorize(stake_authorize)
+        .str(authority_seed)
+        .pubkey(authority_owner)
+        .into_instruction(accounts)
+}
+
+pub fn authorize_checked_with_seed(
+    stake_pubkey: &Pubkey,
+    authority_base: &Pubkey,
+    authority_seed: &str,
+    authority_owner: &Pubkey,
+    new_authorized_pubkey: &Pubkey,
+    stake_authorize: StakeAuthorize,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(*authority_base, true),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(*new_authorized_pubkey, true),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::AuthorizeCheckedWithSeed)
+        .stake_authorize(stake_authorize)
+        .str(authority_seed)
+        .pubkey(authority_owner)
+        .into_instruction(accounts)
+}
+
+pub fn delegate_stake(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::DelegateStake).into_instruction(std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(sysvar::STAKE_HISTORY_ID, false),
+        AccountMeta::new_readonly(STAKE_CONFIG_ID, false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ])
+}
+
+/// The Split instruction alone; `split_stake_pubkey` must already be allocated to
+/// `StakeStateV2::size_of()` bytes and assigned to this program.
+pub fn split(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    lamports: u64,
+    split_stake_pubkey: &Pubkey,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::Split)
+        .u64(lamports)
+        .into_instruction(std::vec![
+            AccountMeta::new(*stake_pubkey, false),
+            AccountMeta::new(*split_stake_pubkey, false),
+            AccountMeta::new_readonly(*authorized_pubkey, true),
+        ])
+}
+
+/// Allocates and assigns a fresh `split_stake_pubkey` (which must sign) before splitting
+/// into it, the sequence `solana_stake_interface::instruction::split` emits.
+pub fn split_into_new_account(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    lamports: u64,
+    split_stake_pubkey: &Pubkey,
+) -> Vec<Instruction> {
+    std::vec![
+        system_instruction::allocate(split_stake_pubkey, StakeStateV2::size_of() as u64),
+        system_instruction::assign(split_stake_pubkey, &PROGRAM_ID),
+        split(stake_pubkey, authorized_pubkey, lamports, split_stake_pubkey),
+    ]
+}
+
+/// Extension: splits `lamports` into each `split_stake_pubkey` in one instruction. Like
+/// `split`, every destination must already be allocated and assigned to this program.
+pub fn split_many(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    splits: &[(Pubkey, u64)],
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ];
+    let mut data = InstructionData::new(StakeInstruction::SplitMany);
+    for (split_stake_pubkey, lamports) in splits {
+        accounts.push(AccountMeta::new(*split_stake_pubkey, false));
+        data = data.u64(*lamports);
+    }
+    data.into_instruction(accounts)
+}
+
+/// Splits into the account derived from `base` and `seed`; `base` signs in place of
+/// the new account. Returns the derived address along with the instructions.
+pub fn split_with_seed(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    lamports: u64,
+    base: &Pubkey,
+    seed: &str,
+) -> Result<(Pubkey, Vec<Instruction>), PubkeyError> {
+    let split_stake_pubkey = stake_address_with_seed(base, seed)?;
+
+    let instructions = std::vec![
+        system_instruction::allocate_with_seed(
+            &split_stake_pubkey,
+            base,
+            seed,
+            StakeStateV2::size_of() as u64,
+            &PROGRAM_ID,
+        ),
+        split(stake_pubkey, authorized_pubkey, lamports, &split_stake_pubkey),
+    ];
+
+    Ok((split_stake_pubkey, instructions))
+}
+
+pub fn withdraw(
+    stake_pubkey: &Pubkey,
+    withdrawer_pubkey: &Pubkey,
+    to_pubkey: &Pubkey,
+    lamports: u64,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new(*to_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(sysvar::STAKE_HISTORY_ID, false),
+        AccountMeta::new_readonly(*withdrawer_pubkey, true),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::Withdraw)
+        .u64(lamports)
+        .into_instruction(accounts)
+}
+
+/// Extension: `withdraw` by the withdraw authority `create_with_seed` derives from
+/// `authority_base`, `authority_seed` and `authority_owner`; `authority_base` signs.
+pub fn withdraw_with_seed(
+    stake_pubkey: &Pubkey,
+    authority_base: &Pubkey,
+    authority_seed: &str,
+    authority_owner: &Pubkey,
+    to_pubkey: &Pubkey,
+    lamports: u64,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new(*to_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(sysvar::STAKE_HISTORY_ID, false),
+        AccountMeta::new_readonly(*authority_base, true),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::WithdrawWithSeed)
+        .u64(lamports)
+        .str(authority_seed)
+        .pubkey(authority_owner)
+        .into_instruction(accounts)
+}
+
+/// Extension: withdraws everything `stake_pubkey` can release at execution time, which
+/// closes the account once nothing is staked. Same accounts as `withdraw`; the program
+/// sets the amount moved as its return data.
+pub fn withdraw_max(
+    stake_pubkey: &Pubkey,
+    withdrawer_pubkey: &Pubkey,
+    to_pubkey: &Pubkey,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new(*to_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(sysvar::STAKE_HISTORY_ID, false),
+        AccountMeta::new_readonly(*withdrawer_pubkey, true),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::WithdrawMax).into_instruction(accounts)
+}
+
+/// Extension: withdraws only what `stake_pubkey` holds above its delegated stake and
+/// rent-exempt reserve. Same accounts as `withdraw`; the program sets the amount moved
+/// as its return data.
+pub fn withdraw_excess(
+    stake_pubkey: &Pubkey,
+    withdrawer_pubkey: &Pubkey,
+    to_pubkey: &Pubkey,
+    custodian_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new(*to_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(sysvar::STAKE_HISTORY_ID, false),
+        AccountMeta::new_readonly(*withdrawer_pubkey, true),
+    ];
+    push_custodian(&mut accounts, custodian_pubkey);
+
+    InstructionData::new(StakeInstruction::WithdrawExcess).into_instruction(accounts)
+}
+
+pub fn deactivate_stake(stake_pubkey: &Pubkey, authorized_pubkey: &Pubkey) -> Instruction {
+    InstructionData::new(StakeInstruction::Deactivate).into_instruction(std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ])
+}
+
+/// Extension: splits `lamports` into `split_stake_pubkey` and deactivates only that
+/// part. Like `split`, the destination must already be allocated and assigned to this
+/// program.
+pub fn partial_deactivate(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    lamports: u64,
+    split_stake_pubkey: &Pubkey,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::PartialDeactivate)
+        .u64(lamports)
+        .into_instruction(std::vec![
+            AccountMeta::new(*stake_pubkey, false),
+            AccountMeta::new(*split_stake_pubkey, false),
+            AccountMeta::new_readonly(*authorized_pubkey, true),
+        ])
+}
+
+/// Extension: deactivates every stake account in `stake_pubkeys`, all under
+/// `authorized_pubkey`, in one instruction.
+///
+/// # Panics
+///
+/// If there are more than 255 stake accounts, which no transaction can hold anyway.
+pub fn deactivate_many(authorized_pubkey: &Pubkey, stake_pubkeys: &[Pubkey]) -> Instruction {
+    let count = u8::try_from(stake_pubkeys.len()).expect("at most 255 stake accounts");
+    let mut accounts = std::vec![AccountMeta::new_readonly(*authorized_pubkey, true)];
+    accounts.extend(
+        stake_pubkeys
+            .iter()
+            .map(|stake_pubkey| AccountMeta::new(*stake_pubkey, false)),
+    );
+    InstructionData::new(StakeInstruction::DeactivateMany)
+        .bytes(&[count])
+        .into_instruction(accounts)
+}
+
+pub fn set_lockup(
+    stake_pubkey: &Pubkey,
+    lockup: &LockupArgs,
+    custodian_pubkey: &Pubkey,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::SetLockup)
+        .lockup_args(lockup)
+        .into_instruction(std::vec![
+            AccountMeta::new(*stake_pubkey, false),
+            AccountMeta::new_readonly(*custodian_pubkey, true),
+        ])
+}
+
+/// `lockup.custodian`, if set, is passed as a signing account rather than as data.
+pub fn set_lockup_checked(
+    stake_pubkey: &Pubkey,
+    lockup: &LockupArgs,
+    custodian_pubkey: &Pubkey,
+) -> Instruction {
+    let mut accounts = std::vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(*custodian_pubkey, true),
+    ];
+    if let Some(new_custodian) = lockup.custodian {
+        accounts.push(AccountMeta::new_readonly(
+            Pubkey::new_from_array(new_custodian),
+            true,
+        ));
+    }
+
+    InstructionData::new(StakeInstruction::SetLockupChecked)
+        .option(lockup.unix_timestamp.as_ref().map(|v| v.as_slice()))
+        .option(lockup.epoch.as_ref().map(|v| v.as_slice()))
+        .into_instruction(accounts)
+}
+
+pub fn merge(
+    destination_stake_pubkey: &Pubkey,
+    source_stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::Merge).into_instruction(std::vec![
+        AccountMeta::new(*destination_stake_pubkey, false),
+        AccountMeta::new(*source_stake_pubkey, false),
+        AccountMeta::new_readonly(sysvar::CLOCK_ID, false),
+        AccountMeta::new_readonly(sysvar::STAKE_HISTORY_ID, false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ])
+}
+
+pub fn get_minimum_delegation() -> Instruction {
+    InstructionData::new(StakeInstruction::GetMinimumDelegation).into_instruction(Vec::new())
+}
+
+pub fn deactivate_delinquent_stake(
+    stake_account: &Pubkey,
+    delinquent_vote_account: &Pubkey,
+    reference_vote_account: &Pubkey,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::DeactivateDelinquent).into_instruction(std::vec![
+        AccountMeta::new(*stake_account, false),
+        AccountMeta::new_readonly(*delinquent_vote_account, false),
+        AccountMeta::new_readonly(*reference_vote_account, false),
+    ])
+}
+
+pub fn move_stake(
+    source_stake_pubkey: &Pubkey,
+    destination_stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::MoveStake)
+        .u64(lamports)
+        .into_instruction(std::vec![
+            AccountMeta::new(*source_stake_pubkey, false),
+            AccountMeta::new(*destination_stake_pubkey, false),
+            AccountMeta::new_readonly(*authorized_pubkey, true),
+        ])
+}
+
+pub fn move_lamports(
+    source_stake_pubkey: &Pubkey,
+    destination_stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    InstructionData::new(StakeInstruction::MoveLamports)
+        .u64(lamports)
+        .into_instruction(std::vec![
+            AccountMeta::new(*source_stake_pubkey, false),
+            AccountMeta::new(*destination_stake_pubkey, false),
+            AccountMeta::new_readonly(*authorized_pubkey, true),
+        ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_data_and_accounts() {
+        let stake = Pubkey::new_from_array([1; 32]);
+        let authority = Pubkey::new_from_array([2; 32]);
+        let split_stake = Pubkey::new_from_array([3; 32]);
+
+        let ix = split(&stake, &authority, 42, &split_stake);
+
+        assert_eq!(ix.program_id, PROGRAM_ID);
+        assert_eq!(ix.data[0], StakeInstruction::Split as u8);
+        assert_eq!(ix.data[1..], 42u64.to_le_bytes());
+        assert_eq!(
+            ix.accounts,
+            std::vec![
+                AccountMeta::new(stake, false),
+                AccountMeta::new(split_stake, false),
+                AccountMeta::new_readonly(authority, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_stake_account() {
+        let from = Pubkey::new_from_array([1; 32]);
+        let stake = Pubkey::new_from_array([2; 32]);
+        let rent = Rent::default();
+
+        let ixs = create_stake_account(
+            &from,
+            &stake,
+            &Authorized::auto(&[3; 32]),
+            &Lockup::default(),
+            &rent,
+            1_000,
+        );
+
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(
+            ixs[0],
+            system_instruction::create_account(
+                &from,
+                &stake,
+                rent.minimum_balance(200) + 1_000,
+                200,
+                &PROGRAM_ID,
+            )
+        );
+        assert_eq!(ixs[1].data[0], StakeInstruction::Initialize as u8);
+        assert_eq!(ixs[1].data.len(), 1 + 64 + 48);
+    }
+
+    #[test]
+    fn test_split_with_seed() {
+        let stake = Pubkey::new_from_array([1; 32]);
+        let authority = Pubkey::new_from_array([2; 32]);
+        let base = Pubkey::new_from_array([3; 32]);
+
+        let (split_stake, ixs) = split_with_seed(&stake, &authority, 42, &base, "split").unwrap();
+
+        assert_eq!(split_stake, Pubkey::create_with_seed(&base, "split", &PROGRAM_ID).unwrap());
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(ixs[1], split(&stake, &authority, 42, &split_stake));
+    }
+
+    #[test]
+    fn test_set_lockup_data_round_trip() {
+        let lockup = LockupArgs {
+            unix_timestamp: None,
+            epoch: Some(9464321479845648u64.to_le_bytes()),
+            custodian: Some([7; 32]),
+        };
+        let ix = set_lockup(
+            &Pubkey::new_from_array([1; 32]),
+            &lockup,
+            &Pubkey::new_from_array([2; 32]),
+        );
+
+        assert_eq!(ix.data[0], StakeInstruction::SetLockup as u8);
+        assert_eq!(LockupArgs::from_data(&ix.data[1..]).unwrap(), lockup);
+    }
+}
@@ -0,0 +1,47 @@
+//! Off-chain helpers for building transactions against this program.
+//!
+//! Everything in here needs `std` and is only compiled with the `sdk` feature, so
+//! the on-chain build never pulls in `solana-instruction`.
+
+#[cfg(feature = "svm-account")]
+pub mod account;
+pub mod activation;
+pub mod display;
+pub mod events;
+pub mod instruction;
+pub mod parse;
+pub mod plan;
+pub mod rent;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
+#[cfg(feature = "svm-account")]
+pub use account::*;
+pub use activation::*;
+pub use events::*;
+pub use instruction::*;
+pub use parse::*;
+pub use plan::*;
+pub use rent::*;
+#[cfg(feature = "rpc")]
+pub use rpc::*;
+
+use solana_pubkey::Pubkey;
+
+/// This program's ID as a `solana_pubkey::Pubkey`; see `crate::ID`
+pub const PROGRAM_ID: Pubkey = Pubkey::new_from_array(crate::ID);
+
+/// The native stake program's ID, which `PROGRAM_ID` equals unless built with
+/// `custom-program-id`
+pub const CANONICAL_PROGRAM_ID: Pubkey = Pubkey::new_from_array(crate::CANONICAL_ID);
+
+pub mod sysvar {
+    use solana_pubkey::{pubkey, Pubkey};
+
+    pub const CLOCK_ID: Pubkey = pubkey!("SysvarC1ock11111111111111111111111111111111");
+    pub const RENT_ID: Pubkey = pubkey!("SysvarRent111111111111111111111111111111111");
+    pub const STAKE_HISTORY_ID: Pubkey = pubkey!("SysvarStakeHistory1111111111111111111111111");
+}
+
+/// The deprecated stake config account DelegateStake still expects in its account list
+pub const STAKE_CONFIG_ID: Pubkey = solana_pubkey::pubkey!("StakeConfig11111111111111111111111111111111");
@@ -0,0 +1,603 @@
+//! The inverse of the instruction builders: decodes an instruction addressed to this
+//! program into its arguments and named accounts, for indexers labelling stake
+//! operations in transactions.
+
+use core::fmt;
+use std::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use pinocchio::program_error::ProgramError;
+use solana_instruction::AccountMeta;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    instruction::{LockupArgs, LockupCheckedArgs, StakeInstruction},
+    state::{Authorized, Lockup, StakeAuthorize},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ParseError {
+    /// the discriminator byte names no instruction of this program
+    UnknownInstruction(u8),
+    /// the arguments are truncated, malformed or followed by extra bytes
+    InvalidData,
+    NotEnoughAccounts,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownInstruction(discriminator) => {
+                write!(f, "unknown stake instruction {discriminator}")
+            }
+            Self::InvalidData => f.write_str("invalid stake instruction data"),
+            Self::NotEnoughAccounts => f.write_str("not enough accounts for stake instruction"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The error the entrypoint returns for the same input
+impl From<ParseError> for ProgramError {
+    fn from(error: ParseError) -> Self {
+        match error {
+            ParseError::UnknownInstruction(_) | ParseError::InvalidData => {
+                ProgramError::InvalidInstructionData
+            }
+            ParseError::NotEnoughAccounts => ProgramError::NotEnoughAccountKeys,
+        }
+    }
+}
+
+/// A decoded instruction, with accounts named as in the IDL. Optional trailing
+/// accounts, such as the lockup custodian, are `None` when not passed.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParsedStakeInstruction {
+    Initialize {
+        stake: Pubkey,
+        authorized: Authorized,
+        lockup: Lockup,
+    },
+    Authorize {
+        stake: Pubkey,
+        authority: Pubkey,
+        new_authority: Pubkey,
+        stake_authorize: StakeAuthorize,
+        custodian: Option<Pubkey>,
+    },
+    DelegateStake {
+        stake: Pubkey,
+        vote: Pubkey,
+        stake_authority: Pubkey,
+    },
+    Split {
+        stake: Pubkey,
+        split_stake: Pubkey,
+        stake_authority: Pubkey,
+        lamports: u64,
+    },
+    Withdraw {
+        stake: Pubkey,
+        recipient: Pubkey,
+        withdraw_authority: Pubkey,
+        custodian: Option<Pubkey>,
+        lamports: u64,
+    },
+    Deactivate {
+        stake: Pubkey,
+        stake_authority: Pubkey,
+    },
+    SetLockup {
+        stake: Pubkey,
+        authority: Pubkey,
+        lockup: LockupArgs,
+    },
+    Merge {
+        destination_stake: Pubkey,
+        source_stake: Pubkey,
+        stake_authority: Pubkey,
+    },
+    AuthorizeWithSeed {
+        stake: Pubkey,
+        authority_base: Pubkey,
+        new_authority: Pubkey,
+        stake_authorize: StakeAuthorize,
+        authority_seed: String,
+        authority_owner: Pubkey,
+        custodian: Option<Pubkey>,
+    },
+    InitializeChecked {
+        stake: Pubkey,
+        stake_authority: Pubkey,
+        withdraw_authority: Pubkey,
+    },
+    AuthorizeChecked {
+        stake: Pubkey,
+        authority: Pubkey,
+        new_authority: Pubkey,
+        stake_authorize: StakeAuthorize,
+        custodian: Option<Pubkey>,
+    },
+    AuthorizeCheckedWithSeed {
+        stake: Pubkey,
+        authority_base: Pubkey,
+        new_authority: Pubkey,
+        stake_authorize: StakeAuthorize,
+        authority_seed: String,
+        authority_owner: Pubkey,
+        custodian: Option<Pubkey>,
+    },
+    SetLockupChecked {
+        stake: Pubkey,
+        authority: Pubkey,
+        new_custodian: Option<Pubkey>,
+        lockup: LockupCheckedArgs,
+    },
+    GetMinimumDelegation,
+    DeactivateDelinquent {
+        stake: Pubkey,
+        delinquent_vote: Pubkey,
+        reference_vote: Pubkey,
+    },
+    Redelegate {
+        stake: Pubkey,
+        uninitialized_stake: Pubkey,
+        vote: Pubkey,
+        stake_authority: Pubkey,
+    },
+    MoveStake {
+        source_stake: Pubkey,
+        destination_stake: Pubkey,
+        stake_authority: Pubkey,
+        lamports: u64,
+    },
+    MoveLamports {
+        source_stake: Pubkey,
+        destination_stake: Pubkey,
+        stake_authority: Pubkey,
+        lamports: u64,
+    },
+    WithdrawMax {
+        stake: Pubkey,
+        recipient: Pubkey,
+        withdraw_authority: Pubkey,
+        custodian: Option<Pubkey>,
+    },
+    SplitMany {
+        stake: Pubkey,
+        stake_authority: Pubkey,
+        /// each destination and the lamports split into it
+        splits: Vec<(Pubkey, u64)>,
+    },
+    AuthorizeBoth {
+        stake: Pubkey,
+        staker: Pubkey,
+        withdrawer: Pubkey,
+        new_authorized: Authorized,
+        custodian: Option<Pubkey>,
+    },
+    DeactivateMany {
+        stake_authority: Pubkey,
+        stakes: Vec<Pubkey>,
+    },
+    InitializeAndDelegate {
+        stake: Pubkey,
+        vote: Pubkey,
+        authorized: Authorized,
+        lockup: Lockup,
+    },
+    PartialDeactivate {
+        stake: Pubkey,
+        split_stake: Pubkey,
+        stake_authority: Pubkey,
+        lamports: u64,
+    },
+    WithdrawWithSeed {
+        stake: Pubkey,
+        recipient: Pubkey,
+        authority_base: Pubkey,
+        authority_seed: String,
+        authority_owner: Pubkey,
+        custodian: Option<Pubkey>,
+        lamports: u64,
+    },
+    WithdrawExcess {
+        stake: Pubkey,
+        recipient: Pubkey,
+        withdraw_authority: Pubkey,
+        custodian: Option<Pubkey>,
+    },
+}
+
+/// Decodes `data` and `accounts` of an instruction whose program ID is this
+/// program's. Accounts past the ones an instruction names are ignored, as the
+/// processors ignore them.
+pub fn parse_instruction(
+    data: &[u8],
+    accounts: &[AccountMeta],
+) -> Result<ParsedStakeInstruction, ParseError> {
+    let (discriminator, data) = data.split_first().ok_or(ParseError::InvalidData)?;
+    let instruction = StakeInstruction::try_from(discriminator)
+        .map_err(|_| ParseError::UnknownInstruction(*discriminator))?;
+    let mut data = Reader(data);
+    let keys = Accounts(accounts);
+
+    let parsed = match instruction {
+        StakeInstruction::Initialize => ParsedStakeInstruction::Initialize {
+            stake: keys.get(0)?,
+            authorized: data.authorized()?,
+            lockup: data.lockup()?,
+        },
+        StakeInstruction::Authorize => ParsedStakeInstruction::Authorize {
+            stake: keys.get(0)?,
+            authority: keys.get(2)?,
+            new_authority: data.pubkey()?,
+            stake_authorize: data.stake_authorize()?,
+            custodian: keys.optional(3),
+        },
+        StakeInstruction::DelegateStake => ParsedStakeInstruction::DelegateStake {
+            stake: keys.get(0)?,
+            vote: keys.get(1)?,
+            stake_authority: keys.get(5)?,
+        },
+        StakeInstruction::Split => ParsedStakeInstruction::Split {
+            stake: keys.get(0)?,
+            split_stake: keys.get(1)?,
+            stake_authority: keys.get(2)?,
+            lamports: data.u64()?,
+        },
+        StakeInstruction::Withdraw => ParsedStakeInstruction::Withdraw {
+            stake: keys.get(0)?,
+            recipient: keys.get(1)?,
+            withdraw_authority: keys.get(4)?,
+            custodian: keys.optional(5),
+            lamports: data.u64()?,
+        },
+        StakeInstruction::Deactivate => ParsedStakeInstruction::Deactivate {
+            stake: keys.get(0)?,
+            stake_authority: keys.get(2)?,
+        },
+        StakeInstruction::SetLockup => ParsedStakeInstruction::SetLockup {
+            stake: keys.get(0)?,
+            authority: keys.get(1)?,
+            lockup: LockupArgs::from_data(data.rest()).map_err(|_| ParseError::InvalidData)?,
+        },
+        StakeInstruction::Merge => ParsedStakeInstruction::Merge {
+            destination_stake: keys.get(0)?,
+            source_stake: keys.get(1)?,
+            stake_authority: keys.get(4)?,
+        },
+        StakeInstruction::AuthorizeWithSeed => ParsedStakeInstruction::AuthorizeWithSeed {
+            stake: keys.get(0)?,
+            authority_base: keys.get(1)?,
+            new_authority: data.pubkey()?,
+            stake_authorize: data.stake_authorize()?,
+            authority_seed: data.str()?,
+            authority_owner: data.pubkey()?,
+            custodian: keys.optional(3),
+        },
+        StakeInstruction::InitializeChecked => ParsedStakeInstruction::InitializeChecked {
+            stake: keys.get(0)?,
+            stake_authority: keys.get(2)?,
+            withdraw_authority: keys.get(3)?,
+        },
+        StakeInstruction::AuthorizeChecked => ParsedStakeInstruction::AuthorizeChecked {
+            stake: keys.get(0)?,
+            authority: keys.get(2)?,
+            new_authority: keys.get(3)?,
+            stake_authorize: data.stake_authorize()?,
+            custodian: keys.optional(4),
+        },
+        StakeInstruction::AuthorizeCheckedWithSeed => {
+            ParsedStakeInstruction::AuthorizeCheckedWithSeed {
+                stake: keys.get(0)?,
+                authority_base: keys.get(1)?,
+                new_authority: keys.get(3)?,
+                stake_authorize: data.stake_authorize()?,
+                authority_seed: data.str()?,
+                authority_owner: data.pubkey()?,
+                custodian: keys.optional(4),
+            }
+        }
+        StakeInstruction::SetLockupChecked => ParsedStakeInstruction::SetLockupChecked {
+            stake: keys.get(0)?,
+            authority: keys.get(1)?,
+            new_custodian: keys.optional(2),
+            lockup: LockupCheckedArgs::from_data(data.rest())
+                .map_err(|_| ParseError::InvalidData)?,
+        },
+        StakeInstruction::GetMinimumDelegation => ParsedStakeInstruction::GetMinimumDelegation,
+        StakeInstruction::DeactivateDelinquent => ParsedStakeInstruction::DeactivateDelinquent {
+            stake: keys.get(0)?,
+            delinquent_vote: keys.get(1)?,
+            reference_vote: keys.get(2)?,
+        },
+        #[allow(deprecated)]
+        StakeInstruction::Redelegate => ParsedStakeInstruction::Redelegate {
+            stake: keys.get(0)?,
+            uninitialized_stake: keys.get(1)?,
+            vote: keys.get(2)?,
+            stake_authority: keys.get(4)?,
+        },
+        StakeInstruction::MoveStake => ParsedStakeInstruction::MoveStake {
+            source_stake: keys.get(0)?,
+            destination_stake: keys.get(1)?,
+            stake_authority: keys.get(2)?,
+            lamports: data.u64()?,
+        },
+        StakeInstruction::MoveLamports => ParsedStakeInstruction::MoveLamports {
+            source_stake: keys.get(0)?,
+            destination_stake: keys.get(1)?,
+            stake_authority: keys.get(2)?,
+            lamports: data.u64()?,
+        },
+        StakeInstruction::WithdrawMax => ParsedStakeInstruction::WithdrawMax {
+            stake: keys.get(0)?,
+            recipient: keys.get(1)?,
+            withdraw_authority: keys.get(4)?,
+            custodian: keys.optional(5),
+        },
+        StakeInstruction::SplitMany => {
+            let mut splits = Vec::new();
+            for index in 2.. {
+                if data.is_empty() {
+                    break;
+                }
+                splits.push((keys.get(index)?, data.u64()?));
+            }
+            ParsedStakeInstruction::SplitMany {
+                stake: keys.get(0)?,
+                stake_authority: keys.get(1)?,
+                splits,
+            }
+        }
+        StakeInstruction::AuthorizeBoth => ParsedStakeInstruction::AuthorizeBoth {
+            stake: keys.get(0)?,
+            staker: keys.get(2)?,
+            withdrawer: keys.get(3)?,
+            new_authorized: data.authorized()?,
+            custodian: keys.optional(4),
+        },
+        StakeInstruction::DeactivateMany => {
+            let count = usize::from(data.u8()?);
+            ParsedStakeInstruction::DeactivateMany {
+                stake_authority: keys.get(0)?,
+                stakes: (1..=count)
+                    .map(|index| keys.get(index))
+                    .collect::<Result<_, _>>()?,
+            }
+        }
+        StakeInstruction::InitializeAndDelegate => ParsedStakeInstruction::InitializeAndDelegate {
+            stake: keys.get(0)?,
+            vote: keys.get(1)?,
+            authorized: data.authorized()?,
+            lockup: data.lockup()?,
+        },
+        StakeInstruction::PartialDeactivate => ParsedStakeInstruction::PartialDeactivate {
+            stake: keys.get(0)?,
+            split_stake: keys.get(1)?,
+            stake_authority: keys.get(2)?,
+            lamports: data.u64()?,
+        },
+        StakeInstruction::WithdrawWithSeed => ParsedStakeInstruction::WithdrawWithSeed {
+            stake: keys.get(0)?,
+            recipient: keys.get(1)?,
+            authority_base: keys.get(4)?,
+            custodian: keys.optional(5),
+            lamports: data.u64()?,
+            authority_seed: data.str()?,
+            authority_owner: data.pubkey()?,
+        },
+        StakeInstruction::WithdrawExcess => ParsedStakeInstruction::WithdrawExcess {
+            stake: keys.get(0)?,
+            recipient: keys.get(1)?,
+            withdraw_authority: keys.get(4)?,
+            custodian: keys.optional(5),
+        },
+    };
+
+    if !data.is_empty() {
+        return Err(ParseError::InvalidData);
+    }
+    Ok(parsed)
+}
+
+struct Accounts<'a>(&'a [AccountMeta]);
+
+impl Accounts<'_> {
+    fn get(&self, index: usize) -> Result<Pubkey, ParseError> {
+        self.optional(index).ok_or(ParseError::NotEnoughAccounts)
+    }
+
+    fn optional(&self, index: usize) -> Option<Pubkey> {
+        self.0.get(index).map(|account| account.pubkey)
+    }
+}
+
+/// Reads the bincode encoding `InstructionData` writes, front to back
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Everything left, for arguments parsed by the program's own `from_data`
+    fn rest(&mut self) -> &[u8] {
+        core::mem::take(&mut self.0)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ParseError> {
+        let (value, rest) = self
+            .0
+            .split_first_chunk::<N>()
+            .ok_or(ParseError::InvalidData)?;
+        self.0 = rest;
+        Ok(*value)
+    }
+
+    fn u8(&mut self) -> Result<u8, ParseError> {
+        self.array::<1>().map(|[value]| value)
+    }
+
+    fn u64(&mut self) -> Result<u64, ParseError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey, ParseError> {
+        self.array().map(Pubkey::new_from_array)
+    }
+
+    fn str(&mut self) -> Result<String, ParseError> {
+        let len = usize::try_from(self.u64()?).map_err(|_| ParseError::InvalidData)?;
+        let bytes = self.0.get(..len).ok_or(ParseError::InvalidData)?;
+        let value = core::str::from_utf8(bytes).map_err(|_| ParseError::InvalidData)?;
+        self.0 = &self.0[len..];
+        Ok(value.to_string())
+    }
+
+    fn stake_authorize(&mut self) -> Result<StakeAuthorize, ParseError> {
+        match u32::from_le_bytes(self.array()?) {
+            0 => Ok(StakeAuthorize::Staker),
+            1 => Ok(StakeAuthorize::Withdrawer),
+            _ => Err(ParseError::InvalidData),
+        }
+    }
+
+    fn authorized(&mut self) -> Result<Authorized, ParseError> {
+        Ok(Authorized {
+            staker: self.array()?,
+            withdrawer: self.array()?,
+        })
+    }
+
+    fn lockup(&mut self) -> Result<Lockup, ParseError> {
+        Ok(Lockup {
+            unix_timestamp: self.array()?,
+            epoch: self.array()?,
+            custodian: self.array()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sdk::{self, sysvar};
+
+    const STAKE: Pubkey = Pubkey::new_from_array([1; 32]);
+    const AUTHORITY: Pubkey = Pubkey::new_from_array([2; 32]);
+    const OTHER: Pubkey = Pubkey::new_from_array([3; 32]);
+
+    fn parse(instruction: solana_instruction::Instruction) -> ParsedStakeInstruction {
+        parse_instruction(&instruction.data, &instruction.accounts).unwrap()
+    }
+
+    #[test]
+    fn test_parse_builders() {
+        assert_eq!(
+            parse(sdk::split(&STAKE, &AUTHORITY, 42, &OTHER)),
+            ParsedStakeInstruction::Split {
+                stake: STAKE,
+                split_stake: OTHER,
+                stake_authority: AUTHORITY,
+                lamports: 42,
+            }
+        );
+        assert_eq!(
+            parse(sdk::authorize(
+                &STAKE,
+                &AUTHORITY,
+                &OTHER,
+                StakeAuthorize::Withdrawer,
+                Some(&sysvar::RENT_ID),
+            )),
+            ParsedStakeInstruction::Authorize {
+                stake: STAKE,
+                authority: AUTHORITY,
+                new_authority: OTHER,
+                stake_authorize: StakeAuthorize::Withdrawer,
+                custodian: Some(sysvar::RENT_ID),
+            }
+        );
+        assert_eq!(
+            parse(sdk::withdraw_with_seed(
+                &STAKE, &AUTHORITY, "seed", &OTHER, &OTHER, 7, None,
+            )),
+            ParsedStakeInstruction::WithdrawWithSeed {
+                stake: STAKE,
+                recipient: OTHER,
+                authority_base: AUTHORITY,
+                authority_seed: "seed".into(),
+                authority_owner: OTHER,
+                custodian: None,
+                lamports: 7,
+            }
+        );
+        assert_eq!(
+            parse(sdk::split_many(
+                &STAKE,
+                &AUTHORITY,
+                &[(OTHER, 5), (STAKE, 6)]
+            )),
+            ParsedStakeInstruction::SplitMany {
+                stake: STAKE,
+                stake_authority: AUTHORITY,
+                splits: std::vec![(OTHER, 5), (STAKE, 6)],
+            }
+        );
+        assert_eq!(
+            parse(sdk::deactivate_many(&AUTHORITY, &[STAKE, OTHER])),
+            ParsedStakeInstruction::DeactivateMany {
+                stake_authority: AUTHORITY,
+                stakes: std::vec![STAKE, OTHER],
+            }
+        );
+
+        let lockup = LockupArgs {
+            unix_timestamp: None,
+            epoch: Some(9u64.to_le_bytes()),
+            custodian: Some(OTHER.to_bytes()),
+        };
+        assert_eq!(
+            parse(sdk::set_lockup_checked(&STAKE, &lockup, &AUTHORITY)),
+            ParsedStakeInstruction::SetLockupChecked {
+                stake: STAKE,
+                authority: AUTHORITY,
+                new_custodian: Some(OTHER),
+                lockup: LockupCheckedArgs {
+                    unix_timestamp: None,
+                    epoch: Some(9u64.to_le_bytes()),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let split = sdk::split(&STAKE, &AUTHORITY, 42, &OTHER);
+
+        assert_eq!(
+            parse_instruction(&[200], &[]),
+            Err(ParseError::UnknownInstruction(200))
+        );
+        assert_eq!(parse_instruction(&[], &[]), Err(ParseError::InvalidData));
+        assert_eq!(
+            parse_instruction(&split.data[..5], &split.accounts),
+            Err(ParseError::InvalidData)
+        );
+        let mut data = split.data.clone();
+        data.push(0);
+        assert_eq!(
+            parse_instruction(&data, &split.accounts),
+            Err(ParseError::InvalidData)
+        );
+        assert_eq!(
+            parse_instruction(&split.data, &split.accounts[..2]),
+            Err(ParseError::NotEnoughAccounts)
+        );
+    }
+}
@@ -0,0 +1,471 @@
+//! Multi-epoch stake choreography planned up front. Unstaking takes two transactions
+//! an epoch or more apart: deactivate now, withdraw once the stake has cooled down.
+//! The planners here pick the accounts, emit the instructions to submit now and
+//! describe the ones left for later; staking is planned the same way, as one
+//! create-and-delegate batch per new account.
+
+use core::{cmp::Reverse, fmt};
+use std::vec::Vec;
+
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use solana_system_interface::instruction as system_instruction;
+
+use super::{
+    create_stake_account, deactivate_many, delegate_stake, minimum_delegation_plus_reserve,
+    minimum_split_lamports, minimum_stake_account_balance, partial_deactivate, withdraw,
+    PROGRAM_ID,
+};
+use crate::state::{get_minimum_delegation, Authorized, Lockup, StakeStateV2};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PlanError {
+    /// below the smallest amount a split can move out of delegated stake
+    AmountTooSmall { minimum: u64 },
+    /// the accounts given don't hold enough undeactivated stake
+    InsufficientStake { available: u64 },
+    /// a per-account cap below the minimum delegation
+    CapTooSmall { minimum: u64 },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AmountTooSmall { minimum } => {
+                write!(f, "amount is below the minimum of {minimum} lamports")
+            }
+            Self::InsufficientStake { available } => {
+                write!(f, "only {available} lamports of active stake available")
+            }
+            Self::CapTooSmall { minimum } => {
+                write!(
+                    f,
+                    "per-account cap is below the minimum delegation of {minimum} lamports"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// A stake account the unstake planner may draw from, as fetched over RPC
+#[derive(Debug, Clone, Copy)]
+pub struct StakeSource {
+    pub address: Pubkey,
+    pub lamports: u64,
+    pub state: StakeStateV2,
+}
+
+/// A withdrawal to submit once the deactivated stake has cooled down
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PlannedWithdrawal {
+    pub stake_pubkey: Pubkey,
+    /// the account's whole balance, closing it
+    pub lamports: u64,
+}
+
+impl PlannedWithdrawal {
+    pub fn instruction(
+        &self,
+        withdrawer_pubkey: &Pubkey,
+        to_pubkey: &Pubkey,
+        custodian_pubkey: Option<&Pubkey>,
+    ) -> Instruction {
+        withdraw(
+            &self.stake_pubkey,
+            withdrawer_pubkey,
+            to_pubkey,
+            self.lamports,
+            custodian_pubkey,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnstakePlan {
+    /// to submit now, signed by the staker, who also funds the split accounts' reserves,
+    /// and every `split_stake_pubkeys` account
+    pub instructions: Vec<Instruction>,
+    /// fresh accounts the instructions split stake into
+    pub split_stake_pubkeys: Vec<Pubkey>,
+    /// to submit in a later epoch, once `get_stake_accounts` or `stake_activation`
+    /// reports the accounts inactive
+    pub withdrawals: Vec<PlannedWithdrawal>,
+}
+
+impl UnstakePlan {
+    /// Lamports the withdrawals release, at least the amount asked for: whole accounts
+    /// are deactivated where possible, splits are rounded up to the minimum, and the
+    /// reserves prefunded into split accounts are returned
+    pub fn lamports(&self) -> u64 {
+        self.withdrawals
+            .iter()
+            .map(|withdrawal| withdrawal.lamports)
+            .sum()
+    }
+}
+
+/// Plans unstaking `lamports` from `sources`, all under `staker_pubkey`. Accounts
+/// that fit in what is left of `lamports` are deactivated whole, largest first; the
+/// remainder is split off the smallest account that can spare it, into an address
+/// taken from `new_split_stake_pubkey`. Accounts that aren't delegated or are already
+/// deactivating are skipped.
+///
+/// Splitting part of an active delegation needs a destination that already holds its
+/// rent-exempt reserve, so the staker prefunds the split account with it. The reserve
+/// comes back with the split's withdrawal.
+pub fn plan_unstake(
+    sources: &[StakeSource],
+    lamports: u64,
+    staker_pubkey: &Pubkey,
+    rent: &Rent,
+    mut new_split_stake_pubkey: impl FnMut() -> Pubkey,
+) -> Result<UnstakePlan, PlanError> {
+    let reserve = minimum_stake_account_balance(rent);
+    let minimum_split = minimum_split_lamports(rent, reserve, true, true)
+        .expect("a destination holding its reserve takes a split of active stake");
+    if lamports < minimum_split {
+        return Err(PlanError::AmountTooSmall {
+            minimum: minimum_split,
+        });
+    }
+
+    let mut candidates: Vec<(Pubkey, u64)> = sources
+        .iter()
+        .filter(|source| {
+            source
+                .state
+                .delegation_ref()
+                .is_some_and(|delegation| delegation.deactivation_epoch() == u64::MAX)
+        })
+        .map(|source| (source.address, source.lamports))
+        .collect();
+    let available = candidates.iter().map(|(_, lamports)| lamports).sum();
+    candidates.sort_by_key(|&(_, lamports)| Reverse(lamports));
+
+    let mut whole = Vec::new();
+    let mut remaining = lamports;
+    candidates.retain(|&(address, lamports)| {
+        if lamports > remaining {
+            return true;
+        }
+        remaining -= lamports;
+        whole.push(PlannedWithdrawal {
+            stake_pubkey: address,
+            lamports,
+        });
+        false
+    });
+
+    let mut plan = UnstakePlan {
+        instructions: Vec::new(),
+        split_stake_pubkeys: Vec::new(),
+        withdrawals: Vec::new(),
+    };
+
+    if remaining > 0 {
+        let split_lamports = remaining.max(minimum_split);
+        let spare = |lamports: u64| {
+            lamports.saturating_sub(split_lamports) >= minimum_delegation_plus_reserve(rent)
+        };
+
+        // candidates are largest first; the last is the smallest
+        if let Some(&(address, _)) = candidates
+            .iter()
+            .rev()
+            .find(|(_, lamports)| spare(*lamports))
+        {
+            let split_stake_pubkey = new_split_stake_pubkey();
+            plan.instructions.extend([
+                system_instruction::transfer(staker_pubkey, &split_stake_pubkey, reserve),
+                system_instruction::allocate(&split_stake_pubkey, StakeStateV2::size_of() as u64),
+                system_instruction::assign(&split_stake_pubkey, &PROGRAM_ID),
+                partial_deactivate(&address, staker_pubkey, split_lamports, &split_stake_pubkey),
+            ]);
+            plan.split_stake_pubkeys.push(split_stake_pubkey);
+            plan.withdrawals.push(PlannedWithdrawal {
+                stake_pubkey: split_stake_pubkey,
+                lamports: split_lamports + reserve,
+            });
+        } else if let Some(&(address, lamports)) = candidates.last() {
+            // none can be split, but every one left covers the remainder whole
+            whole.push(PlannedWithdrawal {
+                stake_pubkey: address,
+                lamports,
+            });
+        } else {
+            return Err(PlanError::InsufficientStake { available });
+        }
+    }
+
+    if !whole.is_empty() {
+        let stake_pubkeys: Vec<Pubkey> = whole
+            .iter()
+            .map(|withdrawal| withdrawal.stake_pubkey)
+            .collect();
+        plan.instructions
+            .push(deactivate_many(staker_pubkey, &stake_pubkeys));
+        plan.withdrawals.splice(0..0, whole);
+    }
+
+    Ok(plan)
+}
+
+/// A new stake account and the batch that creates and delegates it, sized to fit one
+/// transaction signed by the funder, the new account and the staker
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedStakeAccount {
+    pub stake_pubkey: Pubkey,
+    /// delegated lamports, on top of the rent-exempt reserve
+    pub stake_lamports: u64,
+    pub instructions: Vec<Instruction>,
+}
+
+/// Plans staking `lamports` from `from_pubkey`, rent-exempt reserves included, to
+/// `vote_pubkey`. With `max_stake_per_account` the stake is spread evenly over as
+/// few accounts as keep each delegation under the cap; every account, addressed
+/// by `new_stake_pubkey`, must still meet the minimum delegation.
+#[allow(clippy::too_many_arguments)]
+pub fn plan_stake(
+    from_pubkey: &Pubkey,
+    lamports: u64,
+    vote_pubkey: &Pubkey,
+    authorized: &Authorized,
+    lockup: &Lockup,
+    rent: &Rent,
+    max_stake_per_account: Option<u64>,
+    mut new_stake_pubkey: impl FnMut() -> Pubkey,
+) -> Result<Vec<PlannedStakeAccount>, PlanError> {
+    let reserve = minimum_stake_account_balance(rent);
+    let minimum_delegation = get_minimum_delegation();
+
+    let count = match max_stake_per_account {
+        Some(cap) if cap < minimum_delegation => {
+            return Err(PlanError::CapTooSmall {
+                minimum: minimum_delegation,
+            })
+        }
+        Some(cap) => lamports.div_ceil(cap.saturating_add(reserve)),
+        None => 1,
+    }
+    .max(1);
+
+    let minimum = count.saturating_mul(minimum_delegation_plus_reserve(rent));
+    if lamports < minimum {
+        return Err(PlanError::AmountTooSmall { minimum });
+    }
+
+    // what is left over after an even spread goes one lamport each to the first accounts
+    let stake = lamports - count * reserve;
+    let (share, leftover) = (stake / count, stake % count);
+    let staker_pubkey = Pubkey::new_from_array(authorized.staker);
+
+    Ok((0..count)
+        .map(|index| {
+            let stake_pubkey = new_stake_pubkey();
+            let stake_lamports = share + u64::from(index < leftover);
+            let mut instructions = create_stake_account(
+                from_pubkey,
+                &stake_pubkey,
+                authorized,
+                lockup,
+                rent,
+                stake_lamports,
+            );
+            instructions.push(delegate_stake(&stake_pubkey, &staker_pubkey, vote_pubkey));
+            PlannedStakeAccount {
+                stake_pubkey,
+                stake_lamports,
+                instructions,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        instruction::StakeInstruction,
+        sdk::minimum_stake_account_balance,
+        state::{Authorized, Delegation, Lockup, Meta, Stake, StakeFlags},
+    };
+
+    const STAKER: Pubkey = Pubkey::new_from_array([1; 32]);
+
+    fn source(address: u8, lamports: u64) -> StakeSource {
+        let reserve = minimum_stake_account_balance(&Rent::default());
+        let meta = Meta::new(
+            reserve,
+            Authorized::auto(&STAKER.to_bytes()),
+            Lockup::default(),
+        );
+        let delegation = Delegation::new(&[9; 32], lamports - reserve, 0);
+        StakeSource {
+            address: Pubkey::new_from_array([address; 32]),
+            lamports,
+            state: StakeStateV2::Stake(meta, Stake::new(delegation, 0), StakeFlags::empty()),
+        }
+    }
+
+    fn plan(sources: &[StakeSource], lamports: u64) -> Result<UnstakePlan, PlanError> {
+        let split_stake_pubkey = Pubkey::new_from_array([7; 32]);
+        plan_unstake(sources, lamports, &STAKER, &Rent::default(), || {
+            split_stake_pubkey
+        })
+    }
+
+    #[test]
+    fn test_unstake_whole_accounts() {
+        let sources = [source(2, 3_000_000_000), source(3, 5_000_000_000)];
+
+        let plan = plan(&sources, 8_000_000_000).unwrap();
+        assert_eq!(plan.instructions.len(), 1);
+        assert_eq!(
+            plan.instructions[0].data[0],
+            StakeInstruction::DeactivateMany as u8
+        );
+        assert!(plan.split_stake_pubkeys.is_empty());
+        assert_eq!(
+            plan.withdrawals,
+            [
+                PlannedWithdrawal {
+                    stake_pubkey: sources[1].address,
+                    lamports: 5_000_000_000,
+                },
+                PlannedWithdrawal {
+                    stake_pubkey: sources[0].address,
+                    lamports: 3_000_000_000,
+                },
+            ]
+        );
+        assert_eq!(plan.lamports(), 8_000_000_000);
+    }
+
+    #[test]
+    fn test_unstake_splits_remainder() {
+        let sources = [source(2, 4_000_000_000), source(3, 5_000_000_000)];
+
+        // the 5 SOL account goes whole, the rest is split off the 4 SOL one
+        let plan = plan(&sources, 7_000_000_000).unwrap();
+        let kinds: Vec<_> = plan
+            .instructions
+            .iter()
+            .map(|ix| (ix.program_id, ix.data.first().copied()))
+            .collect();
+        assert_eq!(kinds.len(), 5);
+        assert_eq!(
+            kinds[3],
+            (PROGRAM_ID, Some(StakeInstruction::PartialDeactivate as u8))
+        );
+        assert_eq!(
+            kinds[4],
+            (PROGRAM_ID, Some(StakeInstruction::DeactivateMany as u8))
+        );
+        assert_eq!(plan.split_stake_pubkeys, [Pubkey::new_from_array([7; 32])]);
+        assert_eq!(plan.withdrawals[0].stake_pubkey, sources[1].address);
+        // the split account is prefunded with its reserve, which the withdrawal returns
+        let reserve = minimum_stake_account_balance(&Rent::default());
+        assert_eq!(
+            plan.instructions[0],
+            system_instruction::transfer(&STAKER, &plan.split_stake_pubkeys[0], reserve)
+        );
+        assert_eq!(plan.withdrawals[1].lamports, 2_000_000_000 + reserve);
+        assert_eq!(plan.lamports(), 7_000_000_000 + reserve);
+    }
+
+    #[test]
+    fn test_unstake_errors() {
+        let sources = [source(2, 3_000_000_000)];
+
+        assert_eq!(
+            plan(&sources, 0),
+            Err(PlanError::AmountTooSmall {
+                minimum: get_minimum_delegation(),
+            })
+        );
+        assert_eq!(
+            plan(&sources, 4_000_000_000),
+            Err(PlanError::InsufficientStake {
+                available: 3_000_000_000,
+            })
+        );
+
+        // already deactivating stake can't be unstaked again
+        let mut deactivating = sources[0];
+        if let StakeStateV2::Stake(_, stake, _) = &mut deactivating.state {
+            stake.delegation.set_deactivation_epoch(0);
+        }
+        assert_eq!(
+            plan(&[deactivating], 2_000_000_000),
+            Err(PlanError::InsufficientStake { available: 0 })
+        );
+    }
+
+    fn plan_stake(lamports: u64, cap: Option<u64>) -> Result<Vec<PlannedStakeAccount>, PlanError> {
+        let mut next = 10u8;
+        super::plan_stake(
+            &Pubkey::new_from_array([2; 32]),
+            lamports,
+            &Pubkey::new_from_array([9; 32]),
+            &Authorized::auto(&STAKER.to_bytes()),
+            &Lockup::default(),
+            &Rent::default(),
+            cap,
+            || {
+                next += 1;
+                Pubkey::new_from_array([next; 32])
+            },
+        )
+    }
+
+    #[test]
+    fn test_stake_single_account() {
+        let reserve = minimum_stake_account_balance(&Rent::default());
+
+        let accounts = plan_stake(5_000_000_000, None).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].stake_lamports, 5_000_000_000 - reserve);
+        assert_eq!(accounts[0].instructions.len(), 3);
+        assert_eq!(
+            accounts[0].instructions[2].data[0],
+            StakeInstruction::DelegateStake as u8
+        );
+    }
+
+    #[test]
+    fn test_stake_spreads_under_cap() {
+        let reserve = minimum_stake_account_balance(&Rent::default());
+        let lamports = 10_000_000_000 + 3 * reserve + 2;
+
+        let accounts = plan_stake(lamports, Some(4_000_000_000)).unwrap();
+        let stakes: Vec<u64> = accounts.iter().map(|a| a.stake_lamports).collect();
+        assert_eq!(stakes, [3_333_333_334, 3_333_333_334, 3_333_333_334]);
+        assert_eq!(
+            accounts
+                .iter()
+                .map(|a| a.stake_lamports + reserve)
+                .sum::<u64>(),
+            lamports
+        );
+        assert_ne!(accounts[0].stake_pubkey, accounts[1].stake_pubkey);
+    }
+
+    #[test]
+    fn test_stake_errors() {
+        let minimum = minimum_delegation_plus_reserve(&Rent::default());
+
+        assert_eq!(
+            plan_stake(minimum - 1, None),
+            Err(PlanError::AmountTooSmall { minimum })
+        );
+        assert_eq!(
+            plan_stake(minimum, Some(get_minimum_delegation() - 1)),
+            Err(PlanError::CapTooSmall {
+                minimum: get_minimum_delegation(),
+            })
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Lamport requirements for stake accounts, following the rules `validate_split_amount`
+//! enforces on chain.
+
+use solana_rent::Rent;
+
+use crate::state::{get_minimum_delegation, StakeStateV2};
+
+/// Rent-exempt reserve of a `StakeStateV2::size_of()` byte stake account
+pub fn minimum_stake_account_balance(rent: &Rent) -> u64 {
+    rent.minimum_balance(StakeStateV2::size_of())
+}
+
+/// Smallest balance a delegated stake account can hold: its rent-exempt reserve plus
+/// the minimum delegation
+pub fn minimum_delegation_plus_reserve(rent: &Rent) -> u64 {
+    minimum_stake_account_balance(rent).saturating_add(get_minimum_delegation())
+}
+
+/// Minimum number of lamports a Split must move into a destination that already holds
+/// `destination_lamports`. A prefunded destination only offsets its rent-exempt reserve:
+/// when splitting delegated stake, the split stake itself must still meet the minimum
+/// delegation.
+///
+/// Active stake can't pay for the destination's reserve, so a partial split of an
+/// active source needs a destination already prefunded to the reserve. `None` when it
+/// isn't: then only a split of the whole source goes through.
+///
+/// Callers of the earlier three-argument form, which returned a plain `u64`, pass
+/// whether the source's stake is active and handle `None` by prefunding the destination
+/// with `minimum_stake_account_balance` first. Passing `false` gives the old result.
+pub fn minimum_split_lamports(
+    rent: &Rent,
+    destination_lamports: u64,
+    source_is_delegated: bool,
+    source_is_active: bool,
+) -> Option<u64> {
+    let reserve_deficit = minimum_stake_account_balance(rent).saturating_sub(destination_lamports);
+    if source_is_active && reserve_deficit > 0 {
+        None
+    } else if source_is_delegated {
+        Some(reserve_deficit.saturating_add(get_minimum_delegation()))
+    } else {
+        // the split amount is never allowed to be zero
+        Some(reserve_deficit.max(1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_minimum_split_lamports() {
+        let rent = Rent::default();
+        let reserve = rent.minimum_balance(200);
+
+        assert_eq!(minimum_stake_account_balance(&rent), reserve);
+        assert_eq!(
+            minimum_delegation_plus_reserve(&rent),
+            reserve + get_minimum_delegation()
+        );
+
+        assert_eq!(
+            minimum_split_lamports(&rent, 0, true, false),
+            Some(reserve + get_minimum_delegation())
+        );
+        assert_eq!(
+            minimum_split_lamports(&rent, 0, false, false),
+            Some(reserve)
+        );
+        assert_eq!(
+            minimum_split_lamports(&rent, reserve, true, false),
+            Some(get_minimum_delegation())
+        );
+        assert_eq!(
+            minimum_split_lamports(&rent, u64::MAX, true, false),
+            Some(get_minimum_delegation())
+        );
+        assert_eq!(
+            minimum_split_lamports(&rent, u64::MAX, false, false),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_minimum_split_lamports_active_source() {
+        let rent = Rent::default();
+        let reserve = rent.minimum_balance(200);
+
+        // an under-funded destination can't take part of an active source at all
+        assert_eq!(minimum_split_lamports(&rent, 0, true, true), None);
+        assert_eq!(minimum_split_lamports(&rent, reserve - 1, true, true), None);
+
+        assert_eq!(
+            minimum_split_lamports(&rent, reserve, true, true),
+            Some(get_minimum_delegation())
+        );
+        assert_eq!(
+            minimum_split_lamports(&rent, u64::MAX, true, true),
+            Some(get_minimum_delegation())
+        );
+    }
+}
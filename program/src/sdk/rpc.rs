@@ -0,0 +1,319 @@
+//! Enumerating stake accounts over RPC: a `getProgramAccounts` query filtered on the
+//! staker, withdrawer or voter field, each match decoded and classified by its
+//! activation status at the current epoch.
+
+use std::vec::Vec;
+
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::RpcClient,
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_pubkey::Pubkey;
+
+use super::{
+    stake_activation, sysvar::STAKE_HISTORY_ID, StakeActivation, StakeActivationState, StakeSource,
+};
+use crate::state::{
+    layout::{STAKER_OFFSET, TAG_OFFSET, VOTER_PUBKEY_OFFSET, WITHDRAWER_OFFSET},
+    StakeActivationStatus, StakeAuthorize, StakeHistory, StakeHistoryGetEntry, StakeStateTag,
+    StakeStateV2,
+};
+
+#[derive(Debug, Clone)]
+pub struct StakeAccount {
+    pub address: Pubkey,
+    pub lamports: u64,
+    pub state: StakeStateV2,
+    /// `Inactive` with no stake for accounts that were never delegated
+    pub activation: StakeActivation,
+}
+
+impl From<&StakeAccount> for StakeSource {
+    fn from(account: &StakeAccount) -> Self {
+        Self {
+            address: account.address,
+            lamports: account.lamports,
+            state: account.state,
+        }
+    }
+}
+
+/// Filters matching stake accounts whose `authority_type` authority is `authority`
+pub fn stake_account_filters(
+    authority: &Pubkey,
+    authority_type: StakeAuthorize,
+) -> Vec<RpcFilterType> {
+    let offset = match authority_type {
+        StakeAuthorize::Staker => STAKER_OFFSET,
+        StakeAuthorize::Withdrawer => WITHDRAWER_OFFSET,
+    };
+    std::vec![
+        RpcFilterType::DataSize(StakeStateV2::size_of() as u64),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, authority.as_ref())),
+    ]
+}
+
+/// Filters matching stake accounts delegated to `vote_account`. The tag is matched
+/// too, as the voter field of an undelegated account is just zeroes.
+pub fn vote_account_filters(vote_account: &Pubkey) -> Vec<RpcFilterType> {
+    std::vec![
+        RpcFilterType::DataSize(StakeStateV2::size_of() as u64),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            TAG_OFFSET,
+            &(StakeStateTag::Stake as u32).to_le_bytes(),
+        )),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            VOTER_PUBKEY_OFFSET,
+            vote_account.as_ref(),
+        )),
+    ]
+}
+
+/// Decodes a stake account and classifies it at `epoch`; `None` if `data` isn't an
+/// initialized or delegated stake account
+pub fn classify_stake_account<T: StakeHistoryGetEntry>(
+    address: Pubkey,
+    lamports: u64,
+    data: &[u8],
+    epoch: u64,
+    stake_history: &T,
+) -> Option<StakeAccount> {
+    let state = StakeStateV2::deserialize(data).ok()?;
+    let activation = match &state {
+        StakeStateV2::Initialized(_) => StakeActivation {
+            state: StakeActivationState::Inactive,
+            active: 0,
+            inactive: 0,
+        },
+        StakeStateV2::Stake(_, stake, _) => {
+            stake_activation(&stake.delegation, epoch, stake_history)
+        }
+        StakeStateV2::Uninitialized | StakeStateV2::RewardsPool => return None,
+    };
+
+    Some(StakeAccount {
+        address,
+        lamports,
+        state,
+        activation,
+    })
+}
+
+/// Every stake account `authority` holds as `authority_type`, classified at the
+/// current epoch
+pub fn get_stake_accounts(
+    rpc: &RpcClient,
+    authority: &Pubkey,
+    authority_type: StakeAuthorize,
+) -> Result<Vec<StakeAccount>, ClientError> {
+    fetch_stake_accounts(rpc, stake_account_filters(authority, authority_type))
+}
+
+/// Stake accounts delegated to a vote account and their summed stake
+#[derive(Debug, Clone)]
+pub struct VoteAccountStake {
+    pub accounts: Vec<StakeAccount>,
+    pub total: StakeActivationStatus,
+}
+
+/// Every stake account delegated to `vote_account`, with the effective, activating
+/// and deactivating stake summed across them at the current epoch
+pub fn get_vote_account_stake(
+    rpc: &RpcClient,
+    vote_account: &Pubkey,
+) -> Result<VoteAccountStake, ClientError> {
+    let accounts = fetch_stake_accounts(rpc, vote_account_filters(vote_account))?;
+    let total = sum_stake(&accounts);
+    Ok(VoteAccountStake { accounts, total })
+}
+
+/// Totals in the shape of a stake history entry: deactivating stake is still
+/// effective, activating stake is not yet
+pub fn sum_stake(accounts: &[StakeAccount]) -> StakeActivationStatus {
+    accounts
+        .iter()
+        .fold(StakeActivationStatus::default(), |mut total, account| {
+            let StakeActivation {
+                state,
+                active,
+                inactive,
+            } = account.activation;
+            total.effective = total.effective.saturating_add(active);
+            match state {
+                StakeActivationState::Activating => {
+                    total.activating = total.activating.saturating_add(inactive)
+                }
+                StakeActivationState::Deactivating => {
+                    total.deactivating = total.deactivating.saturating_add(active)
+                }
+                StakeActivationState::Active | StakeActivationState::Inactive => {}
+            }
+            total
+        })
+}
+
+fn fetch_stake_accounts(
+    rpc: &RpcClient,
+    filters: Vec<RpcFilterType>,
+) -> Result<Vec<StakeAccount>, ClientError> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = rpc.get_program_accounts_with_config(&super::PROGRAM_ID, config)?;
+
+    let epoch = rpc.get_epoch_info()?.epoch;
+    let stake_history = rpc.get_account_data(&STAKE_HISTORY_ID)?;
+    let stake_history = StakeHistory::from_sysvar_data(&stake_history).map_err(|err| {
+        ClientError::from(ClientErrorKind::Custom(std::format!(
+            "cannot decode stake history: {err:?}"
+        )))
+    })?;
+
+    Ok(accounts
+        .into_iter()
+        .filter_map(|(address, account)| {
+            classify_stake_account(
+                address,
+                account.lamports,
+                &account.data,
+                epoch,
+                &stake_history,
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        state::{Authorized, Delegation, Lockup, Meta, Stake, StakeFlags},
+        test_utils::TestAccount,
+    };
+
+    const STAKER: [u8; 32] = [2; 32];
+    const WITHDRAWER: [u8; 32] = [3; 32];
+
+    fn data(state: StakeStateV2) -> Vec<u8> {
+        TestAccount::stake([1; 32], 0, state).data
+    }
+
+    #[test]
+    fn test_filters_match_authority() {
+        let authorized = Authorized {
+            staker: STAKER,
+            withdrawer: WITHDRAWER,
+        };
+        let data = data(StakeStateV2::Initialized(Meta::new(
+            0,
+            authorized,
+            Lockup::default(),
+        )));
+
+        let matches = |authority: [u8; 32], authority_type| {
+            stake_account_filters(&Pubkey::new_from_array(authority), authority_type)
+                .iter()
+                .all(|filter| match filter {
+                    RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+                    RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(&data),
+                    _ => false,
+                })
+        };
+        assert!(matches(STAKER, StakeAuthorize::Staker));
+        assert!(matches(WITHDRAWER, StakeAuthorize::Withdrawer));
+        assert!(!matches(WITHDRAWER, StakeAuthorize::Staker));
+        assert!(!matches(STAKER, StakeAuthorize::Withdrawer));
+    }
+
+    #[test]
+    fn test_classify_stake_account() {
+        let history = StakeHistory::default();
+        let meta = Meta::new(0, Authorized::auto(&STAKER), Lockup::default());
+        let classify = |state| {
+            classify_stake_account(Pubkey::default(), 10, &data(state), 5, &history)
+                .map(|account| account.activation.state)
+        };
+
+        assert_eq!(
+            classify(StakeStateV2::Initialized(meta)),
+            Some(StakeActivationState::Inactive)
+        );
+
+        let mut delegation = Delegation::new(&[9; 32], 1_000, 0);
+        let delegated =
+            |delegation| StakeStateV2::Stake(meta, Stake::new(delegation, 0), StakeFlags::empty());
+        assert_eq!(
+            classify(delegated(delegation)),
+            Some(StakeActivationState::Active)
+        );
+
+        delegation.set_deactivation_epoch(5);
+        assert_eq!(
+            classify(delegated(delegation)),
+            Some(StakeActivationState::Deactivating)
+        );
+
+        assert_eq!(classify(StakeStateV2::Uninitialized), None);
+    }
+
+    #[test]
+    fn test_vote_account_filters_skip_undelegated() {
+        let vote_account = [9; 32];
+        let meta = Meta::new(0, Authorized::auto(&STAKER), Lockup::default());
+        let matches = |state| {
+            let data = data(state);
+            vote_account_filters(&Pubkey::new_from_array(vote_account))
+                .iter()
+                .all(|filter| match filter {
+                    RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+                    RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(&data),
+                    _ => false,
+                })
+        };
+        let delegation = |voter| Delegation::new(&voter, 1_000, 0);
+
+        assert!(matches(StakeStateV2::Stake(
+            meta,
+            Stake::new(delegation(vote_account), 0),
+            StakeFlags::empty()
+        )));
+        assert!(!matches(StakeStateV2::Stake(
+            meta,
+            Stake::new(delegation([8; 32]), 0),
+            StakeFlags::empty()
+        )));
+        assert!(!matches(StakeStateV2::Initialized(meta)));
+    }
+
+    #[test]
+    fn test_sum_stake() {
+        let account = |state, active, inactive| StakeAccount {
+            address: Pubkey::default(),
+            lamports: 0,
+            state: StakeStateV2::Uninitialized,
+            activation: StakeActivation {
+                state,
+                active,
+                inactive,
+            },
+        };
+        let accounts = [
+            account(StakeActivationState::Active, 1_000, 0),
+            account(StakeActivationState::Activating, 100, 900),
+            account(StakeActivationState::Deactivating, 400, 600),
+            account(StakeActivationState::Inactive, 0, 0),
+        ];
+
+        assert_eq!(
+            sum_stake(&accounts),
+            StakeActivationStatus {
+                effective: 1_500,
+                activating: 900,
+                deactivating: 400,
+            }
+        );
+    }
+}
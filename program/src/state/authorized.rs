@@ -1,43 +1,69 @@
+use core::fmt;
+
 use pinocchio::{program_error::ProgramError, pubkey::Pubkey, sysvars::clock::Clock};
 
 use crate::error::StakeError;
 
-use super::{Lockup, StakeAuthorize};
+use super::{utils::Base58, Lockup, SignerSet, StakeAuthorize};
 
 #[repr(C)]
-#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct Authorized {
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::pubkey"))]
     pub staker: Pubkey,
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::pubkey"))]
     pub withdrawer: Pubkey,
 }
 
+impl fmt::Debug for Authorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authorized")
+            .field("staker", &Base58(&self.staker))
+            .field("withdrawer", &Base58(&self.withdrawer))
+            .finish()
+    }
+}
+
 impl Authorized {
-    pub fn auto(authorized: &Pubkey) -> Self {
+    /// Both authorities zeroed. Same as `Authorized::default()`, but usable in `const`
+    /// and `static` items.
+    pub const DEFAULT: Self = Self {
+        staker: [0; 32],
+        withdrawer: [0; 32],
+    };
+
+    pub const fn auto(authorized: &Pubkey) -> Self {
         Self {
             staker: *authorized,
             withdrawer: *authorized,
         }
     }
 
-    pub fn check(
+    pub fn check<S: SignerSet + ?Sized>(
         &self,
-        signers: &[Pubkey],
+        signers: &S,
         stake_authorize: StakeAuthorize,
     ) -> Result<(), ProgramError> {
         let authorized_signer = match stake_authorize {
             StakeAuthorize::Staker => &self.staker,
             StakeAuthorize::Withdrawer => &self.withdrawer,
         };
-        if signers.iter().any(|p| p == authorized_signer) {
+        if signers.contains_signer(authorized_signer) {
             Ok(())
         } else {
             Err(ProgramError::MissingRequiredSignature)
         }
     }
 
-    pub fn authorize(
+    /// Replaces the `stake_authorize` authority with `new_authorized`, the one place
+    /// deciding who may: the staker can be changed by the staker or the withdrawer, the
+    /// withdrawer only by the withdrawer, and while `lockup_custodian_args`' lockup is in
+    /// force only with the custodian's signature as well
+    pub fn authorize<S: SignerSet + ?Sized>(
         &mut self,
-        signers: &[Pubkey],
+        signers: &S,
         new_authorized: &Pubkey,
         stake_authorize: StakeAuthorize,
         lockup_custodian_args: Option<(&Lockup, &Clock, Option<&Pubkey>)>,
@@ -45,7 +71,9 @@ impl Authorized {
         match stake_authorize {
             StakeAuthorize::Staker => {
                 // Allow either the staker or the withdrawer to change the staker key
-                if !signers.contains(&self.staker) && !signers.contains(&self.withdrawer) {
+                if !signers.contains_signer(&self.staker)
+                    && !signers.contains_signer(&self.withdrawer)
+                {
                     return Err(ProgramError::MissingRequiredSignature);
                 }
                 self.staker = *new_authorized
@@ -58,7 +86,7 @@ impl Authorized {
                                 return Err(StakeError::CustodianMissing.into());
                             }
                             Some(custodian) => {
-                                if !signers.contains(custodian) {
+                                if !signers.contains_signer(custodian) {
                                     return Err(StakeError::CustodianSignatureMissing.into());
                                 }
 
@@ -76,3 +104,107 @@ impl Authorized {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const STAKER: Pubkey = [1; 32];
+    const WITHDRAWER: Pubkey = [2; 32];
+    const CUSTODIAN: Pubkey = [3; 32];
+    const NEW: Pubkey = [4; 32];
+
+    fn authorize(
+        signers: &[Pubkey],
+        stake_authorize: StakeAuthorize,
+        lockup_epoch: u64,
+        custodian: Option<&Pubkey>,
+    ) -> Result<Authorized, ProgramError> {
+        let mut authorized = Authorized {
+            staker: STAKER,
+            withdrawer: WITHDRAWER,
+        };
+        let lockup = Lockup {
+            epoch: lockup_epoch.to_le_bytes(),
+            custodian: CUSTODIAN,
+            ..Lockup::default()
+        };
+        let clock = Clock {
+            epoch: 10,
+            ..Clock::default()
+        };
+        authorized
+            .authorize(
+                signers,
+                &NEW,
+                stake_authorize,
+                Some((&lockup, &clock, custodian)),
+            )
+            .map(|()| authorized)
+    }
+
+    #[test]
+    fn test_authorize_staker() {
+        for signer in [STAKER, WITHDRAWER] {
+            let authorized = authorize(&[signer], StakeAuthorize::Staker, 0, None).unwrap();
+            assert_eq!(
+                (authorized.staker, authorized.withdrawer),
+                (NEW, WITHDRAWER)
+            );
+        }
+        assert_eq!(
+            authorize(&[CUSTODIAN], StakeAuthorize::Staker, 0, None),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        // the lockup only guards the withdrawer
+        assert!(authorize(&[STAKER], StakeAuthorize::Staker, 20, None).is_ok());
+    }
+
+    #[test]
+    fn test_authorize_withdrawer() {
+        let authorized = authorize(&[WITHDRAWER], StakeAuthorize::Withdrawer, 0, None).unwrap();
+        assert_eq!((authorized.staker, authorized.withdrawer), (STAKER, NEW));
+        assert_eq!(
+            authorize(&[STAKER], StakeAuthorize::Withdrawer, 0, None),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_authorize_withdrawer_in_lockup() {
+        assert_eq!(
+            authorize(&[WITHDRAWER], StakeAuthorize::Withdrawer, 20, None),
+            Err(StakeError::CustodianMissing.into())
+        );
+        assert_eq!(
+            authorize(
+                &[WITHDRAWER],
+                StakeAuthorize::Withdrawer,
+                20,
+                Some(&CUSTODIAN)
+            ),
+            Err(StakeError::CustodianSignatureMissing.into())
+        );
+        assert_eq!(
+            authorize(&[WITHDRAWER], StakeAuthorize::Withdrawer, 20, Some(&NEW)),
+            Err(StakeError::CustodianSignatureMissing.into())
+        );
+        // the custodian lifts the lockup, but the withdrawer still has to sign
+        assert_eq!(
+            authorize(
+                &[CUSTODIAN],
+                StakeAuthorize::Withdrawer,
+                20,
+                Some(&CUSTODIAN)
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert!(authorize(
+            &[WITHDRAWER, CUSTODIAN],
+            StakeAuthorize::Withdrawer,
+            20,
+            Some(&CUSTODIAN)
+        )
+        .is_ok());
+    }
+}
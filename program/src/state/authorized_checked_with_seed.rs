@@ -83,6 +83,20 @@ impl <'a> AuthorizeCheckedWithSeedArgs<'a>{
 
 }
 
+// `authority_seed_len` always matches the seed, as it would after deserializing
+#[cfg(feature = "fuzz")]
+impl<'a> arbitrary::Arbitrary<'a> for AuthorizeCheckedWithSeedArgs<'a> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let authority_seed: &'a str = u.arbitrary()?;
+        Ok(Self {
+            stake_authorize: u.arbitrary()?,
+            authority_seed_len: authority_seed.len() as u32,
+            authority_seed,
+            authority_owner: u.arbitrary()?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,3 +133,4 @@ mod tests {
         assert_eq!(deserialized_args.authority_owner, args.authority_owner);
     }
 }
+
@@ -1,21 +1,85 @@
+use core::fmt;
+
 use pinocchio::pubkey::Pubkey;
 
-use super::{bytes_to_u64, warmup_cooldown_rate, Epoch, StakeHistoryEntry, StakeHistoryGetEntry};
+use super::{
+    bytes_to_u64, stake_history::MAX_ENTRIES, utils::Base58, warmup_cooldown_rate, Epoch,
+    ProjectedStakeHistory, StakeHistoryEntry, StakeHistoryGetEntry,
+};
+
+/// Effective, activating and deactivating stake of a delegation at an epoch
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy)]
+pub struct StakeActivationStatus {
+    pub effective: u64,
+    pub activating: u64,
+    pub deactivating: u64,
+}
+
+impl StakeActivationStatus {
+    pub fn with_effective(effective: u64) -> Self {
+        Self {
+            effective,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_effective_and_activating(effective: u64, activating: u64) -> Self {
+        Self {
+            effective,
+            activating,
+            ..Self::default()
+        }
+    }
 
-pub type StakeActivationStatus = StakeHistoryEntry;
+    pub fn with_deactivating(deactivating: u64) -> Self {
+        Self {
+            effective: deactivating,
+            deactivating,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<StakeHistoryEntry> for StakeActivationStatus {
+    fn from(entry: StakeHistoryEntry) -> Self {
+        Self {
+            effective: entry.effective(),
+            activating: entry.activating(),
+            deactivating: entry.deactivating(),
+        }
+    }
+}
+
+impl From<StakeActivationStatus> for StakeHistoryEntry {
+    fn from(status: StakeActivationStatus) -> Self {
+        Self {
+            effective: status.effective.to_le_bytes(),
+            activating: status.activating.to_le_bytes(),
+            deactivating: status.deactivating.to_le_bytes(),
+        }
+    }
+}
 
 #[repr(C)]
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "fuzz"), allow(deprecated))]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct Delegation {
     /// to whom the stake is delegated
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::pubkey"))]
     pub voter_pubkey: Pubkey,
     /// activated stake amount, set at delegate() time
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::u64_le"))]
     pub stake: [u8; 8], // u64
     /// epoch at which this stake was activated, std::Epoch::MAX if is a bootstrap stake
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::u64_le"))]
     pub activation_epoch: Epoch,
     /// epoch the stake was deactivated, std::Epoch::MAX if not deactivated
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::u64_le"))]
     pub deactivation_epoch: Epoch,
     /// how much stake we can activate per-epoch as a fraction of currently effective stake
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::f64_le"))]
     #[deprecated(
         since = "1.16.7",
         note = "Please use `solana_sdk::stake::state::warmup_cooldown_rate()` instead"
@@ -24,17 +88,19 @@ pub struct Delegation {
 }
 
 impl Delegation {
-    pub fn new(voter_pubkey: &Pubkey, stake: u64, activation_epoch: Epoch) -> Self {
+    pub fn new(voter_pubkey: &Pubkey, stake: u64, activation_epoch: u64) -> Self {
         Self {
             voter_pubkey: *voter_pubkey,
             stake: stake.to_le_bytes(),
-            activation_epoch,
+            activation_epoch: activation_epoch.to_le_bytes(),
             ..Delegation::default()
         }
     }
 
+    /// Genesis stake, marked by an activation epoch of `u64::MAX`, which is fully
+    /// effective from the first epoch without warming up
     pub fn is_bootstrap(&self) -> bool {
-        u64::from_le_bytes(self.activation_epoch) == u64::MAX
+        self.activation_epoch() == u64::MAX
     }
 
     #[inline(always)]
@@ -42,25 +108,20 @@ impl Delegation {
         self.stake = stake.to_le_bytes();
     }
 
-    /*
-    // Implemented by Stanislav
+    /// The full delegated amount; `stake` is the part of it effective at an epoch
     #[inline(always)]
-    pub fn stake(&self) -> u64 {
+    pub fn delegated_stake(&self) -> u64 {
         u64::from_le_bytes(self.stake)
     }
 
-     */
-
     pub fn stake<T: StakeHistoryGetEntry>(
         &self,
         epoch: Epoch,
         history: &T,
         new_rate_activation_epoch: Option<Epoch>,
     ) -> u64 {
-        let result = self
-            .stake_activating_and_deactivating(epoch, history, new_rate_activation_epoch)
-            .effective;
-        u64::from_be_bytes(result)
+        self.stake_activating_and_deactivating(epoch, history, new_rate_activation_epoch)
+            .effective
     }
 
     #[allow(clippy::comparison_chain)]
@@ -74,26 +135,30 @@ impl Delegation {
         let (effective_stake, activating_stake) =
             self.stake_and_activating(target_epoch, history, new_rate_activation_epoch);
 
+        // epochs are compared as integers; the little-endian byte arrays don't order correctly
+        let target_epoch = bytes_to_u64(target_epoch);
+        let deactivation_epoch = self.deactivation_epoch();
+
         // then de-activate some portion if necessary
-        if target_epoch < self.deactivation_epoch {
+        if target_epoch < deactivation_epoch {
             // not deactivated
             if activating_stake == 0 {
-                StakeActivationStatus::with_effective(effective_stake.to_le_bytes())
+                StakeActivationStatus::with_effective(effective_stake)
             } else {
                 StakeActivationStatus::with_effective_and_activating(
-                    effective_stake.to_le_bytes(),
-                    activating_stake.to_le_bytes(),
+                    effective_stake,
+                    activating_stake,
                 )
             }
-        } else if target_epoch == self.deactivation_epoch {
+        } else if target_epoch == deactivation_epoch {
             // can only deactivate what's activated
             StakeActivationStatus::with_deactivating(effective_stake)
         } else if let Some((history, mut prev_epoch, mut prev_cluster_stake)) = history
-            .get_entry(bytes_to_u64(self.deactivation_epoch))
+            .get_entry(deactivation_epoch)
             .map(|cluster_stake_at_deactivation_epoch| {
                 (
                     history,
-                    self.deactivation_epoch,
+                    deactivation_epoch,
                     cluster_stake_at_deactivation_epoch,
                 )
             })
@@ -104,27 +169,25 @@ impl Delegation {
             // current effective stake is updated using its previous epoch's cluster stake
             let mut current_epoch;
             let mut current_effective_stake = effective_stake;
-            let prev_cluster_stake_deactivating = bytes_to_u64(prev_cluster_stake.deactivating);
-            let prev_cluster_stake_effective = bytes_to_u64(prev_cluster_stake.effective);
 
             loop {
-                current_epoch = bytes_to_u64(prev_epoch) + 1;
+                current_epoch = prev_epoch + 1;
                 // if there is no deactivating stake at prev epoch, we should have been
                 // fully undelegated at this moment
-                if bytes_to_u64(prev_cluster_stake.deactivating) == 0 {
+                if prev_cluster_stake.deactivating() == 0 {
                     break;
                 }
 
                 // I'm trying to get to zero, how much of the deactivation in stake
                 //   this account is entitled to take
-                let weight =
-                    current_effective_stake as f64 / prev_cluster_stake_deactivating as f64;
+                let weight = current_effective_stake as f64
+                    / prev_cluster_stake.deactivating() as f64;
                 let warmup_cooldown_rate =
-                    warmup_cooldown_rate(current_epoch.to_be_bytes(), new_rate_activation_epoch);
+                    warmup_cooldown_rate(current_epoch.to_le_bytes(), new_rate_activation_epoch);
 
                 // portion of newly not-effective cluster stake I'm entitled to at current epoch
                 let newly_not_effective_cluster_stake =
-                    prev_cluster_stake_effective as f64 * warmup_cooldown_rate;
+                    prev_cluster_stake.effective() as f64 * warmup_cooldown_rate;
                 let newly_not_effective_stake =
                     ((weight * newly_not_effective_cluster_stake) as u64).max(1);
 
@@ -134,11 +197,11 @@ impl Delegation {
                     break;
                 }
 
-                if current_epoch >= bytes_to_u64(target_epoch) {
+                if current_epoch >= target_epoch {
                     break;
                 }
                 if let Some(current_cluster_stake) = history.get_entry(current_epoch) {
-                    prev_epoch = current_epoch.to_le_bytes();
+                    prev_epoch = current_epoch;
                     prev_cluster_stake = current_cluster_stake;
                 } else {
                     break;
@@ -153,6 +216,45 @@ impl Delegation {
         }
     }
 
+    /// Epochs from `current_epoch` until all of the stake is effective, projecting the
+    /// cluster's stake forward unchanged from the latest `history` entry. `Some(0)` if
+    /// it already is; `None` once deactivated, or if it would take over `MAX_ENTRIES`
+    pub fn epochs_until_fully_active<T: StakeHistoryGetEntry + ?Sized>(
+        &self,
+        current_epoch: u64,
+        history: &T,
+        new_rate_activation_epoch: Option<Epoch>,
+    ) -> Option<u64> {
+        if self.deactivation_epoch() != u64::MAX {
+            return None;
+        }
+        let history = ProjectedStakeHistory::new(history, current_epoch);
+        let delegated_stake = self.delegated_stake();
+        (0..=MAX_ENTRIES as u64).find(|epochs| {
+            let epoch = current_epoch.saturating_add(*epochs);
+            self.stake(epoch.to_le_bytes(), &history, new_rate_activation_epoch) == delegated_stake
+        })
+    }
+
+    /// Epochs from `current_epoch` until none of the stake is effective, projecting the
+    /// cluster's stake forward unchanged from the latest `history` entry. `Some(0)` if
+    /// it already is; `None` if not deactivated, or if it would take over `MAX_ENTRIES`
+    pub fn epochs_until_fully_inactive<T: StakeHistoryGetEntry + ?Sized>(
+        &self,
+        current_epoch: u64,
+        history: &T,
+        new_rate_activation_epoch: Option<Epoch>,
+    ) -> Option<u64> {
+        if self.deactivation_epoch() == u64::MAX {
+            return None;
+        }
+        let history = ProjectedStakeHistory::new(history, current_epoch);
+        (0..=MAX_ENTRIES as u64).find(|epochs| {
+            let epoch = current_epoch.saturating_add(*epochs);
+            self.stake(epoch.to_le_bytes(), &history, new_rate_activation_epoch) == 0
+        })
+    }
+
     // returned tuple is (effective, activating) stake
     fn stake_and_activating<T: StakeHistoryGetEntry>(
         &self,
@@ -160,27 +262,29 @@ impl Delegation {
         history: &T,
         new_rate_activation_epoch: Option<Epoch>,
     ) -> (u64, u64) {
-        let delegated_stake = self.stake;
+        let delegated_stake = self.delegated_stake();
+        let target_epoch = bytes_to_u64(target_epoch);
+        let activation_epoch = self.activation_epoch();
 
         if self.is_bootstrap() {
             // fully effective immediately
-            (bytes_to_u64(delegated_stake), 0)
+            (delegated_stake, 0)
         } else if self.activation_epoch == self.deactivation_epoch {
             // activated but instantly deactivated; no stake at all regardless of target_epoch
             // this must be after the bootstrap check and before all-is-activating check
             (0, 0)
-        } else if target_epoch == self.activation_epoch {
+        } else if target_epoch == activation_epoch {
             // all is activating
-            (0, bytes_to_u64(delegated_stake))
-        } else if target_epoch < self.activation_epoch {
+            (0, delegated_stake)
+        } else if target_epoch < activation_epoch {
             // not yet enabled
             (0, 0)
         } else if let Some((history, mut prev_epoch, mut prev_cluster_stake)) = history
-            .get_entry(bytes_to_u64(self.activation_epoch))
+            .get_entry(activation_epoch)
             .map(|cluster_stake_at_activation_epoch| {
                 (
                     history,
-                    self.activation_epoch,
+                    activation_epoch,
                     cluster_stake_at_activation_epoch,
                 )
             })
@@ -192,41 +296,39 @@ impl Delegation {
             let mut current_epoch;
             let mut current_effective_stake = 0;
             loop {
-                current_epoch = bytes_to_u64(prev_epoch) + 1;
+                current_epoch = prev_epoch + 1;
                 // if there is no activating stake at prev epoch, we should have been
                 // fully effective at this moment
-                if bytes_to_u64(prev_cluster_stake.activating) == 0 {
+                if prev_cluster_stake.activating() == 0 {
                     break;
                 }
 
                 // how much of the growth in stake this account is
                 //  entitled to take
                 let remaining_activating_stake =
-                    u64::from_le_bytes(delegated_stake) - current_effective_stake;
+                    delegated_stake - current_effective_stake;
                 let weight = remaining_activating_stake as f64
-                    / bytes_to_u64(prev_cluster_stake.activating) as f64;
+                    / prev_cluster_stake.activating() as f64;
                 let warmup_cooldown_rate =
                     warmup_cooldown_rate(current_epoch.to_le_bytes(), new_rate_activation_epoch);
 
                 // portion of newly effective cluster stake I'm entitled to at current epoch
                 let newly_effective_cluster_stake =
-                    bytes_to_u64(prev_cluster_stake.effective) as f64 * warmup_cooldown_rate;
+                    prev_cluster_stake.effective() as f64 * warmup_cooldown_rate;
                 let newly_effective_stake =
                     ((weight * newly_effective_cluster_stake) as u64).max(1);
 
                 current_effective_stake += newly_effective_stake;
-                if current_effective_stake >= bytes_to_u64(delegated_stake) {
-                    current_effective_stake = bytes_to_u64(delegated_stake);
+                if current_effective_stake >= delegated_stake {
+                    current_effective_stake = delegated_stake;
                     break;
                 }
 
-                if current_epoch >= bytes_to_u64(target_epoch)
-                    || current_epoch >= bytes_to_u64(self.deactivation_epoch)
-                {
+                if current_epoch >= target_epoch || current_epoch >= self.deactivation_epoch() {
                     break;
                 }
                 if let Some(current_cluster_stake) = history.get_entry(current_epoch) {
-                    prev_epoch = current_epoch.to_le_bytes();
+                    prev_epoch = current_epoch;
                     prev_cluster_stake = current_cluster_stake;
                 } else {
                     break;
@@ -235,11 +337,11 @@ impl Delegation {
 
             (
                 current_effective_stake,
-                bytes_to_u64(delegated_stake) - current_effective_stake,
+                delegated_stake - current_effective_stake,
             )
         } else {
             // no history or I've dropped out of history, so assume fully effective
-            (bytes_to_u64(delegated_stake), 0)
+            (delegated_stake, 0)
         }
     }
 
@@ -278,3 +380,231 @@ impl Default for Delegation {
         }
     }
 }
+
+impl fmt::Debug for Delegation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[allow(deprecated)]
+        let warmup_cooldown_rate = f64::from_le_bytes(self.warmup_cooldown_rate);
+        f.debug_struct("Delegation")
+            .field("voter_pubkey", &Base58(&self.voter_pubkey))
+            .field("stake", &self.delegated_stake())
+            .field("activation_epoch", &self.activation_epoch())
+            .field("deactivation_epoch", &self.deactivation_epoch())
+            .field("warmup_cooldown_rate", &warmup_cooldown_rate)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH, state::StakeHistory,
+        test_utils::EpochSimulation,
+    };
+
+    fn bootstrap(stake: u64) -> Delegation {
+        Delegation::new(&[1; 32], stake, u64::MAX)
+    }
+
+    fn status<T: StakeHistoryGetEntry>(
+        delegation: &Delegation,
+        epoch: u64,
+        history: &T,
+    ) -> (u64, u64, u64) {
+        let status = delegation.stake_activating_and_deactivating(
+            epoch.to_le_bytes(),
+            history,
+            PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+        );
+        (status.effective, status.activating, status.deactivating)
+    }
+
+    #[test]
+    fn test_bootstrap_is_fully_effective() {
+        let delegation = bootstrap(1_000);
+        assert!(delegation.is_bootstrap());
+
+        // warmup history is never consulted, not even for the epoch it was created in
+        let mut history = StakeHistory::default();
+        history.add(
+            0,
+            StakeHistoryEntry {
+                effective: 0u64.to_le_bytes(),
+                activating: 1_000u64.to_le_bytes(),
+                deactivating: [0; 8],
+            },
+        );
+        for epoch in [0, 1, 100, u64::MAX - 1] {
+            assert_eq!(status(&delegation, epoch, &history), (1_000, 0, 0));
+            assert_eq!(
+                status(&delegation, epoch, &StakeHistory::default()),
+                (1_000, 0, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_bootstrap_cools_down() {
+        let mut delegation = bootstrap(1_000);
+        delegation.set_deactivation_epoch(5);
+
+        let mut history = StakeHistory::default();
+        history.add(
+            5,
+            StakeHistoryEntry {
+                effective: 1_000u64.to_le_bytes(),
+                activating: [0; 8],
+                deactivating: 1_000u64.to_le_bytes(),
+            },
+        );
+        assert_eq!(status(&delegation, 4, &history), (1_000, 0, 0));
+        assert_eq!(status(&delegation, 5, &history), (1_000, 0, 1_000));
+        // 9% of the 1k effective cluster stake cools down, all of it ours
+        assert_eq!(status(&delegation, 6, &history), (910, 0, 910));
+        // dropped out of history: presumed fully inactive
+        assert_eq!(status(&delegation, 6, &StakeHistory::default()), (0, 0, 0));
+    }
+
+    fn history_until(
+        epoch: u64,
+        effective: u64,
+        activating: u64,
+        deactivating: u64,
+    ) -> StakeHistory {
+        let mut history = StakeHistory::default();
+        history.add(
+            epoch,
+            StakeHistoryEntry {
+                effective: effective.to_le_bytes(),
+                activating: activating.to_le_bytes(),
+                deactivating: deactivating.to_le_bytes(),
+            },
+        );
+        history
+    }
+
+    #[test]
+    fn test_epochs_until_fully_active() {
+        let rate = PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH;
+        let delegation = Delegation::new(&[1; 32], 1_000, 10);
+
+        // all of the cluster's warmup is ours: 900, 90, 9 and then the 1 minimum
+        let history = history_until(9, 10_000, 1_000, 0);
+        assert_eq!(
+            delegation.epochs_until_fully_active(10, &history, rate),
+            Some(4)
+        );
+        assert_eq!(
+            delegation.epochs_until_fully_active(14, &history, rate),
+            Some(0)
+        );
+
+        // enough cluster stake to warm up in a single epoch
+        let history = history_until(9, 100_000, 1_000, 0);
+        assert_eq!(
+            delegation.epochs_until_fully_active(10, &history, rate),
+            Some(1)
+        );
+
+        let mut deactivated = delegation;
+        deactivated.set_deactivation_epoch(12);
+        assert_eq!(
+            deactivated.epochs_until_fully_active(12, &history, rate),
+            None
+        );
+    }
+
+    #[test]
+    fn test_epochs_until_fully_inactive() {
+        let rate = PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH;
+        let mut delegation = Delegation::new(&[1; 32], 1_000, 0);
+
+        let history = history_until(9, 10_000, 0, 1_000);
+        assert_eq!(
+            delegation.epochs_until_fully_inactive(10, &history, rate),
+            None
+        );
+
+        delegation.set_deactivation_epoch(10);
+        assert_eq!(
+            delegation.epochs_until_fully_inactive(10, &history, rate),
+            Some(4)
+        );
+        assert_eq!(
+            delegation.epochs_until_fully_inactive(14, &history, rate),
+            Some(0)
+        );
+
+        let history = history_until(9, 100_000, 0, 1_000);
+        assert_eq!(
+            delegation.epochs_until_fully_inactive(10, &history, rate),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_status_history_entry_round_trip() {
+        let status = StakeActivationStatus {
+            effective: 1,
+            activating: 2,
+            deactivating: u64::MAX,
+        };
+        let entry = StakeHistoryEntry::from(status);
+        assert_eq!(entry.deactivating, u64::MAX.to_le_bytes());
+        assert_eq!(StakeActivationStatus::from(entry), status);
+    }
+
+    #[test]
+    fn test_simulated_warmup_is_shared() {
+        let mut simulation = EpochSimulation::new(
+            0,
+            std::vec![
+                bootstrap(10_000),
+                Delegation::new(&[2; 32], 1_000, 0),
+                Delegation::new(&[3; 32], 3_000, 0),
+            ],
+        );
+
+        // 9% of the 10k effective warms up each epoch, split by remaining activating
+        // stake, so both delegations finish together
+        for (epoch, small, large) in [(0, 0, 0), (1, 225, 675), (2, 470, 1_410), (3, 737, 2_211)] {
+            simulation.advance_to(epoch);
+            assert_eq!(
+                simulation.status(1),
+                StakeActivationStatus::with_effective_and_activating(small, 1_000 - small)
+            );
+            assert_eq!(
+                simulation.status(2),
+                StakeActivationStatus::with_effective_and_activating(large, 3_000 - large)
+            );
+        }
+        simulation.advance();
+        assert_eq!(
+            simulation.cluster(),
+            StakeActivationStatus::with_effective(14_000)
+        );
+    }
+
+    #[test]
+    fn test_simulated_cooldown_shrinks() {
+        let mut deactivating = bootstrap(4_000);
+        deactivating.set_deactivation_epoch(0);
+        let mut simulation = EpochSimulation::new(0, std::vec![bootstrap(10_000), deactivating]);
+
+        // each epoch cools 9% of what was effective cluster-wide the epoch before, a
+        // shrinking amount as this delegation's share leaves
+        for (epoch, effective) in [(0, 4_000), (1, 2_740), (2, 1_594), (3, 551), (4, 0)] {
+            simulation.advance_to(epoch);
+            assert_eq!(
+                simulation.status(1),
+                StakeActivationStatus::with_deactivating(effective)
+            );
+        }
+        simulation.advance_to(10);
+        assert_eq!(
+            simulation.cluster(),
+            StakeActivationStatus::with_effective(10_000)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! The two vote-history checks behind `DeactivateDelinquent`, on the vote account's
+//! `(epoch, credits, prev_credits)` history as in `VoteState::epoch_credits`: the
+//! reference vote account proves the cluster has been voting, and the delinquent one
+//! has to have stopped for `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs.
+
+use crate::consts::MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION;
+
+/// Whether the reference vote account earned credits in each of the last
+/// `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs, up to and including
+/// `current_epoch`
+pub fn acceptable_reference_epoch_credits(
+    epoch_credits: &[(u64, u64, u64)],
+    current_epoch: u64,
+) -> bool {
+    let Some(epoch_index) = epoch_credits
+        .len()
+        .checked_sub(MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION)
+    else {
+        return false;
+    };
+    let mut epoch = current_epoch;
+    for (vote_epoch, ..) in epoch_credits[epoch_index..].iter().rev() {
+        if *vote_epoch != epoch {
+            return false;
+        }
+        epoch = epoch.saturating_sub(1);
+    }
+    true
+}
+
+/// Whether the vote account last earned credits at least
+/// `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs before `current_epoch`. One
+/// that never voted is always eligible.
+pub fn eligible_for_deactivate_delinquent(
+    epoch_credits: &[(u64, u64, u64)],
+    current_epoch: u64,
+) -> bool {
+    match epoch_credits.last() {
+        None => true,
+        Some((epoch, ..)) => current_epoch
+            .checked_sub(MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION as u64)
+            .is_some_and(|minimum_epoch| *epoch <= minimum_epoch),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WINDOW: u64 = MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION as u64;
+
+    /// One `epoch_credits` entry for each epoch in `epochs`
+    fn voted_in(epochs: impl IntoIterator<Item = u64>) -> Vec<(u64, u64, u64)> {
+        epochs
+            .into_iter()
+            .map(|epoch| (epoch, epoch * 10 + 10, epoch * 10))
+            .collect()
+    }
+
+    #[test]
+    fn test_acceptable_reference_epoch_credits() {
+        let current_epoch = 100;
+
+        assert!(!acceptable_reference_epoch_credits(&[], current_epoch));
+        // one epoch short of the window
+        assert!(!acceptable_reference_epoch_credits(
+            &voted_in(current_epoch - WINDOW + 2..=current_epoch),
+            current_epoch
+        ));
+        // exactly the window, and the window after older votes
+        assert!(acceptable_reference_epoch_credits(
+            &voted_in(current_epoch - WINDOW + 1..=current_epoch),
+            current_epoch
+        ));
+        assert!(acceptable_reference_epoch_credits(
+            &voted_in(current_epoch - WINDOW - 10..=current_epoch),
+            current_epoch
+        ));
+        // the last vote has to be in the current epoch
+        assert!(!acceptable_reference_epoch_credits(
+            &voted_in(current_epoch - WINDOW..current_epoch),
+            current_epoch
+        ));
+        // a missed epoch inside the window
+        let mut gap = voted_in(current_epoch - WINDOW..=current_epoch);
+        gap.remove(gap.len() - 2);
+        assert!(!acceptable_reference_epoch_credits(&gap, current_epoch));
+    }
+
+    #[test]
+    fn test_eligible_for_deactivate_delinquent() {
+        let current_epoch = 100;
+
+        assert!(eligible_for_deactivate_delinquent(&[], current_epoch));
+        assert!(eligible_for_deactivate_delinquent(
+            &voted_in([current_epoch - WINDOW]),
+            current_epoch
+        ));
+        assert!(!eligible_for_deactivate_delinquent(
+            &voted_in([current_epoch - WINDOW + 1]),
+            current_epoch
+        ));
+        assert!(!eligible_for_deactivate_delinquent(
+            &voted_in(0..=current_epoch),
+            current_epoch
+        ));
+        // too early in the cluster's life for anything to have been delinquent that long
+        assert!(!eligible_for_deactivate_delinquent(
+            &voted_in([0]),
+            WINDOW - 1
+        ));
+    }
+}
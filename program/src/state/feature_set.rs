@@ -0,0 +1,90 @@
+//! Runtime features whose activation changes what the processors accept. The program
+//! builds in mainnet's current settings; forks and historical-replay tools pick others
+//! through `SysvarProvider::features`, for instance by wrapping their provider in
+//! `WithFeatures`, without patching the crate.
+
+use crate::consts::{
+    FEATURE_STAKE_RAISE_MINIMUM_DELEGATION_TO_1_SOL, LAMPORTS_PER_SOL,
+    PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeatureSet {
+    /// Epoch from which stake warms up and cools down at the new 9% rate instead of
+    /// 25%, or `None` if the reduced rate was never activated
+    pub new_warmup_cooldown_rate_epoch: Option<u64>,
+    /// `stake_raise_minimum_delegation_to_1_sol`: delegations need 1 SOL instead of
+    /// 1 lamport
+    pub raise_minimum_delegation_to_1_sol: bool,
+    /// Whether `Redelegate` may run. It was never enabled on mainnet and this program
+    /// has no processor for it, so the entrypoint rejects it regardless; a fork that
+    /// enables it has to supply one.
+    pub redelegation_enabled: bool,
+}
+
+impl FeatureSet {
+    /// What mainnet runs today
+    pub const MAINNET: Self = Self {
+        new_warmup_cooldown_rate_epoch: match PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH {
+            Some(epoch) => Some(u64::from_le_bytes(epoch)),
+            None => None,
+        },
+        raise_minimum_delegation_to_1_sol: FEATURE_STAKE_RAISE_MINIMUM_DELEGATION_TO_1_SOL,
+        redelegation_enabled: false,
+    };
+
+    /// The smallest amount of stake a delegation may hold
+    #[inline(always)]
+    pub const fn minimum_delegation(&self) -> u64 {
+        if self.raise_minimum_delegation_to_1_sol {
+            const MINIMUM_DELEGATION_SOL: u64 = 1;
+            MINIMUM_DELEGATION_SOL * LAMPORTS_PER_SOL
+        } else {
+            1
+        }
+    }
+
+    /// `new_warmup_cooldown_rate_epoch` in the form `Delegation` takes it
+    #[inline(always)]
+    pub const fn warmup_cooldown_rate_epoch(&self) -> Option<[u8; 8]> {
+        match self.new_warmup_cooldown_rate_epoch {
+            Some(epoch) => Some(epoch.to_le_bytes()),
+            None => None,
+        }
+    }
+}
+
+impl Default for FeatureSet {
+    fn default() -> Self {
+        Self::MAINNET
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mainnet_matches_consts() {
+        let features = FeatureSet::default();
+        assert_eq!(features, FeatureSet::MAINNET);
+        assert_eq!(
+            features.warmup_cooldown_rate_epoch(),
+            PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH
+        );
+        assert_eq!(features.minimum_delegation(), 1);
+        assert!(!features.redelegation_enabled);
+
+        let raised = FeatureSet {
+            raise_minimum_delegation_to_1_sol: true,
+            ..FeatureSet::MAINNET
+        };
+        assert_eq!(raised.minimum_delegation(), LAMPORTS_PER_SOL);
+
+        let legacy = FeatureSet {
+            new_warmup_cooldown_rate_epoch: None,
+            ..FeatureSet::MAINNET
+        };
+        assert_eq!(legacy.warmup_cooldown_rate_epoch(), None);
+    }
+}
@@ -0,0 +1,132 @@
+//! Byte offsets of the fields of a serialized `StakeStateV2`, for building
+//! `getProgramAccounts` memcmp filters and reading fields straight out of raw
+//! account data without deserializing the whole state.
+//!
+//! Every offset is from the start of the account data. The `Meta` fields are valid
+//! for both `Initialized` and `Stake` accounts; the delegation fields only hold
+//! meaningful values when the tag is `Stake`. All integers are little-endian.
+
+use core::mem::size_of;
+
+use pinocchio::pubkey::Pubkey;
+
+use super::{Authorized, Delegation, Lockup, Meta, Stake, StakeStateV2};
+
+/// Offset of the `u32` state tag (see `StakeStateTag`)
+pub const TAG_OFFSET: usize = 0;
+pub const TAG_LEN: usize = size_of::<u32>();
+
+/// Offset of `Meta`, shared by `Initialized` and `Stake` accounts
+pub const META_OFFSET: usize = TAG_OFFSET + TAG_LEN;
+pub const RENT_EXEMPT_RESERVE_OFFSET: usize = META_OFFSET;
+
+pub const AUTHORIZED_OFFSET: usize = RENT_EXEMPT_RESERVE_OFFSET + size_of::<u64>();
+pub const STAKER_OFFSET: usize = AUTHORIZED_OFFSET;
+pub const WITHDRAWER_OFFSET: usize = STAKER_OFFSET + size_of::<Pubkey>();
+
+pub const LOCKUP_OFFSET: usize = AUTHORIZED_OFFSET + size_of::<Authorized>();
+pub const LOCKUP_UNIX_TIMESTAMP_OFFSET: usize = LOCKUP_OFFSET;
+pub const LOCKUP_EPOCH_OFFSET: usize = LOCKUP_UNIX_TIMESTAMP_OFFSET + size_of::<i64>();
+pub const CUSTODIAN_OFFSET: usize = LOCKUP_EPOCH_OFFSET + size_of::<u64>();
+
+/// Offset of `Stake`, only present when the tag is `Stake`
+pub const STAKE_OFFSET: usize = META_OFFSET + size_of::<Meta>();
+pub const DELEGATION_OFFSET: usize = STAKE_OFFSET;
+pub const VOTER_PUBKEY_OFFSET: usize = DELEGATION_OFFSET;
+pub const DELEGATED_STAKE_OFFSET: usize = VOTER_PUBKEY_OFFSET + size_of::<Pubkey>();
+pub const ACTIVATION_EPOCH_OFFSET: usize = DELEGATED_STAKE_OFFSET + size_of::<u64>();
+pub const DEACTIVATION_EPOCH_OFFSET: usize = ACTIVATION_EPOCH_OFFSET + size_of::<u64>();
+pub const WARMUP_COOLDOWN_RATE_OFFSET: usize = DEACTIVATION_EPOCH_OFFSET + size_of::<u64>();
+pub const CREDITS_OBSERVED_OFFSET: usize = DELEGATION_OFFSET + size_of::<Delegation>();
+
+/// Offset of the `StakeFlags` byte that follows `Stake`
+pub const STAKE_FLAGS_OFFSET: usize = STAKE_OFFSET + size_of::<Stake>();
+
+// the layout is part of the on-chain ABI, pin it so a field reorder fails to compile
+const _: () = assert!(size_of::<Lockup>() == 48);
+const _: () = assert!(CUSTODIAN_OFFSET + size_of::<Pubkey>() == STAKE_OFFSET);
+const _: () = assert!(CREDITS_OBSERVED_OFFSET + size_of::<u64>() == STAKE_FLAGS_OFFSET);
+const _: () = assert!(STAKE_FLAGS_OFFSET < StakeStateV2::size_of());
+// `StakeStateV2::reset` zeroes the in-memory value as the whole account state
+const _: () = assert!(size_of::<StakeStateV2>() == StakeStateV2::size_of());
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::{StakeFlags, StakeStateTag};
+
+    #[test]
+    fn test_offsets_match_native_layout() {
+        assert_eq!(STAKER_OFFSET, 12);
+        assert_eq!(WITHDRAWER_OFFSET, 44);
+        assert_eq!(CUSTODIAN_OFFSET, 92);
+        assert_eq!(VOTER_PUBKEY_OFFSET, 124);
+        assert_eq!(DELEGATED_STAKE_OFFSET, 156);
+        assert_eq!(ACTIVATION_EPOCH_OFFSET, 164);
+        assert_eq!(DEACTIVATION_EPOCH_OFFSET, 172);
+        assert_eq!(CREDITS_OBSERVED_OFFSET, 188);
+        assert_eq!(STAKE_FLAGS_OFFSET, 196);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_offsets_match_struct_fields() {
+        let state = StakeStateV2::Stake(
+            Meta {
+                rent_exempt_reserve: 1u64.to_le_bytes(),
+                authorized: Authorized {
+                    staker: [2; 32],
+                    withdrawer: [3; 32],
+                },
+                lockup: Lockup {
+                    unix_timestamp: 4i64.to_le_bytes(),
+                    epoch: 5u64.to_le_bytes(),
+                    custodian: [6; 32],
+                },
+            },
+            Stake {
+                delegation: Delegation {
+                    voter_pubkey: [7; 32],
+                    stake: 8u64.to_le_bytes(),
+                    activation_epoch: 9u64.to_le_bytes(),
+                    deactivation_epoch: 10u64.to_le_bytes(),
+                    warmup_cooldown_rate: 0.25f64.to_le_bytes(),
+                },
+                credits_observed: 11u64.to_le_bytes(),
+            },
+            StakeFlags::empty(),
+        );
+        let data = unsafe {
+            core::slice::from_raw_parts(
+                &state as *const StakeStateV2 as *const u8,
+                StakeStateV2::size_of(),
+            )
+        };
+
+        assert_eq!(StakeStateV2::peek_tag(data), Ok(StakeStateTag::Stake));
+        let u64_at = |offset: usize| {
+            u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+        };
+
+        assert_eq!(u64_at(RENT_EXEMPT_RESERVE_OFFSET), 1);
+        assert_eq!(data[STAKER_OFFSET..STAKER_OFFSET + 32], [2; 32]);
+        assert_eq!(data[WITHDRAWER_OFFSET..WITHDRAWER_OFFSET + 32], [3; 32]);
+        assert_eq!(u64_at(LOCKUP_UNIX_TIMESTAMP_OFFSET), 4);
+        assert_eq!(u64_at(LOCKUP_EPOCH_OFFSET), 5);
+        assert_eq!(data[CUSTODIAN_OFFSET..CUSTODIAN_OFFSET + 32], [6; 32]);
+        assert_eq!(data[VOTER_PUBKEY_OFFSET..VOTER_PUBKEY_OFFSET + 32], [7; 32]);
+        assert_eq!(u64_at(DELEGATED_STAKE_OFFSET), 8);
+        assert_eq!(u64_at(ACTIVATION_EPOCH_OFFSET), 9);
+        assert_eq!(u64_at(DEACTIVATION_EPOCH_OFFSET), 10);
+        assert_eq!(
+            f64::from_le_bytes(
+                data[WARMUP_COOLDOWN_RATE_OFFSET..WARMUP_COOLDOWN_RATE_OFFSET + 8]
+                    .try_into()
+                    .unwrap()
+            ),
+            0.25
+        );
+        assert_eq!(u64_at(CREDITS_OBSERVED_OFFSET), 11);
+        assert_eq!(data[STAKE_FLAGS_OFFSET], 0);
+    }
+}
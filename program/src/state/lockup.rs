@@ -1,22 +1,57 @@
+use core::fmt;
+
 use pinocchio::{pubkey::Pubkey, sysvars::clock::Clock};
 
-use super::{Epoch, UnixTimestamp};
+use super::{pubkey_eq, utils::Base58, Epoch, UnixTimestamp};
 
 #[repr(C)]
-#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct Lockup {
     /// UnixTimestamp at which this stake will allow withdrawal, unless the
     ///   transaction is signed by the custodian
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::i64_le"))]
     pub unix_timestamp: UnixTimestamp, //i64
     /// epoch height at which this stake will allow withdrawal, unless the
     ///   transaction is signed by the custodian
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::u64_le"))]
     pub epoch: Epoch,
     /// custodian signature on a transaction exempts the operation from
     ///  lockup constraints
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::pubkey"))]
     pub custodian: Pubkey,
 }
 
+impl fmt::Debug for Lockup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lockup")
+            .field("unix_timestamp", &self.unix_timestamp())
+            .field("epoch", &self.epoch())
+            .field("custodian", &Base58(&self.custodian))
+            .finish()
+    }
+}
+
 impl Lockup {
+    /// No lockup: zero timestamp, epoch and custodian. Same as `Lockup::default()`, but
+    /// usable in `const` and `static` items.
+    pub const DEFAULT: Self = Self::new(0, 0, [0; 32]);
+
+    pub const fn new(unix_timestamp: i64, epoch: u64, custodian: Pubkey) -> Self {
+        Self {
+            unix_timestamp: unix_timestamp.to_le_bytes(),
+            epoch: epoch.to_le_bytes(),
+            custodian,
+        }
+    }
+
+    /// `Lockup::DEFAULT` as a function, for call sites that read better with one
+    #[inline(always)]
+    pub const fn default_const() -> Self {
+        Self::DEFAULT
+    }
+
     #[inline(always)]
     pub fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
         self.unix_timestamp = unix_timestamp.to_le_bytes();
@@ -37,12 +72,21 @@ impl Lockup {
         u64::from_le_bytes(self.epoch)
     }
 
+    #[inline(always)]
+    pub fn set_custodian(&mut self, custodian: &Pubkey) {
+        self.custodian = *custodian;
+    }
+
+    #[inline(always)]
+    pub fn custodian(&self) -> &Pubkey {
+        &self.custodian
+    }
+
     pub fn is_in_force(&self, clock: &Clock, custodian: Option<&Pubkey>) -> bool {
-        if custodian == Some(&self.custodian) {
+        if custodian.is_some_and(|custodian| pubkey_eq(custodian, &self.custodian)) {
             return false;
         }
 
-        i64::from_le_bytes(self.unix_timestamp) > clock.unix_timestamp
-            || u64::from_le_bytes(self.epoch) > clock.epoch
+        self.unix_timestamp() > clock.unix_timestamp || self.epoch() > clock.epoch
     }
 }
@@ -1,4 +1,4 @@
-use crate::{consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH, error::StakeError};
+use crate::{error::StakeError, helpers::get_stake_status};
 use pinocchio::{
     program_error::ProgramError,
     sysvars::clock::{Clock, Epoch},
@@ -6,7 +6,10 @@ use pinocchio::{
 };
 use pinocchio_log::log;
 
-use super::{checked_add, Delegation, Meta, Stake, StakeFlags, StakeHistoryGetEntry, StakeStateV2};
+use super::{
+    checked_add, Delegation, Meta, Stake, StakeActivationStatus, StakeFlags, StakeHistoryGetEntry,
+    StakeStateV2,
+};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum MergeKind {
@@ -42,16 +45,13 @@ impl MergeKind {
             StakeStateV2::Stake(meta, stake, stake_flags) => {
                 // stake must not be in a transient state, Transient here meaning
                 // activating or deactivating with non-zero effective stake
-                let epoch_bytes = clock.epoch.to_le_bytes(); // converts u64 to [u8; 8]
-                let status = stake.delegation.stake_activating_and_deactivating(
-                    epoch_bytes,
-                    stake_history,
-                    PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
-                );
+                let status = get_stake_status(stake, clock.epoch, stake_history);
 
-                let effective = u64::from_le_bytes(status.effective);
-                let activating = u64::from_le_bytes(status.activating);
-                let deactivating = u64::from_le_bytes(status.deactivating);
+                let StakeActivationStatus {
+                    effective,
+                    activating,
+                    deactivating,
+                } = status;
 
                 match (effective, activating, deactivating) {
                     (0, 0, 0) => Ok(Self::Inactive(*meta, stake_lamports, *stake_flags)),
@@ -92,8 +92,8 @@ impl MergeKind {
         if stake.voter_pubkey != source.voter_pubkey {
             log!("Unable to merge due to voter mismatch");
             Err(StakeError::MergeMismatch.into())
-        } else if u64::from_le_bytes(stake.deactivation_epoch) == Epoch::MAX
-            && u64::from_le_bytes(source.deactivation_epoch) == Epoch::MAX
+        } else if stake.deactivation_epoch() == Epoch::MAX
+            && source.deactivation_epoch() == Epoch::MAX
         {
             Ok(())
         } else {
@@ -225,14 +225,16 @@ pub(crate) fn stake_weighted_credits_observed(
     absorbed_credits_observed: [u8; 8],
 ) -> Option<u64> {
     if stake.credits_observed == absorbed_credits_observed {
-        Some(u64::from_le_bytes(stake.credits_observed))
+        Some(stake.credits_observed())
     } else {
         let total_stake = u128::from(
-            u64::from_le_bytes(stake.delegation.stake)
+            stake
+                .delegation
+                .delegated_stake()
                 .checked_add(u64::from_le_bytes(absorbed_lamports))?,
         );
-        let stake_weighted_credits = u128::from(u64::from_le_bytes(stake.credits_observed))
-            .checked_mul(u128::from(u64::from_le_bytes(stake.delegation.stake)))?;
+        let stake_weighted_credits = u128::from(stake.credits_observed())
+            .checked_mul(u128::from(stake.delegation.delegated_stake()))?;
         let absorbed_weighted_credits =
             u128::from(u64::from_le_bytes(absorbed_credits_observed))
                 .checked_mul(u128::from(u64::from_le_bytes(absorbed_lamports)))?;
@@ -1,12 +1,17 @@
-use pinocchio::sysvars::clock::Clock;
+use core::fmt;
+
+use pinocchio::{pubkey::Pubkey, sysvars::clock::Clock};
 
 use crate::{error::InstructionError, instruction::LockupArgs};
 
 use super::{Authorized, Lockup};
 
 #[repr(C)]
-#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct Meta {
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::u64_le"))]
     pub rent_exempt_reserve: [u8; 8], // u64
     pub authorized: Authorized,
     pub lockup: Lockup,
@@ -17,7 +22,37 @@ pub struct SetLockupSignerArgs {
     pub has_withdrawer_signer: bool,
 }
 
+impl fmt::Debug for Meta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Meta")
+            .field("rent_exempt_reserve", &self.rent_exempt_reserve())
+            .field("authorized", &self.authorized)
+            .field("lockup", &self.lockup)
+            .finish()
+    }
+}
+
 impl Meta {
+    /// No reserve, zeroed authorities and no lockup. Same as `Meta::default()`, but
+    /// usable in `const` and `static` items.
+    pub const DEFAULT: Self = Self::new(0, Authorized::DEFAULT, Lockup::DEFAULT);
+
+    pub const fn new(rent_exempt_reserve: u64, authorized: Authorized, lockup: Lockup) -> Self {
+        Self {
+            rent_exempt_reserve: rent_exempt_reserve.to_le_bytes(),
+            authorized,
+            lockup,
+        }
+    }
+
+    /// Meta with `authority` as both staker and withdrawer, no lockup and no rent reserve
+    pub const fn auto(authority: &Pubkey) -> Self {
+        Self {
+            authorized: Authorized::auto(authority),
+            ..Meta::DEFAULT
+        }
+    }
+
     #[inline(always)]
     pub fn set_rent_exempt_reserve(&mut self, rent_exempt_reserve: u64) {
         self.rent_exempt_reserve = rent_exempt_reserve.to_le_bytes();
@@ -37,22 +72,59 @@ impl Meta {
         // post-stake_program_v4 behavior:
         // * custodian can update the lockup while in force
         // * withdraw authority can set a new lockup
-        if self.lockup.is_in_force(clock, None) {
+        let in_force = self.lockup.is_in_force(clock, None);
+        debug_log!(
+            "set_lockup: in force, custodian signed, withdrawer signed",
+            in_force,
+            signer_args.has_custodian_signer,
+            signer_args.has_withdrawer_signer
+        );
+        if in_force {
             if !signer_args.has_custodian_signer {
                 return Err(InstructionError::MissingRequiredSignature);
             }
         } else if !signer_args.has_withdrawer_signer {
             return Err(InstructionError::MissingRequiredSignature);
         }
-        if let Some(unix_timestamp) = lockup.unix_timestamp {
-            self.lockup.unix_timestamp = unix_timestamp;
-        }
-        if let Some(epoch) = lockup.epoch {
-            self.lockup.epoch = epoch;
-        }
-        if let Some(custodian) = lockup.custodian {
-            self.lockup.custodian = custodian;
-        }
+        self.lockup = lockup.merge(&self.lockup);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Authorized, Lockup, Meta};
+
+    #[test]
+    fn test_meta_constructors() {
+        let authority = [7u8; 32];
+
+        let meta = Meta::auto(&authority);
+        assert_eq!(meta.authorized, Authorized::auto(&authority));
+        assert_eq!(meta.lockup, Lockup::default());
+        assert_eq!(meta.rent_exempt_reserve(), 0);
+
+        let meta = Meta::new(2_282_880, Authorized::auto(&authority), Lockup::default());
+        assert_eq!(meta.rent_exempt_reserve, 2_282_880u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_const_defaults() {
+        // built at compile time, the way a fixture or another program's constant would be
+        const LOCKUP: Lockup = Lockup::new(-1, 10, [3; 32]);
+        const META: Meta = Meta::new(1_000, Authorized::auto(&[7; 32]), LOCKUP);
+        static AUTO: Meta = Meta::auto(&[7; 32]);
+
+        assert_eq!(Meta::DEFAULT, Meta::default());
+        assert_eq!(Lockup::DEFAULT, Lockup::default());
+        assert_eq!(Lockup::default_const(), Lockup::default());
+        assert_eq!(Authorized::DEFAULT, Authorized::default());
+
+        assert_eq!(LOCKUP.unix_timestamp(), -1);
+        assert_eq!(LOCKUP.epoch(), 10);
+        assert_eq!(LOCKUP.custodian(), &[3; 32]);
+        assert_eq!(META.rent_exempt_reserve(), 1_000);
+        assert_eq!(META.lockup, LOCKUP);
+        assert_eq!(AUTO, Meta::auto(&[7; 32]));
+    }
+}
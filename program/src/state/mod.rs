@@ -2,27 +2,41 @@
 pub mod authorized;
 pub mod authorized_checked_with_seed;
 pub mod delegation;
+pub mod delinquency;
+pub mod feature_set;
+pub mod layout;
 pub mod lockup;
 pub mod merge;
 pub mod meta;
+pub mod points;
 pub mod redelegate_state;
+#[cfg(feature = "serde")]
+pub mod serde_fields;
+pub mod signers;
 pub mod stake;
 pub mod stake_authorize;
 pub mod stake_flags;
 pub mod stake_history;
 pub mod stake_history_sysvar;
+#[cfg(feature = "stake-interface")]
+pub mod stake_interface;
+pub mod stake_state_v1;
 pub mod stake_state_v2;
+pub mod sysvar_provider;
 pub mod vote_state_v3;
 pub mod authorized_voters;
 pub mod utils;
 
 pub use authorized::*;
 pub use delegation::*;
+pub use delinquency::*;
+pub use feature_set::*;
 pub use vote_state_v3::*;
 pub use authorized_voters::*;
 pub use lockup::*;
 pub use merge::*;
 pub use meta::*;
+pub use points::*;
 pub use authorized_checked_with_seed::*;
 use pinocchio::{
     account_info::{ AccountInfo, Ref, RefMut },
@@ -34,11 +48,20 @@ pub use stake_authorize::*;
 pub use stake_flags::*;
 pub use stake_history::*;
 pub use stake_history_sysvar::*;
+pub use stake_state_v1::*;
 pub use stake_state_v2::*;
+pub use sysvar_provider::*;
 pub use utils::*;
 
-use crate::consts::VOTE_PROGRAM_ID;
+use crate::{
+    consts::{
+        VOTE_PROGRAM_ID, VOTE_STATE_VERSION_CURRENT, VOTE_STATE_VERSION_V0_23_5,
+        VOTE_STATE_VERSION_V1_14_11,
+    },
+    error::{AccountCheck, AccountError},
+};
 pub use redelegate_state::*;
+pub use signers::*;
 
 pub type Epoch = [u8; 8]; //u64
 pub type UnixTimestamp = [u8; 8]; //i64;
@@ -46,28 +69,20 @@ pub type UnixTimestamp = [u8; 8]; //i64;
 pub fn get_stake_state(
     stake_account_info: &AccountInfo
 ) -> Result<Ref<StakeStateV2>, ProgramError> {
-    if stake_account_info.is_owned_by(&crate::ID) {
+    if !stake_account_info.is_owned_by(&crate::ID) {
         return Err(ProgramError::InvalidAccountOwner);
     }
 
     StakeStateV2::from_account_info(stake_account_info)
 }
 
+/// Writes `new_state` to the account. Bytes the new state doesn't use are zeroed, so
+/// closing an account back to `Uninitialized` leaves no stale authorities behind.
 pub fn set_stake_state(
     stake_account_info: &AccountInfo,
     new_state: &StakeStateV2
 ) -> Result<(), ProgramError> {
-    let new_state_size = core::mem::size_of::<StakeStateV2>();
-    let data = stake_account_info.try_borrow_mut_data()?;
-    if data.len() < new_state_size {
-        return Err(ProgramError::AccountDataTooSmall);
-    }
-    let mut new_state_bytes = [0u8; core::mem::size_of::<StakeStateV2>()];
-    new_state_bytes.copy_from_slice(unsafe {
-        core::slice::from_raw_parts(new_state as *const StakeStateV2 as *const u8, new_state_size)
-    });
-    stake_account_info.try_borrow_mut_data()?.copy_from_slice(&new_state_bytes);
-    Ok(())
+    new_state.serialize(&mut stake_account_info.try_borrow_mut_data()?)
 }
 
 /// # Safety
@@ -87,43 +102,142 @@ pub unsafe fn get_stake_state_unchecked(
 pub fn try_get_stake_state_mut(
     stake_account_info: &AccountInfo
 ) -> Result<RefMut<StakeStateV2>, ProgramError> {
-    if stake_account_info.is_owned_by(&crate::ID) {
+    if !stake_account_info.is_owned_by(&crate::ID) {
         return Err(ProgramError::InvalidAccountOwner);
     }
 
     StakeStateV2::try_from_account_info_mut(stake_account_info)
 }
 
+/// Owner, size and state checks of `get_stake_state`, reporting which one failed for
+/// the account at `index` in the instruction
+pub(crate) fn get_stake_state_at(
+    stake_account_info: &AccountInfo,
+    index: usize,
+) -> Result<Ref<'_, StakeStateV2>, ProgramError> {
+    check_stake_account_at(stake_account_info, index)?;
+    StakeStateV2::from_account_info(stake_account_info)
+        .map_err(|e| AccountError::new(index, AccountCheck::State, e).into())
+}
+
+/// `try_get_stake_state_mut`, reporting which check failed for the account at `index`
+/// in the instruction
+pub(crate) fn try_get_stake_state_mut_at(
+    stake_account_info: &AccountInfo,
+    index: usize,
+) -> Result<RefMut<'_, StakeStateV2>, ProgramError> {
+    check_stake_account_at(stake_account_info, index)?;
+    StakeStateV2::try_from_account_info_mut(stake_account_info)
+        .map_err(|e| AccountError::new(index, AccountCheck::State, e).into())
+}
+
+fn check_stake_account_at(stake_account_info: &AccountInfo, index: usize) -> ProgramResult {
+    if !stake_account_info.is_owned_by(&crate::ID) {
+        return Err(AccountError::new(
+            index,
+            AccountCheck::Owner,
+            ProgramError::InvalidAccountOwner,
+        )
+        .into());
+    }
+    if stake_account_info.data_len() != StakeStateV2::size_of() {
+        return Err(AccountError::new(
+            index,
+            AccountCheck::Size,
+            ProgramError::InvalidAccountData,
+        )
+        .into());
+    }
+    Ok(())
+}
+
 // dont call this "move" because we have an instruction MoveLamports
+//
+// The destination can be any account (Withdraw sends to an arbitrary recipient), so
+// nothing here checks ownership; the runtime already forbids debiting unowned accounts.
 pub fn relocate_lamports(
     source_account_info: &AccountInfo,
     destination_account_info: &AccountInfo,
     lamports: u64
 ) -> ProgramResult {
-    {
-        let mut source_lamports = source_account_info.try_borrow_mut_lamports()?;
-        *source_lamports = source_lamports
-            .checked_sub(lamports)
-            .ok_or(ProgramError::InsufficientFunds)?;
+    if lamports == 0 {
+        return Ok(());
     }
 
-    {
-        let mut destination_lamports = destination_account_info.try_borrow_mut_lamports()?;
-        *destination_lamports = destination_lamports
-            .checked_add(lamports)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
+    // the same account passed twice would fail the second mutable borrow, and
+    // moving lamports onto itself is a no-op once the balance is known to cover it
+    if source_account_info.key() == destination_account_info.key() {
+        if source_account_info.lamports() < lamports {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        return Ok(());
     }
 
+    let mut source_lamports = source_account_info.try_borrow_mut_lamports()?;
+    let mut destination_lamports = destination_account_info.try_borrow_mut_lamports()?;
+
+    let (new_source_lamports, new_destination_lamports) =
+        checked_relocation(*source_lamports, *destination_lamports, lamports)?;
+
+    *source_lamports = new_source_lamports;
+    *destination_lamports = new_destination_lamports;
+
     Ok(())
 }
 
-pub fn get_vote_state(vote_account_info: &AccountInfo) -> Result<Ref<VoteState>, ProgramError> {
-    if vote_account_info.is_owned_by(&VOTE_PROGRAM_ID) {
+/// Drains the full balance of `source_account_info` into `destination_account_info`,
+/// returning the number of lamports moved.
+pub fn relocate_all_lamports(
+    source_account_info: &AccountInfo,
+    destination_account_info: &AccountInfo
+) -> Result<u64, ProgramError> {
+    let lamports = source_account_info.lamports();
+    relocate_lamports(source_account_info, destination_account_info, lamports)?;
+    Ok(lamports)
+}
+
+/// Computes the post-move `(source, destination)` balances without touching any account,
+/// failing before either side is written if the debit underflows or the credit overflows.
+pub fn checked_relocation(
+    source_lamports: u64,
+    destination_lamports: u64,
+    lamports: u64
+) -> Result<(u64, u64), ProgramError> {
+    let new_source_lamports = source_lamports
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let new_destination_lamports = destination_lamports
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok((new_source_lamports, new_destination_lamports))
+}
+
+/// Checks that `vote_account_info` is a vote account this program can read: owned by
+/// the vote program, large enough for a `VoteState` and tagged with a version it knows.
+/// Every processor that takes a vote account goes through this before reading it.
+pub fn validate_vote_account(vote_account_info: &AccountInfo) -> ProgramResult {
+    if !vote_account_info.is_owned_by(&VOTE_PROGRAM_ID) {
         return Err(ProgramError::IncorrectProgramId);
     }
 
-    let vote_state = VoteState::from_account_info(vote_account_info)?;
-    return Ok(vote_state);
+    let data = vote_account_info.try_borrow_data()?;
+    if data.len() < VoteState::size_of() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    match u32::from_le_bytes([data[0], data[1], data[2], data[3]]) {
+        VOTE_STATE_VERSION_V0_23_5 | VOTE_STATE_VERSION_V1_14_11 | VOTE_STATE_VERSION_CURRENT => {
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Credits the vote account has earned, as `VoteState::credits` reports them, read
+/// from the serialized account with `VoteState::credits_from_bytes`
+pub fn get_vote_credits(vote_account_info: &AccountInfo) -> Result<u64, ProgramError> {
+    validate_vote_account(vote_account_info)?;
+
+    VoteState::credits_from_bytes(&vote_account_info.try_borrow_data()?)
 }
 
 pub fn checked_add(a: [u8; 8], b: [u8; 8]) -> Result<[u8; 8], ProgramError> {
@@ -133,3 +247,77 @@ pub fn checked_add(a: [u8; 8], b: [u8; 8]) -> Result<[u8; 8], ProgramError> {
         .map(|result| result.to_le_bytes())
         .ok_or(ProgramError::InsufficientFunds)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{checked_relocation, validate_vote_account, VoteState};
+    use crate::{
+        consts::VOTE_PROGRAM_ID,
+        test_utils::{SerializedInput, TestAccount},
+    };
+    use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+
+    #[test]
+    fn test_checked_relocation() {
+        assert_eq!(checked_relocation(10, 5, 4), Ok((6, 9)));
+
+        // full-balance drain
+        assert_eq!(checked_relocation(10, 5, 10), Ok((0, 15)));
+
+        // zero amount leaves both sides untouched
+        assert_eq!(checked_relocation(10, 5, 0), Ok((10, 5)));
+    }
+
+    #[test]
+    fn test_checked_relocation_errors() {
+        assert_eq!(
+            checked_relocation(10, 5, 11),
+            Err(ProgramError::InsufficientFunds)
+        );
+        assert_eq!(
+            checked_relocation(10, u64::MAX - 5, 6),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn test_validate_vote_account() {
+        let vote_account = |owner, tag: u32, len| {
+            let mut data = vec![0; len];
+            data[..4].copy_from_slice(&tag.to_le_bytes());
+            TestAccount {
+                owner,
+                data,
+                ..TestAccount::readonly([7; 32])
+            }
+        };
+        let validate = |account| {
+            let mut input = SerializedInput::new(&[account]);
+            let [account]: [AccountInfo; 1] = input.accounts();
+            validate_vote_account(&account)
+        };
+
+        for tag in [0, 1, 2] {
+            assert_eq!(
+                validate(vote_account(VOTE_PROGRAM_ID, tag, VoteState::size_of())),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            validate(vote_account([0; 32], 2, VoteState::size_of())),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        assert_eq!(
+            validate(vote_account(crate::ID, 2, VoteState::size_of())),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        assert_eq!(
+            validate(vote_account(VOTE_PROGRAM_ID, 2, VoteState::size_of() - 1)),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            validate(vote_account(VOTE_PROGRAM_ID, 3, VoteState::size_of())),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
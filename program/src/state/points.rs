@@ -0,0 +1,207 @@
+//! Stake points, the unit inflation rewards are divided by: a delegation's effective
+//! stake times the vote credits its validator earned, summed over epochs. Mirrors the
+//! runtime's reward calculation so rewards can be estimated off-chain from a stake
+//! account, its vote account's epoch credits and stake history.
+
+use super::{Epoch, Stake, StakeHistoryGetEntry};
+
+/// Points a stake earned since its credits were last observed
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct CalculatedStakePoints {
+    pub points: u128,
+    /// what `Stake::credits_observed` becomes once the rewards are paid
+    pub new_credits_observed: u64,
+    /// the vote account has fewer credits than the stake observed, as when it was
+    /// closed and recreated; the runtime then updates the credits without paying
+    pub force_credits_update_with_skipped_reward: bool,
+}
+
+/// Rewards paid for an epoch and the total points they are divided by
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct PointValue {
+    pub rewards: u64,
+    pub points: u128,
+}
+
+/// Points `stake` earned from `epoch_credits`, the vote account's
+/// `(epoch, credits, prev_credits)` history as in `VoteState::epoch_credits`
+pub fn calculate_stake_points_and_credits<T: StakeHistoryGetEntry>(
+    stake: &Stake,
+    epoch_credits: &[(u64, u64, u64)],
+    stake_history: &T,
+    new_rate_activation_epoch: Option<Epoch>,
+) -> CalculatedStakePoints {
+    let credits_in_stake = stake.credits_observed();
+    let credits_in_vote = epoch_credits.last().map_or(0, |(_, credits, _)| *credits);
+
+    if credits_in_vote < credits_in_stake {
+        return CalculatedStakePoints {
+            points: 0,
+            new_credits_observed: credits_in_vote,
+            force_credits_update_with_skipped_reward: true,
+        };
+    }
+    if credits_in_vote == credits_in_stake {
+        return CalculatedStakePoints {
+            points: 0,
+            new_credits_observed: credits_in_stake,
+            force_credits_update_with_skipped_reward: false,
+        };
+    }
+
+    let mut points = 0u128;
+    let mut new_credits_observed = credits_in_stake;
+
+    for &(epoch, final_epoch_credits, initial_epoch_credits) in epoch_credits {
+        let stake_amount = u128::from(stake.delegation.stake(
+            epoch.to_le_bytes(),
+            stake_history,
+            new_rate_activation_epoch,
+        ));
+
+        let earned_credits = if credits_in_stake < initial_epoch_credits {
+            // the stake observed the whole epoch
+            final_epoch_credits.saturating_sub(initial_epoch_credits)
+        } else if credits_in_stake < final_epoch_credits {
+            // the stake was delegated or last paid during the epoch
+            final_epoch_credits.saturating_sub(new_credits_observed)
+        } else {
+            // already paid for this epoch, or delegated after it
+            0
+        };
+
+        // epochs are not assumed to be in order
+        new_credits_observed = new_credits_observed.max(final_epoch_credits);
+        points = points.saturating_add(stake_amount * u128::from(earned_credits));
+    }
+
+    CalculatedStakePoints {
+        points,
+        new_credits_observed,
+        force_credits_update_with_skipped_reward: false,
+    }
+}
+
+/// `(staker, voter)` shares of the rewards `points` earn at `point_value`, split by the
+/// validator's `commission` percentage. `None` where the runtime pays nothing: no
+/// points, rewards that round to zero, or a split leaving either side nothing.
+pub fn calculate_stake_rewards(
+    points: u128,
+    point_value: &PointValue,
+    commission: u8,
+) -> Option<(u64, u64)> {
+    if points == 0 || point_value.points == 0 {
+        return None;
+    }
+    let rewards = points
+        .checked_mul(u128::from(point_value.rewards))?
+        .checked_div(point_value.points)?;
+    let rewards = u64::try_from(rewards).ok()?;
+    if rewards == 0 {
+        return None;
+    }
+
+    match commission.min(100) {
+        0 => Some((rewards, 0)),
+        100 => Some((0, rewards)),
+        split => {
+            let voter = u128::from(rewards) * u128::from(split) / 100;
+            let staker = u128::from(rewards) * u128::from(100 - split) / 100;
+            if voter == 0 || staker == 0 {
+                return None;
+            }
+            Some((staker as u64, voter as u64))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+        state::{Delegation, StakeHistory},
+    };
+
+    fn points(stake: &Stake, epoch_credits: &[(u64, u64, u64)]) -> CalculatedStakePoints {
+        calculate_stake_points_and_credits(
+            stake,
+            epoch_credits,
+            &StakeHistory::default(),
+            PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+        )
+    }
+
+    #[test]
+    fn test_points_per_epoch() {
+        // active since epoch 0, last paid at 100 credits
+        let stake = Stake::new(Delegation::new(&[1; 32], 1_000, 0), 100);
+        let epoch_credits = [(1, 100, 0), (2, 150, 100), (3, 180, 150)];
+
+        assert_eq!(
+            points(&stake, &epoch_credits),
+            CalculatedStakePoints {
+                points: 1_000 * (50 + 30),
+                new_credits_observed: 180,
+                force_credits_update_with_skipped_reward: false,
+            }
+        );
+
+        // delegated partway through epoch 2, at 120 credits
+        let stake = Stake::new(Delegation::new(&[1; 32], 1_000, 0), 120);
+        assert_eq!(points(&stake, &epoch_credits).points, 1_000 * (30 + 30));
+    }
+
+    #[test]
+    fn test_points_without_new_credits() {
+        let stake = Stake::new(Delegation::new(&[1; 32], 1_000, 0), 180);
+
+        assert_eq!(
+            points(&stake, &[(3, 180, 150)]),
+            CalculatedStakePoints {
+                points: 0,
+                new_credits_observed: 180,
+                force_credits_update_with_skipped_reward: false,
+            }
+        );
+        // a recreated vote account starts over from zero credits
+        assert_eq!(
+            points(&stake, &[(5, 10, 0)]),
+            CalculatedStakePoints {
+                points: 0,
+                new_credits_observed: 10,
+                force_credits_update_with_skipped_reward: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_calculate_stake_rewards() {
+        let point_value = PointValue {
+            rewards: 1_000,
+            points: 10_000,
+        };
+
+        assert_eq!(
+            calculate_stake_rewards(5_000, &point_value, 0),
+            Some((500, 0))
+        );
+        assert_eq!(
+            calculate_stake_rewards(5_000, &point_value, 10),
+            Some((450, 50))
+        );
+        assert_eq!(
+            calculate_stake_rewards(5_000, &point_value, 100),
+            Some((0, 500))
+        );
+        assert_eq!(
+            calculate_stake_rewards(5_000, &point_value, 200),
+            Some((0, 500))
+        );
+
+        assert_eq!(calculate_stake_rewards(0, &point_value, 10), None);
+        assert_eq!(calculate_stake_rewards(9, &point_value, 0), None);
+        // 10 lamports at 1% commission leaves the voter nothing
+        assert_eq!(calculate_stake_rewards(100, &point_value, 1), None);
+    }
+}
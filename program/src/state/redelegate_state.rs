@@ -6,7 +6,6 @@ use pinocchio::{
     ProgramResult,
 };
 
-use pinocchio::sysvars::Sysvar;
 use pinocchio::sysvars::clock::Clock;
 
 use crate::{
@@ -55,10 +54,14 @@ impl RedelegateState {
         Ok(())
     }
 
-    pub fn start_redelegation(&mut self, ix_data: &StartRedelegationIxData) -> ProgramResult {
+    pub fn start_redelegation(
+        &mut self,
+        ix_data: &StartRedelegationIxData,
+        clock: &Clock,
+    ) -> ProgramResult {
         self.new_validator = ix_data.new_validator;
         self.state = State::Redelegating;
-        self.redelegation_timestamp = Clock::get()?.unix_timestamp;
+        self.redelegation_timestamp = clock.unix_timestamp;
         Ok(())
     }
 
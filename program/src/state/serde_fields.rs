@@ -0,0 +1,96 @@
+//! `#[serde(with = ...)]` adapters for the byte-array fields the account layout uses.
+//!
+//! Human-readable formats get base58 pubkeys and plain numbers; binary formats get the
+//! same bytes bincode would write for the native types.
+
+pub mod pubkey {
+    use pinocchio::pubkey::Pubkey;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&bs58::encode(pubkey).into_string())
+        } else {
+            serde::Serialize::serialize(pubkey, serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        if deserializer.is_human_readable() {
+            let encoded = <alloc::string::String>::deserialize(deserializer)?;
+            let mut pubkey = Pubkey::default();
+            let len = bs58::decode(&encoded)
+                .onto(&mut pubkey)
+                .map_err(D::Error::custom)?;
+            if len != pubkey.len() {
+                return Err(D::Error::custom("invalid pubkey length"));
+            }
+            Ok(pubkey)
+        } else {
+            Pubkey::deserialize(deserializer)
+        }
+    }
+}
+
+pub mod u64_le {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8; 8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::from_le_bytes(*value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 8], D::Error> {
+        u64::deserialize(deserializer).map(u64::to_le_bytes)
+    }
+}
+
+pub mod i64_le {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8; 8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(i64::from_le_bytes(*value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 8], D::Error> {
+        i64::deserialize(deserializer).map(i64::to_le_bytes)
+    }
+}
+
+pub mod f64_le {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8; 8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(f64::from_le_bytes(*value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 8], D::Error> {
+        f64::deserialize(deserializer).map(f64::to_le_bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::state::{Delegation, Meta, Stake, StakeFlags, StakeStateV2};
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut meta = Meta::auto(&[1; 32]);
+        meta.set_rent_exempt_reserve(2_282_880);
+        meta.lockup.set_unix_timestamp(-1);
+        let stake = Stake::new(Delegation::new(&[2; 32], 42, 7), 969);
+        let state = StakeStateV2::Stake(meta, stake, StakeFlags::empty());
+
+        let json = serde_json::to_value(state).unwrap();
+        assert_eq!(
+            json["Stake"][0]["authorized"]["staker"],
+            "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi"
+        );
+        assert_eq!(json["Stake"][0]["rent_exempt_reserve"], 2_282_880);
+        assert_eq!(json["Stake"][0]["lockup"]["unix_timestamp"], -1);
+        assert_eq!(json["Stake"][1]["delegation"]["stake"], 42);
+        assert_eq!(json["Stake"][1]["delegation"]["deactivation_epoch"], u64::MAX);
+
+        let decoded: StakeStateV2 = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, state);
+    }
+}
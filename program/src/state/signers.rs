@@ -0,0 +1,124 @@
+#[cfg(feature = "strict-interface")]
+use pinocchio::program_error::ProgramError;
+use pinocchio::{account_info::AccountInfo, pubkey::Pubkey};
+
+/// Compares two pubkeys as four `u64` words, bailing out at the first that differs.
+/// Cheaper on-chain than the byte-wise slice comparison `==` compiles to, and than a
+/// `sol_memcmp` syscall for only 32 bytes.
+#[inline(always)]
+pub fn pubkey_eq(a: &Pubkey, b: &Pubkey) -> bool {
+    let a = a.as_ptr() as *const u64;
+    let b = b.as_ptr() as *const u64;
+    // SAFETY: both point at 32 readable bytes; pubkeys are only 1-aligned
+    unsafe {
+        a.read_unaligned() == b.read_unaligned()
+            && a.add(1).read_unaligned() == b.add(1).read_unaligned()
+            && a.add(2).read_unaligned() == b.add(2).read_unaligned()
+            && a.add(3).read_unaligned() == b.add(3).read_unaligned()
+    }
+}
+
+/// Anything an authority check can ask "did this key sign?".
+///
+/// Implemented for plain pubkey slices and arrays (what `collect_signers_checked`
+/// assembles) and for `Signers`, which answers straight from the instruction accounts.
+pub trait SignerSet {
+    fn contains_signer(&self, key: &Pubkey) -> bool;
+}
+
+impl SignerSet for [Pubkey] {
+    #[inline(always)]
+    fn contains_signer(&self, key: &Pubkey) -> bool {
+        self.iter().any(|signer| pubkey_eq(signer, key))
+    }
+}
+
+impl<const N: usize> SignerSet for [Pubkey; N] {
+    #[inline(always)]
+    fn contains_signer(&self, key: &Pubkey) -> bool {
+        self.as_slice().contains_signer(key)
+    }
+}
+
+/// Zero-copy view of the signers of an instruction.
+///
+/// Unlike `collect_signers` this has no upper bound on the number of signers and
+/// copies nothing onto the stack; lookups scan the account list instead.
+#[derive(Clone, Copy)]
+pub struct Signers<'a> {
+    accounts: &'a [AccountInfo],
+}
+
+impl<'a> Signers<'a> {
+    #[inline(always)]
+    pub fn new(accounts: &'a [AccountInfo]) -> Self {
+        Self { accounts }
+    }
+
+    /// Only the account at `index`, for the `strict-interface` build where an authority
+    /// has to sign at its documented position. Fails if the account is missing.
+    #[cfg(feature = "strict-interface")]
+    #[inline(always)]
+    pub fn at(accounts: &'a [AccountInfo], index: usize) -> Result<Self, ProgramError> {
+        accounts
+            .get(index..=index)
+            .map(Self::new)
+            .ok_or(ProgramError::NotEnoughAccountKeys)
+    }
+
+    /// Iterates over signer pubkeys, yielding an account passed more than once only
+    /// the first time it appears.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Pubkey> + 'a {
+        let accounts = self.accounts;
+        accounts
+            .iter()
+            .enumerate()
+            .filter(|(_, account)| account.is_signer())
+            .filter(move |(index, account)| {
+                !accounts[..*index]
+                    .iter()
+                    .any(|prior| prior.is_signer() && pubkey_eq(prior.key(), account.key()))
+            })
+            .map(|(_, account)| account.key())
+    }
+
+    /// Number of distinct signers
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.accounts.iter().any(|account| account.is_signer())
+    }
+}
+
+impl SignerSet for Signers<'_> {
+    #[inline(always)]
+    fn contains_signer(&self, key: &Pubkey) -> bool {
+        self.accounts
+            .iter()
+            .any(|account| account.is_signer() && pubkey_eq(account.key(), key))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pubkey_eq() {
+        let key = core::array::from_fn::<u8, 32, _>(|i| i as u8);
+        assert!(pubkey_eq(&key, &key.clone()));
+
+        // a difference in any word, including the last byte, is caught
+        for i in [0, 8, 15, 16, 24, 31] {
+            let mut other = key;
+            other[i] ^= 1;
+            assert!(!pubkey_eq(&key, &other), "byte {i}");
+        }
+
+        let signers = [[1; 32], key];
+        assert!(signers.contains_signer(&key));
+        assert!(!signers.contains_signer(&[2; 32]));
+    }
+}
@@ -1,17 +1,38 @@
+use core::fmt;
+
 use crate::error::StakeError;
 
-use super::{bytes_to_u64, Delegation, Epoch, StakeHistoryGetEntry};
+use super::{Delegation, Epoch, StakeHistoryGetEntry};
 
 #[repr(C)]
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct Stake {
     pub delegation: Delegation,
     /// credits observed is credits from vote account state when delegated or redeemed
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_fields::u64_le"))]
     pub credits_observed: [u8; 8], //u64
     // changed to pub (as required in utils.rs L511 and L455)
 }
 
+impl fmt::Debug for Stake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stake")
+            .field("delegation", &self.delegation)
+            .field("credits_observed", &self.credits_observed())
+            .finish()
+    }
+}
+
 impl Stake {
+    pub fn new(delegation: Delegation, credits_observed: u64) -> Self {
+        Self {
+            delegation,
+            credits_observed: credits_observed.to_le_bytes(),
+        }
+    }
+
     #[inline(always)]
     pub fn set_credits_observed(&mut self, credits_observed: u64) {
         self.credits_observed = credits_observed.to_le_bytes();
@@ -37,12 +58,7 @@ impl Stake {
         remaining_stake_delta: u64,
         split_stake_amount: u64,
     ) -> Result<Self, StakeError> {
-        if remaining_stake_delta > bytes_to_u64(self.delegation.stake) {
-            return Err(StakeError::InsufficientStake);
-        }
-        self.delegation.stake = bytes_to_u64(self.delegation.stake)
-            .saturating_sub(remaining_stake_delta)
-            .to_le_bytes();
+        self.remove_stake(remaining_stake_delta)?;
         let new = Self {
             delegation: Delegation {
                 stake: split_stake_amount.to_le_bytes(),
@@ -53,8 +69,20 @@ impl Stake {
         Ok(new)
     }
 
+    /// Takes `remaining_stake_delta` off the delegation in place, the source side of
+    /// `split`
+    #[inline(always)]
+    pub fn remove_stake(&mut self, remaining_stake_delta: u64) -> Result<(), StakeError> {
+        let stake = self.delegation.delegated_stake();
+        if remaining_stake_delta > stake {
+            return Err(StakeError::InsufficientStake);
+        }
+        self.delegation.set_stake(stake - remaining_stake_delta);
+        Ok(())
+    }
+
     pub fn deactivate(&mut self, epoch: Epoch) -> Result<(), StakeError> {
-        if bytes_to_u64(self.delegation.deactivation_epoch) != u64::MAX {
+        if self.delegation.deactivation_epoch() != u64::MAX {
             Err(StakeError::AlreadyDeactivated)
         } else {
             self.delegation.deactivation_epoch = epoch;
@@ -1,14 +1,26 @@
 #[repr(C)]
 #[derive(Copy, PartialEq, Eq, Clone, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct StakeFlags {
     bits: u8,
 }
 
 impl StakeFlags {
+    /// Stake must be fully activated before deactivation is allowed (bit 1).
+    pub const MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED: Self =
+        Self { bits: 0b0000_0001 };
+
+    /// Every flag the program defines
+    pub const ALL: Self = Self::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED;
+
     pub const fn empty() -> Self {
         Self { bits: 0 }
     }
 
+    pub const fn bits(&self) -> u8 {
+        self.bits
+    }
+
     pub const fn contains(&self, other: Self) -> bool {
         (self.bits & other.bits) == other.bits
     }
@@ -33,3 +45,15 @@ impl Default for StakeFlags {
         StakeFlags::empty()
     }
 }
+
+// only flags the program defines, so generated accounts stay deserializable
+#[cfg(feature = "fuzz")]
+impl<'a> arbitrary::Arbitrary<'a> for StakeFlags {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(if u.arbitrary()? {
+            StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED
+        } else {
+            StakeFlags::empty()
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use crate::declare_sysvar_id;
+use pinocchio::program_error::ProgramError;
 use pinocchio::pubkey::Pubkey;
 use pinocchio::sysvars::clock::Epoch;
 extern crate alloc;
@@ -51,24 +52,35 @@ impl StakeHistoryEntry {
             ..Self::default()
         }
     }
+
+    #[inline(always)]
+    pub fn effective(&self) -> u64 {
+        u64::from_le_bytes(self.effective)
+    }
+
+    #[inline(always)]
+    pub fn activating(&self) -> u64 {
+        u64::from_le_bytes(self.activating)
+    }
+
+    #[inline(always)]
+    pub fn deactivating(&self) -> u64 {
+        u64::from_le_bytes(self.deactivating)
+    }
 }
 
 impl core::ops::Add for StakeHistoryEntry {
     type Output = StakeHistoryEntry;
     fn add(self, rhs: StakeHistoryEntry) -> Self::Output {
-        let effective = u64::from_le_bytes(self.effective);
-        let activating = u64::from_le_bytes(self.activating);
-        let deactivating = u64::from_le_bytes(self.deactivating);
+        let effective = self.effective();
+        let activating = self.activating();
+        let deactivating = self.deactivating();
         Self {
-            effective: effective
-                .saturating_add(u64::from_le_bytes(rhs.effective))
-                .to_be_bytes(),
-            activating: activating
-                .saturating_add(u64::from_le_bytes(rhs.activating))
-                .to_be_bytes(),
+            effective: effective.saturating_add(rhs.effective()).to_le_bytes(),
+            activating: activating.saturating_add(rhs.activating()).to_le_bytes(),
             deactivating: deactivating
-                .saturating_add(u64::from_le_bytes(rhs.deactivating))
-                .to_be_bytes(),
+                .saturating_add(rhs.deactivating())
+                .to_le_bytes(),
         }
     }
 }
@@ -93,6 +105,37 @@ impl StakeHistory {
         }
         (self.0).truncate(MAX_ENTRIES);
     }
+
+    /// Decodes the stake history sysvar's account data, as fetched over RPC: a `u64`
+    /// entry count, then each `(epoch, effective, activating, deactivating)` newest
+    /// first. Trailing padding past the counted entries is ignored.
+    pub fn from_sysvar_data(data: &[u8]) -> Result<Self, ProgramError> {
+        let (len, mut rest) = data
+            .split_first_chunk::<8>()
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let len = u64::from_le_bytes(*len) as usize;
+        if len > MAX_ENTRIES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut history = Self(alloc::vec::Vec::with_capacity(len));
+        for _ in 0..len {
+            let (entry, next) = rest
+                .split_first_chunk::<32>()
+                .ok_or(ProgramError::InvalidAccountData)?;
+            let field = |i: usize| -> [u8; 8] { entry[i * 8..(i + 1) * 8].try_into().unwrap() };
+            history.add(
+                u64::from_le_bytes(field(0)),
+                StakeHistoryEntry {
+                    effective: field(1),
+                    activating: field(2),
+                    deactivating: field(3),
+                },
+            );
+            rest = next;
+        }
+        Ok(history)
+    }
 }
 
 #[deprecated(
@@ -125,3 +168,173 @@ impl StakeHistoryGetEntry for StakeHistory {
             .map(|index| self[index].1.clone())
     }
 }
+
+/// `history` carried on past `current_epoch` by repeating its latest entry, as if the
+/// cluster's stake stayed as it is now, for projecting warmup and cooldown
+pub struct ProjectedStakeHistory<'a, T: ?Sized> {
+    history: &'a T,
+    current_epoch: Epoch,
+    latest: Option<StakeHistoryEntry>,
+}
+
+impl<'a, T: StakeHistoryGetEntry + ?Sized> ProjectedStakeHistory<'a, T> {
+    pub fn new(history: &'a T, current_epoch: Epoch) -> Self {
+        // the sysvar only has entries for epochs that have ended
+        let latest = current_epoch
+            .checked_sub(1)
+            .and_then(|epoch| history.get_entry(epoch));
+        Self {
+            history,
+            current_epoch,
+            latest,
+        }
+    }
+}
+
+impl<T: StakeHistoryGetEntry + ?Sized> StakeHistoryGetEntry for ProjectedStakeHistory<'_, T> {
+    fn get_entry(&self, epoch: Epoch) -> Option<StakeHistoryEntry> {
+        match self.history.get_entry(epoch) {
+            None if epoch >= self.current_epoch => self.latest.clone(),
+            entry => entry,
+        }
+    }
+}
+
+/// Builds a `StakeHistory`, or the stake history sysvar's account data, from
+/// `(epoch, effective, activating, deactivating)` entries, so multi-epoch activation
+/// scenarios can be written as a table
+#[cfg(any(test, feature = "std"))]
+#[derive(Debug, Default, Clone)]
+pub struct StakeHistoryBuilder(StakeHistory);
+
+#[cfg(any(test, feature = "std"))]
+impl StakeHistoryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the cluster totals for `epoch`, replacing any earlier entry for it
+    pub fn entry(
+        mut self,
+        epoch: Epoch,
+        effective: u64,
+        activating: u64,
+        deactivating: u64,
+    ) -> Self {
+        self.0.add(
+            epoch,
+            StakeHistoryEntry {
+                effective: effective.to_le_bytes(),
+                activating: activating.to_le_bytes(),
+                deactivating: deactivating.to_le_bytes(),
+            },
+        );
+        self
+    }
+
+    pub fn entries(self, entries: impl IntoIterator<Item = (Epoch, u64, u64, u64)>) -> Self {
+        entries.into_iter().fold(
+            self,
+            |builder, (epoch, effective, activating, deactivating)| {
+                builder.entry(epoch, effective, activating, deactivating)
+            },
+        )
+    }
+
+    pub fn build(self) -> StakeHistory {
+        self.0
+    }
+
+    /// The bincode layout the sysvar account holds: a `u64` entry count, then each
+    /// `(epoch, entry)` newest first. Unlike the live account this isn't padded to the
+    /// full 512-entry size.
+    pub fn to_sysvar_data(&self) -> alloc::vec::Vec<u8> {
+        let mut data = alloc::vec::Vec::with_capacity(8 + self.0.len() * 32);
+        data.extend_from_slice(&(self.0.len() as u64).to_le_bytes());
+        for (epoch, entry) in self.0.iter() {
+            data.extend_from_slice(&epoch.to_le_bytes());
+            data.extend_from_slice(&entry.effective);
+            data.extend_from_slice(&entry.activating);
+            data.extend_from_slice(&entry.deactivating);
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builder_orders_newest_first() {
+        let history = StakeHistoryBuilder::new()
+            .entries([(1, 100, 50, 0), (3, 300, 0, 10)])
+            .entry(2, 200, 25, 5)
+            .entry(1, 150, 50, 0)
+            .build();
+
+        assert_eq!(
+            history
+                .iter()
+                .map(|(epoch, _)| *epoch)
+                .collect::<alloc::vec::Vec<_>>(),
+            [3, 2, 1]
+        );
+        assert_eq!(
+            history.get_entry(2),
+            Some(StakeHistoryEntry {
+                effective: 200u64.to_le_bytes(),
+                activating: 25u64.to_le_bytes(),
+                deactivating: 5u64.to_le_bytes(),
+            })
+        );
+        assert_eq!(
+            history.get_entry(1).map(|entry| entry.effective()),
+            Some(150)
+        );
+        assert_eq!(history.get_entry(4), None);
+    }
+
+    #[test]
+    fn test_sysvar_data_matches_native_serialization() {
+        let entries = [(10, 1_000, 200, 0), (11, 1_150, 50, 30), (12, 1_180, 0, 0)];
+
+        let mut native = solana_sdk::stake_history::StakeHistory::default();
+        for (epoch, effective, activating, deactivating) in entries {
+            native.add(
+                epoch,
+                solana_sdk::stake_history::StakeHistoryEntry {
+                    effective,
+                    activating,
+                    deactivating,
+                },
+            );
+        }
+
+        assert_eq!(
+            StakeHistoryBuilder::new().entries(entries).to_sysvar_data(),
+            bincode::serialize(&native).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_sysvar_data_round_trips() {
+        let builder =
+            StakeHistoryBuilder::new().entries([(10, 1_000, 200, 0), (11, 1_150, 50, 30)]);
+        let mut data = builder.to_sysvar_data();
+
+        assert_eq!(
+            StakeHistory::from_sysvar_data(&data),
+            Ok(builder.clone().build())
+        );
+
+        // the live account is zero-padded to its full size
+        data.resize(16_392, 0);
+        assert_eq!(StakeHistory::from_sysvar_data(&data), Ok(builder.build()));
+
+        assert_eq!(
+            StakeHistory::from_sysvar_data(&data[..20]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
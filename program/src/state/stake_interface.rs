@@ -0,0 +1,229 @@
+//! Conversions to and from the `solana-stake-interface` state types, so off-chain
+//! tooling and differential tests can compare states field by field.
+
+use solana_pubkey::Pubkey;
+use solana_stake_interface::{stake_flags::StakeFlags as InterfaceStakeFlags, state as interface};
+
+use super::{Authorized, Delegation, Lockup, Meta, Stake, StakeFlags, StakeStateV2};
+
+impl From<Authorized> for interface::Authorized {
+    fn from(authorized: Authorized) -> Self {
+        Self {
+            staker: Pubkey::new_from_array(authorized.staker),
+            withdrawer: Pubkey::new_from_array(authorized.withdrawer),
+        }
+    }
+}
+
+impl From<interface::Authorized> for Authorized {
+    fn from(authorized: interface::Authorized) -> Self {
+        Self {
+            staker: authorized.staker.to_bytes(),
+            withdrawer: authorized.withdrawer.to_bytes(),
+        }
+    }
+}
+
+impl From<Lockup> for interface::Lockup {
+    fn from(lockup: Lockup) -> Self {
+        Self {
+            unix_timestamp: lockup.unix_timestamp(),
+            epoch: lockup.epoch(),
+            custodian: Pubkey::new_from_array(lockup.custodian),
+        }
+    }
+}
+
+impl From<interface::Lockup> for Lockup {
+    fn from(lockup: interface::Lockup) -> Self {
+        Self {
+            unix_timestamp: lockup.unix_timestamp.to_le_bytes(),
+            epoch: lockup.epoch.to_le_bytes(),
+            custodian: lockup.custodian.to_bytes(),
+        }
+    }
+}
+
+impl From<Meta> for interface::Meta {
+    fn from(meta: Meta) -> Self {
+        Self {
+            rent_exempt_reserve: meta.rent_exempt_reserve(),
+            authorized: meta.authorized.into(),
+            lockup: meta.lockup.into(),
+        }
+    }
+}
+
+impl From<interface::Meta> for Meta {
+    fn from(meta: interface::Meta) -> Self {
+        Self {
+            rent_exempt_reserve: meta.rent_exempt_reserve.to_le_bytes(),
+            authorized: meta.authorized.into(),
+            lockup: meta.lockup.into(),
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<Delegation> for interface::Delegation {
+    fn from(delegation: Delegation) -> Self {
+        Self {
+            voter_pubkey: Pubkey::new_from_array(delegation.voter_pubkey),
+            stake: delegation.delegated_stake(),
+            activation_epoch: delegation.activation_epoch(),
+            deactivation_epoch: delegation.deactivation_epoch(),
+            warmup_cooldown_rate: f64::from_le_bytes(delegation.warmup_cooldown_rate),
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<interface::Delegation> for Delegation {
+    fn from(delegation: interface::Delegation) -> Self {
+        Self {
+            voter_pubkey: delegation.voter_pubkey.to_bytes(),
+            stake: delegation.stake.to_le_bytes(),
+            activation_epoch: delegation.activation_epoch.to_le_bytes(),
+            deactivation_epoch: delegation.deactivation_epoch.to_le_bytes(),
+            warmup_cooldown_rate: delegation.warmup_cooldown_rate.to_le_bytes(),
+        }
+    }
+}
+
+impl From<Stake> for interface::Stake {
+    fn from(stake: Stake) -> Self {
+        Self {
+            delegation: stake.delegation.into(),
+            credits_observed: stake.credits_observed(),
+        }
+    }
+}
+
+impl From<interface::Stake> for Stake {
+    fn from(stake: interface::Stake) -> Self {
+        Self {
+            delegation: stake.delegation.into(),
+            credits_observed: stake.credits_observed.to_le_bytes(),
+        }
+    }
+}
+
+// the flag bits aren't exposed by either side, so map the only defined flag
+#[allow(deprecated)]
+impl From<StakeFlags> for InterfaceStakeFlags {
+    fn from(flags: StakeFlags) -> Self {
+        let mut converted = InterfaceStakeFlags::empty();
+        if flags.contains(StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED) {
+            converted
+                .set(InterfaceStakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED);
+        }
+        converted
+    }
+}
+
+#[allow(deprecated)]
+impl From<InterfaceStakeFlags> for StakeFlags {
+    fn from(flags: InterfaceStakeFlags) -> Self {
+        let mut converted = StakeFlags::empty();
+        if flags.contains(InterfaceStakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED)
+        {
+            converted.set(StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED);
+        }
+        converted
+    }
+}
+
+impl From<StakeStateV2> for interface::StakeStateV2 {
+    fn from(state: StakeStateV2) -> Self {
+        match state {
+            StakeStateV2::Uninitialized => Self::Uninitialized,
+            StakeStateV2::Initialized(meta) => Self::Initialized(meta.into()),
+            StakeStateV2::Stake(meta, stake, flags) => {
+                Self::Stake(meta.into(), stake.into(), flags.into())
+            }
+            StakeStateV2::RewardsPool => Self::RewardsPool,
+        }
+    }
+}
+
+impl From<interface::StakeStateV2> for StakeStateV2 {
+    fn from(state: interface::StakeStateV2) -> Self {
+        match state {
+            interface::StakeStateV2::Uninitialized => Self::Uninitialized,
+            interface::StakeStateV2::Initialized(meta) => Self::Initialized(meta.into()),
+            interface::StakeStateV2::Stake(meta, stake, flags) => {
+                Self::Stake(meta.into(), stake.into(), flags.into())
+            }
+            interface::StakeStateV2::RewardsPool => Self::RewardsPool,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_stake_state_round_trip() {
+        let meta = Meta {
+            rent_exempt_reserve: 2_282_880u64.to_le_bytes(),
+            authorized: Authorized {
+                staker: [1; 32],
+                withdrawer: [2; 32],
+            },
+            lockup: Lockup {
+                unix_timestamp: (-3i64).to_le_bytes(),
+                epoch: 4u64.to_le_bytes(),
+                custodian: [5; 32],
+            },
+        };
+        let stake = Stake {
+            delegation: Delegation {
+                voter_pubkey: [6; 32],
+                stake: 7u64.to_le_bytes(),
+                activation_epoch: 8u64.to_le_bytes(),
+                deactivation_epoch: u64::MAX.to_le_bytes(),
+                warmup_cooldown_rate: 0.25f64.to_le_bytes(),
+            },
+            credits_observed: 9u64.to_le_bytes(),
+        };
+        let state = StakeStateV2::Stake(
+            meta,
+            stake,
+            StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+        );
+
+        let converted = interface::StakeStateV2::from(state);
+        let interface::StakeStateV2::Stake(interface_meta, interface_stake, interface_flags) =
+            converted
+        else {
+            panic!("expected a Stake state");
+        };
+        assert_eq!(interface_meta.rent_exempt_reserve, 2_282_880);
+        assert_eq!(
+            interface_meta.authorized.staker,
+            Pubkey::new_from_array([1; 32])
+        );
+        assert_eq!(interface_meta.lockup.unix_timestamp, -3);
+        assert_eq!(
+            interface_meta.lockup.custodian,
+            Pubkey::new_from_array([5; 32])
+        );
+        assert_eq!(interface_stake.delegation.stake, 7);
+        assert_eq!(interface_stake.delegation.deactivation_epoch, u64::MAX);
+        assert_eq!(interface_stake.credits_observed, 9);
+        assert!(interface_flags
+            .contains(InterfaceStakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED));
+
+        assert_eq!(StakeStateV2::from(converted), state);
+        assert_eq!(
+            StakeStateV2::from(interface::StakeStateV2::Initialized(interface_meta)),
+            StakeStateV2::Initialized(meta)
+        );
+        assert_eq!(
+            StakeStateV2::from(interface::StakeStateV2::Uninitialized),
+            StakeStateV2::Uninitialized
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use pinocchio::program_error::ProgramError;
+
+use super::{Meta, Stake, StakeFlags, StakeStateTag, StakeStateV2};
+
+/// The pre-`StakeFlags` stake account layout. Byte-for-byte it only differs from
+/// `StakeStateV2` in that the flags byte of the `Stake` variant was unused padding.
+#[repr(C)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StakeState {
+    Uninitialized,
+    Initialized(Meta),
+    Stake(Meta, Stake),
+    RewardsPool,
+}
+
+// 4 byte tag + Meta (120) + Stake (72)
+const STAKE_FLAGS_OFFSET: usize = 196;
+
+impl StakeState {
+    /// The fixed number of bytes used to serialize each stake account
+    pub const fn size_of() -> usize {
+        200
+    }
+}
+
+impl From<StakeState> for StakeStateV2 {
+    fn from(state: StakeState) -> Self {
+        match state {
+            StakeState::Uninitialized => StakeStateV2::Uninitialized,
+            StakeState::Initialized(meta) => StakeStateV2::Initialized(meta),
+            StakeState::Stake(meta, stake) => {
+                StakeStateV2::Stake(meta, stake, StakeFlags::empty())
+            }
+            StakeState::RewardsPool => StakeStateV2::RewardsPool,
+        }
+    }
+}
+
+/// Rewrites `StakeState` (V1) account data as `StakeStateV2` in place.
+///
+/// The only difference between the layouts is the `Stake` variant's flags byte, which
+/// V1 left as padding and may therefore hold anything. Only the bits outside
+/// `StakeFlags::ALL` are cleared there, so data that is already V2 passes through
+/// unchanged with its flags intact.
+pub fn migrate_v1_to_v2(data: &mut [u8]) -> Result<StakeStateTag, ProgramError> {
+    if data.len() != StakeState::size_of() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let tag = StakeStateV2::peek_tag(data)?;
+    if tag == StakeStateTag::Stake {
+        data[STAKE_FLAGS_OFFSET] &= StakeFlags::ALL.bits();
+        // trailing alignment padding
+        data[STAKE_FLAGS_OFFSET + 1..].fill(0);
+    }
+
+    Ok(tag)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{migrate_v1_to_v2, StakeState, StakeStateTag};
+    use crate::state::{Delegation, Meta, Stake, StakeFlags, StakeStateV2};
+    use pinocchio::program_error::ProgramError;
+
+    #[test]
+    fn test_migrate_stake() {
+        let meta = Meta::auto(&[3u8; 32]);
+        let stake = Stake::new(Delegation::new(&[4u8; 32], 42, 1), 969);
+
+        let mut data = [0u8; 200];
+        data[0] = 2;
+        data[4..124].copy_from_slice(unsafe {
+            core::slice::from_raw_parts(&meta as *const Meta as *const u8, 120)
+        });
+        data[124..196].copy_from_slice(unsafe {
+            core::slice::from_raw_parts(&stake as *const Stake as *const u8, 72)
+        });
+        // garbage outside the known flags, left behind in what used to be padding
+        data[196..].copy_from_slice(&[0xfe; 4]);
+
+        assert_eq!(migrate_v1_to_v2(&mut data), Ok(StakeStateTag::Stake));
+
+        let state = unsafe { StakeStateV2::from_bytes(&data) };
+        assert_eq!(
+            *state,
+            StakeStateV2::from(StakeState::Stake(meta, stake))
+        );
+        assert_eq!(
+            *state,
+            StakeStateV2::Stake(meta, stake, StakeFlags::empty())
+        );
+    }
+
+    #[test]
+    fn test_migrate_keeps_v2_flags() {
+        let meta = Meta::auto(&[3u8; 32]);
+        let stake = Stake::new(Delegation::new(&[4u8; 32], 42, 1), 969);
+        let flags = StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED;
+
+        let mut data = [0u8; 200];
+        StakeStateV2::Stake(meta, stake, flags)
+            .serialize(&mut data)
+            .unwrap();
+        let before = data;
+
+        assert_eq!(migrate_v1_to_v2(&mut data), Ok(StakeStateTag::Stake));
+        assert_eq!(data, before);
+
+        let state = unsafe { StakeStateV2::from_bytes(&data) };
+        assert_eq!(*state, StakeStateV2::Stake(meta, stake, flags));
+    }
+
+    #[test]
+    fn test_migrate_invalid() {
+        let mut data = [0u8; 200];
+        data[0] = 4;
+        assert_eq!(migrate_v1_to_v2(&mut data), Err(ProgramError::InvalidAccountData));
+
+        let mut data = [0u8; 196];
+        assert_eq!(migrate_v1_to_v2(&mut data), Err(ProgramError::InvalidAccountData));
+    }
+}
@@ -3,10 +3,49 @@ use pinocchio::{
     program_error::ProgramError,
 };
 
-use super::{Authorized, Delegation, Lockup, Meta, Stake, StakeFlags};
+use super::{
+    layout::{META_OFFSET, STAKE_FLAGS_OFFSET, STAKE_OFFSET, TAG_LEN},
+    Authorized, Delegation, Lockup, Meta, Stake, StakeFlags,
+};
+
+/// Raw bytes of one of the state structs
+///
+/// # Safety
+///
+/// `T` must have no padding; `Meta` and `Stake` are built only from byte arrays and
+/// `StakeFlags` is a single byte.
+unsafe fn bytes_of<T>(value: &T) -> &[u8] {
+    core::slice::from_raw_parts(value as *const T as *const u8, core::mem::size_of::<T>())
+}
+
+/// The 4-byte discriminant that prefixes every serialized `StakeStateV2`
+#[repr(u32)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StakeStateTag {
+    Uninitialized = 0,
+    Initialized = 1,
+    Stake = 2,
+    RewardsPool = 3,
+}
+
+impl TryFrom<u32> for StakeStateTag {
+    type Error = ProgramError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(StakeStateTag::Uninitialized),
+            1 => Ok(StakeStateTag::Initialized),
+            2 => Ok(StakeStateTag::Stake),
+            3 => Ok(StakeStateTag::RewardsPool),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
 
 #[repr(C)]
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum StakeStateV2 {
     Uninitialized,
     Initialized(Meta),
@@ -107,6 +146,75 @@ impl<'a> StakeStateV2 {
         &mut *(bytes.as_mut_ptr() as *mut Self)
     }
 
+    /// Decodes raw stake account data (e.g. as returned by `getAccountInfo`) into an owned
+    /// `StakeStateV2`. No syscalls and no `AccountInfo`, so this works on the host, and the
+    /// input slice needs no particular alignment.
+    pub fn deserialize(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::size_of() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // rejects anything but the four known variants before reinterpreting the bytes
+        Self::peek_tag(data)?;
+
+        // SAFETY: the length and discriminant were checked above and every other field is
+        // a plain byte array, so any remaining bit pattern is a valid `StakeStateV2`
+        Ok(unsafe { core::ptr::read_unaligned(data.as_ptr() as *const Self) })
+    }
+
+    /// Reads only the discriminant of serialized stake account data, so precondition
+    /// checks don't need to borrow or reinterpret the full 200 bytes.
+    #[inline(always)]
+    pub fn peek_tag(data: &[u8]) -> Result<StakeStateTag, ProgramError> {
+        let tag_bytes: [u8; 4] = data
+            .get(..4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        StakeStateTag::try_from(u32::from_le_bytes(tag_bytes))
+    }
+
+    /// Writes the state over the first `size_of()` bytes of `data`, field by field. Bytes
+    /// the variant doesn't use are zeroed rather than left as they were, so an account
+    /// going back to `Uninitialized` keeps nothing of its old authorities or delegation.
+    ///
+    /// This deliberately differs from the native program, whose bincode `set_state`
+    /// rewrites only the 4-byte tag on that transition; the differential fuzz target
+    /// accepts exactly that difference.
+    pub fn serialize(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let data = data
+            .get_mut(..Self::size_of())
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        data.fill(0);
+        data[..TAG_LEN].copy_from_slice(&(self.tag() as u32).to_le_bytes());
+        // SAFETY: `Meta`, `Stake` and `StakeFlags` have no padding
+        unsafe {
+            if let Some(meta) = self.meta() {
+                data[META_OFFSET..STAKE_OFFSET].copy_from_slice(bytes_of::<Meta>(&meta));
+            }
+            if let Self::Stake(_, stake, flags) = self {
+                data[STAKE_OFFSET..STAKE_FLAGS_OFFSET].copy_from_slice(bytes_of::<Stake>(stake));
+                data[STAKE_FLAGS_OFFSET..][..1].copy_from_slice(bytes_of::<StakeFlags>(flags));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets a borrowed state to `Uninitialized` and zeroes everything after the tag.
+    /// Assigning `Uninitialized` only writes the tag, leaving the old fields readable,
+    /// which is what the native program leaves behind; see `serialize`.
+    pub fn reset(&mut self) {
+        // SAFETY: all zeros is `Uninitialized`, and every other field is a byte array
+        unsafe { core::ptr::write_bytes(self as *mut Self as *mut u8, 0, Self::size_of()) }
+    }
+
+    pub fn tag(&self) -> StakeStateTag {
+        match self {
+            Self::Uninitialized => StakeStateTag::Uninitialized,
+            Self::Initialized(_) => StakeStateTag::Initialized,
+            Self::Stake(_, _, _) => StakeStateTag::Stake,
+            Self::RewardsPool => StakeStateTag::RewardsPool,
+        }
+    }
+
     fn is_aligned_to_4(data: &[u8]) -> bool {
         let ptr = data.as_ptr() as usize;
         ptr % 4 == 0
@@ -126,6 +234,14 @@ impl<'a> StakeStateV2 {
         }
     }
 
+    /// For updating single fields of a borrowed account without rewriting the state
+    pub fn stake_mut(&mut self) -> Option<&mut Stake> {
+        match self {
+            Self::Stake(_meta, stake, _stake_flags) => Some(stake),
+            Self::Uninitialized | Self::Initialized(_) | Self::RewardsPool => None,
+        }
+    }
+
     pub fn delegation(&self) -> Option<Delegation> {
         match self {
             Self::Stake(_meta, stake, _stake_flags) => Some(stake.delegation),
@@ -159,10 +275,42 @@ impl<'a> StakeStateV2 {
             Self::Uninitialized | Self::RewardsPool => None,
         }
     }
+
+    /// For updating single fields of a borrowed account without rewriting the state
+    pub fn meta_mut(&mut self) -> Option<&mut Meta> {
+        match self {
+            Self::Stake(meta, _stake, _stake_flags) => Some(meta),
+            Self::Initialized(meta) => Some(meta),
+            Self::Uninitialized | Self::RewardsPool => None,
+        }
+    }
 }
 #[cfg(test)]
 mod test {
-    use super::StakeStateV2;
+    use super::{StakeStateTag, StakeStateV2};
+    use crate::state::{
+        layout::STAKE_OFFSET, Authorized, Delegation, Lockup, Meta, Stake, StakeFlags,
+    };
+    use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+    #[test]
+    fn test_peek_tag() {
+        let mut data = [0u8; 200];
+        assert_eq!(StakeStateV2::peek_tag(&data), Ok(StakeStateTag::Uninitialized));
+
+        data[0] = 2;
+        assert_eq!(StakeStateV2::peek_tag(&data), Ok(StakeStateTag::Stake));
+
+        data[0] = 4;
+        assert_eq!(StakeStateV2::peek_tag(&data), Err(ProgramError::InvalidAccountData));
+
+        // a set high byte is not a valid tag even though the low byte is
+        data[0] = 1;
+        data[3] = 1;
+        assert_eq!(StakeStateV2::peek_tag(&data), Err(ProgramError::InvalidAccountData));
+
+        assert_eq!(StakeStateV2::peek_tag(&data[..3]), Err(ProgramError::InvalidAccountData));
+    }
 
     #[test]
     fn test_from_initialized() {
@@ -199,8 +347,90 @@ mod test {
             255, 255, 255, 255, 0, 0, 0, 0, 0, 0, 208, 63, 201, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         ];
 
-        let val = unsafe { &*(data.as_ptr() as *const StakeStateV2) };
+        // a stack array is only byte-aligned, so read it through the layout
+        let val = StakeStateV2::deserialize(&data).unwrap();
 
-        println!("{:?}", val);
+        let staker: Pubkey = data[12..44].try_into().unwrap();
+        let voter: Pubkey = data[124..156].try_into().unwrap();
+        assert_eq!(
+            val,
+            StakeStateV2::Stake(
+                Meta::auto(&staker),
+                Stake::new(Delegation::new(&voter, 1, 1), 969),
+                StakeFlags::empty(),
+            )
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let mut data = [0u8; 201];
+        data[1] = 1;
+        data[5..13].copy_from_slice(&2282880u64.to_le_bytes());
+        data[13..45].copy_from_slice(&[7; 32]);
+
+        // deliberately misaligned by one byte
+        let state = StakeStateV2::deserialize(&data[1..]).unwrap();
+        let meta = state.meta().unwrap();
+        assert_eq!(state.tag(), StakeStateTag::Initialized);
+        assert_eq!(meta.rent_exempt_reserve(), 2282880);
+        assert_eq!(meta.authorized.staker, [7; 32]);
+
+        assert_eq!(
+            StakeStateV2::deserialize(&data[..200]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            StakeStateV2::deserialize(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_serialize_zeroes_unused_bytes() {
+        let meta = Meta::new(2282880, Authorized::auto(&[7; 32]), Lockup::DEFAULT);
+        let stake = Stake::new(Delegation::new(&[8; 32], 42, 1), 3);
+
+        let mut data = [0xff; 201];
+        StakeStateV2::Stake(meta, stake, StakeFlags::empty())
+            .serialize(&mut data)
+            .unwrap();
+        assert_eq!(
+            StakeStateV2::deserialize(&data[..200]),
+            Ok(StakeStateV2::Stake(meta, stake, StakeFlags::empty()))
+        );
+        // past the state is left alone
+        assert_eq!(data[200], 0xff);
+
+        StakeStateV2::Initialized(meta).serialize(&mut data).unwrap();
+        assert_eq!(
+            StakeStateV2::deserialize(&data[..200]),
+            Ok(StakeStateV2::Initialized(meta))
+        );
+        assert!(data[STAKE_OFFSET..200].iter().all(|byte| *byte == 0));
+
+        StakeStateV2::Uninitialized.serialize(&mut data).unwrap();
+        assert!(data[..200].iter().all(|byte| *byte == 0));
+
+        assert_eq!(
+            StakeStateV2::Uninitialized.serialize(&mut data[..199]),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_reset_zeroes_state() {
+        let meta = Meta::new(2282880, Authorized::auto(&[7; 32]), Lockup::DEFAULT);
+        let mut data = [0u8; 200];
+        StakeStateV2::Initialized(meta).serialize(&mut data).unwrap();
+
+        let mut state = StakeStateV2::deserialize(&data).unwrap();
+        state.reset();
+        assert_eq!(state, StakeStateV2::Uninitialized);
+        // SAFETY: `state` is `size_of()` bytes with no padding read back
+        let bytes = unsafe {
+            core::slice::from_raw_parts(&state as *const _ as *const u8, StakeStateV2::size_of())
+        };
+        assert!(bytes.iter().all(|byte| *byte == 0));
     }
 }
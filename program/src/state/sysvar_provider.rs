@@ -0,0 +1,194 @@
+//! Clock, Rent and StakeHistory access behind a trait. Processors read sysvars through
+//! `SyscallSysvars`; host tests substitute `MockSysvars` so the helpers that take a
+//! provider run without an SVM.
+
+use pinocchio::{
+    program_error::ProgramError,
+    sysvars::{
+        clock::{Clock, Epoch},
+        rent::Rent,
+        Sysvar,
+    },
+};
+
+use super::{FeatureSet, StakeHistoryGetEntry, StakeHistorySysvar};
+
+pub trait SysvarProvider {
+    type StakeHistory: StakeHistoryGetEntry;
+
+    fn clock(&self) -> Result<Clock, ProgramError>;
+
+    fn rent(&self) -> Result<Rent, ProgramError>;
+
+    /// Stake history as seen from `current_epoch`
+    fn stake_history(&self, current_epoch: Epoch) -> Self::StakeHistory;
+
+    /// Runtime features the processors apply, mainnet's unless overridden
+    #[inline(always)]
+    fn features(&self) -> FeatureSet {
+        FeatureSet::MAINNET
+    }
+}
+
+/// Reads each sysvar through its syscall
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SyscallSysvars;
+
+impl SysvarProvider for SyscallSysvars {
+    type StakeHistory = StakeHistorySysvar;
+
+    #[inline(always)]
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        Clock::get()
+    }
+
+    #[inline(always)]
+    fn rent(&self) -> Result<Rent, ProgramError> {
+        Rent::get()
+    }
+
+    #[inline(always)]
+    fn stake_history(&self, current_epoch: Epoch) -> Self::StakeHistory {
+        StakeHistorySysvar(current_epoch)
+    }
+}
+
+/// Fixed sysvar values for host-side tests
+#[cfg(any(test, feature = "std"))]
+#[derive(Clone)]
+pub struct MockSysvars {
+    pub clock: Clock,
+    pub rent: Rent,
+    pub stake_history: super::StakeHistory,
+}
+
+#[cfg(any(test, feature = "std"))]
+impl Default for MockSysvars {
+    /// Epoch 0, mainnet rent parameters and no stake history
+    fn default() -> Self {
+        Self {
+            clock: Clock::default(),
+            rent: Rent {
+                lamports_per_byte_year: 3_480,
+                exemption_threshold: 2.0,
+                burn_percent: 50,
+            },
+            stake_history: super::StakeHistory::default(),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl SysvarProvider for MockSysvars {
+    type StakeHistory = super::StakeHistory;
+
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        Ok(self.clock)
+    }
+
+    fn rent(&self) -> Result<Rent, ProgramError> {
+        Ok(self.rent.clone())
+    }
+
+    fn stake_history(&self, _current_epoch: Epoch) -> Self::StakeHistory {
+        self.stake_history.clone()
+    }
+}
+
+/// Wraps another provider and counts how often each sysvar is read, so tests can
+/// hold processors to a syscall budget
+#[cfg(any(test, feature = "std"))]
+#[derive(Default)]
+pub struct CountingSysvars<S> {
+    pub inner: S,
+    clock_reads: core::cell::Cell<usize>,
+    rent_reads: core::cell::Cell<usize>,
+    stake_history_reads: core::cell::Cell<usize>,
+}
+
+#[cfg(any(test, feature = "std"))]
+impl<S> CountingSysvars<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            clock_reads: Default::default(),
+            rent_reads: Default::default(),
+            stake_history_reads: Default::default(),
+        }
+    }
+
+    pub fn clock_reads(&self) -> usize {
+        self.clock_reads.get()
+    }
+
+    pub fn rent_reads(&self) -> usize {
+        self.rent_reads.get()
+    }
+
+    pub fn stake_history_reads(&self) -> usize {
+        self.stake_history_reads.get()
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl<S: SysvarProvider> SysvarProvider for CountingSysvars<S> {
+    type StakeHistory = S::StakeHistory;
+
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        self.clock_reads.set(self.clock_reads.get() + 1);
+        self.inner.clock()
+    }
+
+    fn rent(&self) -> Result<Rent, ProgramError> {
+        self.rent_reads.set(self.rent_reads.get() + 1);
+        self.inner.rent()
+    }
+
+    fn stake_history(&self, current_epoch: Epoch) -> Self::StakeHistory {
+        self.stake_history_reads
+            .set(self.stake_history_reads.get() + 1);
+        self.inner.stake_history(current_epoch)
+    }
+
+    fn features(&self) -> FeatureSet {
+        self.inner.features()
+    }
+}
+
+/// Wraps another provider and replaces the features it reports, so forks and replay
+/// tools can run the processors under a different `FeatureSet`
+#[derive(Clone, Debug, Default)]
+pub struct WithFeatures<S> {
+    pub inner: S,
+    pub features: FeatureSet,
+}
+
+impl<S> WithFeatures<S> {
+    pub const fn new(inner: S, features: FeatureSet) -> Self {
+        Self { inner, features }
+    }
+}
+
+impl<S: SysvarProvider> SysvarProvider for WithFeatures<S> {
+    type StakeHistory = S::StakeHistory;
+
+    #[inline(always)]
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        self.inner.clock()
+    }
+
+    #[inline(always)]
+    fn rent(&self) -> Result<Rent, ProgramError> {
+        self.inner.rent()
+    }
+
+    #[inline(always)]
+    fn stake_history(&self, current_epoch: Epoch) -> Self::StakeHistory {
+        self.inner.stake_history(current_epoch)
+    }
+
+    #[inline(always)]
+    fn features(&self) -> FeatureSet {
+        self.features
+    }
+}
@@ -2,23 +2,22 @@ use pinocchio::{
     account_info::{ AccountInfo, Ref },
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    sysvars::{clock::Clock, rent::Rent},
     ProgramResult, SUCCESS,
 };
 
 extern crate alloc;
 use super::{
-    get_stake_state, try_get_stake_state_mut, Delegation, Meta, Stake, StakeAuthorize, StakeHistorySysvar, StakeStateV2, VoteState, DEFAULT_WARMUP_COOLDOWN_RATE
+    get_stake_state, try_get_stake_state_mut, Delegation, FeatureSet, Meta, SignerSet, Signers, Stake, StakeAuthorize, StakeHistoryGetEntry, StakeHistorySysvar, StakeStateV2, DEFAULT_WARMUP_COOLDOWN_RATE
 };
 use crate::{
-    consts::{
-        FEATURE_STAKE_RAISE_MINIMUM_DELEGATION_TO_1_SOL, LAMPORTS_PER_SOL, MAX_SIGNERS,
-        NEW_WARMUP_COOLDOWN_RATE,
+    consts::{MAX_CHECKED_SIGNERS, MAX_SIGNERS, NEW_WARMUP_COOLDOWN_RATE},
+    helpers::{
+        get_stake_status, get_stake_status_with_features, lamports_add, lamports_sub, MergeKind,
     },
-    helpers::MergeKind,
 };
 use crate::{consts::{
-    CLOCK_ID, HASH_BYTES, MAX_BASE58_LEN, PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH
+    CLOCK_ID, HASH_BYTES, MAX_BASE58_LEN
 }, error::StakeError};
 use alloc::boxed::Box;
 use core::{ cell::UnsafeCell, fmt, str::from_utf8 };
@@ -100,6 +99,32 @@ pub fn collect_signers(
     Ok(signer_len)
 }
 
+/// Signers of the instruction, plus the lockup custodian for the instructions that take
+/// it as an optional trailing account: the Withdraw family, SetLockupChecked and the
+/// checked authorize variants. A custodian account that didn't sign doesn't count.
+/// With `custodian_must_sign`, as in SetLockupChecked and `collect_signers_checked`,
+/// passing one that didn't sign is an error instead.
+pub fn collect_signers_checked_with_custodian<'a>(
+    accounts: &'a [AccountInfo],
+    custodian_info: Option<&'a AccountInfo>,
+    custodian_must_sign: bool,
+) -> Result<(Signers<'a>, Option<&'a Pubkey>), ProgramError> {
+    let custodian = signing_custodian(custodian_info, custodian_must_sign)?;
+    Ok((Signers::new(accounts), custodian))
+}
+
+/// The custodian's key, if its account was passed and signed
+fn signing_custodian(
+    custodian_info: Option<&AccountInfo>,
+    custodian_must_sign: bool,
+) -> Result<Option<&Pubkey>, ProgramError> {
+    match custodian_info {
+        Some(custodian_info) if custodian_info.is_signer() => Ok(Some(custodian_info.key())),
+        Some(_) if custodian_must_sign => Err(ProgramError::MissingRequiredSignature),
+        _ => Ok(None),
+    }
+}
+
 pub fn next_account_info<'a, I: Iterator<Item = &'a AccountInfo>>(
     iter: &mut I
 ) -> Result<&'a AccountInfo, ProgramError> {
@@ -129,10 +154,18 @@ macro_rules! declare_sysvar_id {
     );
 }
 
+/// Rent-exempt reserve of a stake account. Stake accounts are always exactly
+/// `StakeStateV2::size_of()` bytes, so processors compute this once from Rent and
+/// pass the result down instead of asking Rent again for every check.
+#[inline(always)]
+pub fn stake_rent_exempt_reserve(rent: &Rent) -> u64 {
+    rent.minimum_balance(StakeStateV2::size_of())
+}
+
 /// After calling `validate_split_amount()`, this struct contains calculated
 /// values that are used by the caller.
 #[derive(Copy, Clone, Debug, Default)]
-pub(crate) struct ValidatedSplitInfo {
+pub struct ValidatedSplitInfo {
     pub source_remaining_balance: u64,
     pub destination_rent_exempt_reserve: u64,
 }
@@ -141,12 +174,15 @@ pub(crate) struct ValidatedSplitInfo {
 /// accounts meet the minimum balance requirements, which is the rent exempt
 /// reserve plus the minimum stake delegation, and that the source account has
 /// enough lamports for the request split amount.  If not, return an error.
-pub(crate) fn validate_split_amount(
+///
+/// `destination_rent_exempt_reserve` comes from `stake_rent_exempt_reserve`, computed
+/// once per instruction; validation itself makes no syscalls.
+pub fn validate_split_amount(
     source_lamports: u64,
     destination_lamports: u64,
     split_lamports: u64,
     source_meta: &Meta,
-    destination_data_len: usize,
+    destination_rent_exempt_reserve: u64,
     additional_required_lamports: u64,
     source_is_active: bool
 ) -> Result<ValidatedSplitInfo, ProgramError> {
@@ -164,10 +200,11 @@ pub(crate) fn validate_split_amount(
     // splitting: EITHER at least the minimum balance, OR zero (in this case the
     // source account is transferring all lamports to new destination account,
     // and the source account will be closed)
-    let source_minimum_balance = u64
-        ::from_le_bytes(source_meta.rent_exempt_reserve)
-        .saturating_add(additional_required_lamports);
-    let source_remaining_balance = source_lamports.saturating_sub(split_lamports);
+    let source_minimum_balance = lamports_add(
+        source_meta.rent_exempt_reserve(),
+        additional_required_lamports
+    )?;
+    let source_remaining_balance = lamports_sub(source_lamports, split_lamports)?;
     if source_remaining_balance == 0 {
         // full amount is a withdrawal
         // nothing to do here
@@ -179,9 +216,6 @@ pub(crate) fn validate_split_amount(
         // nothing to do here
     }
 
-    let rent = Rent::get()?;
-    let destination_rent_exempt_reserve = rent.minimum_balance(destination_data_len);
-
     // If the source is active stake, one of these criteria must be met:
     // 1. the destination account must be prefunded with at least the rent-exempt
     //    reserve, or
@@ -200,9 +234,10 @@ pub(crate) fn validate_split_amount(
     //    size changes
     // 2. The destination account being prefunded, which would lower the minimum
     //    split amount
-    let destination_minimum_balance = destination_rent_exempt_reserve.saturating_add(
+    let destination_minimum_balance = lamports_add(
+        destination_rent_exempt_reserve,
         additional_required_lamports
-    );
+    )?;
     let destination_balance_deficit =
         destination_minimum_balance.saturating_sub(destination_lamports);
     if split_lamports < destination_balance_deficit {
@@ -330,17 +365,15 @@ pub fn to_program_error(e: ProgramError) -> ProgramError {
 
 #[inline(always)]
 pub fn get_minimum_delegation() -> u64 {
-    if FEATURE_STAKE_RAISE_MINIMUM_DELEGATION_TO_1_SOL {
-        const MINIMUM_DELEGATION_SOL: u64 = 1;
-        MINIMUM_DELEGATION_SOL * LAMPORTS_PER_SOL
-    } else {
-        1
-    }
+    FeatureSet::MAINNET.minimum_delegation()
 }
 
-pub fn do_authorize(
+/// The authorize step shared by `Authorize`, `AuthorizeWithSeed`, `AuthorizeChecked`
+/// and `AuthorizeCheckedWithSeed`, which differ only in how they gather `signers`;
+/// who may change which authority is decided by `Authorized::authorize`
+pub fn do_authorize<S: SignerSet + ?Sized>(
     stake_account_info: &AccountInfo,
-    signers: &[Pubkey],
+    signers: &S,
     new_authority: &Pubkey,
     authority_type: StakeAuthorize,
     custodian: Option<&Pubkey>,
@@ -348,34 +381,22 @@ pub fn do_authorize(
 ) -> ProgramResult {
     let mut stake_account: pinocchio::account_info::RefMut<'_, StakeStateV2> =
         try_get_stake_state_mut(stake_account_info)?;
-    match *stake_account {
-        StakeStateV2::Initialized(mut meta) => {
-            meta.authorized
-                .authorize(
-                    signers,
-                    new_authority,
-                    authority_type,
-                    Some((&meta.lockup, clock, custodian)),
-                )
-                .map_err(to_program_error)?;
-            *stake_account = StakeStateV2::Initialized(meta);
-            Ok(())
-        }
-        StakeStateV2::Stake(mut meta, stake, stake_flags) => {
-            meta.authorized
-                .authorize(
-                    signers,
-                    new_authority,
-                    authority_type,
-                    Some((&meta.lockup, clock, custodian)),
-                )
-                .map_err(to_program_error)?;
-
-            *stake_account = StakeStateV2::Stake(meta, stake, stake_flags);
-            Ok(())
-        }
-        _ => Err(ProgramError::InvalidAccountData),
-    }
+    let meta = stake_account
+        .meta_mut()
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    // updated on a copy so a failed check leaves the account untouched
+    let mut authorized = meta.authorized;
+    authorized
+        .authorize(
+            signers,
+            new_authority,
+            authority_type,
+            Some((&meta.lockup, clock, custodian)),
+        )
+        .map_err(to_program_error)?;
+    meta.authorized = authorized;
+    Ok(())
 }
 
 // Means that no more than RATE of current effective stake may be added or subtracted per
@@ -407,36 +428,31 @@ pub fn bytes_to_u64(bytes: [u8; 8]) -> u64 {
 pub fn collect_signers_checked<'a>(
     authority_info: Option<&'a AccountInfo>,
     custodian_info: Option<&'a AccountInfo>,
-) -> Result<([Pubkey; MAX_SIGNERS], Option<&'a Pubkey>, usize), ProgramError> {
-    let mut signers: [Pubkey; MAX_SIGNERS] = Default::default();
+) -> Result<([Pubkey; MAX_CHECKED_SIGNERS], Option<&'a Pubkey>, usize), ProgramError> {
+    let mut signers: [Pubkey; MAX_CHECKED_SIGNERS] = Default::default();
     let mut signers_count = 0;
 
     if let Some(authority_info) = authority_info {
         if !authority_info.is_signer() {
             return Err(ProgramError::MissingRequiredSignature);
         }
-        add_signer(&mut signers, &mut signers_count, authority_info.key());
+        add_signer(&mut signers, &mut signers_count, authority_info.key())?;
     }
 
-    let custodian = if let Some(custodian_info) = custodian_info {
-        if !custodian_info.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        add_signer(&mut signers, &mut signers_count, &custodian_info.key());
-        Some(custodian_info.key())
-    } else {
-        None
-    };
+    let custodian = signing_custodian(custodian_info, true)?;
+    if let Some(custodian) = custodian {
+        add_signer(&mut signers, &mut signers_count, custodian)?;
+    }
 
     Ok((signers, custodian, signers_count))
 }
 
-pub fn add_signer(
-    signers: &mut [Pubkey; MAX_SIGNERS],
+pub fn add_signer<const N: usize>(
+    signers: &mut [Pubkey; N],
     signers_count: &mut usize,
     account_key: &Pubkey,
 ) -> Result<(), ProgramError> {
-    if *signers_count >= MAX_SIGNERS {
+    if *signers_count >= N {
         return Err(ProgramError::MaxAccountsDataAllocationsExceeded);
     }
     signers[*signers_count] = *account_key;
@@ -444,10 +460,34 @@ pub fn add_signer(
     Ok(())
 }
 
+/// Suffix of `PDA_MARKER` in the runtime: an owner ending in it could make a seeded
+/// address collide with a program-derived one
+const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
+
+/// The address `SystemProgram::CreateAccountWithSeed` derives from `base`, `seed` and
+/// `owner`. A seed-derived authority signs through `base`, so the `*WithSeed`
+/// instructions add this key to the signers when `base` signed.
+pub fn create_with_seed(
+    base: &Pubkey,
+    seed: &[u8],
+    owner: &Pubkey,
+) -> Result<Pubkey, ProgramError> {
+    if seed.len() > pinocchio::pubkey::MAX_SEED_LEN {
+        return Err(ProgramError::MaxSeedLengthExceeded);
+    }
+    if owner.ends_with(PDA_MARKER) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(solana_sha256_hasher::hashv(&[&base[..], seed, &owner[..]]).to_bytes())
+}
+
+/// `clock` is the caller's Clock, fetched once per instruction and shared with any
+/// later checks instead of being read again here.
 pub fn move_stake_or_lamports_shared_checks(
     source_stake_account_info: &AccountInfo,
     destination_stake_account_info: &AccountInfo,
     stake_authority_info: &AccountInfo,
+    clock: &Clock,
 ) -> Result<(MergeKind, MergeKind), ProgramError> {
     // authority must sign
     let (signers, _, _) = collect_signers_checked(Some(stake_authority_info), None)?;
@@ -464,7 +504,6 @@ pub fn move_stake_or_lamports_shared_checks(
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    let clock = Clock::get()?;
     let stake_history = StakeHistorySysvar(clock.epoch);
 
     // get_if_mergeable ensures accounts are not partly activated or in any form of deactivating
@@ -472,7 +511,7 @@ pub fn move_stake_or_lamports_shared_checks(
     let source_merge_kind = MergeKind::get_if_mergeable(
         &*get_stake_state(source_stake_account_info)?,
         source_stake_account_info.lamports(),
-        &clock,
+        clock,
         &stake_history,
     )?;
 
@@ -487,7 +526,7 @@ pub fn move_stake_or_lamports_shared_checks(
     let destination_merge_kind = MergeKind::get_if_mergeable(
         &*get_stake_state(destination_stake_account_info)?,
         destination_stake_account_info.lamports(),
-        &clock,
+        clock,
         &stake_history,
     )?;
 
@@ -495,7 +534,7 @@ pub fn move_stake_or_lamports_shared_checks(
     MergeKind::metas_can_merge(
         source_merge_kind.meta(),
         destination_merge_kind.meta(),
-        &clock,
+        clock,
     )?;
 
     Ok((source_merge_kind, destination_merge_kind))
@@ -527,49 +566,115 @@ pub(crate) struct ValidatedDelegatedInfo {
 pub(crate) fn new_stake(
     stake: [u8; 8],
     voter_pubkey: &Pubkey,
-    vote_state: &VoteState,
-    activation_epoch: [u8; 8]
+    credits_observed: u64,
+    activation_epoch: u64
 ) -> Stake {
-    Stake {
-        delegation: Delegation::new(
-            voter_pubkey,
-            bytes_to_u64(stake),
-            activation_epoch
-        ),
-        credits_observed: vote_state.credits().to_le_bytes(),
-    }
+    Stake::new(
+        Delegation::new(voter_pubkey, bytes_to_u64(stake), activation_epoch),
+        credits_observed
+    )
 }
 
 /// Ensure the stake delegation amount is valid.  This checks that the account
 /// meets the minimum balance requirements of delegated stake.  If not, return
 /// an error.
+///
+/// `InitializeAndDelegate` checks its new delegation with this. `process_delegate`
+/// uses it for fresh delegations and redelegations alike, but isn't dispatched from
+/// the entrypoint yet, so `DelegateStake` itself doesn't reach it.
 pub(crate) fn validate_delegated_amount(
     account: &AccountInfo,
-    meta: &Meta
+    meta: &Meta,
+    minimum_delegation: u64
+) -> Result<ValidatedDelegatedInfo, ProgramError> {
+    validate_delegated_lamports(account.lamports(), meta, minimum_delegation)
+}
+
+/// Same check as `validate_delegated_amount()` on a raw lamport balance, against the
+/// same minimum delegation the split path enforces.
+pub(crate) fn validate_delegated_lamports(
+    lamports: u64,
+    meta: &Meta,
+    minimum_delegation: u64
 ) -> Result<ValidatedDelegatedInfo, ProgramError> {
-    let stake_amount = account.lamports().saturating_sub(bytes_to_u64(meta.rent_exempt_reserve)); // can't stake the rent
+    // below the reserve is a delegation problem, not an arithmetic one, with or without
+    // strict-math
+    if lamports < meta.rent_exempt_reserve() {
+        return Err(StakeError::InsufficientDelegation.into());
+    }
+    let stake_amount = lamports_sub(lamports, meta.rent_exempt_reserve())?; // can't stake the rent
 
     // Stake accounts may be initialized with a stake amount below the minimum
     // delegation so check that the minimum is met before delegation.
-    if stake_amount < get_minimum_delegation() {
+    if stake_amount < minimum_delegation {
         return Err(StakeError::InsufficientDelegation.into());
     }
-    Ok(ValidatedDelegatedInfo { stake_amount: stake_amount.to_be_bytes() })
+    Ok(ValidatedDelegatedInfo { stake_amount: stake_amount.to_le_bytes() })
+}
+
+/// The most lamports a Withdraw from an account holding `stake_state` and `lamports` can
+/// take at `clock.epoch`, mirroring the checks the processor applies.
+///
+/// Delegated stake that hasn't finished cooling down stays locked along with the rent
+/// reserve. An account with nothing staked may be drained completely, which closes it.
+/// Lockup is not considered: a withdrawal before it expires also needs the custodian.
+pub fn max_withdrawable<T: StakeHistoryGetEntry>(
+    stake_state: &StakeStateV2,
+    lamports: u64,
+    clock: &Clock,
+    stake_history: &T
+) -> Result<u64, ProgramError> {
+    max_withdrawable_with_features(stake_state, lamports, clock, stake_history, &FeatureSet::MAINNET)
+}
+
+/// `max_withdrawable` with stake cooling down at the rate `features` selects
+pub fn max_withdrawable_with_features<T: StakeHistoryGetEntry>(
+    stake_state: &StakeStateV2,
+    lamports: u64,
+    clock: &Clock,
+    stake_history: &T,
+    features: &FeatureSet
+) -> Result<u64, ProgramError> {
+    let (reserve, is_staked) = match stake_state {
+        StakeStateV2::Stake(meta, stake, _) => {
+            let staked = if clock.epoch >= stake.delegation.deactivation_epoch() {
+                get_stake_status_with_features(stake, clock.epoch, stake_history, features)
+                    .effective
+            } else {
+                // Assume full stake if the stake account hasn't been
+                // de-activated, because in the future the exposed stake
+                // might be higher than stake.stake() due to warmup
+                stake.delegation.delegated_stake()
+            };
+            let staked_and_reserve = staked
+                .checked_add(meta.rent_exempt_reserve())
+                .ok_or(ProgramError::InsufficientFunds)?;
+            (staked_and_reserve, staked != 0)
+        }
+        StakeStateV2::Initialized(meta) => (meta.rent_exempt_reserve(), false),
+        StakeStateV2::Uninitialized => (0, false),
+        StakeStateV2::RewardsPool => {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    };
+
+    if is_staked {
+        Ok(lamports.saturating_sub(reserve))
+    } else {
+        Ok(lamports)
+    }
 }
 
-pub(crate) fn redelegate_stake(
+pub(crate) fn redelegate_stake<T: StakeHistoryGetEntry>(
     stake: &mut Stake,
     stake_lamports: [u8; 8],
     voter_pubkey: &Pubkey,
-    vote_state: &VoteState,
+    credits_observed: u64,
     epoch: [u8;8],
-    stake_history: &StakeHistorySysvar
+    stake_history: &T
 ) -> Result<(), ProgramError> {
     // If stake is currently active:
-    if
-        stake.stake(epoch, stake_history, PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH) !=
-        0
-    {
+    if get_stake_status(stake, bytes_to_u64(epoch), stake_history).effective != 0 {
         // If pubkey of new voter is the same as current,
         // and we are scheduled to start deactivating this epoch,
         // we rescind deactivation
@@ -577,7 +682,7 @@ pub(crate) fn redelegate_stake(
             stake.delegation.voter_pubkey == *voter_pubkey &&
             epoch == stake.delegation.deactivation_epoch
         {
-            stake.delegation.deactivation_epoch = u64::MAX.to_le_bytes();
+            stake.delegation.set_deactivation_epoch(u64::MAX);
             return Ok(());
         } else {
             // can't redelegate to another pubkey if stake is active.
@@ -590,9 +695,9 @@ pub(crate) fn redelegate_stake(
 
     stake.delegation.stake = stake_lamports;
     stake.delegation.activation_epoch = epoch;
-    stake.delegation.deactivation_epoch = u64::MAX.to_le_bytes();
+    stake.delegation.set_deactivation_epoch(u64::MAX);
     stake.delegation.voter_pubkey = *voter_pubkey;
-    stake.credits_observed = vote_state.credits().to_be_bytes();
+    stake.set_credits_observed(credits_observed);
     Ok(())
 }
 
@@ -637,6 +742,16 @@ impl fmt::Display for Hash {
     }
 }
 
+/// Formats a raw `Pubkey` in base58 the way `solana_pubkey::Pubkey`'s `Debug` does, for
+/// the state types' `Debug` impls
+pub(crate) struct Base58<'a>(pub(crate) &'a Pubkey);
+
+impl fmt::Debug for Base58<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_as_base58(f, &Hash(*self.0))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseHashError {
     WrongSize,
@@ -701,59 +816,204 @@ impl Hash {
     // }
 }
 
-#[cfg(target_arch = "wasm32")]
-#[allow(non_snake_case)]
-#[wasm_bindgen]
-impl Hash {
-    /// Create a new Hash object
-    ///
-    /// * `value` - optional hash as a base58 encoded string, `Uint8Array`, `[number]`
-    #[wasm_bindgen(constructor)]
-    pub fn constructor(value: JsValue) -> Result<Hash, JsValue> {
-        if let Some(base58_str) = value.as_string() {
-            base58_str.parse::<Hash>().map_err(|x| JsValue::from(x.to_string()))
-        } else if let Some(uint8_array) = value.dyn_ref::<Uint8Array>() {
-            <[u8; HASH_BYTES]>
-                ::try_from(uint8_array.to_vec())
-                .map(Hash::new_from_array)
-                .map_err(|err| format!("Invalid Hash value: {err:?}").into())
-        } else if let Some(array) = value.dyn_ref::<Array>() {
-            let mut bytes = vec![];
-            let iterator = js_sys::try_iter(&array.values())?.expect("array to be iterable");
-            for x in iterator {
-                let x = x?;
-
-                if let Some(n) = x.as_f64() {
-                    if n >= 0.0 && n <= 255.0 {
-                        bytes.push(n as u8);
-                        continue;
-                    }
-                }
-                return Err(format!("Invalid array argument: {:?}", x).into());
-            }
-            <[u8; HASH_BYTES]>
-                ::try_from(bytes)
-                .map(Hash::new_from_array)
-                .map_err(|err| format!("Invalid Hash value: {err:?}").into())
-        } else if value.is_undefined() {
-            Ok(Hash::default())
-        } else {
-            Err("Unsupported argument".into())
-        }
-    }
-
-    /// Return the base58 string representation of the hash
-    pub fn toString(&self) -> String {
-        self.to_string()
-    }
-
-    /// Checks if two `Hash`s are equal
-    pub fn equals(&self, other: &Hash) -> bool {
-        self == other
-    }
-
-    /// Return the `Uint8Array` representation of the hash
-    pub fn toBytes(&self) -> Box<[u8]> {
-        self.0.clone().into()
+#[cfg(test)]
+mod test {
+    use super::{
+        add_signer, collect_signers_checked, collect_signers_checked_with_custodian,
+        create_with_seed, max_withdrawable,
+        stake_rent_exempt_reserve, validate_delegated_lamports, validate_split_amount, Meta,
+    };
+    use crate::state::{
+        Delegation, MockSysvars, SignerSet, Stake, StakeFlags, StakeHistory, StakeStateV2,
+        SysvarProvider,
+    };
+    use crate::error::StakeError;
+    use crate::test_utils::{SerializedInput, TestAccount};
+    use pinocchio::{account_info::AccountInfo, program_error::ProgramError, sysvars::clock::Clock};
+
+    #[test]
+    fn test_checked_signers_hold_authority_custodian_and_seed() {
+        let mut input = SerializedInput::new(&[
+            TestAccount::signer([1; 32]),
+            TestAccount::signer([2; 32]),
+        ]);
+        let [authority, custodian]: [AccountInfo; 2] = input.accounts();
+
+        let (mut signers, custodian, mut count) =
+            collect_signers_checked(Some(&authority), Some(&custodian)).unwrap();
+        assert_eq!(custodian, Some(&[2; 32]));
+        assert_eq!(count, 2);
+
+        // the seed-derived key the `*WithSeed` instructions add still fits
+        add_signer(&mut signers, &mut count, &[3; 32]).unwrap();
+        assert_eq!(signers, [[1; 32], [2; 32], [3; 32]]);
+        assert_eq!(
+            add_signer(&mut signers, &mut count, &[4; 32]),
+            Err(ProgramError::MaxAccountsDataAllocationsExceeded)
+        );
+    }
+
+    #[test]
+    fn test_signers_with_custodian() {
+        let mut input = SerializedInput::new(&[
+            TestAccount::signer([1; 32]),
+            TestAccount::signer([2; 32]),
+            TestAccount::readonly([3; 32]),
+        ]);
+        let accounts: [AccountInfo; 3] = input.accounts();
+
+        let (signers, custodian) =
+            collect_signers_checked_with_custodian(&accounts, accounts.get(1), true).unwrap();
+        assert_eq!(custodian, Some(&[2; 32]));
+        assert!(signers.contains_signer(&[1; 32]));
+
+        let (_, custodian) =
+            collect_signers_checked_with_custodian(&accounts, None, true).unwrap();
+        assert_eq!(custodian, None);
+
+        // an unsigned custodian is dropped, or rejected when it has to sign
+        let (_, custodian) =
+            collect_signers_checked_with_custodian(&accounts, accounts.get(2), false).unwrap();
+        assert_eq!(custodian, None);
+        assert_eq!(
+            collect_signers_checked_with_custodian(&accounts, accounts.get(2), true).err(),
+            Some(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_create_with_seed_matches_native() {
+        use solana_sdk::pubkey::Pubkey as NativePubkey;
+
+        let base = [7; 32];
+        let owner = [8; 32];
+        let native = NativePubkey::create_with_seed(
+            &NativePubkey::new_from_array(base),
+            "withdrawer",
+            &NativePubkey::new_from_array(owner),
+        )
+        .unwrap();
+        assert_eq!(
+            create_with_seed(&base, b"withdrawer", &owner),
+            Ok(native.to_bytes())
+        );
+
+        assert_eq!(
+            create_with_seed(&base, &[b'x'; 33], &owner),
+            Err(ProgramError::MaxSeedLengthExceeded)
+        );
+        let mut pda_owner = [0; 32];
+        pda_owner[11..].copy_from_slice(b"ProgramDerivedAddress");
+        assert_eq!(
+            create_with_seed(&base, b"withdrawer", &pda_owner),
+            Err(ProgramError::IllegalOwner)
+        );
+    }
+
+    #[test]
+    fn test_validate_delegated_lamports() {
+        let mut meta = Meta::default();
+        meta.set_rent_exempt_reserve(2_282_880);
+
+        let info = validate_delegated_lamports(2_282_880 + 42, &meta, 1).unwrap();
+        assert_eq!(u64::from_le_bytes(info.stake_amount), 42);
+
+        assert_eq!(
+            validate_delegated_lamports(2_282_880, &meta, 1).err(),
+            Some(ProgramError::from(StakeError::InsufficientDelegation))
+        );
+        // short of the reserve itself, which strict-math must not turn into an overflow
+        assert_eq!(
+            validate_delegated_lamports(2_282_880 - 1, &meta, 1).err(),
+            Some(ProgramError::from(StakeError::InsufficientDelegation))
+        );
+    }
+
+    #[test]
+    fn test_max_withdrawable() {
+        let reserve = 2_282_880;
+        let mut meta = Meta::default();
+        meta.set_rent_exempt_reserve(reserve);
+        let history = StakeHistory::default();
+        let clock = Clock { epoch: 10, ..Clock::default() };
+        let lamports = reserve + 1_000;
+
+        assert_eq!(
+            max_withdrawable(&StakeStateV2::Uninitialized, lamports, &clock, &history),
+            Ok(lamports)
+        );
+        // nothing staked, so the account can be closed
+        assert_eq!(
+            max_withdrawable(&StakeStateV2::Initialized(meta), lamports, &clock, &history),
+            Ok(lamports)
+        );
+        assert_eq!(
+            max_withdrawable(&StakeStateV2::RewardsPool, lamports, &clock, &history),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        // active stake and the reserve stay locked
+        let mut stake = Stake::new(Delegation::new(&[1; 32], 600, u64::MAX), 0);
+        let state = StakeStateV2::Stake(meta, stake, StakeFlags::empty());
+        assert_eq!(max_withdrawable(&state, lamports, &clock, &history), Ok(400));
+
+        // deactivating this epoch is still fully effective
+        stake.delegation.set_deactivation_epoch(10);
+        let state = StakeStateV2::Stake(meta, stake, StakeFlags::empty());
+        assert_eq!(max_withdrawable(&state, lamports, &clock, &history), Ok(400));
+
+        // with no history for the deactivation epoch the stake is fully cooled down
+        stake.delegation.set_deactivation_epoch(5);
+        let state = StakeStateV2::Stake(meta, stake, StakeFlags::empty());
+        assert_eq!(max_withdrawable(&state, lamports, &clock, &history), Ok(lamports));
+    }
+
+    #[test]
+    fn test_validate_split_amount() {
+        let rent = MockSysvars::default().rent().unwrap();
+        let reserve = stake_rent_exempt_reserve(&rent);
+        let mut meta = Meta::default();
+        meta.set_rent_exempt_reserve(reserve);
+        let split = |source, destination, lamports, additional, active| {
+            validate_split_amount(
+                source,
+                destination,
+                lamports,
+                &meta,
+                reserve,
+                additional,
+                active,
+            )
+        };
+
+        let info = split(reserve * 2, 0, reserve, 0, false).unwrap();
+        assert_eq!(info.source_remaining_balance, reserve);
+        assert_eq!(info.destination_rent_exempt_reserve, reserve);
+
+        // full split closes the source
+        let info = split(reserve * 2, 0, reserve * 2, 0, false).unwrap();
+        assert_eq!(info.source_remaining_balance, 0);
+
+        assert_eq!(split(reserve * 2, 0, 0, 0, false).err(), Some(ProgramError::InsufficientFunds));
+        // source left below its reserve plus the minimum delegation
+        assert_eq!(
+            split(reserve * 2, 0, reserve, 1, false).err(),
+            Some(ProgramError::InsufficientFunds)
+        );
+        // destination short of its reserve
+        assert_eq!(
+            split(reserve * 2, 0, reserve - 1, 0, false).err(),
+            Some(ProgramError::InsufficientFunds)
+        );
+        // a prefunded destination lowers the minimum split
+        assert!(split(reserve * 2, reserve, 1, 0, false).is_ok());
+
+        // active stake needs a prefunded destination unless the split is total
+        assert_eq!(
+            split(reserve * 3, 0, reserve + 1, 1, true).err(),
+            Some(ProgramError::InsufficientFunds)
+        );
+        assert!(split(reserve * 3, reserve, reserve, 1, true).is_ok());
+        assert!(split(reserve * 3, 0, reserve * 3, 1, true).is_ok());
     }
-}
\ No newline at end of file
+}
@@ -1,12 +1,17 @@
 use pinocchio::{
-    account_info::{ AccountInfo, Ref },
     program_error::ProgramError,
     pubkey::Pubkey,
     sysvars::{ clock::{ Clock, Epoch, Slot, UnixTimestamp }, rent::Rent },
 };
 use alloc::collections::VecDeque;
 use alloc::vec::Vec;
-use crate::{consts::{INITIAL_LOCKOUT, MAX_LOCKOUT_HISTORY}, state::Hash};
+use crate::{
+    consts::{
+        INITIAL_LOCKOUT, MAX_LOCKOUT_HISTORY, VOTE_STATE_VERSION_CURRENT,
+        VOTE_STATE_VERSION_V0_23_5, VOTE_STATE_VERSION_V1_14_11,
+    },
+    state::Hash,
+};
 
 // available in /solana-vote-interface-2.2.4/src/state/vote_state_v3.rs
 #[repr(C)]
@@ -76,18 +81,45 @@ impl VoteState {
         3762 // see test_vote_state_size_of.
     }
 
-    #[inline]
-    pub fn from_account_info(account_info: &AccountInfo) -> Result<Ref<VoteState>, ProgramError> {
-        if account_info.data_len() != Self::size_of() {
-            return Err(ProgramError::InvalidAccountData);
+    /// The raw `epoch_credits` entries of a serialized `VoteStateVersions`, 24 bytes
+    /// each: `(epoch, credits, prev_credits)` as little-endian u64s. The fields before
+    /// them are walked with every length checked against `data`, so this works on any
+    /// version the stake program accepts and fails rather than reading past the end.
+    pub fn epoch_credits_from_bytes(data: &[u8]) -> Result<&[u8], ProgramError> {
+        let mut reader = VoteStateReader::new(data);
+        match reader.read_u32()? {
+            VOTE_STATE_VERSION_V0_23_5 => {
+                // node_pubkey, authorized_voter, authorized_voter_epoch, prior_voters,
+                // authorized_withdrawer, commission
+                reader.skip(32 + 32 + 8 + PRIOR_VOTERS_V0_23_5_LEN + 32 + 1)?;
+                reader.skip_vec(LOCKOUT_LEN)?;
+                reader.skip_option_u64()?;
+            }
+            version @ (VOTE_STATE_VERSION_V1_14_11 | VOTE_STATE_VERSION_CURRENT) => {
+                // node_pubkey, authorized_withdrawer, commission
+                reader.skip(32 + 32 + 1)?;
+                reader.skip_vec(if version == VOTE_STATE_VERSION_CURRENT {
+                    LANDED_VOTE_LEN
+                } else {
+                    LOCKOUT_LEN
+                })?;
+                reader.skip_option_u64()?;
+                reader.skip_vec(AUTHORIZED_VOTER_LEN)?;
+                reader.skip(PRIOR_VOTERS_LEN)?;
+            }
+            _ => return Err(ProgramError::InvalidAccountData),
         }
-        let data = account_info.try_borrow_data()?;
-        Ok(Ref::map(data, |data| unsafe { Self::from_bytes(data) }))
+        let len = reader.read_len(EPOCH_CREDITS_ENTRY_LEN)?;
+        reader.take(len)
     }
 
-    #[inline(always)]
-    pub unsafe fn from_bytes(bytes: &[u8]) -> &Self {
-        &*(bytes.as_ptr() as *const Self)
+    /// `credits()` of the serialized `VoteStateVersions` in `data`
+    pub fn credits_from_bytes(data: &[u8]) -> Result<u64, ProgramError> {
+        let epoch_credits = Self::epoch_credits_from_bytes(data)?;
+        Ok(match epoch_credits.len().checked_sub(EPOCH_CREDITS_ENTRY_LEN) {
+            Some(last) => u64::from_le_bytes(epoch_credits[last + 8..last + 16].try_into().unwrap()),
+            None => 0,
+        })
     }
 
     /// Number of "credits" owed to this account from the mining pool. Submit this
@@ -101,6 +133,73 @@ impl VoteState {
     }
 }
 
+// Serialized sizes of the fields `VoteState::epoch_credits_from_bytes` steps over
+
+/// `Lockout`: slot, confirmation count
+const LOCKOUT_LEN: usize = 8 + 4;
+/// `LandedVote`: latency, then the lockout
+const LANDED_VOTE_LEN: usize = 1 + LOCKOUT_LEN;
+/// `AuthorizedVoters` entry: epoch, voter
+const AUTHORIZED_VOTER_LEN: usize = 8 + 32;
+/// `CircBuf<(Pubkey, Epoch, Epoch)>`: the buffer, `idx` and `is_empty`
+const PRIOR_VOTERS_LEN: usize = MAX_ITEMS * (32 + 8 + 8) + 8 + 1;
+/// The 0.23.5 `CircBuf<(Pubkey, Epoch, Epoch, Slot)>`, which has no `is_empty`
+const PRIOR_VOTERS_V0_23_5_LEN: usize = MAX_ITEMS * (32 + 8 + 8 + 8) + 8;
+/// `epoch_credits` entry: epoch, credits, prev_credits
+const EPOCH_CREDITS_ENTRY_LEN: usize = 8 + 8 + 8;
+
+/// Bounds-checked cursor over bincode-serialized vote state
+struct VoteStateReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> VoteStateReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ProgramError> {
+        let end = self.offset.checked_add(len).ok_or(ProgramError::InvalidAccountData)?;
+        let bytes = self.data.get(self.offset..end).ok_or(ProgramError::InvalidAccountData)?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), ProgramError> {
+        self.take(len).map(|_| ())
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ProgramError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, ProgramError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Byte length of a sequence of `entry_len`-byte entries, from its u64 count
+    fn read_len(&mut self, entry_len: usize) -> Result<usize, ProgramError> {
+        usize::try_from(self.read_u64()?)
+            .ok()
+            .and_then(|count| count.checked_mul(entry_len))
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+
+    fn skip_vec(&mut self, entry_len: usize) -> Result<(), ProgramError> {
+        let len = self.read_len(entry_len)?;
+        self.skip(len)
+    }
+
+    fn skip_option_u64(&mut self) -> Result<(), ProgramError> {
+        match self.take(1)?[0] {
+            0 => Ok(()),
+            1 => self.skip(8),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
 // -------------solana-vote-interface/src/state/mod.rs------------------
 /// Vote state
 
@@ -1242,3 +1341,84 @@ impl<I> CircBuf<I> {
 //         assert_eq!(circ_buf.last(), None);
 //     }
 // }
+
+#[cfg(test)]
+mod test {
+    use super::VoteState;
+    use pinocchio::program_error::ProgramError;
+    use solana_sdk::{
+        clock::Clock,
+        pubkey::Pubkey,
+        vote::state::{VoteInit, VoteState as NativeVoteState, VoteState1_14_11, VoteStateVersions},
+    };
+
+    fn native_vote_state(epoch_credits: &[(u64, u64, u64)]) -> NativeVoteState {
+        let vote_init = VoteInit {
+            node_pubkey: Pubkey::new_unique(),
+            authorized_voter: Pubkey::new_unique(),
+            authorized_withdrawer: Pubkey::new_unique(),
+            commission: 5,
+        };
+        let mut vote_state = NativeVoteState {
+            epoch_credits: epoch_credits.to_vec(),
+            root_slot: Some(7),
+            ..NativeVoteState::new(&vote_init, &Clock::default())
+        };
+        for slot in 0..5 {
+            vote_state.process_next_vote_slot(slot, 0, 0);
+        }
+        vote_state
+    }
+
+    #[test]
+    fn test_credits_from_bytes() {
+        let epoch_credits = [(3, 100, 0), (4, 250, 100)];
+        for versions in [
+            VoteStateVersions::new_current(native_vote_state(&epoch_credits)),
+            VoteStateVersions::V1_14_11(Box::new(VoteState1_14_11::from(native_vote_state(
+                &epoch_credits,
+            )))),
+        ] {
+            let data = bincode::serialize(&versions).unwrap();
+            let entries = VoteState::epoch_credits_from_bytes(&data).unwrap();
+            assert_eq!(entries.len(), 2 * 24);
+            assert_eq!(VoteState::credits_from_bytes(&data), Ok(250));
+        }
+
+        let data = bincode::serialize(&VoteStateVersions::new_current(native_vote_state(&[])))
+            .unwrap();
+        assert_eq!(VoteState::credits_from_bytes(&data), Ok(0));
+    }
+
+    #[test]
+    fn test_credits_from_bytes_is_bounds_checked() {
+        let data = bincode::serialize(&VoteStateVersions::new_current(native_vote_state(&[(
+            3, 100, 0,
+        )])))
+        .unwrap();
+
+        // cut anywhere short of the last entry
+        for len in [0, 3, 4, 100, data.len() - 44, data.len() - 17] {
+            assert_eq!(
+                VoteState::credits_from_bytes(&data[..len]),
+                Err(ProgramError::InvalidAccountData)
+            );
+        }
+
+        // a votes length that runs past the end of the account
+        let mut huge_votes = data.clone();
+        huge_votes[69..77].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            VoteState::credits_from_bytes(&huge_votes),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        // an unknown version
+        let mut unknown = data;
+        unknown[..4].copy_from_slice(&3u32.to_le_bytes());
+        assert_eq!(
+            VoteState::credits_from_bytes(&unknown),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! `AccountInfo`s for driving processors in host unit tests. The accounts are written
+//! in the runtime's serialized input layout and handed to pinocchio's own
+//! deserializer, the same path the entrypoint takes.
+//!
+//! Test builds also count heap allocations per thread, so a test can assert that a
+//! processor stays off the heap like the `no_allocator!` on-chain build requires.
+//!
+//! `EpochSimulation` steps a cluster of delegations through epochs, so warmup and
+//! cooldown can be checked against the history they produce themselves.
+
+use alloc::{vec, vec::Vec};
+use core::{cell::Cell, mem::MaybeUninit};
+use std::alloc::{GlobalAlloc, Layout, System};
+
+use pinocchio::{
+    account_info::AccountInfo, entrypoint::deserialize, pubkey::Pubkey, sysvars::clock::Clock,
+};
+
+use crate::{
+    consts::{PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH, VOTE_PROGRAM_ID},
+    state::{
+        Delegation, MockSysvars, StakeActivationStatus, StakeHistory, StakeHistoryEntry,
+        StakeStateV2,
+    },
+};
+
+/// Realloc headroom the runtime reserves after each account's data
+const MAX_PERMITTED_DATA_INCREASE: usize = 10 * 1024;
+
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl TestAccount {
+    /// Writable, program-owned stake account holding `state`
+    pub fn stake(key: Pubkey, lamports: u64, state: StakeStateV2) -> Self {
+        let mut data = vec![0; StakeStateV2::size_of()];
+        unsafe { (data.as_mut_ptr() as *mut StakeStateV2).write_unaligned(state) };
+        Self {
+            key,
+            owner: crate::ID,
+            lamports,
+            data,
+            is_signer: false,
+            is_writable: true,
+        }
+    }
+
+    /// Read-only signer with no data
+    pub fn signer(key: Pubkey) -> Self {
+        Self {
+            is_signer: true,
+            ..Self::readonly(key)
+        }
+    }
+
+    /// Writable system account with no data or lamports, e.g. a withdrawal recipient
+    pub fn writable(key: Pubkey) -> Self {
+        Self {
+            is_writable: true,
+            ..Self::readonly(key)
+        }
+    }
+
+    /// Read-only vote account holding a current-version vote state, serialized by the
+    /// vote program's own types, that earned `epoch_credits`
+    pub fn vote(key: Pubkey, epoch_credits: &[(u64, u64, u64)]) -> Self {
+        use solana_sdk::vote::state::{VoteState, VoteStateVersions};
+
+        let vote_state = VoteState {
+            epoch_credits: epoch_credits.to_vec(),
+            ..VoteState::default()
+        };
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data).unwrap();
+        Self {
+            owner: VOTE_PROGRAM_ID,
+            data,
+            ..Self::readonly(key)
+        }
+    }
+
+    /// Read-only non-signer with no data, for accounts a processor only passes over
+    pub fn readonly(key: Pubkey) -> Self {
+        Self {
+            key,
+            owner: [0; 32],
+            lamports: 0,
+            data: Vec::new(),
+            is_signer: false,
+            is_writable: false,
+        }
+    }
+}
+
+/// Serialized program input; the `AccountInfo`s it hands out point into it
+pub struct SerializedInput(Vec<u64>);
+
+impl SerializedInput {
+    pub fn new(accounts: &[TestAccount]) -> Self {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(accounts.len() as u64).to_le_bytes());
+        for account in accounts {
+            // not a duplicate, which doubles as the unborrowed state
+            bytes.push(u8::MAX);
+            bytes.push(account.is_signer as u8);
+            bytes.push(account.is_writable as u8);
+            // executable
+            bytes.push(0);
+            // resize delta
+            bytes.extend_from_slice(&[0; 4]);
+            bytes.extend_from_slice(&account.key);
+            bytes.extend_from_slice(&account.owner);
+            bytes.extend_from_slice(&account.lamports.to_le_bytes());
+            bytes.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&account.data);
+            bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            bytes.resize(bytes.len().next_multiple_of(8), 0);
+            // rent epoch
+            bytes.extend_from_slice(&u64::MAX.to_le_bytes());
+        }
+        // empty instruction data, then the program id
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.extend_from_slice(&crate::ID);
+
+        // u64 words keep the buffer aligned like the runtime's
+        let mut words = vec![0u64; bytes.len().div_ceil(8)];
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                words.as_mut_ptr() as *mut u8,
+                bytes.len(),
+            )
+        };
+        Self(words)
+    }
+
+    pub fn accounts<const N: usize>(&mut self) -> [AccountInfo; N] {
+        let mut accounts = [const { MaybeUninit::<AccountInfo>::uninit() }; N];
+        let (_, count, _) =
+            unsafe { deserialize::<N>(self.0.as_mut_ptr() as *mut u8, &mut accounts) };
+        assert_eq!(count, N);
+        accounts.map(|account| unsafe { account.assume_init() })
+    }
+}
+
+/// Mainnet's epoch length at 400ms slots, for keeping the simulated clock plausible
+const SLOTS_PER_EPOCH: u64 = 432_000;
+const SECONDS_PER_EPOCH: i64 = 172_800;
+
+/// Delegations making up the whole cluster, advanced epoch by epoch. Each boundary
+/// records the cluster's totals for the ending epoch into the stake history, as the
+/// runtime does, and every status is re-derived from that history, so a delegation's
+/// warmup and cooldown depend on everyone else's the way they do on chain.
+///
+/// Bootstrap delegations (`activation_epoch` of `u64::MAX`) make a convenient
+/// already-effective rest of the cluster.
+pub struct EpochSimulation {
+    pub clock: Clock,
+    pub stake_history: StakeHistory,
+    pub delegations: Vec<Delegation>,
+}
+
+impl EpochSimulation {
+    pub fn new(epoch: u64, delegations: Vec<Delegation>) -> Self {
+        Self {
+            clock: Clock {
+                slot: epoch * SLOTS_PER_EPOCH,
+                epoch_start_timestamp: epoch as i64 * SECONDS_PER_EPOCH,
+                epoch,
+                leader_schedule_epoch: epoch + 1,
+                unix_timestamp: epoch as i64 * SECONDS_PER_EPOCH,
+            },
+            stake_history: StakeHistory::default(),
+            delegations,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.clock.epoch
+    }
+
+    /// Status of `delegations[index]` at the current epoch
+    pub fn status(&self, index: usize) -> StakeActivationStatus {
+        self.delegations[index].stake_activating_and_deactivating(
+            self.epoch().to_le_bytes(),
+            &self.stake_history,
+            PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+        )
+    }
+
+    /// Totals over every delegation at the current epoch, what `advance` records
+    pub fn cluster(&self) -> StakeActivationStatus {
+        (0..self.delegations.len())
+            .map(|index| self.status(index))
+            .fold(StakeActivationStatus::default(), |total, status| {
+                StakeActivationStatus {
+                    effective: total.effective + status.effective,
+                    activating: total.activating + status.activating,
+                    deactivating: total.deactivating + status.deactivating,
+                }
+            })
+    }
+
+    /// Ends the current epoch: records its totals and moves the clock into the next
+    pub fn advance(&mut self) {
+        let entry = StakeHistoryEntry::from(self.cluster());
+        self.stake_history.add(self.epoch(), entry);
+        self.clock.epoch += 1;
+        self.clock.leader_schedule_epoch += 1;
+        self.clock.slot += SLOTS_PER_EPOCH;
+        self.clock.unix_timestamp += SECONDS_PER_EPOCH;
+        self.clock.epoch_start_timestamp = self.clock.unix_timestamp;
+    }
+
+    pub fn advance_to(&mut self, epoch: u64) {
+        while self.epoch() < epoch {
+            self.advance();
+        }
+    }
+
+    /// The clock and stake history as of the current epoch, for running processors
+    pub fn sysvars(&self) -> MockSysvars {
+        MockSysvars {
+            clock: Clock { ..self.clock },
+            stake_history: self.stake_history.clone(),
+            ..MockSysvars::default()
+        }
+    }
+}
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // `try_with` because the thread-local may already be gone during thread exit
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs `f` and returns its result with the number of allocations it made on this
+/// thread
+pub fn heap_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
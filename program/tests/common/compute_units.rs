@@ -0,0 +1,75 @@
+//! Instructions whose compute units are tracked, shared by the bencher in
+//! `benches/compute_units.rs` and the regression check in `tests/compute_units.rs`.
+
+use mollusk_svm::Mollusk;
+use solana_sdk::{account::Account, instruction::Instruction, pubkey::Pubkey};
+
+use super::*;
+
+/// Baseline the regression check compares against, keyed by case name
+pub const BASELINE_PATH: &str = "benches/compute_units.json";
+
+pub struct Case {
+    pub name: &'static str,
+    pub instruction: Instruction,
+    pub accounts: Vec<(Pubkey, Account)>,
+}
+
+/// One successful run of every dispatched instruction path worth tracking
+pub fn cases(mollusk: &Mollusk) -> Vec<Case> {
+    let reserve = rent_exempt_reserve(mollusk);
+    let authority = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let voter = Pubkey::new_unique();
+    let meta = meta(mollusk, &authority);
+    let stake = 1_000_000_000;
+
+    let split = |name, source_account: Account, destination_lamports, lamports| Case {
+        name,
+        instruction: split_instruction(&source, &destination, &authority, lamports),
+        accounts: vec![
+            (source, source_account),
+            (
+                destination,
+                uninitialized_stake_account(destination_lamports),
+            ),
+            (authority, authority_account()),
+        ],
+    };
+
+    vec![
+        split(
+            "Split: initialized",
+            initialized_stake_account(2 * reserve + stake, meta),
+            0,
+            reserve + stake / 2,
+        ),
+        split(
+            "Split: activating",
+            delegated_stake_account(reserve + stake, meta, inactive_stake(&voter, stake, 1)),
+            0,
+            reserve + stake / 2,
+        ),
+        split(
+            "Split: active",
+            delegated_stake_account(reserve + stake, meta, active_stake(&voter, stake)),
+            reserve,
+            stake / 2,
+        ),
+        split(
+            "Split: entire balance",
+            delegated_stake_account(reserve + stake, meta, active_stake(&voter, stake)),
+            0,
+            reserve + stake,
+        ),
+        Case {
+            name: "SetLockup: epoch",
+            instruction: set_lockup_epoch_instruction(&source, &authority, 10),
+            accounts: vec![
+                (source, initialized_stake_account(reserve, meta)),
+                (authority, authority_account()),
+            ],
+        },
+    ]
+}
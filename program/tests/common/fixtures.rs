@@ -0,0 +1,88 @@
+//! Loader for the captured stake accounts in `tests/fixtures/stake_accounts`, see the
+//! README there for the format and how to capture more.
+
+use std::{fs, path::Path, str::FromStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+const FIXTURES_DIR: &str = "tests/fixtures/stake_accounts";
+
+pub struct Fixture {
+    pub label: String,
+    pub description: String,
+    /// Epoch the account was captured in
+    pub epoch: u64,
+    pub pubkey: Pubkey,
+    pub account: Account,
+}
+
+#[derive(Deserialize)]
+struct FixtureFile {
+    label: String,
+    description: String,
+    epoch: u64,
+    account: KeyedUiAccount,
+}
+
+// shape of `solana account --output json-compact`
+#[derive(Deserialize)]
+struct KeyedUiAccount {
+    pubkey: Option<String>,
+    account: UiAccount,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UiAccount {
+    lamports: u64,
+    data: (String, String),
+    owner: String,
+    executable: bool,
+    rent_epoch: u64,
+}
+
+impl FixtureFile {
+    fn into_fixture(self) -> Fixture {
+        let (data, encoding) = self.account.account.data;
+        assert_eq!(encoding, "base64", "{}: unsupported encoding", self.label);
+        Fixture {
+            pubkey: self
+                .account
+                .pubkey
+                .map(|key| Pubkey::from_str(&key).unwrap())
+                // seed vectors have no address
+                .unwrap_or_else(Pubkey::new_unique),
+            account: Account {
+                lamports: self.account.account.lamports,
+                data: STANDARD.decode(data).unwrap(),
+                owner: Pubkey::from_str(&self.account.account.owner).unwrap(),
+                executable: self.account.account.executable,
+                rent_epoch: self.account.account.rent_epoch,
+            },
+            label: self.label,
+            description: self.description,
+            epoch: self.epoch,
+        }
+    }
+}
+
+/// Every fixture in the corpus, sorted by file name
+pub fn stake_account_fixtures() -> Vec<Fixture> {
+    let mut paths = fs::read_dir(Path::new(FIXTURES_DIR))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let file: FixtureFile = serde_json::from_str(&fs::read_to_string(path).unwrap())
+                .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+            file.into_fixture()
+        })
+        .collect()
+}
@@ -0,0 +1,147 @@
+//! Shared mollusk fixtures: stake accounts in each state and the sysvars the
+//! processors read. Tests need the program built with `cargo build-sbf` first.
+
+#![allow(dead_code)]
+
+pub mod compute_units;
+pub mod fixtures;
+
+use mollusk_svm::Mollusk;
+use solana_pinocchio_starter::{
+    state::{Authorized, Delegation, Lockup, Meta, Stake, StakeFlags, StakeStateV2},
+    ID,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+pub const PROGRAM: Pubkey = Pubkey::new_from_array(ID);
+
+pub fn mollusk() -> Mollusk {
+    Mollusk::new(&PROGRAM, "target/deploy/solana_pinocchio_starter")
+}
+
+pub fn rent_exempt_reserve(mollusk: &Mollusk) -> u64 {
+    mollusk
+        .sysvars
+        .rent
+        .minimum_balance(StakeStateV2::size_of())
+}
+
+/// `authority` as staker and withdrawer, no lockup, and the reserve for a stake account
+pub fn meta(mollusk: &Mollusk, authority: &Pubkey) -> Meta {
+    Meta::new(
+        rent_exempt_reserve(mollusk),
+        Authorized::auto(&authority.to_bytes()),
+        Lockup::default(),
+    )
+}
+
+/// Delegation that is fully effective at every epoch
+pub fn active_stake(voter: &Pubkey, stake: u64) -> Stake {
+    Stake::new(
+        Delegation::new(&voter.to_bytes(), stake, u64::MAX),
+        0,
+    )
+}
+
+/// Delegation that only starts activating at `activation_epoch`
+pub fn inactive_stake(voter: &Pubkey, stake: u64, activation_epoch: u64) -> Stake {
+    Stake::new(
+        Delegation::new(&voter.to_bytes(), stake, activation_epoch),
+        0,
+    )
+}
+
+pub fn stake_state_data(state: &StakeStateV2) -> Vec<u8> {
+    unsafe {
+        core::slice::from_raw_parts(
+            state as *const StakeStateV2 as *const u8,
+            StakeStateV2::size_of(),
+        )
+    }
+    .to_vec()
+}
+
+pub fn stake_state(account: &Account) -> StakeStateV2 {
+    StakeStateV2::deserialize(&account.data).unwrap()
+}
+
+pub fn stake_account_with_state(lamports: u64, state: &StakeStateV2) -> Account {
+    Account {
+        lamports,
+        data: stake_state_data(state),
+        owner: PROGRAM,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn uninitialized_stake_account(lamports: u64) -> Account {
+    stake_account_with_state(lamports, &StakeStateV2::Uninitialized)
+}
+
+pub fn initialized_stake_account(lamports: u64, meta: Meta) -> Account {
+    stake_account_with_state(lamports, &StakeStateV2::Initialized(meta))
+}
+
+pub fn delegated_stake_account(lamports: u64, meta: Meta, stake: Stake) -> Account {
+    stake_account_with_state(
+        lamports,
+        &StakeStateV2::Stake(meta, stake, StakeFlags::empty()),
+    )
+}
+
+pub fn clock_account(mollusk: &Mollusk) -> (Pubkey, Account) {
+    mollusk.sysvars.keyed_account_for_clock_sysvar()
+}
+
+pub fn rent_account(mollusk: &Mollusk) -> (Pubkey, Account) {
+    mollusk.sysvars.keyed_account_for_rent_sysvar()
+}
+
+pub fn stake_history_account(mollusk: &Mollusk) -> (Pubkey, Account) {
+    mollusk.sysvars.keyed_account_for_stake_history_sysvar()
+}
+
+pub fn split_instruction(
+    source: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction::new_with_bytes(
+        PROGRAM,
+        &data,
+        vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Plain system account used as a signer
+pub fn authority_account() -> Account {
+    Account::new(0, 0, &solana_sdk::system_program::ID)
+}
+
+/// SetLockup moving the lockup epoch, signed by the withdrawer
+pub fn set_lockup_epoch_instruction(stake: &Pubkey, authority: &Pubkey, epoch: u64) -> Instruction {
+    // discriminator, then LockupArgs { unix_timestamp: None, epoch: Some, custodian: None }
+    let mut data = vec![6, 0, 1];
+    data.extend_from_slice(&epoch.to_le_bytes());
+    data.push(0);
+    Instruction::new_with_bytes(
+        PROGRAM,
+        &data,
+        vec![
+            AccountMeta::new(*stake, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
@@ -0,0 +1,61 @@
+//! Fails when an instruction uses more compute units than recorded in
+//! `benches/compute_units.json`, beyond a small tolerance. After an intentional
+//! change, rerun with `UPDATE_CU_BASELINE=1` to rewrite the baseline.
+
+mod common;
+
+use std::{collections::BTreeMap, fs};
+
+use common::compute_units::{cases, BASELINE_PATH};
+
+/// Allowed growth over the baseline, in percent, before a case counts as a regression
+const TOLERANCE_PERCENT: u64 = 2;
+
+fn read_baseline() -> BTreeMap<String, u64> {
+    fs::read_to_string(BASELINE_PATH)
+        .map(|json| serde_json::from_str(&json).unwrap())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_compute_units_within_baseline() {
+    let mollusk = common::mollusk();
+    let measured = cases(&mollusk)
+        .into_iter()
+        .map(|case| {
+            let result = mollusk.process_instruction(&case.instruction, &case.accounts);
+            assert!(
+                result.program_result.is_ok(),
+                "{}: {:?}",
+                case.name,
+                result.program_result
+            );
+            (case.name.to_string(), result.compute_units_consumed)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    if std::env::var_os("UPDATE_CU_BASELINE").is_some() {
+        let json = serde_json::to_string_pretty(&measured).unwrap();
+        fs::write(BASELINE_PATH, json + "\n").unwrap();
+        return;
+    }
+
+    let baseline = read_baseline();
+    let mut regressions = Vec::new();
+    for (name, units) in &measured {
+        let Some(&recorded) = baseline.get(name) else {
+            eprintln!("{name}: {units} CUs, not in the baseline yet");
+            continue;
+        };
+        if *units > recorded + recorded * TOLERANCE_PERCENT / 100 {
+            regressions.push(format!("{name}: {recorded} -> {units} CUs"));
+        } else if *units < recorded {
+            eprintln!("{name}: improved {recorded} -> {units} CUs, consider updating the baseline");
+        }
+    }
+    assert!(
+        regressions.is_empty(),
+        "compute units regressed by more than {TOLERANCE_PERCENT}%:\n{}",
+        regressions.join("\n")
+    );
+}
@@ -0,0 +1,387 @@
+//! End-to-end flows on LiteSVM: real transactions against the compiled program, with
+//! the clock and stake history advanced between epochs so activation is read from the
+//! sysvars the way it is on a cluster. Needs `cargo build-sbf` first.
+
+mod common;
+
+use common::*;
+use litesvm::LiteSVM;
+use solana_pinocchio_starter::state::{
+    Authorized, Delegation, Lockup, Meta, Stake, StakeFlags, StakeStateV2,
+};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    epoch_schedule::EpochSchedule,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake_history::{StakeHistory, StakeHistoryEntry},
+    system_instruction, sysvar,
+    transaction::{Transaction, TransactionError},
+    vote::state::{VoteInit, VoteState, VoteStateVersions},
+};
+
+const STAKE: u64 = 10 * LAMPORTS_PER_SOL;
+
+struct Env {
+    svm: LiteSVM,
+    payer: Keypair,
+    staker: Keypair,
+    history: StakeHistory,
+}
+
+impl Env {
+    fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(PROGRAM, "target/deploy/solana_pinocchio_starter.so")
+            .unwrap();
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 1_000 * LAMPORTS_PER_SOL)
+            .unwrap();
+        Self {
+            svm,
+            payer,
+            staker: Keypair::new(),
+            history: StakeHistory::default(),
+        }
+    }
+
+    fn reserve(&self) -> u64 {
+        self.svm
+            .minimum_balance_for_rent_exemption(StakeStateV2::size_of())
+    }
+
+    fn epoch(&self) -> u64 {
+        self.svm.get_sysvar::<Clock>().epoch
+    }
+
+    fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransactionError> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.svm.latest_blockhash(),
+        );
+        let result = self.svm.send_transaction(tx).map(|_| ()).map_err(|e| e.err);
+        // identical transactions in later epochs must not be rejected as duplicates
+        self.svm.expire_blockhash();
+        result
+    }
+
+    fn account(&self, pubkey: &Pubkey) -> Account {
+        self.svm.get_account(pubkey).unwrap()
+    }
+
+    /// Program-owned, zeroed (so `Uninitialized`) stake account holding `lamports`
+    fn create_stake_account(&mut self, lamports: u64) -> Pubkey {
+        let stake = Keypair::new();
+        self.send(
+            &[system_instruction::create_account(
+                &self.payer.pubkey(),
+                &stake.pubkey(),
+                lamports,
+                StakeStateV2::size_of() as u64,
+                &PROGRAM,
+            )],
+            &[&stake],
+        )
+        .unwrap();
+        stake.pubkey()
+    }
+
+    fn create_vote_account(&mut self) -> Pubkey {
+        let vote = Pubkey::new_unique();
+        let clock = self.svm.get_sysvar::<Clock>();
+        let vote_state = VoteState::new(
+            &VoteInit {
+                node_pubkey: Pubkey::new_unique(),
+                authorized_voter: Pubkey::new_unique(),
+                authorized_withdrawer: Pubkey::new_unique(),
+                commission: 0,
+            },
+            &clock,
+        );
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data).unwrap();
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.svm
+            .set_account(
+                vote,
+                Account {
+                    lamports,
+                    data,
+                    owner: solana_sdk::vote::program::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+        vote
+    }
+
+    /// Stands in for Initialize + DelegateStake, which the entrypoint doesn't dispatch
+    /// yet: writes a delegation activating in the current epoch into `stake`
+    fn delegate_in_place(&mut self, stake: &Pubkey, voter: &Pubkey, amount: u64) {
+        let meta = Meta::new(
+            self.reserve(),
+            Authorized::auto(&self.staker.pubkey().to_bytes()),
+            Lockup::default(),
+        );
+        let delegation = Delegation::new(&voter.to_bytes(), amount, self.epoch());
+        let mut account = self.account(stake);
+        account.data = stake_state_data(&StakeStateV2::Stake(
+            meta,
+            Stake::new(delegation, 0),
+            StakeFlags::empty(),
+        ));
+        self.svm.set_account(*stake, account).unwrap();
+    }
+
+    /// Moves to the next epoch, recording `entry` as the cluster's stake for the epoch
+    /// being left, like the runtime does at the epoch boundary
+    fn advance_epoch(&mut self, entry: StakeHistoryEntry) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        self.history.add(clock.epoch, entry);
+        let slots_per_epoch = self.svm.get_sysvar::<EpochSchedule>().slots_per_epoch;
+        clock.epoch += 1;
+        clock.slot = clock.epoch * slots_per_epoch;
+        clock.leader_schedule_epoch = clock.epoch + 1;
+        self.svm.set_sysvar(&clock);
+        self.svm.set_sysvar(&self.history);
+    }
+
+    fn split(
+        &mut self,
+        source: &Pubkey,
+        destination: &Pubkey,
+        lamports: u64,
+    ) -> Result<(), TransactionError> {
+        let staker = self.staker.insecure_clone();
+        let ix = split_instruction(source, destination, &staker.pubkey(), lamports);
+        self.send(&[ix], &[&staker])
+    }
+}
+
+/// Cluster-wide entry for an epoch where `activating` lamports warm up against an
+/// effective stake large enough that they finish within the epoch
+fn warmup_entry(activating: u64) -> StakeHistoryEntry {
+    StakeHistoryEntry {
+        effective: 1_000_000 * LAMPORTS_PER_SOL,
+        activating,
+        deactivating: 0,
+    }
+}
+
+fn delegation(account: &Account) -> Delegation {
+    match stake_state(account) {
+        StakeStateV2::Stake(_, stake, _) => stake.delegation,
+        state => panic!("expected a delegated account, got {state:?}"),
+    }
+}
+
+#[test]
+fn test_split_follows_activation_across_epochs() {
+    let mut env = Env::new();
+    let reserve = env.reserve();
+    env.advance_epoch(StakeHistoryEntry::default());
+    let activation_epoch = env.epoch();
+
+    let vote = env.create_vote_account();
+    let source = env.create_stake_account(reserve + STAKE);
+    env.delegate_in_place(&source, &vote, STAKE);
+
+    // still activating: the destination may be funded from the split itself
+    let activating_split = env.create_stake_account(0);
+    env.split(&source, &activating_split, reserve + LAMPORTS_PER_SOL)
+        .unwrap();
+    assert_eq!(
+        u64::from_le_bytes(delegation(&env.account(&activating_split)).stake),
+        LAMPORTS_PER_SOL
+    );
+
+    env.advance_epoch(warmup_entry(STAKE));
+
+    // now effective, so the destination has to bring its own reserve
+    let unfunded = env.create_stake_account(0);
+    assert_eq!(
+        env.split(&source, &unfunded, LAMPORTS_PER_SOL),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InsufficientFunds
+        ))
+    );
+    assert_eq!(env.account(&unfunded).lamports, 0);
+
+    let prefunded = env.create_stake_account(reserve);
+    let source_before = env.account(&source).lamports;
+    env.split(&source, &prefunded, LAMPORTS_PER_SOL).unwrap();
+
+    let source_after = env.account(&source);
+    let prefunded_after = env.account(&prefunded);
+    assert_eq!(
+        source_after.lamports + prefunded_after.lamports,
+        source_before + reserve
+    );
+    assert_eq!(
+        delegation(&prefunded_after).activation_epoch,
+        activation_epoch.to_le_bytes()
+    );
+    assert_eq!(
+        u64::from_le_bytes(delegation(&source_after).stake),
+        STAKE - reserve - 2 * LAMPORTS_PER_SOL
+    );
+}
+
+fn initialize_instruction(stake: &Pubkey, authorized: &Pubkey) -> Instruction {
+    let mut data = vec![0];
+    data.extend_from_slice(authorized.as_ref());
+    data.extend_from_slice(authorized.as_ref());
+    data.extend_from_slice(&[0; 48]);
+    Instruction::new_with_bytes(
+        PROGRAM,
+        &data,
+        vec![
+            AccountMeta::new(*stake, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+fn delegate_instruction(stake: &Pubkey, vote: &Pubkey, staker: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM,
+        &[2],
+        vec![
+            AccountMeta::new(*stake, false),
+            AccountMeta::new_readonly(*vote, false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::stake_history::ID, false),
+            #[allow(deprecated)]
+            AccountMeta::new_readonly(solana_sdk::stake::config::ID, false),
+            AccountMeta::new_readonly(*staker, true),
+        ],
+    )
+}
+
+fn deactivate_instruction(stake: &Pubkey, staker: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM,
+        &[5],
+        vec![
+            AccountMeta::new(*stake, false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(*staker, true),
+        ],
+    )
+}
+
+fn withdraw_instruction(
+    stake: &Pubkey,
+    to: &Pubkey,
+    withdrawer: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    let mut data = vec![4];
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction::new_with_bytes(
+        PROGRAM,
+        &data,
+        vec![
+            AccountMeta::new(*stake, false),
+            AccountMeta::new(*to, false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::stake_history::ID, false),
+            AccountMeta::new_readonly(*withdrawer, true),
+        ],
+    )
+}
+
+#[test]
+#[ignore = "Initialize, DelegateStake, Deactivate and Withdraw aren't dispatched yet"]
+fn test_stake_lifecycle() {
+    let mut env = Env::new();
+    let reserve = env.reserve();
+    let staker = env.staker.insecure_clone();
+    let recipient = Pubkey::new_unique();
+
+    let vote = env.create_vote_account();
+    let stake = env.create_stake_account(reserve + STAKE);
+    env.send(&[initialize_instruction(&stake, &staker.pubkey())], &[])
+        .unwrap();
+    env.send(
+        &[delegate_instruction(&stake, &vote, &staker.pubkey())],
+        &[&staker],
+    )
+    .unwrap();
+    let activation_epoch = env.epoch();
+    assert_eq!(
+        delegation(&env.account(&stake)).activation_epoch,
+        activation_epoch.to_le_bytes()
+    );
+
+    env.advance_epoch(warmup_entry(STAKE));
+    env.advance_epoch(StakeHistoryEntry {
+        effective: 1_000_000 * LAMPORTS_PER_SOL + STAKE,
+        ..StakeHistoryEntry::default()
+    });
+
+    let split_stake = env.create_stake_account(reserve);
+    env.split(&stake, &split_stake, STAKE / 2).unwrap();
+
+    env.send(
+        &[deactivate_instruction(&stake, &staker.pubkey())],
+        &[&staker],
+    )
+    .unwrap();
+    // still cooling down, only what isn't staked can leave
+    assert_eq!(
+        env.send(
+            &[withdraw_instruction(
+                &stake,
+                &recipient,
+                &staker.pubkey(),
+                reserve + STAKE / 2
+            )],
+            &[&staker],
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InsufficientFunds
+        ))
+    );
+
+    env.advance_epoch(StakeHistoryEntry {
+        effective: 1_000_000 * LAMPORTS_PER_SOL + STAKE,
+        deactivating: STAKE / 2,
+        ..StakeHistoryEntry::default()
+    });
+
+    env.send(
+        &[withdraw_instruction(
+            &stake,
+            &recipient,
+            &staker.pubkey(),
+            reserve + STAKE / 2,
+        )],
+        &[&staker],
+    )
+    .unwrap();
+    assert_eq!(env.account(&recipient).lamports, reserve + STAKE / 2);
+    assert!(env
+        .svm
+        .get_account(&stake)
+        .map_or(true, |account| account.lamports == 0));
+    assert_eq!(
+        u64::from_le_bytes(delegation(&env.account(&split_stake)).stake),
+        STAKE / 2
+    );
+}
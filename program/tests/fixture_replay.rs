@@ -0,0 +1,108 @@
+//! Replays instructions against the captured stake accounts in `tests/fixtures`, so
+//! account shapes seen in production keep deserializing and splitting the way they
+//! did when they were captured.
+
+mod common;
+
+use common::{fixtures::stake_account_fixtures, *};
+use solana_pinocchio_starter::state::{layout, StakeStateV2};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+#[test]
+fn test_fixtures_deserialize() {
+    for fixture in stake_account_fixtures() {
+        let label = &fixture.label;
+        let data = &fixture.account.data;
+        assert_eq!(
+            fixture.account.owner,
+            solana_sdk::stake::program::ID,
+            "{label}"
+        );
+        assert_eq!(data.len(), StakeStateV2::size_of(), "{label}");
+
+        let state = StakeStateV2::deserialize(data).unwrap_or_else(|e| panic!("{label}: {e:?}"));
+        match state {
+            StakeStateV2::Initialized(meta) | StakeStateV2::Stake(meta, _, _) => {
+                assert_eq!(
+                    u64_at(data, layout::RENT_EXEMPT_RESERVE_OFFSET),
+                    meta.rent_exempt_reserve(),
+                    "{label}"
+                );
+                assert_eq!(
+                    data[layout::STAKER_OFFSET..layout::STAKER_OFFSET + 32],
+                    meta.authorized.staker,
+                    "{label}"
+                );
+                assert_eq!(
+                    data[layout::WITHDRAWER_OFFSET..layout::WITHDRAWER_OFFSET + 32],
+                    meta.authorized.withdrawer,
+                    "{label}"
+                );
+            }
+            _ => {}
+        }
+        if let StakeStateV2::Stake(_, stake, _) = state {
+            assert_eq!(
+                u64_at(data, layout::DELEGATED_STAKE_OFFSET),
+                u64::from_le_bytes(stake.delegation.stake),
+                "{label}"
+            );
+        }
+    }
+}
+
+/// Splitting the whole balance away either moves the account intact, authorities and
+/// lockup included, or fails without touching it
+#[test]
+fn test_fixtures_split_entire_balance() {
+    for fixture in stake_account_fixtures() {
+        let label = &fixture.label;
+        let meta = match StakeStateV2::deserialize(&fixture.account.data).unwrap() {
+            StakeStateV2::Initialized(meta) | StakeStateV2::Stake(meta, _, _) => meta,
+            _ => continue,
+        };
+
+        let mut mollusk = mollusk();
+        mollusk.sysvars.clock.epoch = fixture.epoch;
+
+        let staker = Pubkey::new_from_array(meta.authorized.staker);
+        let destination_key = Pubkey::new_unique();
+        // replay against this program rather than the native one
+        let source = Account {
+            owner: PROGRAM,
+            ..fixture.account.clone()
+        };
+        let destination = uninitialized_stake_account(0);
+
+        let result = mollusk.process_instruction(
+            &split_instruction(&fixture.pubkey, &destination_key, &staker, source.lamports),
+            &[
+                (fixture.pubkey, source.clone()),
+                (destination_key, destination.clone()),
+                (staker, authority_account()),
+            ],
+        );
+        let resulting_source = result.get_account(&fixture.pubkey).unwrap();
+        let resulting_destination = result.get_account(&destination_key).unwrap();
+
+        if result.program_result.is_err() {
+            assert_eq!(resulting_source, &source, "{label}");
+            assert_eq!(resulting_destination, &destination, "{label}");
+            continue;
+        }
+
+        assert_eq!(resulting_source.lamports, 0, "{label}");
+        assert_eq!(resulting_destination.lamports, source.lamports, "{label}");
+        match stake_state(resulting_destination) {
+            StakeStateV2::Initialized(split_meta) | StakeStateV2::Stake(split_meta, _, _) => {
+                assert_eq!(split_meta.authorized, meta.authorized, "{label}");
+                assert_eq!(split_meta.lockup, meta.lockup, "{label}");
+            }
+            state => panic!("{label}: unexpected destination state {state:?}"),
+        }
+    }
+}
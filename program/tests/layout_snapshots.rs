@@ -0,0 +1,238 @@
+//! Golden 200-byte images of representative stake accounts, checked against both the
+//! native `StakeStateV2` serialization and this crate's in-memory layout, so a field
+//! reorder or an endianness slip fails here before it reaches an account.
+//!
+//! The images in `tests/snapshots/stake_state_v2` are the native bincode output padded
+//! to the account size. Rerun with `UPDATE_SNAPSHOTS=1` to rewrite them after adding a
+//! case.
+
+#![allow(deprecated)]
+
+mod common;
+
+use std::fs;
+
+use common::stake_state_data;
+use solana_pinocchio_starter::state::{
+    layout, Authorized, Delegation, Lockup, Meta, Stake, StakeFlags, StakeStateV2,
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    stake::{
+        stake_flags::StakeFlags as NativeStakeFlags,
+        state::{
+            Authorized as NativeAuthorized, Delegation as NativeDelegation, Lockup as NativeLockup,
+            Meta as NativeMeta, Stake as NativeStake, StakeStateV2 as NativeStakeStateV2,
+        },
+    },
+};
+
+const SNAPSHOT_DIR: &str = "tests/snapshots/stake_state_v2";
+
+const STAKER: [u8; 32] = [0x11; 32];
+const WITHDRAWER: [u8; 32] = [0x22; 32];
+const CUSTODIAN: [u8; 32] = [0x33; 32];
+const VOTER: [u8; 32] = [0x44; 32];
+const RESERVE: u64 = 2_282_880;
+
+struct Case {
+    name: &'static str,
+    state: StakeStateV2,
+    native: NativeStakeStateV2,
+}
+
+fn meta(lockup: Option<(i64, u64)>) -> (Meta, NativeMeta) {
+    let (unix_timestamp, epoch, custodian) = match lockup {
+        Some((unix_timestamp, epoch)) => (unix_timestamp, epoch, CUSTODIAN),
+        None => (0, 0, [0; 32]),
+    };
+    (
+        Meta::new(
+            RESERVE,
+            Authorized {
+                staker: STAKER,
+                withdrawer: WITHDRAWER,
+            },
+            Lockup {
+                unix_timestamp: unix_timestamp.to_le_bytes(),
+                epoch: epoch.to_le_bytes(),
+                custodian,
+            },
+        ),
+        NativeMeta {
+            rent_exempt_reserve: RESERVE,
+            authorized: NativeAuthorized {
+                staker: Pubkey::new_from_array(STAKER),
+                withdrawer: Pubkey::new_from_array(WITHDRAWER),
+            },
+            lockup: NativeLockup {
+                unix_timestamp,
+                epoch,
+                custodian: Pubkey::new_from_array(custodian),
+            },
+        },
+    )
+}
+
+fn stake(
+    amount: u64,
+    activation_epoch: u64,
+    deactivation_epoch: u64,
+    credits_observed: u64,
+) -> (Stake, NativeStake) {
+    (
+        Stake {
+            delegation: Delegation {
+                voter_pubkey: VOTER,
+                stake: amount.to_le_bytes(),
+                activation_epoch: activation_epoch.to_le_bytes(),
+                deactivation_epoch: deactivation_epoch.to_le_bytes(),
+                warmup_cooldown_rate: 0.25f64.to_le_bytes(),
+            },
+            credits_observed: credits_observed.to_le_bytes(),
+        },
+        NativeStake {
+            delegation: NativeDelegation {
+                voter_pubkey: Pubkey::new_from_array(VOTER),
+                stake: amount,
+                activation_epoch,
+                deactivation_epoch,
+                warmup_cooldown_rate: 0.25,
+            },
+            credits_observed,
+        },
+    )
+}
+
+fn cases() -> Vec<Case> {
+    let lockup = Some((1_700_000_000, 600));
+    let (plain_meta, native_plain_meta) = meta(None);
+    let (locked_meta, native_locked_meta) = meta(lockup);
+    let (activating, native_activating) = stake(5_000_000_000, 700, u64::MAX, 123_456);
+    let (deactivating, native_deactivating) = stake(1_000_000_000, 650, 701, 7);
+
+    vec![
+        Case {
+            name: "uninitialized",
+            state: StakeStateV2::Uninitialized,
+            native: NativeStakeStateV2::Uninitialized,
+        },
+        Case {
+            name: "initialized",
+            state: StakeStateV2::Initialized(plain_meta),
+            native: NativeStakeStateV2::Initialized(native_plain_meta),
+        },
+        Case {
+            name: "initialized_lockup",
+            state: StakeStateV2::Initialized(locked_meta),
+            native: NativeStakeStateV2::Initialized(native_locked_meta),
+        },
+        Case {
+            name: "stake_activating",
+            state: StakeStateV2::Stake(plain_meta, activating, StakeFlags::empty()),
+            native: NativeStakeStateV2::Stake(
+                native_plain_meta,
+                native_activating,
+                NativeStakeFlags::empty(),
+            ),
+        },
+        Case {
+            name: "stake_deactivating_flagged",
+            state: StakeStateV2::Stake(
+                locked_meta,
+                deactivating,
+                StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+            ),
+            native: NativeStakeStateV2::Stake(
+                native_locked_meta,
+                native_deactivating,
+                NativeStakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+            ),
+        },
+        Case {
+            name: "rewards_pool",
+            state: StakeStateV2::RewardsPool,
+            native: NativeStakeStateV2::RewardsPool,
+        },
+    ]
+}
+
+fn native_account_data(state: &NativeStakeStateV2) -> Vec<u8> {
+    let mut data = bincode::serialize(state).unwrap();
+    data.resize(StakeStateV2::size_of(), 0);
+    data
+}
+
+fn read_snapshot(name: &str) -> Vec<u8> {
+    let path = format!("{SNAPSHOT_DIR}/{name}.hex");
+    let hex = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+    let hex = hex.split_whitespace().collect::<String>();
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn write_snapshot(name: &str, data: &[u8]) {
+    let hex = data
+        .chunks(16)
+        .map(|line| line.iter().map(|b| format!("{b:02x}")).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(format!("{SNAPSHOT_DIR}/{name}.hex"), hex + "\n").unwrap();
+}
+
+/// Bytes the variant actually occupies; the rest of the account is padding
+fn used_len(state: &StakeStateV2) -> usize {
+    match state {
+        StakeStateV2::Uninitialized | StakeStateV2::RewardsPool => layout::TAG_LEN,
+        StakeStateV2::Initialized(_) => layout::STAKE_OFFSET,
+        StakeStateV2::Stake(..) => layout::STAKE_FLAGS_OFFSET + 1,
+    }
+}
+
+#[test]
+fn test_snapshots_match_native_serialization() {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    for case in cases() {
+        let native = native_account_data(&case.native);
+        if update {
+            write_snapshot(case.name, &native);
+        }
+        assert_eq!(read_snapshot(case.name), native, "{}", case.name);
+    }
+}
+
+#[test]
+fn test_layout_matches_snapshots() {
+    for case in cases() {
+        let snapshot = read_snapshot(case.name);
+        assert_eq!(snapshot.len(), StakeStateV2::size_of(), "{}", case.name);
+
+        let used = used_len(&case.state);
+        assert_eq!(
+            stake_state_data(&case.state)[..used],
+            snapshot[..used],
+            "{}",
+            case.name
+        );
+        assert_eq!(
+            StakeStateV2::deserialize(&snapshot),
+            Ok(case.state),
+            "{}",
+            case.name
+        );
+    }
+}
+
+#[test]
+fn test_debug_matches_native() {
+    for case in cases() {
+        assert_eq!(
+            format!("{:?}", case.state),
+            format!("{:?}", case.native),
+            "{}",
+            case.name
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Serialization against `solana-sdk` on the host: states and instruction arguments
+//! bincode-encoded from the native types must decode here to the same values, and
+//! this crate's bytes must decode natively, so layout drift between the two (a field
+//! reorder, an endianness slip) fails here for arbitrary values, not just the golden
+//! images in `layout_snapshots`.
+
+#![allow(deprecated)]
+
+mod common;
+
+use common::stake_state_data;
+use proptest::prelude::*;
+use solana_pinocchio_starter::{
+    instruction::{LockupArgs, LockupCheckedArgs},
+    state::{
+        Authorized, Delegation, Lockup, Meta, Stake, StakeFlags, StakeHistoryEntry, StakeStateV2,
+    },
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    stake::{
+        instruction::{
+            LockupArgs as NativeLockupArgs, LockupCheckedArgs as NativeLockupCheckedArgs,
+        },
+        stake_flags::StakeFlags as NativeStakeFlags,
+        state::{
+            Authorized as NativeAuthorized, Delegation as NativeDelegation, Lockup as NativeLockup,
+            Meta as NativeMeta, Stake as NativeStake, StakeStateV2 as NativeStakeStateV2,
+        },
+    },
+    stake_history::StakeHistoryEntry as NativeStakeHistoryEntry,
+};
+
+prop_compose! {
+    fn meta()(
+        rent_exempt_reserve: u64,
+        staker: [u8; 32],
+        withdrawer: [u8; 32],
+        unix_timestamp: i64,
+        epoch: u64,
+        custodian: [u8; 32],
+    ) -> (Meta, NativeMeta) {
+        (
+            Meta::new(
+                rent_exempt_reserve,
+                Authorized { staker, withdrawer },
+                Lockup {
+                    unix_timestamp: unix_timestamp.to_le_bytes(),
+                    epoch: epoch.to_le_bytes(),
+                    custodian,
+                },
+            ),
+            NativeMeta {
+                rent_exempt_reserve,
+                authorized: NativeAuthorized {
+                    staker: Pubkey::new_from_array(staker),
+                    withdrawer: Pubkey::new_from_array(withdrawer),
+                },
+                lockup: NativeLockup {
+                    unix_timestamp,
+                    epoch,
+                    custodian: Pubkey::new_from_array(custodian),
+                },
+            },
+        )
+    }
+}
+
+prop_compose! {
+    fn stake()(
+        voter: [u8; 32],
+        amount: u64,
+        activation_epoch: u64,
+        deactivation_epoch: u64,
+        warmup_cooldown_rate in 0.0..1.0f64,
+        credits_observed: u64,
+    ) -> (Stake, NativeStake) {
+        (
+            Stake {
+                delegation: Delegation {
+                    voter_pubkey: voter,
+                    stake: amount.to_le_bytes(),
+                    activation_epoch: activation_epoch.to_le_bytes(),
+                    deactivation_epoch: deactivation_epoch.to_le_bytes(),
+                    warmup_cooldown_rate: warmup_cooldown_rate.to_le_bytes(),
+                },
+                credits_observed: credits_observed.to_le_bytes(),
+            },
+            NativeStake {
+                delegation: NativeDelegation {
+                    voter_pubkey: Pubkey::new_from_array(voter),
+                    stake: amount,
+                    activation_epoch,
+                    deactivation_epoch,
+                    warmup_cooldown_rate,
+                },
+                credits_observed,
+            },
+        )
+    }
+}
+
+fn state() -> impl Strategy<Value = (StakeStateV2, NativeStakeStateV2)> {
+    prop_oneof![
+        Just((
+            StakeStateV2::Uninitialized,
+            NativeStakeStateV2::Uninitialized
+        )),
+        meta().prop_map(|(meta, native)| {
+            (
+                StakeStateV2::Initialized(meta),
+                NativeStakeStateV2::Initialized(native),
+            )
+        }),
+        (meta(), stake(), any::<bool>()).prop_map(
+            |((meta, native_meta), (stake, native_stake), flagged)| {
+                let (flags, native_flags) = if flagged {
+                    (
+                        StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+                        NativeStakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+                    )
+                } else {
+                    (StakeFlags::empty(), NativeStakeFlags::empty())
+                };
+                (
+                    StakeStateV2::Stake(meta, stake, flags),
+                    NativeStakeStateV2::Stake(native_meta, native_stake, native_flags),
+                )
+            }
+        ),
+        Just((StakeStateV2::RewardsPool, NativeStakeStateV2::RewardsPool)),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn stake_state_decodes_both_ways((state, native) in state()) {
+        let mut native_data = bincode::serialize(&native).unwrap();
+        native_data.resize(StakeStateV2::size_of(), 0);
+        prop_assert_eq!(StakeStateV2::deserialize(&native_data), Ok(state));
+
+        let data = stake_state_data(&state);
+        prop_assert_eq!(
+            bincode::deserialize::<NativeStakeStateV2>(&data).unwrap(),
+            native
+        );
+    }
+
+    #[test]
+    fn lockup_args_decode_native_data(
+        unix_timestamp: Option<i64>,
+        epoch: Option<u64>,
+        custodian: Option<[u8; 32]>,
+    ) {
+        let native = NativeLockupArgs {
+            unix_timestamp,
+            epoch,
+            custodian: custodian.map(Pubkey::new_from_array),
+        };
+        prop_assert_eq!(
+            LockupArgs::from_data(&bincode::serialize(&native).unwrap()),
+            Ok(LockupArgs {
+                unix_timestamp: unix_timestamp.map(i64::to_le_bytes),
+                epoch: epoch.map(u64::to_le_bytes),
+                custodian,
+            })
+        );
+    }
+
+    #[test]
+    fn lockup_checked_args_decode_native_data(
+        unix_timestamp: Option<i64>,
+        epoch: Option<u64>,
+    ) {
+        let native = NativeLockupCheckedArgs {
+            unix_timestamp,
+            epoch,
+        };
+        prop_assert_eq!(
+            LockupCheckedArgs::from_data(&bincode::serialize(&native).unwrap()),
+            Ok(LockupCheckedArgs {
+                unix_timestamp: unix_timestamp.map(i64::to_le_bytes),
+                epoch: epoch.map(u64::to_le_bytes),
+            })
+        );
+    }
+
+    #[test]
+    fn stake_history_entries_add_like_native(
+        lhs: (u64, u64, u64),
+        rhs: (u64, u64, u64),
+    ) {
+        let entry = |(effective, activating, deactivating): (u64, u64, u64)| {
+            StakeHistoryEntry {
+                effective: effective.to_le_bytes(),
+                activating: activating.to_le_bytes(),
+                deactivating: deactivating.to_le_bytes(),
+            }
+        };
+        let native_entry = |(effective, activating, deactivating)| NativeStakeHistoryEntry {
+            effective,
+            activating,
+            deactivating,
+        };
+
+        let sum = entry(lhs) + entry(rhs);
+        let native = native_entry(lhs) + native_entry(rhs);
+        prop_assert_eq!(
+            (sum.effective(), sum.activating(), sum.deactivating()),
+            (native.effective, native.activating, native.deactivating)
+        );
+    }
+}
@@ -0,0 +1,442 @@
+mod common;
+
+use common::*;
+use mollusk_svm::result::Check;
+use solana_pinocchio_starter::state::{Meta, StakeStateV2};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+// StakeError::InsufficientDelegation
+const INSUFFICIENT_DELEGATION: ProgramError = ProgramError::Custom(12);
+
+struct Accounts {
+    source: Pubkey,
+    destination: Pubkey,
+    authority: Pubkey,
+}
+
+impl Accounts {
+    fn new() -> Self {
+        Self {
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+        }
+    }
+
+    fn split(&self, lamports: u64) -> Instruction {
+        split_instruction(&self.source, &self.destination, &self.authority, lamports)
+    }
+
+    /// A split of an uninitialized source, which signs for itself with no authority
+    fn split_uninitialized(&self, lamports: u64) -> Instruction {
+        let mut data = vec![3];
+        data.extend(lamports.to_le_bytes());
+        Instruction::new_with_bytes(
+            PROGRAM,
+            &data,
+            vec![
+                AccountMeta::new(self.source, true),
+                AccountMeta::new(self.destination, false),
+            ],
+        )
+    }
+
+    fn keyed(&self, source: Account, destination: Account) -> Vec<(Pubkey, Account)> {
+        vec![
+            (self.source, source),
+            (self.destination, destination),
+            (self.authority, authority_account()),
+        ]
+    }
+}
+
+#[test]
+fn test_split_not_enough_accounts() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[3, 1, 0, 0, 0, 0, 0, 0, 0],
+        vec![AccountMeta::new(keys.source, true)],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(keys.source, uninitialized_stake_account(1))],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
+
+#[test]
+fn test_split_destination_checks() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+    let reserve = rent_exempt_reserve(&mollusk);
+    let source = initialized_stake_account(reserve * 2, meta(&mollusk, &keys.authority));
+
+    // wrong size
+    let mut destination = uninitialized_stake_account(0);
+    destination.data.truncate(100);
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve),
+        &keys.keyed(source.clone(), destination),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+
+    // already initialized
+    let destination = initialized_stake_account(0, meta(&mollusk, &keys.authority));
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve),
+        &keys.keyed(source.clone(), destination),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+
+    // not owned by the stake program
+    let mut destination = uninitialized_stake_account(0);
+    destination.owner = Pubkey::new_unique();
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve),
+        &keys.keyed(source, destination),
+        &[Check::err(ProgramError::InvalidAccountOwner)],
+    );
+}
+
+#[test]
+fn test_split_source_checks() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+    let reserve = rent_exempt_reserve(&mollusk);
+
+    // more than the source holds
+    let source = initialized_stake_account(reserve, meta(&mollusk, &keys.authority));
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve + 1),
+        &keys.keyed(source, uninitialized_stake_account(0)),
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+
+    // not owned by the stake program
+    let mut source = initialized_stake_account(reserve * 2, meta(&mollusk, &keys.authority));
+    source.owner = Pubkey::new_unique();
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve),
+        &keys.keyed(source, uninitialized_stake_account(0)),
+        &[Check::err(ProgramError::InvalidAccountOwner)],
+    );
+
+    // rewards pools can't be split
+    let source = stake_account_with_state(reserve * 2, &StakeStateV2::RewardsPool);
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve),
+        &keys.keyed(source, uninitialized_stake_account(0)),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_split_uninitialized() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+
+    // the source itself must sign
+    mollusk.process_and_validate_instruction(
+        &keys.split(100),
+        &keys.keyed(
+            uninitialized_stake_account(100),
+            uninitialized_stake_account(0),
+        ),
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &keys.split_uninitialized(40),
+        &[
+            (keys.source, uninitialized_stake_account(100)),
+            (keys.destination, uninitialized_stake_account(0)),
+        ],
+        &[
+            Check::success(),
+            Check::account(&keys.source).lamports(60).build(),
+            Check::account(&keys.destination)
+                .lamports(40)
+                .data(&stake_state_data(&StakeStateV2::Uninitialized))
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_split_uninitialized_full_balance() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+
+    // the source ends up empty and stays uninitialized; the destination only gains
+    // lamports, with nothing requiring it to reach rent exemption
+    for prefunded in [0, 7] {
+        mollusk.process_and_validate_instruction(
+            &keys.split_uninitialized(100),
+            &[
+                (keys.source, uninitialized_stake_account(100)),
+                (keys.destination, uninitialized_stake_account(prefunded)),
+            ],
+            &[
+                Check::success(),
+                Check::account(&keys.source)
+                    .lamports(0)
+                    .data(&stake_state_data(&StakeStateV2::Uninitialized))
+                    .build(),
+                Check::account(&keys.destination)
+                    .lamports(prefunded + 100)
+                    .data(&stake_state_data(&StakeStateV2::Uninitialized))
+                    .build(),
+            ],
+        );
+    }
+}
+
+#[test]
+fn test_split_uninitialized_leaves_destination_data() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+
+    // only the destination's tag is checked; whatever follows it is neither validated
+    // nor cleared, since an uninitialized split writes no state
+    let mut destination = uninitialized_stake_account(0);
+    destination.data[4..].fill(0xab);
+    let destination_data = destination.data.clone();
+    mollusk.process_and_validate_instruction(
+        &keys.split_uninitialized(100),
+        &[
+            (keys.source, uninitialized_stake_account(100)),
+            (keys.destination, destination),
+        ],
+        &[
+            Check::success(),
+            Check::account(&keys.destination)
+                .lamports(100)
+                .data(&destination_data)
+                .build(),
+        ],
+    );
+
+    // the size is still checked
+    let mut destination = uninitialized_stake_account(0);
+    destination.data.push(0);
+    mollusk.process_and_validate_instruction(
+        &keys.split_uninitialized(100),
+        &[
+            (keys.source, uninitialized_stake_account(100)),
+            (keys.destination, destination),
+        ],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_split_initialized() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+    let reserve = rent_exempt_reserve(&mollusk);
+    let meta = meta(&mollusk, &keys.authority);
+    let source = initialized_stake_account(reserve * 2 + 100, meta);
+
+    // staker must sign
+    let mut instruction = keys.split(reserve + 50);
+    instruction.accounts[2].is_signer = false;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &keys.keyed(source.clone(), uninitialized_stake_account(0)),
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+
+    // nothing to split
+    mollusk.process_and_validate_instruction(
+        &keys.split(0),
+        &keys.keyed(source.clone(), uninitialized_stake_account(0)),
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+
+    // the source would drop below its reserve
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve + 101),
+        &keys.keyed(source.clone(), uninitialized_stake_account(0)),
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+
+    // the destination wouldn't reach its reserve
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve - 1),
+        &keys.keyed(source.clone(), uninitialized_stake_account(0)),
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve + 50),
+        &keys.keyed(source.clone(), uninitialized_stake_account(0)),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(reserve + 50)
+                .data(&stake_state_data(&StakeStateV2::Initialized(meta)))
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve + 50)
+                .data(&stake_state_data(&StakeStateV2::Initialized(meta)))
+                .build(),
+        ],
+    );
+
+    // splitting everything closes the source
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve * 2 + 100),
+        &keys.keyed(source, uninitialized_stake_account(0)),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(0)
+                .data(&stake_state_data(&StakeStateV2::Uninitialized))
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve * 2 + 100)
+                .data(&stake_state_data(&StakeStateV2::Initialized(meta)))
+                .build(),
+        ],
+    );
+}
+
+fn delegated_stake(state: &StakeStateV2) -> u64 {
+    match state {
+        StakeStateV2::Stake(_, stake, _) => u64::from_le_bytes(stake.delegation.stake),
+        other => panic!("expected a Stake state, got {other:?}"),
+    }
+}
+
+fn meta_of(state: &StakeStateV2) -> Meta {
+    match state {
+        StakeStateV2::Initialized(meta) | StakeStateV2::Stake(meta, _, _) => *meta,
+        other => panic!("expected an initialized state, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_split_active_stake() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+    let voter = Pubkey::new_unique();
+    let reserve = rent_exempt_reserve(&mollusk);
+    let meta = meta(&mollusk, &keys.authority);
+    let source = delegated_stake_account(reserve + 10_000, meta, active_stake(&voter, 10_000));
+
+    // staker must sign
+    let mut instruction = keys.split(4_000);
+    instruction.accounts[2].is_signer = false;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &keys.keyed(source.clone(), uninitialized_stake_account(reserve)),
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+
+    // a partial split of active stake needs a prefunded destination
+    mollusk.process_and_validate_instruction(
+        &keys.split(4_000),
+        &keys.keyed(source.clone(), uninitialized_stake_account(0)),
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+
+    let result = mollusk.process_and_validate_instruction(
+        &keys.split(4_000),
+        &keys.keyed(source.clone(), uninitialized_stake_account(reserve)),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(reserve + 6_000)
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve + 4_000)
+                .build(),
+        ],
+    );
+    let source_state = stake_state(result.get_account(&keys.source).unwrap());
+    let destination_state = stake_state(result.get_account(&keys.destination).unwrap());
+    assert_eq!(delegated_stake(&source_state), 6_000);
+    assert_eq!(delegated_stake(&destination_state), 4_000);
+    assert_eq!(meta_of(&destination_state), meta);
+
+    // a full split needs no prefunding and moves the whole delegation
+    let result = mollusk.process_and_validate_instruction(
+        &keys.split(reserve + 10_000),
+        &keys.keyed(source, uninitialized_stake_account(0)),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(0)
+                .data(&stake_state_data(&StakeStateV2::Uninitialized))
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve + 10_000)
+                .build(),
+        ],
+    );
+    let destination_state = stake_state(result.get_account(&keys.destination).unwrap());
+    assert_eq!(delegated_stake(&destination_state), 10_000);
+}
+
+#[test]
+fn test_split_inactive_stake() {
+    let mollusk = mollusk();
+    let keys = Accounts::new();
+    let voter = Pubkey::new_unique();
+    let reserve = rent_exempt_reserve(&mollusk);
+    let meta = meta(&mollusk, &keys.authority);
+
+    // an inactive source doesn't need a prefunded destination, so the destination's
+    // reserve comes out of the split stake
+    let source = delegated_stake_account(
+        reserve * 2 + 20_000,
+        meta,
+        inactive_stake(&voter, reserve + 20_000, 5),
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &keys.split(reserve + 4_000),
+        &keys.keyed(source, uninitialized_stake_account(0)),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(reserve + 16_000)
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve + 4_000)
+                .build(),
+        ],
+    );
+    let source_state = stake_state(result.get_account(&keys.source).unwrap());
+    let destination_state = stake_state(result.get_account(&keys.destination).unwrap());
+    assert_eq!(delegated_stake(&source_state), 16_000);
+    assert_eq!(delegated_stake(&destination_state), 4_000);
+
+    // splitting off the whole delegation would leave the source below the minimum,
+    // even with undelegated lamports keeping its balance up
+    let source = delegated_stake_account(
+        reserve * 2 + 40_000,
+        meta,
+        inactive_stake(&voter, reserve + 20_000, 5),
+    );
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve + 20_000),
+        &keys.keyed(source, uninitialized_stake_account(reserve)),
+        &[Check::err(INSUFFICIENT_DELEGATION)],
+    );
+
+    // a full split of an account with no delegated stake has nothing to move
+    let source = delegated_stake_account(reserve, meta, inactive_stake(&voter, 0, 5));
+    mollusk.process_and_validate_instruction(
+        &keys.split(reserve),
+        &keys.keyed(source, uninitialized_stake_account(1)),
+        &[Check::err(INSUFFICIENT_DELEGATION)],
+    );
+}
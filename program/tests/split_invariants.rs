@@ -0,0 +1,87 @@
+//! Randomized split checks over balances and rent: lamports are conserved, every
+//! resulting delegation stays within its account balance and at or above the
+//! minimum, and failed splits leave both accounts untouched.
+
+mod common;
+
+use common::*;
+use proptest::prelude::*;
+use solana_pinocchio_starter::state::{get_minimum_delegation, StakeStateV2};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent};
+
+fn check_invariants(account: &Account) -> Result<(), TestCaseError> {
+    match stake_state(account) {
+        StakeStateV2::Initialized(meta) => {
+            prop_assert!(account.lamports == 0 || meta.rent_exempt_reserve() <= account.lamports);
+        }
+        StakeStateV2::Stake(meta, stake, _) => {
+            let delegated = u64::from_le_bytes(stake.delegation.stake);
+            prop_assert!(delegated >= get_minimum_delegation());
+            prop_assert!(delegated + meta.rent_exempt_reserve() <= account.lamports);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn split_preserves_invariants(
+        lamports_per_byte_year in 1..10_000u64,
+        delegated in prop::option::of(1..1_000_000_000_000u64),
+        undelegated in 0..1_000_000u64,
+        destination_prefund_fraction in 0.0..2.0f64,
+        split_fraction in 0.0..1.05f64,
+    ) {
+        let mut mollusk = mollusk();
+        mollusk.sysvars.rent = Rent {
+            lamports_per_byte_year,
+            ..Rent::default()
+        };
+        let reserve = rent_exempt_reserve(&mollusk);
+
+        let authority = Pubkey::new_unique();
+        let source_key = Pubkey::new_unique();
+        let destination_key = Pubkey::new_unique();
+        let meta = meta(&mollusk, &authority);
+
+        let source = match delegated {
+            Some(delegated) => delegated_stake_account(
+                reserve + delegated + undelegated,
+                meta,
+                active_stake(&Pubkey::new_unique(), delegated),
+            ),
+            None => initialized_stake_account(reserve + undelegated, meta),
+        };
+        let destination = uninitialized_stake_account(
+            (reserve as f64 * destination_prefund_fraction) as u64,
+        );
+        let split_lamports = (source.lamports as f64 * split_fraction) as u64;
+        let total = source.lamports + destination.lamports;
+
+        let result = mollusk.process_instruction(
+            &split_instruction(&source_key, &destination_key, &authority, split_lamports),
+            &[
+                (source_key, source.clone()),
+                (destination_key, destination.clone()),
+                (authority, authority_account()),
+            ],
+        );
+        let resulting_source = result.get_account(&source_key).unwrap();
+        let resulting_destination = result.get_account(&destination_key).unwrap();
+
+        if result.program_result.is_err() {
+            prop_assert_eq!(resulting_source, &source);
+            prop_assert_eq!(resulting_destination, &destination);
+        } else {
+            prop_assert_eq!(
+                resulting_source.lamports + resulting_destination.lamports,
+                total
+            );
+            check_invariants(resulting_source)?;
+            check_invariants(resulting_destination)?;
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! Splits into prefunded destinations and between accounts with different rent
+//! reserves, the cases the "magic activation" comments in `split_stake_amounts` guard.
+//! Each expected account is built the way the native program would write it and
+//! compared byte for byte.
+
+mod common;
+
+use common::*;
+use mollusk_svm::result::Check;
+use solana_pinocchio_starter::state::{Authorized, Lockup, Meta, StakeFlags, StakeStateV2};
+use solana_sdk::{account::Account, program_error::ProgramError, pubkey::Pubkey};
+
+struct Accounts {
+    source: Pubkey,
+    destination: Pubkey,
+    authority: Pubkey,
+    voter: Pubkey,
+}
+
+impl Accounts {
+    fn new() -> Self {
+        Self {
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+        }
+    }
+
+    fn meta(&self, rent_exempt_reserve: u64) -> Meta {
+        Meta::new(
+            rent_exempt_reserve,
+            Authorized::auto(&self.authority.to_bytes()),
+            Lockup::default(),
+        )
+    }
+
+    fn keyed(&self, source: Account, destination: Account) -> Vec<(Pubkey, Account)> {
+        vec![
+            (self.source, source),
+            (self.destination, destination),
+            (self.authority, authority_account()),
+        ]
+    }
+
+    fn split(&self, lamports: u64, source: Account, destination: Account, checks: &[Check]) {
+        mollusk().process_and_validate_instruction(
+            &split_instruction(&self.source, &self.destination, &self.authority, lamports),
+            &self.keyed(source, destination),
+            checks,
+        );
+    }
+}
+
+/// `state` with its reserve and delegated stake replaced, as split writes both sides
+fn with(state: StakeStateV2, rent_exempt_reserve: u64, stake: u64) -> Vec<u8> {
+    let StakeStateV2::Stake(mut meta, mut delegated, flags) = state else {
+        panic!("expected a Stake state, got {state:?}");
+    };
+    meta.set_rent_exempt_reserve(rent_exempt_reserve);
+    delegated.delegation.set_stake(stake);
+    stake_state_data(&StakeStateV2::Stake(meta, delegated, flags))
+}
+
+#[test]
+fn test_split_into_destination_prefunded_above_reserve() {
+    let keys = Accounts::new();
+    let reserve = rent_exempt_reserve(&mollusk());
+    let source = StakeStateV2::Stake(
+        keys.meta(reserve),
+        active_stake(&keys.voter, 10_000),
+        StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED,
+    );
+
+    // the destination's reserve is already covered, so every split lamport is stake
+    // and the surplus prefunding stays undelegated
+    keys.split(
+        4_000,
+        stake_account_with_state(reserve + 10_000, &source),
+        uninitialized_stake_account(reserve + 500),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(reserve + 6_000)
+                .data(&with(source, reserve, 6_000))
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve + 4_500)
+                .data(&with(source, reserve, 4_000))
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_split_into_destination_prefunded_below_reserve() {
+    let keys = Accounts::new();
+    let reserve = rent_exempt_reserve(&mollusk());
+
+    // active stake needs the whole reserve prefunded
+    let source = StakeStateV2::Stake(
+        keys.meta(reserve),
+        active_stake(&keys.voter, 10_000),
+        StakeFlags::empty(),
+    );
+    keys.split(
+        4_000,
+        stake_account_with_state(reserve + 10_000, &source),
+        uninitialized_stake_account(reserve - 300),
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+
+    // inactive stake pays the shortfall out of the split
+    let source = StakeStateV2::Stake(
+        keys.meta(reserve),
+        inactive_stake(&keys.voter, 20_000, 5),
+        StakeFlags::empty(),
+    );
+    keys.split(
+        4_000,
+        stake_account_with_state(reserve + 20_000, &source),
+        uninitialized_stake_account(reserve - 300),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(reserve + 16_000)
+                .data(&with(source, reserve, 16_000))
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve + 3_700)
+                .data(&with(source, reserve, 3_700))
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_full_split_ignores_prefunding() {
+    let keys = Accounts::new();
+    let reserve = rent_exempt_reserve(&mollusk());
+    let source = StakeStateV2::Stake(
+        keys.meta(reserve),
+        active_stake(&keys.voter, 10_000),
+        StakeFlags::empty(),
+    );
+
+    // the destination keeps exactly the source's stake; the prefunded lamports stay
+    // undelegated rather than activating with it
+    for prefunded in [0, 1, reserve, reserve + 5_000] {
+        keys.split(
+            reserve + 10_000,
+            stake_account_with_state(reserve + 10_000, &source),
+            uninitialized_stake_account(prefunded),
+            &[
+                Check::success(),
+                Check::account(&keys.source)
+                    .lamports(0)
+                    .data(&stake_state_data(&StakeStateV2::Uninitialized))
+                    .build(),
+                Check::account(&keys.destination)
+                    .lamports(prefunded + reserve + 10_000)
+                    .data(&with(source, reserve, 10_000))
+                    .build(),
+            ],
+        );
+    }
+}
+
+#[test]
+fn test_full_split_ignores_reserve_difference() {
+    let keys = Accounts::new();
+    let reserve = rent_exempt_reserve(&mollusk());
+
+    // a source recorded with a larger reserve, as from a larger account or an older
+    // rent, doesn't hand the difference to the destination as stake; one recorded
+    // with a smaller reserve keeps its stake even though the destination's reserve
+    // isn't fully covered
+    for source_reserve in [reserve + 1_000, reserve - 1_000] {
+        let source = StakeStateV2::Stake(
+            keys.meta(source_reserve),
+            active_stake(&keys.voter, 10_000),
+            StakeFlags::empty(),
+        );
+        keys.split(
+            source_reserve + 10_000,
+            stake_account_with_state(source_reserve + 10_000, &source),
+            uninitialized_stake_account(0),
+            &[
+                Check::success(),
+                Check::account(&keys.source)
+                    .lamports(0)
+                    .data(&stake_state_data(&StakeStateV2::Uninitialized))
+                    .build(),
+                Check::account(&keys.destination)
+                    .lamports(source_reserve + 10_000)
+                    .data(&with(source, reserve, 10_000))
+                    .build(),
+            ],
+        );
+    }
+
+    // a partial split takes the destination's own reserve from its prefunding
+    let source = StakeStateV2::Stake(
+        keys.meta(reserve + 1_000),
+        active_stake(&keys.voter, 10_000),
+        StakeFlags::empty(),
+    );
+    keys.split(
+        4_000,
+        stake_account_with_state(reserve + 11_000, &source),
+        uninitialized_stake_account(reserve),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(reserve + 7_000)
+                .data(&with(source, reserve + 1_000, 6_000))
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve + 4_000)
+                .data(&with(source, reserve, 4_000))
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_full_split_of_initialized_into_prefunded_destination() {
+    let keys = Accounts::new();
+    let reserve = rent_exempt_reserve(&mollusk());
+    let source_meta = keys.meta(reserve + 1_000);
+
+    // the destination is rent-exempt at its own size, whatever the source recorded
+    keys.split(
+        reserve + 1_000,
+        initialized_stake_account(reserve + 1_000, source_meta),
+        uninitialized_stake_account(700),
+        &[
+            Check::success(),
+            Check::account(&keys.source)
+                .lamports(0)
+                .data(&stake_state_data(&StakeStateV2::Uninitialized))
+                .build(),
+            Check::account(&keys.destination)
+                .lamports(reserve + 1_700)
+                .data(&stake_state_data(&StakeStateV2::Initialized(
+                    keys.meta(reserve),
+                )))
+                .build(),
+        ],
+    );
+}